@@ -0,0 +1,390 @@
+use crate::models::{
+    AlertComparison, AlertCondition, AlertIndicator, AlertRule, AlertTarget, AnalysisParams,
+    Candle, ConsensusParams, CrossDirection, MacdParams, StochasticParams,
+};
+use crate::ta_engine::{
+    self, adx, anchored_vwap, cmf, mfi, rsi, stochastic, williams_r, SignalInputs,
+};
+
+/// 평가할 최근 마감 봉 수 (진행 중인 봉은 호출 측에서 뺀다).
+/// 폴링 사이에 봉이 두 개 마감돼도 앞의 봉 조건을 놓치지 않는다
+const EVALUATION_BARS: usize = 2;
+
+/// 규칙 조건이 성립한 한 건
+#[derive(Debug, Clone)]
+pub struct AlertMatch {
+    pub time: i64,
+    pub price: f64,
+    pub message: String,
+    /// 규칙·심볼 안에서 같은 사건을 가리키는 키 (중복 제거용)
+    pub key: String,
+}
+
+/// 규칙을 평가할 분석 설정. 신호 조건이면 `sources`에 해당하는 전략을 켠다 (비어 있으면 전부).
+pub fn analysis_params(rule: &AlertRule, target: &AlertTarget) -> AnalysisParams {
+    let mut params = AnalysisParams::new(
+        target.symbol.clone(),
+        rule.interval.clone(),
+        target.market.clone(),
+    );
+
+    if let AlertCondition::Signal { sources, .. } = &rule.condition {
+        let wants = |source: &str| sources.is_empty() || sources.iter().any(|s| s == source);
+        let mut strategies = rule.signal_strategies.clone();
+        strategies.supertrend_adx |= wants("supertrend_adx");
+        strategies.ema_crossover |= wants("ema_crossover");
+        strategies.stoch_rsi_combined |= wants("stoch_rsi");
+        strategies.cmf_obv |= wants("cmf_obv");
+        strategies.ttm_squeeze |= wants("ttm_squeeze");
+        strategies.vwap_breakout |= wants("vwap_breakout");
+        strategies.parabolic_sar |= wants("parabolic_sar");
+        strategies.macd_hist_reversal |= wants("macd_hist");
+        strategies.ibs_mean_reversion |= wants("ibs_mean_rev");
+        strategies.rsi_divergence |= wants("rsi_divergence");
+        if strategies.consensus.is_none() && sources.iter().any(|s| s == "consensus") {
+            strategies.consensus = Some(ConsensusParams::default());
+        }
+
+        // TTM Squeeze/MACD 반전/Stoch+RSI는 해당 지표 설정이 있어야 동작한다
+        params.macd = Some(MacdParams::default());
+        params.stochastic = Some(StochasticParams::default());
+        params.signal_strategies = strategies;
+    }
+
+    params
+}
+
+/// 최근 봉에서 규칙 조건이 성립한 사건을 찾는다.
+pub fn evaluate(rule: &AlertRule, params: &AnalysisParams, candles: &[Candle]) -> Vec<AlertMatch> {
+    if candles.len() < 2 {
+        return Vec::new();
+    }
+    let first = candles.len().saturating_sub(EVALUATION_BARS).max(1);
+
+    match &rule.condition {
+        AlertCondition::Signal {
+            sources,
+            signal_types,
+        } => {
+            let mut signals = ta_engine::detect_signals(candles, params, &SignalInputs::default());
+            ta_engine::apply_consensus(candles, params, &mut signals);
+
+            let since = candles[first].time;
+            signals
+                .into_iter()
+                .filter(|s| s.time >= since)
+                .filter(|s| sources.is_empty() || sources.contains(&s.source))
+                .filter(|s| signal_types.is_empty() || signal_types.contains(&s.signal_type))
+                .map(|s| AlertMatch {
+                    time: s.time,
+                    price: s.price,
+                    message: format!(
+                        "{} {} 신호",
+                        s.source,
+                        if s.signal_type.is_buy() {
+                            "매수"
+                        } else {
+                            "매도"
+                        }
+                    ),
+                    key: format!("signal:{}:{:?}:{}", s.source, s.signal_type, s.time),
+                })
+                .collect()
+        }
+        AlertCondition::PriceCross { level, direction } => {
+            let line = vec![Some(*level); candles.len()];
+            crossings(candles, &line, *direction, first, &level.to_string())
+        }
+        AlertCondition::AnchoredVwapCross {
+            anchor_time,
+            direction,
+        } => {
+            let vwap = anchored_vwap::calculate(candles, *anchor_time).data;
+            let offset = candles.len() - vwap.len();
+            let mut line = vec![None; offset];
+            line.extend(vwap.iter().map(|p| Some(p.value)));
+            crossings(candles, &line, *direction, first, "앵커드 VWAP")
+        }
+        AlertCondition::Threshold {
+            indicator,
+            period,
+            comparison,
+            value,
+        } => threshold(candles, *indicator, *period, *comparison, *value, first),
+    }
+}
+
+/// 종가가 `line`을 교차한 봉. `line[i]`는 `candles[i]` 시점의 기준값.
+fn crossings(
+    candles: &[Candle],
+    line: &[Option<f64>],
+    direction: CrossDirection,
+    first: usize,
+    label: &str,
+) -> Vec<AlertMatch> {
+    let mut matches = Vec::new();
+    for i in first..candles.len() {
+        let (Some(prev_line), Some(line_i)) = (line[i - 1], line[i]) else {
+            continue;
+        };
+        let prev_close = candles[i - 1].close;
+        let close = candles[i].close;
+
+        let crossed_up = prev_close <= prev_line && close > line_i;
+        let crossed_down = prev_close >= prev_line && close < line_i;
+        let (up, fired) = match direction {
+            CrossDirection::Above => (true, crossed_up),
+            CrossDirection::Below => (false, crossed_down),
+            CrossDirection::Either => (crossed_up, crossed_up || crossed_down),
+        };
+        if !fired {
+            continue;
+        }
+
+        matches.push(AlertMatch {
+            time: candles[i].time,
+            price: close,
+            message: format!(
+                "종가가 {} {} ({:.4})",
+                label,
+                if up { "상향 돌파" } else { "하향 돌파" },
+                close
+            ),
+            key: format!(
+                "cross:{}:{}",
+                if up { "up" } else { "down" },
+                candles[i].time
+            ),
+        });
+    }
+    matches
+}
+
+/// 지표 값이 기준을 새로 넘어선 봉 (직전 봉에서는 성립하지 않음).
+fn threshold(
+    candles: &[Candle],
+    indicator: AlertIndicator,
+    period: Option<usize>,
+    comparison: AlertComparison,
+    value: f64,
+    first: usize,
+) -> Vec<AlertMatch> {
+    let (name, default_period) = match indicator {
+        AlertIndicator::Rsi => ("RSI", 14),
+        AlertIndicator::Mfi => ("MFI", 14),
+        AlertIndicator::Cmf => ("CMF", 20),
+        AlertIndicator::WilliamsR => ("Williams %R", 14),
+        AlertIndicator::Adx => ("ADX", 14),
+        AlertIndicator::StochasticK => ("Stochastic %K", 14),
+    };
+    let period = period.unwrap_or(default_period).max(1);
+
+    let series: Vec<(i64, f64)> = match indicator {
+        AlertIndicator::Rsi => rsi::calculate(candles, period)
+            .into_iter()
+            .map(|p| (p.time, p.value))
+            .collect(),
+        AlertIndicator::Mfi => mfi::calculate(candles, period)
+            .data
+            .into_iter()
+            .map(|p| (p.time, p.value))
+            .collect(),
+        AlertIndicator::Cmf => cmf::calculate(candles, period)
+            .data
+            .into_iter()
+            .map(|p| (p.time, p.value))
+            .collect(),
+        AlertIndicator::WilliamsR => williams_r::calculate(candles, period)
+            .data
+            .into_iter()
+            .map(|p| (p.time, p.value))
+            .collect(),
+        AlertIndicator::Adx => adx::calculate(candles, period)
+            .data
+            .into_iter()
+            .map(|p| (p.time, p.adx))
+            .collect(),
+        AlertIndicator::StochasticK => stochastic::calculate(candles, period, 3, 3)
+            .data
+            .into_iter()
+            .map(|p| (p.time, p.k))
+            .collect(),
+    };
+
+    let holds = |v: f64| match comparison {
+        AlertComparison::Above => v > value,
+        AlertComparison::Below => v < value,
+    };
+    let since = candles[first].time;
+    let (symbol, verb) = match comparison {
+        AlertComparison::Above => (">", "above"),
+        AlertComparison::Below => ("<", "below"),
+    };
+
+    series
+        .windows(2)
+        .filter(|w| w[1].0 >= since && holds(w[1].1) && !holds(w[0].1))
+        .filter_map(|w| {
+            let (time, current) = w[1];
+            let index = candles.binary_search_by_key(&time, |c| c.time).ok()?;
+            Some(AlertMatch {
+                time,
+                price: candles[index].close,
+                message: format!("{}({}) {:.2} {} {}", name, period, current, symbol, value),
+                key: format!("threshold:{}:{}", verb, time),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MarketType;
+
+    fn candles(closes: &[f64]) -> Vec<Candle> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| Candle {
+                time: 1_700_000_000 + i as i64 * 3_600,
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 1.0,
+                session: None,
+                flow: None,
+            })
+            .collect()
+    }
+
+    fn rule(condition: AlertCondition) -> AlertRule {
+        AlertRule {
+            id: 1,
+            name: "test".to_string(),
+            enabled: true,
+            targets: Vec::new(),
+            interval: "1h".to_string(),
+            condition,
+            signal_strategies: Default::default(),
+            notify: false,
+        }
+    }
+
+    fn run(condition: AlertCondition, candles: &[Candle]) -> Vec<AlertMatch> {
+        let params = AnalysisParams::new("X".to_string(), "1h".to_string(), MarketType::Crypto);
+        evaluate(&rule(condition), &params, candles)
+    }
+
+    #[test]
+    fn price_cross_fires_by_direction_within_the_last_bars() {
+        let candles = candles(&[90.0, 95.0, 105.0, 98.0]);
+        let cross = |direction| AlertCondition::PriceCross {
+            level: 100.0,
+            direction,
+        };
+
+        let either = run(cross(CrossDirection::Either), &candles);
+        let keys: Vec<&str> = either.iter().map(|m| m.key.as_str()).collect();
+        assert_eq!(keys, ["cross:up:1700007200", "cross:down:1700010800"]);
+        assert_eq!(either[0].price, 105.0);
+
+        let above = run(cross(CrossDirection::Above), &candles);
+        assert_eq!(above.len(), 1);
+        assert_eq!(above[0].time, candles[2].time);
+        let below = run(cross(CrossDirection::Below), &candles);
+        assert_eq!(below.len(), 1);
+        assert_eq!(below[0].time, candles[3].time);
+    }
+
+    #[test]
+    fn crossings_before_the_evaluation_window_are_ignored() {
+        // Crossed up on bar 1, which has already been evaluated by an earlier poll
+        let candles = candles(&[90.0, 105.0, 110.0, 112.0]);
+        let condition = AlertCondition::PriceCross {
+            level: 100.0,
+            direction: CrossDirection::Either,
+        };
+        assert!(run(condition.clone(), &candles).is_empty());
+        assert!(run(condition, &candles[..1]).is_empty());
+    }
+
+    #[test]
+    fn anchored_vwap_cross_skips_bars_before_the_anchor() {
+        let mut candles = candles(&[50.0, 200.0, 100.0, 100.0, 90.0]);
+        for candle in &mut candles {
+            candle.high = candle.close + 1.0;
+            candle.low = candle.close - 1.0;
+        }
+        // The VWAP from bar 2 stays at 100, so only the drop on bar 4 crosses it
+        let condition = AlertCondition::AnchoredVwapCross {
+            anchor_time: candles[2].time,
+            direction: CrossDirection::Either,
+        };
+        let matches = run(condition, &candles);
+        assert_eq!(matches.len(), 1);
+        assert!(matches[0].key.starts_with("cross:down:"));
+        assert_eq!(matches[0].time, candles[4].time);
+
+        // Anchored on the last bar there is no previous VWAP to cross
+        let condition = AlertCondition::AnchoredVwapCross {
+            anchor_time: candles[4].time,
+            direction: CrossDirection::Either,
+        };
+        assert!(run(condition, &candles).is_empty());
+    }
+
+    #[test]
+    fn threshold_fires_only_when_the_condition_newly_holds() {
+        // Sixteen falling closes hold RSI at 0, then one jump lifts it above 70 and it stays there
+        let mut closes: Vec<f64> = (0..16).map(|i| 100.0 - i as f64).collect();
+        closes.extend([1_085.0, 1_086.0]);
+        let candles = candles(&closes);
+        let rsi = |comparison, value| AlertCondition::Threshold {
+            indicator: AlertIndicator::Rsi,
+            period: None,
+            comparison,
+            value,
+        };
+
+        let above = run(rsi(AlertComparison::Above, 70.0), &candles);
+        assert_eq!(above.len(), 1);
+        assert_eq!(above[0].time, candles[16].time);
+        assert_eq!(above[0].price, 1_085.0);
+        assert_eq!(
+            above[0].key,
+            format!("threshold:above:{}", candles[16].time)
+        );
+        assert!(above[0].message.starts_with("RSI(14)"));
+
+        // Below 30 held before the window and stopped holding inside it
+        assert!(run(rsi(AlertComparison::Below, 30.0), &candles).is_empty());
+    }
+
+    #[test]
+    fn analysis_params_enable_only_the_requested_signal_sources() {
+        let condition = AlertCondition::Signal {
+            sources: vec!["ema_crossover".to_string(), "consensus".to_string()],
+            signal_types: Vec::new(),
+        };
+        let target = AlertTarget {
+            symbol: "BTCUSDT".to_string(),
+            market: MarketType::Crypto,
+        };
+        let params = analysis_params(&rule(condition), &target);
+        let strategies = &params.signal_strategies;
+        assert!(strategies.ema_crossover);
+        assert!(!strategies.supertrend_adx && !strategies.ttm_squeeze);
+        assert!(strategies.consensus.is_some());
+        assert!(params.macd.is_some() && params.stochastic.is_some());
+
+        let all = AlertCondition::Signal {
+            sources: Vec::new(),
+            signal_types: Vec::new(),
+        };
+        let strategies = analysis_params(&rule(all), &target).signal_strategies;
+        assert!(strategies.supertrend_adx && strategies.rsi_divergence);
+        assert!(strategies.consensus.is_none());
+    }
+}
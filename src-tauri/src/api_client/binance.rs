@@ -1,13 +1,71 @@
-use crate::models::Candle;
+use serde_json::Value;
 
+use super::provider::{CandleFuture, MarketDataProvider};
+use crate::models::{
+    Candle, FundingRatePoint, LongShortRatioPoint, MarketType, OpenInterestPoint,
+    ProviderCapabilities, TradeFlow,
+};
+
+/// Binance klines 한 페이지의 최대 건수
+const PAGE_LIMIT: u32 = 1_000;
+/// 백필 한 번에 요청할 최대 페이지 수 (1m 기준 약 2년)
+const MAX_BACKFILL_PAGES: usize = 1_000;
+/// 미결제약정·롱숏 비율 한 페이지의 최대 건수
+const FUTURES_STATS_PAGE_LIMIT: usize = 500;
+/// 선물 통계 조회 한 번에 요청할 최대 페이지 수 (5m 기준 30일)
+const MAX_FUTURES_STATS_PAGES: usize = 20;
+/// 미결제약정·롱숏 비율은 최근 30일만 제공된다
+const FUTURES_STATS_RETENTION: i64 = 30 * 86_400;
+const DEFAULT_BASE_URL: &str = "https://api.binance.com";
+const DEFAULT_FUTURES_BASE_URL: &str = "https://fapi.binance.com";
+
+/// klines를 받을 시장
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinanceMarket {
+    Spot,
+    /// USDⓈ-M 무기한 선물
+    UsdM,
+}
+
+#[derive(Clone)]
 pub struct BinanceClient {
     client: reqwest::Client,
+    base_url: String,
+    futures_base_url: String,
+    market: BinanceMarket,
 }
 
 impl BinanceClient {
+    /// `BINANCE_API_URL`/`BINANCE_FUTURES_API_URL` 환경변수로 현물/선물 REST 주소를 바꿀 수 있다
+    /// (로컬 대역 서버 등).
     pub fn new() -> Self {
+        Self {
+            futures_base_url: std::env::var("BINANCE_FUTURES_API_URL")
+                .unwrap_or_else(|_| DEFAULT_FUTURES_BASE_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            ..Self::with_base_url(
+                std::env::var("BINANCE_API_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string()),
+            )
+        }
+    }
+
+    /// 현물과 선물 경로를 모두 `base_url`로 보낸다.
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
         Self {
             client: reqwest::Client::new(),
+            futures_base_url: base_url.clone(),
+            base_url,
+            market: BinanceMarket::Spot,
+        }
+    }
+
+    /// 같은 설정으로 USDⓈ-M 무기한 선물 klines를 받는 클라이언트 (공급자 id `binance_futures`).
+    pub fn perpetual(&self) -> Self {
+        Self {
+            market: BinanceMarket::UsdM,
+            ..self.clone()
         }
     }
 
@@ -17,12 +75,95 @@ impl BinanceClient {
         interval: &str,
         limit: u32,
     ) -> Result<Vec<Candle>, String> {
-        let url = format!(
-            "https://api.binance.com/api/v3/klines?symbol={}&interval={}&limit={}",
+        let candles = self.request_klines(symbol, interval, limit, None, None).await?;
+        if candles.is_empty() {
+            return Err("No candle data received from Binance".to_string());
+        }
+        Ok(candles)
+    }
+
+    /// `since`(초) 이후의 캔들만 조회 (저장소 증분 갱신용).
+    pub async fn fetch_klines_since(
+        &self,
+        symbol: &str,
+        interval: &str,
+        since: i64,
+        limit: u32,
+    ) -> Result<Vec<Candle>, String> {
+        // 마지막 봉 이후 새 봉이 없으면 빈 결과 (보충할 것이 없을 뿐 오류가 아니다)
+        self.request_klines(symbol, interval, limit, Some(since), None)
+            .await
+    }
+
+    /// `[start, end]`(초) 구간을 `endTime` 기준으로 거슬러 올라가며 페이지 단위로 조회.
+    /// 상장 전 구간처럼 데이터가 없으면 빈 결과를 반환한다.
+    pub async fn fetch_klines_range(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<Candle>, String> {
+        let mut all_candles: Vec<Candle> = Vec::new();
+        let mut cursor = end;
+
+        for _ in 0..MAX_BACKFILL_PAGES {
+            if cursor < start {
+                break;
+            }
+
+            // endTime만 지정하면 cursor 이전의 최신 PAGE_LIMIT개가 반환된다
+            let page = self
+                .request_klines(symbol, interval, PAGE_LIMIT, None, Some(cursor))
+                .await?;
+            let Some(first_time) = page.first().map(|c| c.time) else {
+                break;
+            };
+            let page_len = page.len();
+
+            all_candles.extend(page);
+
+            // 구간 시작에 도달했거나 더 이상 과거 데이터가 없음
+            if first_time <= start || page_len < PAGE_LIMIT as usize {
+                break;
+            }
+            cursor = first_time - 1;
+
+            // Rate limit: 100ms delay between calls
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+
+        all_candles.retain(|c| c.time >= start && c.time <= end);
+        all_candles.sort_by_key(|c| c.time);
+        all_candles.dedup_by_key(|c| c.time);
+        Ok(all_candles)
+    }
+
+    async fn request_klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+        limit: u32,
+        start_time: Option<i64>,
+        end_time: Option<i64>,
+    ) -> Result<Vec<Candle>, String> {
+        let endpoint = match self.market {
+            BinanceMarket::Spot => format!("{}/api/v3/klines", self.base_url),
+            BinanceMarket::UsdM => format!("{}/fapi/v1/klines", self.futures_base_url),
+        };
+        let mut url = format!(
+            "{}?symbol={}&interval={}&limit={}",
+            endpoint,
             symbol.to_uppercase(),
             interval,
             limit
         );
+        if let Some(start) = start_time {
+            url.push_str(&format!("&startTime={}", start * 1000));
+        }
+        if let Some(end) = end_time {
+            url.push_str(&format!("&endTime={}", end * 1000));
+        }
 
         let resp = self
             .client
@@ -55,6 +196,14 @@ impl BinanceClient {
                 let low = kline[3].as_str()?.parse::<f64>().ok()?;
                 let close = kline[4].as_str()?.parse::<f64>().ok()?;
                 let volume = kline[5].as_str()?.parse::<f64>().ok()?;
+                // [7] quote volume, [8] trade count, [9] taker buy base volume
+                let flow = (|| {
+                    Some(TradeFlow {
+                        quote_volume: kline.get(7)?.as_str()?.parse().ok()?,
+                        trade_count: kline.get(8)?.as_u64()?,
+                        taker_buy_volume: kline.get(9)?.as_str()?.parse().ok()?,
+                    })
+                })();
 
                 Some(Candle {
                     time,
@@ -63,14 +212,359 @@ impl BinanceClient {
                     low,
                     close,
                     volume,
+                    session: None,
+                    flow,
                 })
             })
             .collect();
 
-        if candles.is_empty() {
-            return Err("No candle data received from Binance".to_string());
+        Ok(candles)
+    }
+
+    /// `[start, end]`(초) 구간의 무기한 선물 펀딩비 (8시간마다 정산).
+    pub async fn fetch_funding_rates(
+        &self,
+        symbol: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<FundingRatePoint>, String> {
+        let url = format!("{}/fapi/v1/fundingRate", self.futures_base_url);
+        let query = vec![("symbol", symbol.to_uppercase())];
+        let items = self
+            .request_futures_pages(&url, query, start, end, PAGE_LIMIT as usize, "fundingTime")
+            .await?;
+
+        Ok(items
+            .iter()
+            .filter_map(|item| {
+                Some(FundingRatePoint {
+                    time: item["fundingTime"].as_i64()? / 1000,
+                    rate: item["fundingRate"].as_str()?.parse().ok()?,
+                })
+            })
+            .collect())
+    }
+
+    /// `[start, end]`(초) 구간의 미결제약정. `period`는 5m~1d이며 최근 30일만 제공된다.
+    pub async fn fetch_open_interest(
+        &self,
+        symbol: &str,
+        period: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<OpenInterestPoint>, String> {
+        let url = format!("{}/futures/data/openInterestHist", self.futures_base_url);
+        let items = self
+            .request_futures_stats(&url, symbol, period, start, end)
+            .await?;
+
+        Ok(items
+            .iter()
+            .filter_map(|item| {
+                Some(OpenInterestPoint {
+                    time: item["timestamp"].as_i64()? / 1000,
+                    open_interest: item["sumOpenInterest"].as_str()?.parse().ok()?,
+                    open_interest_value: item["sumOpenInterestValue"].as_str()?.parse().ok()?,
+                })
+            })
+            .collect())
+    }
+
+    /// `[start, end]`(초) 구간의 상위 트레이더 포지션 기준 롱/숏 비율. 기간 제약은 미결제약정과 같다.
+    pub async fn fetch_long_short_ratio(
+        &self,
+        symbol: &str,
+        period: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<LongShortRatioPoint>, String> {
+        let url = format!(
+            "{}/futures/data/topLongShortPositionRatio",
+            self.futures_base_url
+        );
+        let items = self
+            .request_futures_stats(&url, symbol, period, start, end)
+            .await?;
+
+        Ok(items
+            .iter()
+            .filter_map(|item| {
+                Some(LongShortRatioPoint {
+                    time: item["timestamp"].as_i64()? / 1000,
+                    ratio: item["longShortRatio"].as_str()?.parse().ok()?,
+                    long_share: item["longAccount"].as_str()?.parse().ok()?,
+                    short_share: item["shortAccount"].as_str()?.parse().ok()?,
+                })
+            })
+            .collect())
+    }
+
+    /// 보존 기간(30일) 안으로 시작 시각을 당겨 선물 통계를 조회한다.
+    async fn request_futures_stats(
+        &self,
+        url: &str,
+        symbol: &str,
+        period: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<Value>, String> {
+        // 보존 기간 경계에 딱 맞추면 거부되므로 1시간 여유를 둔다
+        let oldest = chrono::Utc::now().timestamp() - FUTURES_STATS_RETENTION + 3_600;
+        let start = start.max(oldest);
+        if start > end {
+            return Ok(Vec::new());
+        }
+        let query = vec![
+            ("symbol", symbol.to_uppercase()),
+            ("period", period.to_string()),
+        ];
+        self.request_futures_pages(
+            url,
+            query,
+            start,
+            end,
+            FUTURES_STATS_PAGE_LIMIT,
+            "timestamp",
+        )
+        .await
+    }
+
+    /// `startTime`을 마지막 항목 다음으로 옮겨 가며 `end`까지 페이지를 받는다.
+    /// `time_field`는 항목의 ms 시각 필드.
+    async fn request_futures_pages(
+        &self,
+        url: &str,
+        query: Vec<(&str, String)>,
+        start: i64,
+        end: i64,
+        page_limit: usize,
+        time_field: &str,
+    ) -> Result<Vec<Value>, String> {
+        let mut all_items: Vec<Value> = Vec::new();
+        let mut cursor = start * 1000;
+
+        for _ in 0..MAX_FUTURES_STATS_PAGES {
+            let mut page_query = query.clone();
+            page_query.extend([
+                ("startTime", cursor.to_string()),
+                ("endTime", (end * 1000).to_string()),
+                ("limit", page_limit.to_string()),
+            ]);
+
+            let page = match self.get_json(url, &page_query).await? {
+                Value::Array(items) => items,
+                _ => return Err("Binance futures response is not an array".to_string()),
+            };
+            let page_len = page.len();
+            let Some(last_time) = page.last().and_then(|item| item[time_field].as_i64()) else {
+                break;
+            };
+            all_items.extend(page);
+
+            if page_len < page_limit || last_time >= end * 1000 {
+                break;
+            }
+            cursor = last_time + 1;
+
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
         }
 
-        Ok(candles)
+        Ok(all_items)
+    }
+
+    async fn get_json(&self, url: &str, query: &[(&str, String)]) -> Result<Value, String> {
+        let resp = self
+            .client
+            .get(url)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(format!("Binance API error ({}): {}", status, body));
+        }
+
+        resp.json().await.map_err(|e| format!("Parse error: {}", e))
+    }
+}
+
+impl MarketDataProvider for BinanceClient {
+    fn id(&self) -> &'static str {
+        match self.market {
+            BinanceMarket::Spot => "binance",
+            BinanceMarket::UsdM => "binance_futures",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self.market {
+            BinanceMarket::Spot => "Binance",
+            BinanceMarket::UsdM => "Binance USDⓈ-M",
+        }
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            markets: vec![MarketType::Crypto],
+            intervals: [
+                "1m", "3m", "5m", "15m", "30m", "1h", "2h", "4h", "6h", "8h", "12h", "1d", "1w",
+                "1M",
+            ]
+            .map(String::from)
+            .to_vec(),
+            max_bars: PAGE_LIMIT,
+            supports_range: true,
+            // 현물은 IP당 가중치 6,000/분에 1,000건 klines가 가중치 5,
+            // 선물은 2,400/분에 가중치 5
+            requests_per_minute: Some(match self.market {
+                BinanceMarket::Spot => 1_200,
+                BinanceMarket::UsdM => 480,
+            }),
+        }
+    }
+
+    /// `BTCUSDT`처럼 구분자 없는 거래쌍
+    fn supports_symbol(&self, symbol: &str) -> bool {
+        !symbol.contains('-')
+    }
+
+    /// 선물 klines는 현물과 시세가 달라 현물 실패 시 대신 쓰지 않는다
+    fn in_default_order(&self) -> bool {
+        matches!(self.market, BinanceMarket::Spot)
+    }
+
+    fn fetch_klines<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        limit: u32,
+    ) -> CandleFuture<'a> {
+        Box::pin(BinanceClient::fetch_klines(self, symbol, interval, limit))
+    }
+
+    fn fetch_klines_since<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        since: i64,
+        limit: u32,
+    ) -> CandleFuture<'a> {
+        Box::pin(BinanceClient::fetch_klines_since(
+            self, symbol, interval, since, limit,
+        ))
+    }
+
+    fn fetch_klines_range<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        start: i64,
+        end: i64,
+    ) -> CandleFuture<'a> {
+        Box::pin(BinanceClient::fetch_klines_range(
+            self, symbol, interval, start, end,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::BinanceClient;
+
+    /// Serves funding rates at `times` (ms) filtered by `startTime`/`endTime`/`limit`
+    /// and records each requested `startTime`.
+    async fn funding_server(times: Vec<i64>) -> (String, Arc<Mutex<Vec<i64>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let log = requested.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 4096];
+                let read = socket.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..read]).to_string();
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+                let param = |name: &str| -> i64 {
+                    path.split(['?', '&'])
+                        .find_map(|pair| pair.strip_prefix(&format!("{}=", name)))
+                        .and_then(|value| value.parse().ok())
+                        .unwrap()
+                };
+                let (start, end, limit) = (param("startTime"), param("endTime"), param("limit"));
+                log.lock().unwrap().push(start);
+
+                let body = serde_json::Value::Array(
+                    times
+                        .iter()
+                        .filter(|&&time| time >= start && time <= end)
+                        .take(limit as usize)
+                        .map(|&time| {
+                            serde_json::json!({
+                                "symbol": "BTCUSDT",
+                                "fundingTime": time,
+                                "fundingRate": "0.0001"
+                            })
+                        })
+                        .collect(),
+                )
+                .to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (format!("http://{}", addr), requested)
+    }
+
+    async fn pages(base_url: &str, start: i64, end: i64) -> Vec<i64> {
+        let client = BinanceClient::with_base_url(base_url);
+        let url = format!("{}/fapi/v1/fundingRate", base_url);
+        client
+            .request_futures_pages(
+                &url,
+                vec![("symbol", "BTCUSDT".to_string())],
+                start,
+                end,
+                2,
+                "fundingTime",
+            )
+            .await
+            .unwrap()
+            .iter()
+            .map(|item| item["fundingTime"].as_i64().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn futures_pages_advance_past_the_last_item_until_a_short_page() {
+        let (base_url, requested) = funding_server(vec![1_000, 2_000, 3_000, 4_000, 5_000]).await;
+
+        let times = pages(&base_url, 1, 10).await;
+
+        assert_eq!(times, [1_000, 2_000, 3_000, 4_000, 5_000]);
+        assert_eq!(*requested.lock().unwrap(), [1_000, 2_001, 4_001]);
+    }
+
+    #[tokio::test]
+    async fn futures_pages_stop_at_the_end_without_an_extra_request() {
+        let (base_url, requested) = funding_server(vec![1_000, 2_000, 3_000, 4_000, 5_000]).await;
+
+        // The second page is full but already reaches `end`
+        let times = pages(&base_url, 1, 4).await;
+
+        assert_eq!(times, [1_000, 2_000, 3_000, 4_000]);
+        assert_eq!(*requested.lock().unwrap(), [1_000, 2_001]);
     }
 }
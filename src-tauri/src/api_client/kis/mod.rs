@@ -0,0 +1,1589 @@
+pub mod realtime;
+mod token;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::RwLock;
+
+use chrono::{Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::provider::{CandleFuture, MarketDataProvider};
+use crate::models::{Candle, InvestorNetBuy, MarketType, ProviderCapabilities};
+use crate::ta_engine::session;
+use token::{CachedToken, TokenStore};
+
+const KST_OFFSET: i32 = 9 * 3600;
+/// 해외주식 시세 API의 미국 거래소 코드 (나스닥, 뉴욕, 아멕스). 거래소를 모르는 티커는 이 순서로 찾는다
+const US_EXCHANGES: [&str; 3] = ["NAS", "NYS", "AMS"];
+/// 해외 일봉 한 페이지(100건)를 거슬러 올라갈 최대 횟수
+const MAX_OVERSEAS_DAILY_PAGES: usize = 20;
+/// 해외 분봉 한 페이지(120건)를 거슬러 올라갈 최대 횟수
+const MAX_OVERSEAS_MINUTE_PAGES: usize = 50;
+/// 국내 분봉 한 페이지(1분봉 120건)를 거슬러 올라갈 최대 횟수 (약 50거래일)
+const MAX_KR_MINUTE_PAGES: usize = 200;
+/// 국내 정규장(09:00~15:30) 하루치 1분봉 수. 120건 페이지 4개에 걸친다
+const KR_SESSION_MINUTES: usize = 391;
+const KR_PAGES_PER_DAY: usize = 4;
+/// 분봉이 없는 일자가 이만큼 이어지면 조회 가능한 과거가 끝난 것으로 본다 (연휴 감안)
+const MAX_EMPTY_DAYS: usize = 10;
+/// 투자자별 매매동향 한 페이지를 거슬러 올라갈 최대 횟수
+const MAX_INVESTOR_FLOW_PAGES: usize = 30;
+
+// ── Config ──────────────────────────────────────────────────────────
+
+/// 접속 도메인. 모의투자 계좌의 앱 키는 모의투자 도메인에서만 쓸 수 있다
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum KisEnvironment {
+    #[default]
+    Real,
+    Paper,
+}
+
+impl KisEnvironment {
+    fn base_url(self) -> &'static str {
+        match self {
+            KisEnvironment::Real => "https://openapi.koreainvestment.com:9443",
+            KisEnvironment::Paper => "https://openapivts.koreainvestment.com:29443",
+        }
+    }
+
+    fn ws_url(self) -> &'static str {
+        match self {
+            KisEnvironment::Real => "ws://ops.koreainvestment.com:21000",
+            KisEnvironment::Paper => "ws://ops.koreainvestment.com:31000",
+        }
+    }
+
+    /// REST 유량 제한: 실전투자 초당 20건, 모의투자 초당 2건
+    fn requests_per_minute(self) -> u32 {
+        match self {
+            KisEnvironment::Real => 1_200,
+            KisEnvironment::Paper => 120,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct KisConfig {
+    app_key: String,
+    app_secret: String,
+    /// "real" (기본) | "paper"
+    #[serde(default)]
+    environment: KisEnvironment,
+}
+
+// ── Client ──────────────────────────────────────────────────────────
+
+#[derive(Clone)]
+pub struct KisClient {
+    client: reqwest::Client,
+    base_url: String,
+    /// 실시간 시세 WebSocket 주소
+    ws_url: String,
+    config: Option<KisConfig>,
+    token: Arc<RwLock<Option<CachedToken>>>,
+    /// 재시작 후에도 토큰을 다시 쓰기 위한 암호화 저장소. 앱 디렉터리가 정해진 뒤 설정한다
+    token_store: Arc<OnceLock<TokenStore>>,
+    /// 거래소를 찾은 미국 티커 → 거래소 코드
+    us_exchanges: Arc<RwLock<HashMap<String, &'static str>>>,
+}
+
+impl KisClient {
+    /// 주소는 설정의 실전/모의투자 도메인을 따르며,
+    /// `KIS_API_URL`, `KIS_WS_URL` 환경변수로 바꿀 수 있다 (로컬 대역 서버 등).
+    pub fn new() -> Self {
+        let config = Self::load_config();
+        let environment = config
+            .as_ref()
+            .map_or(KisEnvironment::Real, |cfg| cfg.environment);
+        Self {
+            client: reqwest::Client::new(),
+            base_url: std::env::var("KIS_API_URL")
+                .unwrap_or_else(|_| environment.base_url().to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            ws_url: std::env::var("KIS_WS_URL")
+                .unwrap_or_else(|_| environment.ws_url().to_string()),
+            token_store: Arc::new(OnceLock::new()),
+            config,
+            token: Arc::new(RwLock::new(None)),
+            us_exchanges: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// 토큰을 `token_dir`에, 암호화 키를 `key_dir`에 보관하도록 설정한다.
+    /// 자격 증명이 없거나 이미 설정했으면 아무것도 하지 않는다
+    pub fn set_token_dirs(&self, token_dir: &Path, key_dir: &Path) {
+        if let Some(config) = &self.config {
+            let _ = self
+                .token_store
+                .set(TokenStore::new(config, token_dir, key_dir));
+        }
+    }
+
+    /// 환경변수 → config 파일 순으로 자격 증명 로드
+    fn load_config() -> Option<KisConfig> {
+        // 1) 환경변수
+        if let (Ok(key), Ok(secret)) = (
+            std::env::var("KIS_APP_KEY"),
+            std::env::var("KIS_APP_SECRET"),
+        ) {
+            if !key.is_empty() && !secret.is_empty() {
+                let environment = match std::env::var("KIS_ENVIRONMENT").as_deref() {
+                    Ok("paper") => KisEnvironment::Paper,
+                    _ => KisEnvironment::Real,
+                };
+                return Some(KisConfig {
+                    app_key: key,
+                    app_secret: secret,
+                    environment,
+                });
+            }
+        }
+
+        // 2) ~/.quanting/kis_config.json
+        let home = dirs::home_dir()?;
+        let path = home.join(".quanting").join("kis_config.json");
+        let data = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str::<KisConfig>(&data).ok()
+    }
+
+    /// 페이지를 이어 받을 때 요청 사이 간격 (접속 환경의 유량 제한)
+    fn page_delay(&self) -> std::time::Duration {
+        let environment = self
+            .config
+            .as_ref()
+            .map_or(KisEnvironment::Real, |cfg| cfg.environment);
+        std::time::Duration::from_secs(60) / environment.requests_per_minute()
+    }
+
+    fn config(&self) -> Result<&KisConfig, String> {
+        self.config.as_ref().ok_or_else(|| {
+            "한국투자증권 API 키가 설정되지 않았습니다. \
+             KIS_APP_KEY/KIS_APP_SECRET 환경변수를 설정하거나 \
+             ~/.quanting/kis_config.json 파일을 생성해주세요."
+                .to_string()
+        })
+    }
+
+    // ── Token management ────────────────────────────────────────────
+
+    /// 메모리 → 저장 파일 순으로 토큰을 찾고, 만료가 가까우면 미리 새로 발급받는다.
+    /// 미리 갱신하다 실패해도 기존 토큰이 아직 유효하면 그대로 쓴다.
+    async fn get_token(&self) -> Result<String, String> {
+        let now = Utc::now().timestamp();
+        // Fast path: read lock
+        {
+            let guard = self.token.read().await;
+            if let Some(cached) = guard.as_ref() {
+                if cached.usable(now) && !cached.needs_refresh(now) {
+                    return Ok(cached.access_token.clone());
+                }
+            }
+        }
+
+        // Slow path: write lock & refresh
+        let mut guard = self.token.write().await;
+        // 재시작 직후에는 저장해 둔 토큰부터 확인
+        if guard.is_none() {
+            *guard = self.token_store.get().and_then(TokenStore::load);
+        }
+        // Double-check after acquiring write lock
+        if let Some(cached) = guard.as_ref() {
+            if cached.usable(now) && !cached.needs_refresh(now) {
+                return Ok(cached.access_token.clone());
+            }
+        }
+
+        match self.issue_token().await {
+            Ok(token) => {
+                if let Some(store) = self.token_store.get() {
+                    if let Err(error) = store.save(&token) {
+                        log::warn!("KIS token save failed: {}", error);
+                    }
+                }
+                let access_token = token.access_token.clone();
+                *guard = Some(token);
+                Ok(access_token)
+            }
+            Err(error) => match guard.as_mut() {
+                Some(cached) if cached.usable(now) => {
+                    cached.postpone_refresh(now);
+                    Ok(cached.access_token.clone())
+                }
+                _ => Err(error),
+            },
+        }
+    }
+
+    async fn issue_token(&self) -> Result<CachedToken, String> {
+        let cfg = self.config()?;
+        let body = serde_json::json!({
+            "grant_type": "client_credentials",
+            "appkey": cfg.app_key,
+            "appsecret": cfg.app_secret,
+        });
+
+        let resp = self
+            .client
+            .post(format!("{}/oauth2/tokenP", self.base_url))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("KIS token request failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            return Err(format!("KIS token error ({}): {}", status, text));
+        }
+
+        let json: Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("KIS token parse error: {}", e))?;
+
+        let access_token = json["access_token"]
+            .as_str()
+            .ok_or("KIS token response missing access_token")?
+            .to_string();
+
+        // expires_in is seconds from now (typically 86400)
+        let expires_in = json["expires_in"].as_i64().unwrap_or(86400);
+        let expires_at = Utc::now().timestamp() + expires_in;
+
+        Ok(CachedToken {
+            access_token,
+            expires_at,
+            retry_at: 0,
+        })
+    }
+
+    /// 실시간(WebSocket) 접속키. 접속할 때마다 새로 받는다.
+    async fn approval_key(&self) -> Result<String, String> {
+        let cfg = self.config()?;
+        let body = serde_json::json!({
+            "grant_type": "client_credentials",
+            "appkey": cfg.app_key,
+            "secretkey": cfg.app_secret,
+        });
+
+        let resp = self
+            .client
+            .post(format!("{}/oauth2/Approval", self.base_url))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("KIS approval request failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            return Err(format!("KIS approval error ({}): {}", status, text));
+        }
+
+        let json: Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("KIS approval parse error: {}", e))?;
+
+        json["approval_key"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "KIS approval response missing approval_key".to_string())
+    }
+
+    // ── Public entry point ──────────────────────────────────────────
+
+    pub async fn fetch_klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+        limit: u32,
+    ) -> Result<Vec<Candle>, String> {
+        self.fetch_candles(symbol, interval, None, limit).await
+    }
+
+    /// `since`(초) 이후 캔들. 국내 분봉만 그 시각까지만 거슬러 올라가고,
+    /// 나머지는 최근 구간 전체를 받아 저장소 upsert로 병합한다.
+    pub async fn fetch_klines_since(
+        &self,
+        symbol: &str,
+        interval: &str,
+        since: i64,
+        limit: u32,
+    ) -> Result<Vec<Candle>, String> {
+        self.fetch_candles(symbol, interval, Some(since), limit)
+            .await
+    }
+
+    /// `[start, end]`(초) 구간. 국내 분봉만 지원한다.
+    pub async fn fetch_klines_range(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<Candle>, String> {
+        self.config()?;
+        match parse_symbol(symbol)? {
+            KisSymbol::Domestic(code) if self.supports_range(symbol, interval) => {
+                self.fetch_kr_minute_range(&code, interval, start, end)
+                    .await
+            }
+            _ => Err(format!(
+                "KIS는 {} {} 과거 구간 조회를 지원하지 않습니다",
+                symbol, interval
+            )),
+        }
+    }
+
+    async fn fetch_candles(
+        &self,
+        symbol: &str,
+        interval: &str,
+        since: Option<i64>,
+        limit: u32,
+    ) -> Result<Vec<Candle>, String> {
+        self.config()?; // fail fast if no credentials
+
+        let stock_code = match parse_symbol(symbol)? {
+            KisSymbol::Domestic(code) => code,
+            KisSymbol::Overseas { ticker, exchange } => {
+                return self
+                    .fetch_overseas_klines(&ticker, exchange, interval, limit)
+                    .await;
+            }
+        };
+
+        match interval {
+            "1m" | "5m" | "15m" | "30m" | "1h" => {
+                self.fetch_kr_minute_candles(&stock_code, interval, since, limit)
+                    .await
+            }
+            _ => {
+                self.fetch_kr_daily_candles(&stock_code, interval, limit)
+                    .await
+            }
+        }
+    }
+
+    // ── Daily candles ───────────────────────────────────────────────
+    // tr_id: FHKST03010100  (국내주식기간별시세 일/주/월/년)
+
+    async fn fetch_kr_daily_candles(
+        &self,
+        stock_code: &str,
+        interval: &str,
+        limit: u32,
+    ) -> Result<Vec<Candle>, String> {
+        let token = self.get_token().await?;
+        let cfg = self.config()?;
+
+        let period = match interval {
+            "1w" => "W",
+            "1M" => "M",
+            _ => "D",
+        };
+
+        let today = Utc::now()
+            .with_timezone(&FixedOffset::east_opt(KST_OFFSET).unwrap())
+            .format("%Y%m%d")
+            .to_string();
+
+        // 시작일: 충분히 과거
+        let start_date = match interval {
+            "1M" => "19900101",
+            "1w" => "20000101",
+            _ => "20200101",
+        };
+
+        let url = format!(
+            "{}/uapi/domestic-stock/v1/quotations/inquire-daily-itemchartprice",
+            self.base_url
+        );
+
+        let resp = self
+            .client
+            .get(&url)
+            .header("authorization", format!("Bearer {}", token))
+            .header("appkey", &cfg.app_key)
+            .header("appsecret", &cfg.app_secret)
+            .header("tr_id", "FHKST03010100")
+            .header("content-type", "application/json; charset=utf-8")
+            .query(&[
+                ("fid_cond_mrkt_div_code", "J"),
+                ("fid_input_iscd", stock_code),
+                ("fid_input_date_1", start_date),
+                ("fid_input_date_2", &today),
+                ("fid_period_div_code", period),
+                ("fid_org_adj_prc", "0"),
+            ])
+            .send()
+            .await
+            .map_err(|e| format!("KIS daily API error: {}", e))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            return Err(format!("KIS daily API error ({}): {}", status, text));
+        }
+
+        let json: Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("KIS daily parse error: {}", e))?;
+
+        check_kis_error(&json)?;
+
+        let items = json["output2"]
+            .as_array()
+            .ok_or("KIS daily API: output2 missing")?;
+
+        let kst = FixedOffset::east_opt(KST_OFFSET).unwrap();
+        let mut candles: Vec<Candle> = items
+            .iter()
+            .filter_map(|item| {
+                let date_str = item["stck_bsop_date"].as_str()?;
+                let date = NaiveDate::parse_from_str(date_str, "%Y%m%d").ok()?;
+                let dt = date.and_hms_opt(9, 0, 0)?; // 장 시작 시각
+                let timestamp = kst.from_local_datetime(&dt).single()?.timestamp();
+
+                let open = parse_f64(item["stck_oprc"].as_str()?)?;
+                let high = parse_f64(item["stck_hgpr"].as_str()?)?;
+                let low = parse_f64(item["stck_lwpr"].as_str()?)?;
+                let close = parse_f64(item["stck_clpr"].as_str()?)?;
+                let volume = parse_f64(item["acml_vol"].as_str()?)?;
+
+                Some(Candle {
+                    time: timestamp,
+                    open,
+                    high,
+                    low,
+                    close,
+                    volume,
+                    session: None,
+                    flow: None,
+                })
+            })
+            .collect();
+
+        // API returns newest first → sort ascending
+        candles.sort_by_key(|c| c.time);
+
+        // Trim to requested limit
+        if candles.len() > limit as usize {
+            candles = candles.split_off(candles.len() - limit as usize);
+        }
+
+        if candles.is_empty() {
+            return Err("KIS daily API: 캔들 데이터가 없습니다".to_string());
+        }
+
+        Ok(candles)
+    }
+
+    // ── Minute candles ──────────────────────────────────────────────
+    // tr_id: FHKST03010230  (주식일별분봉조회, 당일 포함 과거 일자)
+
+    /// 최근 `limit`개(또는 `since`(초) 이후) 국내 분봉을 1분봉에서 `interval` 봉으로 합친다.
+    async fn fetch_kr_minute_candles(
+        &self,
+        stock_code: &str,
+        interval: &str,
+        since: Option<i64>,
+        limit: u32,
+    ) -> Result<Vec<Candle>, String> {
+        let minutes = minute_step(interval);
+        // 1분봉을 합치는 주기는 페이지 상한 안에서 채울 수 있는 만큼만 요청한다
+        let needed = (limit as usize).min(kr_minute_bar_cap(minutes));
+        let minute_candles = self
+            .fetch_kr_minutes(stock_code, minutes, since, None, needed)
+            .await?;
+
+        let mut all_candles = aggregate_minutes(&minute_candles, minutes);
+        if let Some(since) = since {
+            all_candles.retain(|c| c.time >= since);
+        }
+        if all_candles.len() > needed {
+            all_candles = all_candles.split_off(all_candles.len() - needed);
+        }
+
+        if all_candles.is_empty() {
+            return Err("KIS minute API: 캔들 데이터가 없습니다".to_string());
+        }
+
+        Ok(all_candles)
+    }
+
+    /// `[start, end]`(초) 구간의 국내 분봉 (백필용). 페이지 상한에 걸리면 구간 뒤쪽부터 채워지고,
+    /// 휴장일만 걸친 구간은 빈 결과를 돌려준다.
+    async fn fetch_kr_minute_range(
+        &self,
+        stock_code: &str,
+        interval: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<Candle>, String> {
+        let minutes = minute_step(interval);
+        let minute_candles = self
+            .fetch_kr_minutes(stock_code, minutes, Some(start), Some(end), usize::MAX)
+            .await?;
+
+        let mut candles = aggregate_minutes(&minute_candles, minutes);
+        candles.retain(|c| c.time >= start && c.time <= end);
+        Ok(candles)
+    }
+
+    /// 1분봉을 (일자, 시각) 커서로 `until`(초, 없으면 오늘 장 마감 뒤)부터 거슬러 올라가며 받는다.
+    /// 전 거래일로 넘어가며 `since`(초)에 닿거나 `minutes`분 봉 `needed`개를 채울 때까지 반복한다.
+    async fn fetch_kr_minutes(
+        &self,
+        stock_code: &str,
+        minutes: i64,
+        since: Option<i64>,
+        until: Option<i64>,
+        needed: usize,
+    ) -> Result<Vec<Candle>, String> {
+        let kst = FixedOffset::east_opt(KST_OFFSET).unwrap();
+        let session_open = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+        let after_close = NaiveTime::from_hms_opt(16, 0, 0).unwrap(); // 장 마감 후 시작
+        let today = Utc::now()
+            .with_timezone(&kst)
+            .date_naive()
+            .and_time(after_close);
+        let mut cursor = until
+            .and_then(|until| kst.timestamp_opt(until, 0).single())
+            .map_or(today, |until| until.naive_local().min(today));
+        if cursor.time() < session_open {
+            cursor = (cursor.date() - Duration::days(1)).and_time(after_close);
+        }
+
+        let bucket_seconds = minutes * 60;
+        let mut minute_candles: Vec<Candle> = Vec::new();
+        let mut buckets = HashSet::new();
+        let mut empty_days = 0;
+
+        for _ in 0..MAX_KR_MINUTE_PAGES {
+            // 가장 오래된 봉은 중간부터 받았을 수 있어 하나 더 채운다
+            if buckets.len() > needed {
+                break;
+            }
+            if since.is_some_and(|since| {
+                kst.from_local_datetime(&cursor)
+                    .single()
+                    .is_some_and(|t| t.timestamp() < since)
+            }) {
+                break;
+            }
+
+            let date = cursor.format("%Y%m%d").to_string();
+            let hour = cursor.format("%H%M%S").to_string();
+            let json = self
+                .get_quotation(
+                    "/uapi/domestic-stock/v1/quotations/inquire-time-dailychartprice",
+                    "FHKST03010230",
+                    &[
+                        ("FID_COND_MRKT_DIV_CODE", "J"),
+                        ("FID_INPUT_ISCD", stock_code),
+                        ("FID_INPUT_HOUR_1", hour.as_str()),
+                        ("FID_INPUT_DATE_1", date.as_str()),
+                        ("FID_PW_DATA_INCU_YN", "Y"),
+                        ("FID_FAKE_TICK_INCU_YN", ""),
+                    ],
+                )
+                .await?;
+
+            let items = json["output2"].as_array().cloned().unwrap_or_default();
+            let mut oldest: Option<NaiveDateTime> = None;
+            for item in &items {
+                let parsed = (|| {
+                    let dt = NaiveDateTime::parse_from_str(
+                        &format!(
+                            "{}{}",
+                            item["stck_bsop_date"].as_str()?,
+                            item["stck_cntg_hour"].as_str()?
+                        ),
+                        "%Y%m%d%H%M%S",
+                    )
+                    .ok()?;
+                    let candle = Candle {
+                        time: kst.from_local_datetime(&dt).single()?.timestamp(),
+                        open: parse_f64(item["stck_oprc"].as_str()?)?,
+                        high: parse_f64(item["stck_hgpr"].as_str()?)?,
+                        low: parse_f64(item["stck_lwpr"].as_str()?)?,
+                        close: parse_f64(item["stck_prpr"].as_str()?)?,
+                        volume: item["cntg_vol"].as_str().and_then(parse_f64).unwrap_or(0.0),
+                        session: None,
+                        flow: None,
+                    };
+                    Some((candle, dt))
+                })();
+                if let Some((candle, dt)) = parsed {
+                    buckets.insert(candle.time.div_euclid(bucket_seconds));
+                    minute_candles.push(candle);
+                    oldest = Some(oldest.map_or(dt, |o: NaiveDateTime| o.min(dt)));
+                }
+            }
+
+            match oldest {
+                // 커서보다 뒤의 봉만 돌아오면 더 거슬러 올라갈 수 없다
+                Some(oldest) if oldest > cursor => break,
+                Some(oldest) => {
+                    empty_days = 0;
+                    let reached = kst.from_local_datetime(&oldest).single();
+                    if since.is_some_and(|since| reached.is_some_and(|t| t.timestamp() <= since)) {
+                        break;
+                    }
+                    cursor = oldest - Duration::minutes(1);
+                }
+                // 휴장일은 건너뛰되 연휴보다 길게 비면 보관 기간이 끝난 것으로 본다
+                None => {
+                    empty_days += 1;
+                    if empty_days > MAX_EMPTY_DAYS {
+                        break;
+                    }
+                    cursor = cursor.date().and_time(session_open) - Duration::minutes(1);
+                }
+            }
+            // 정규장 시작 전으로 넘어가면 전 일자의 장 마감 뒤로 옮긴다
+            if cursor.time() < session_open {
+                cursor = (cursor.date() - Duration::days(1)).and_time(after_close);
+            }
+
+            tokio::time::sleep(self.page_delay()).await;
+        }
+
+        minute_candles.sort_by_key(|c| c.time);
+        minute_candles.dedup_by_key(|c| c.time);
+        Ok(minute_candles)
+    }
+
+    // ── Overseas (US) candles ───────────────────────────────────────
+
+    /// 거래소를 모르는 티커는 찾은 적 있는 거래소, 없으면 NAS → NYS → AMS 순으로 조회한다.
+    async fn fetch_overseas_klines(
+        &self,
+        ticker: &str,
+        exchange: Option<&'static str>,
+        interval: &str,
+        limit: u32,
+    ) -> Result<Vec<Candle>, String> {
+        let known = match exchange {
+            Some(exchange) => Some(exchange),
+            None => self.us_exchanges.read().await.get(ticker).copied(),
+        };
+        let candidates = match known {
+            Some(exchange) => vec![exchange],
+            None => US_EXCHANGES.to_vec(),
+        };
+
+        let mut errors = Vec::new();
+        for exchange in candidates {
+            let fetched = match interval {
+                "1m" | "5m" | "15m" | "30m" | "1h" => {
+                    self.fetch_us_minute_candles(exchange, ticker, interval, limit)
+                        .await
+                }
+                _ => {
+                    self.fetch_us_daily_candles(exchange, ticker, interval, limit)
+                        .await
+                }
+            };
+            match fetched {
+                Ok(candles) => {
+                    self.us_exchanges
+                        .write()
+                        .await
+                        .insert(ticker.to_string(), exchange);
+                    return Ok(candles);
+                }
+                Err(error) => errors.push(format!("{}: {}", exchange, error)),
+            }
+        }
+        Err(errors.join(" | "))
+    }
+
+    // tr_id: HHDFS76240000  (해외주식 기간별시세 일/주/월)
+
+    async fn fetch_us_daily_candles(
+        &self,
+        exchange: &str,
+        ticker: &str,
+        interval: &str,
+        limit: u32,
+    ) -> Result<Vec<Candle>, String> {
+        let period = match interval {
+            "1w" => "1",
+            "1M" => "2",
+            _ => "0",
+        };
+
+        let mut all_candles: Vec<Candle> = Vec::new();
+        // 기준일자 이전 100건씩 반환한다 (비우면 오늘)
+        let mut cursor_date = String::new();
+        let needed = limit as usize;
+
+        for _ in 0..MAX_OVERSEAS_DAILY_PAGES {
+            if all_candles.len() >= needed {
+                break;
+            }
+
+            let json = self
+                .get_quotation(
+                    "/uapi/overseas-price/v1/quotations/dailyprice",
+                    "HHDFS76240000",
+                    &[
+                        ("AUTH", ""),
+                        ("EXCD", exchange),
+                        ("SYMB", ticker),
+                        ("GUBN", period),
+                        ("BYMD", cursor_date.as_str()),
+                        ("MODP", "1"), // 수정주가 반영 (분할 보정은 Yahoo 시세와 같은 기준)
+                    ],
+                )
+                .await?;
+
+            let items = json["output2"].as_array().cloned().unwrap_or_default();
+            let mut oldest: Option<NaiveDate> = None;
+            for item in &items {
+                let Some(date) = item["xymd"]
+                    .as_str()
+                    .and_then(|s| NaiveDate::parse_from_str(s, "%Y%m%d").ok())
+                else {
+                    continue;
+                };
+                let candle = (|| {
+                    Some(Candle {
+                        time: session::market_open(date, &MarketType::UsStock),
+                        open: parse_f64(item["open"].as_str()?)?,
+                        high: parse_f64(item["high"].as_str()?)?,
+                        low: parse_f64(item["low"].as_str()?)?,
+                        close: parse_f64(item["clos"].as_str()?)?,
+                        volume: parse_f64(item["tvol"].as_str()?)?,
+                        session: None,
+                        flow: None,
+                    })
+                })();
+                if let Some(candle) = candle {
+                    all_candles.push(candle);
+                    oldest = Some(oldest.map_or(date, |o: NaiveDate| o.min(date)));
+                }
+            }
+
+            // 빈 페이지면 더 이상 과거 데이터가 없음
+            let Some(oldest) = oldest else {
+                break;
+            };
+            cursor_date = (oldest - Duration::days(1)).format("%Y%m%d").to_string();
+
+            tokio::time::sleep(self.page_delay()).await;
+        }
+
+        all_candles.sort_by_key(|c| c.time);
+        all_candles.dedup_by_key(|c| c.time);
+        if all_candles.len() > needed {
+            all_candles = all_candles.split_off(all_candles.len() - needed);
+        }
+
+        if all_candles.is_empty() {
+            return Err("KIS overseas daily API: 캔들 데이터가 없습니다".to_string());
+        }
+
+        Ok(all_candles)
+    }
+
+    // tr_id: HHDFS76950200  (해외주식 분봉조회)
+
+    async fn fetch_us_minute_candles(
+        &self,
+        exchange: &str,
+        ticker: &str,
+        interval: &str,
+        limit: u32,
+    ) -> Result<Vec<Candle>, String> {
+        let minutes: i64 = match interval {
+            "5m" => 5,
+            "15m" => 15,
+            "30m" => 30,
+            "1h" => 60,
+            _ => 1,
+        };
+        let minutes_param = minutes.to_string();
+
+        let kst = FixedOffset::east_opt(KST_OFFSET).unwrap();
+        let mut all_candles: Vec<Candle> = Vec::new();
+        // 다음 조회키: 직전 페이지의 가장 오래된 봉보다 한 주기 앞선 현지 시각
+        let mut next_key: Option<String> = None;
+        let needed = limit as usize;
+
+        for _ in 0..MAX_OVERSEAS_MINUTE_PAGES {
+            if all_candles.len() >= needed {
+                break;
+            }
+
+            let json = self
+                .get_quotation(
+                    "/uapi/overseas-price/v1/quotations/inquire-time-itemchartprice",
+                    "HHDFS76950200",
+                    &[
+                        ("AUTH", ""),
+                        ("EXCD", exchange),
+                        ("SYMB", ticker),
+                        ("NMIN", minutes_param.as_str()),
+                        ("PINC", "1"), // 전일 포함
+                        ("NEXT", if next_key.is_some() { "1" } else { "" }),
+                        ("NREC", "120"),
+                        ("FILL", ""),
+                        ("KEYB", next_key.as_deref().unwrap_or("")),
+                    ],
+                )
+                .await?;
+
+            let items = json["output2"].as_array().cloned().unwrap_or_default();
+            let mut oldest_local: Option<NaiveDateTime> = None;
+            for item in &items {
+                // 한국 시각(kymd/khms)으로 타임스탬프를, 현지 시각(xymd/xhms)으로 다음 조회키를 만든다
+                let parsed = (|| {
+                    let korean = NaiveDateTime::parse_from_str(
+                        &format!("{}{}", item["kymd"].as_str()?, item["khms"].as_str()?),
+                        "%Y%m%d%H%M%S",
+                    )
+                    .ok()?;
+                    let local = NaiveDateTime::parse_from_str(
+                        &format!("{}{}", item["xymd"].as_str()?, item["xhms"].as_str()?),
+                        "%Y%m%d%H%M%S",
+                    )
+                    .ok()?;
+                    let candle = Candle {
+                        time: kst.from_local_datetime(&korean).single()?.timestamp(),
+                        open: parse_f64(item["open"].as_str()?)?,
+                        high: parse_f64(item["high"].as_str()?)?,
+                        low: parse_f64(item["low"].as_str()?)?,
+                        close: parse_f64(item["last"].as_str()?)?,
+                        volume: item["evol"].as_str().and_then(parse_f64).unwrap_or(0.0),
+                        session: None,
+                        flow: None,
+                    };
+                    Some((candle, local))
+                })();
+                if let Some((candle, local)) = parsed {
+                    all_candles.push(candle);
+                    oldest_local =
+                        Some(oldest_local.map_or(local, |o: NaiveDateTime| o.min(local)));
+                }
+            }
+
+            let more = json["output1"]["more"].as_str() == Some("Y");
+            let Some(oldest_local) = oldest_local.filter(|_| more) else {
+                break;
+            };
+            next_key = Some(
+                (oldest_local - Duration::minutes(minutes))
+                    .format("%Y%m%d%H%M%S")
+                    .to_string(),
+            );
+
+            tokio::time::sleep(self.page_delay()).await;
+        }
+
+        all_candles.sort_by_key(|c| c.time);
+        all_candles.dedup_by_key(|c| c.time);
+        if all_candles.len() > needed {
+            all_candles = all_candles.split_off(all_candles.len() - needed);
+        }
+
+        if all_candles.is_empty() {
+            return Err("KIS overseas minute API: 캔들 데이터가 없습니다".to_string());
+        }
+
+        Ok(all_candles)
+    }
+
+    // ── Investor flow ───────────────────────────────────────────────
+    // tr_id: FHPTJ04160001  (종목별 투자자매매동향 일별)
+
+    /// 국내 종목의 투자자별 일별 순매수 (시간 오름차순).
+    /// 기준일을 가장 오래된 행의 전날로 옮겨 가며 `since`(초)가 속한 날까지 받는다.
+    /// 당일 행은 장중 잠정치라 장 마감 후 값이 바뀔 수 있다.
+    pub async fn fetch_investor_flow(
+        &self,
+        symbol: &str,
+        since: i64,
+    ) -> Result<Vec<InvestorNetBuy>, String> {
+        let KisSymbol::Domestic(stock_code) = parse_symbol(symbol)? else {
+            return Err(format!(
+                "투자자별 매매동향은 국내 종목만 조회할 수 있습니다: {}",
+                symbol
+            ));
+        };
+
+        let kst = FixedOffset::east_opt(KST_OFFSET).unwrap();
+        let since_date = Utc
+            .timestamp_opt(since, 0)
+            .single()
+            .ok_or("KIS investor flow: invalid since")?
+            .with_timezone(&kst)
+            .date_naive();
+        let mut cursor = Utc::now().with_timezone(&kst).date_naive();
+        let mut days: BTreeMap<NaiveDate, InvestorNetBuy> = BTreeMap::new();
+
+        for _ in 0..MAX_INVESTOR_FLOW_PAGES {
+            let date = cursor.format("%Y%m%d").to_string();
+            let json = self
+                .get_quotation(
+                    "/uapi/domestic-stock/v1/quotations/investor-trade-by-stock-daily",
+                    "FHPTJ04160001",
+                    &[
+                        ("FID_COND_MRKT_DIV_CODE", "J"),
+                        ("FID_INPUT_ISCD", &stock_code),
+                        ("FID_INPUT_DATE_1", &date),
+                        ("FID_ORG_ADJ_PRC", ""),
+                        ("FID_ETC_CLS_CODE", ""),
+                    ],
+                )
+                .await?;
+            let items = json["output2"]
+                .as_array()
+                .ok_or("KIS investor flow API: output2 missing")?;
+
+            let mut oldest: Option<NaiveDate> = None;
+            for (date, day) in items.iter().filter_map(parse_investor_day) {
+                oldest = Some(oldest.map_or(date, |o| o.min(date)));
+                days.insert(date, day);
+            }
+            // `since` 날짜에 닿았거나 기준일보다 최근 행만 와서 진행이 없으면 멈춘다
+            match oldest {
+                Some(oldest) if oldest > since_date && oldest <= cursor => {
+                    cursor = oldest - Duration::days(1);
+                }
+                _ => break,
+            }
+        }
+
+        if days.is_empty() {
+            return Err("KIS investor flow API: 매매동향 데이터가 없습니다".to_string());
+        }
+        Ok(days.split_off(&since_date).into_values().collect())
+    }
+
+    /// 시세 조회 GET 공통 처리 (인증 헤더, HTTP/KIS 오류 확인).
+    async fn get_quotation(
+        &self,
+        path: &str,
+        tr_id: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Value, String> {
+        let token = self.get_token().await?;
+        let cfg = self.config()?;
+
+        let resp = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .header("authorization", format!("Bearer {}", token))
+            .header("appkey", &cfg.app_key)
+            .header("appsecret", &cfg.app_secret)
+            .header("tr_id", tr_id)
+            .header("content-type", "application/json; charset=utf-8")
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| format!("KIS {} API error: {}", tr_id, e))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            return Err(format!("KIS {} API error ({}): {}", tr_id, status, text));
+        }
+
+        let json: Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("KIS {} parse error: {}", tr_id, e))?;
+
+        check_kis_error(&json)?;
+        Ok(json)
+    }
+}
+
+impl MarketDataProvider for KisClient {
+    fn id(&self) -> &'static str {
+        "kis"
+    }
+
+    fn name(&self) -> &'static str {
+        "한국투자증권"
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            markets: vec![MarketType::KrStock, MarketType::UsStock],
+            intervals: ["1m", "5m", "15m", "30m", "1h", "1d", "1w", "1M"]
+                .map(String::from)
+                .to_vec(),
+            max_bars: 1_500,
+            // 국내 분봉만 (일자, 시각) 커서로 과거 구간을 받을 수 있다
+            supports_range: true,
+            requests_per_minute: self
+                .config
+                .as_ref()
+                .map(|cfg| cfg.environment.requests_per_minute()),
+        }
+    }
+
+    fn supports_symbol(&self, symbol: &str) -> bool {
+        parse_symbol(symbol).is_ok()
+    }
+
+    fn supports_range(&self, symbol: &str, interval: &str) -> bool {
+        matches!(parse_symbol(symbol), Ok(KisSymbol::Domestic(_)))
+            && matches!(interval, "1m" | "5m" | "15m" | "30m" | "1h")
+    }
+
+    /// 자격 증명이 없으면 국내/미국 주식 모두 다른 공급자로 넘긴다
+    fn is_available(&self) -> bool {
+        self.config.is_some()
+    }
+
+    fn fetch_klines<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        limit: u32,
+    ) -> CandleFuture<'a> {
+        Box::pin(KisClient::fetch_klines(self, symbol, interval, limit))
+    }
+
+    fn fetch_klines_since<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        since: i64,
+        limit: u32,
+    ) -> CandleFuture<'a> {
+        Box::pin(KisClient::fetch_klines_since(
+            self, symbol, interval, since, limit,
+        ))
+    }
+
+    fn fetch_klines_range<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        start: i64,
+        end: i64,
+    ) -> CandleFuture<'a> {
+        Box::pin(KisClient::fetch_klines_range(
+            self, symbol, interval, start, end,
+        ))
+    }
+}
+
+// ── Helpers ─────────────────────────────────────────────────────────
+
+enum KisSymbol {
+    /// 국내 종목코드 (KOSPI/KOSDAQ 모두 시장 구분 `J`로 조회한다)
+    Domestic(String),
+    /// 미국 티커와 거래소 코드 (모르면 None)
+    Overseas {
+        ticker: String,
+        exchange: Option<&'static str>,
+    },
+}
+
+/// "005930.KS", "035720.KQ" → 국내 종목코드,
+/// "AAPL" → 미국 (거래소 미정), "IBM.NYS" → 미국 NYS, "BRK-B" → 미국 "BRK/B"
+fn parse_symbol(symbol: &str) -> Result<KisSymbol, String> {
+    let symbol = symbol.trim().to_uppercase();
+    let (ticker, suffix) = match symbol.split_once('.') {
+        Some((ticker, suffix)) => (ticker, Some(suffix)),
+        None => (symbol.as_str(), None),
+    };
+
+    if matches!(suffix, Some("KS" | "KQ")) {
+        if ticker.is_empty() || !ticker.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!("Invalid KR symbol format: {}", symbol));
+        }
+        return Ok(KisSymbol::Domestic(ticker.to_string()));
+    }
+
+    let exchange = match suffix {
+        None => None,
+        Some(suffix) => Some(
+            US_EXCHANGES
+                .into_iter()
+                .find(|exchange| *exchange == suffix)
+                .ok_or_else(|| format!("Unsupported KIS symbol: {}", symbol))?,
+        ),
+    };
+    let starts_alpha = ticker.starts_with(|c: char| c.is_ascii_alphabetic());
+    let valid_chars = ticker
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if !starts_alpha || !valid_chars {
+        return Err(format!("Unsupported KIS symbol: {}", symbol));
+    }
+    // 클래스 주식은 KIS에서 `BRK/B`로 표기한다
+    Ok(KisSymbol::Overseas {
+        ticker: ticker.replace('-', "/"),
+        exchange,
+    })
+}
+
+/// 국내 분봉 주기의 분 수 (1분봉을 합칠 단위)
+fn minute_step(interval: &str) -> i64 {
+    match interval {
+        "5m" => 5,
+        "15m" => 15,
+        "30m" => 30,
+        "1h" | "60m" => 60,
+        _ => 1,
+    }
+}
+
+/// `minutes`분 봉으로 최근 구간을 받을 때 페이지 상한 안에서 채울 수 있는 최대 봉 수
+fn kr_minute_bar_cap(minutes: i64) -> usize {
+    let days = MAX_KR_MINUTE_PAGES / KR_PAGES_PER_DAY;
+    days * KR_SESSION_MINUTES.div_ceil(minutes.max(1) as usize)
+}
+
+/// 시간 오름차순 1분봉을 `minutes`분 봉으로 합친다.
+/// 정규장 시작(09:00 KST)이 00:00 UTC라 UTC 기준으로 잘라도 장 시작에 맞는다.
+fn aggregate_minutes(candles: &[Candle], minutes: i64) -> Vec<Candle> {
+    if minutes <= 1 {
+        return candles.to_vec();
+    }
+    let bucket_seconds = minutes * 60;
+
+    let mut output: Vec<Candle> = Vec::new();
+    for candle in candles {
+        let bucket_start = candle.time.div_euclid(bucket_seconds) * bucket_seconds;
+        match output.last_mut() {
+            Some(last) if last.time == bucket_start => {
+                last.high = last.high.max(candle.high);
+                last.low = last.low.min(candle.low);
+                last.close = candle.close;
+                last.volume += candle.volume;
+            }
+            _ => output.push(Candle {
+                time: bucket_start,
+                ..candle.clone()
+            }),
+        }
+    }
+    output
+}
+
+/// 투자자별 매매동향 한 행. 금액 필드는 백만원 단위로 온다
+fn parse_investor_day(item: &Value) -> Option<(NaiveDate, InvestorNetBuy)> {
+    let date = NaiveDate::parse_from_str(item["stck_bsop_date"].as_str()?, "%Y%m%d").ok()?;
+    let quantity = |key: &str| item[key].as_str().and_then(parse_f64);
+    let amount = |key: &str| quantity(key).map(|value| value * 1_000_000.0);
+
+    Some((
+        date,
+        InvestorNetBuy {
+            time: session::market_open(date, &MarketType::KrStock),
+            individual: quantity("prsn_ntby_qty")?,
+            foreign: quantity("frgn_ntby_qty")?,
+            institution: quantity("orgn_ntby_qty")?,
+            individual_amount: amount("prsn_ntby_tr_pbmn")?,
+            foreign_amount: amount("frgn_ntby_tr_pbmn")?,
+            institution_amount: amount("orgn_ntby_tr_pbmn")?,
+        },
+    ))
+}
+
+fn parse_f64(s: &str) -> Option<f64> {
+    let trimmed = s.trim();
+    if trimmed.is_empty() || trimmed == "0" {
+        return Some(0.0);
+    }
+    trimmed.parse::<f64>().ok()
+}
+
+fn check_kis_error(json: &Value) -> Result<(), String> {
+    let rt_cd = json["rt_cd"].as_str().unwrap_or("1");
+    if rt_cd != "0" {
+        let msg_cd = json["msg_cd"].as_str().unwrap_or("");
+        let msg1 = json["msg1"].as_str().unwrap_or("Unknown error");
+        return Err(format!("KIS API error [{}]: {}", msg_cd, msg1));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex, OnceLock};
+
+    use serde_json::Value;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::sync::RwLock;
+
+    use super::{
+        aggregate_minutes, kr_minute_bar_cap, parse_symbol, CachedToken, KisClient, KisConfig,
+        KisEnvironment, KisSymbol, TokenStore, MAX_EMPTY_DAYS,
+    };
+    use crate::test_support::candle_from;
+
+    type Query = HashMap<String, String>;
+
+    fn client(base_url: String) -> KisClient {
+        KisClient {
+            client: reqwest::Client::new(),
+            base_url,
+            ws_url: String::new(),
+            config: Some(KisConfig {
+                app_key: "app-key".to_string(),
+                app_secret: "app-secret".to_string(),
+                environment: KisEnvironment::Real,
+            }),
+            token: Arc::new(RwLock::new(Some(CachedToken {
+                access_token: "token".to_string(),
+                expires_at: 4_000_000_000,
+                retry_at: 0,
+            }))),
+            token_store: Arc::new(OnceLock::new()),
+            us_exchanges: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Answers every request with `respond(query)` and records the queries in order.
+    async fn quote_server(
+        respond: impl Fn(&Query) -> Value + Send + 'static,
+    ) -> (String, Arc<Mutex<Vec<Query>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&requests);
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                // Headers, then a `Content-Length` body (token requests are POSTs)
+                loop {
+                    let read = socket.read(&mut buf).await.unwrap();
+                    if read == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..read]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head
+                            .lines()
+                            .find_map(|line| {
+                                let line = line.to_ascii_lowercase();
+                                line.strip_prefix("content-length:")
+                                    .map(|value| value.trim().parse::<usize>().unwrap())
+                            })
+                            .unwrap_or(0);
+                        if body.len() >= length {
+                            break;
+                        }
+                    }
+                }
+                let head = String::from_utf8_lossy(&request);
+                let target = head.split_whitespace().nth(1).unwrap_or("/");
+                let query: Query = reqwest::Url::parse(&format!("http://local{}", target))
+                    .unwrap()
+                    .query_pairs()
+                    .into_owned()
+                    .collect();
+                let body = respond(&query).to_string();
+                recorded.lock().unwrap().push(query);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (format!("http://{}", addr), requests)
+    }
+
+    #[tokio::test]
+    async fn a_token_saved_with_other_credentials_is_issued_again() {
+        let (base_url, requests) =
+            quote_server(|_| serde_json::json!({ "access_token": "fresh", "expires_in": 86_400 }))
+                .await;
+        let root = std::env::temp_dir().join(format!("kis-token-refresh-{}", std::process::id()));
+        let (data_dir, key_dir) = (root.join("data"), root.join("config"));
+        let other = KisConfig {
+            app_key: "other-key".to_string(),
+            app_secret: "other-secret".to_string(),
+            environment: KisEnvironment::Real,
+        };
+        TokenStore::new(&other, &data_dir, &key_dir)
+            .save(&CachedToken {
+                access_token: "stale".to_string(),
+                expires_at: 4_000_000_000,
+                retry_at: 0,
+            })
+            .unwrap();
+        let mut kis = client(base_url);
+        kis.token = Arc::new(RwLock::new(None));
+        kis.set_token_dirs(&data_dir, &key_dir);
+
+        let token = kis.get_token().await;
+        let saved = kis.token_store.get().unwrap().load();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(token.unwrap(), "fresh");
+        assert_eq!(requests.lock().unwrap().len(), 1);
+        // The new token replaces the unreadable file
+        assert_eq!(saved.unwrap().access_token, "fresh");
+    }
+
+    fn domestic(symbol: &str) -> Option<String> {
+        match parse_symbol(symbol) {
+            Ok(KisSymbol::Domestic(code)) => Some(code),
+            _ => None,
+        }
+    }
+
+    fn overseas(symbol: &str) -> Option<(String, Option<&'static str>)> {
+        match parse_symbol(symbol) {
+            Ok(KisSymbol::Overseas { ticker, exchange }) => Some((ticker, exchange)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn kr_suffixes_parse_as_domestic_codes() {
+        assert_eq!(domestic("005930.KS").as_deref(), Some("005930"));
+        assert_eq!(domestic(" 035720.kq ").as_deref(), Some("035720"));
+        assert!(parse_symbol(".KS").is_err());
+        assert!(parse_symbol("0059-30.KS").is_err());
+    }
+
+    #[test]
+    fn us_tickers_keep_an_explicit_exchange_and_use_kis_class_notation() {
+        assert_eq!(overseas("aapl"), Some(("AAPL".to_string(), None)));
+        assert_eq!(overseas("IBM.NYS"), Some(("IBM".to_string(), Some("NYS"))));
+        assert_eq!(overseas("BRK-B"), Some(("BRK/B".to_string(), None)));
+    }
+
+    #[test]
+    fn unsupported_symbols_are_rejected() {
+        for symbol in ["7203.T", "AAPL.NYSE", "1AAPL", "BTC/USDT", "KRW-BTC.KS", ""] {
+            assert!(
+                parse_symbol(symbol).is_err(),
+                "{} should be rejected",
+                symbol
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_us_tickers_fall_back_through_the_exchanges_and_remember_the_hit() {
+        let (base_url, requests) = quote_server(|query| match query["EXCD"].as_str() {
+            // NAS has no such ticker, NYS rejects it, AMS lists it
+            "NAS" => serde_json::json!({ "rt_cd": "0", "output2": [] }),
+            "NYS" => serde_json::json!({ "rt_cd": "1", "msg_cd": "E1", "msg1": "no symbol" }),
+            _ => serde_json::json!({
+                "rt_cd": "0",
+                "output2": [{
+                    "xymd": "20240304", "open": "10", "high": "11",
+                    "low": "9", "clos": "10.5", "tvol": "1000"
+                }]
+            }),
+        })
+        .await;
+        let kis = client(base_url);
+
+        let candles = kis.fetch_klines("XYZ", "1d", 1).await.unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0].close, 10.5);
+        let exchanges = |requests: &Mutex<Vec<Query>>| -> Vec<String> {
+            requests
+                .lock()
+                .unwrap()
+                .drain(..)
+                .map(|query| query["EXCD"].clone())
+                .collect()
+        };
+        assert_eq!(exchanges(&requests), ["NAS", "NYS", "AMS"]);
+
+        kis.fetch_klines("XYZ", "1d", 1).await.unwrap();
+        assert_eq!(exchanges(&requests), ["AMS"]);
+    }
+
+    /// 2024-03-04 09:00:00 KST (a Monday)
+    const MONDAY_OPEN: i64 = 1_709_510_400;
+    const DAY: i64 = 86_400;
+
+    /// One FHKST03010230 page: the newest 120 session minutes (09:00–15:30) at or before
+    /// the cursor, newest first. Each bar's open is its minute of the day.
+    fn minute_page(query: &Query, trading: bool) -> Value {
+        if !trading {
+            return serde_json::json!({ "rt_cd": "0", "output2": [] });
+        }
+        let date = &query["FID_INPUT_DATE_1"];
+        let hour = &query["FID_INPUT_HOUR_1"];
+        let cursor: u32 =
+            hour[0..2].parse::<u32>().unwrap() * 60 + hour[2..4].parse::<u32>().unwrap();
+        let items: Vec<Value> = (9 * 60..=15 * 60 + 30)
+            .rev()
+            .filter(|minute| *minute <= cursor)
+            .take(120)
+            .map(|minute| {
+                serde_json::json!({
+                    "stck_bsop_date": date,
+                    "stck_cntg_hour": format!("{:02}{:02}00", minute / 60, minute % 60),
+                    "stck_oprc": minute.to_string(),
+                    "stck_hgpr": (minute + 1).to_string(),
+                    "stck_lwpr": (minute - 1).to_string(),
+                    "stck_prpr": minute.to_string(),
+                    "cntg_vol": "1",
+                })
+            })
+            .collect();
+        serde_json::json!({ "rt_cd": "0", "output2": items })
+    }
+
+    fn cursors(requests: &Mutex<Vec<Query>>) -> Vec<(String, String)> {
+        requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|query| {
+                (
+                    query["FID_INPUT_DATE_1"].clone(),
+                    query["FID_INPUT_HOUR_1"].clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn kr_minutes_page_back_from_the_oldest_bar_of_each_page() {
+        let (base_url, requests) = quote_server(|query| minute_page(query, true)).await;
+        let kis = client(base_url);
+
+        let candles = kis
+            .fetch_klines_range("005930.KS", "1m", MONDAY_OPEN, MONDAY_OPEN + 7 * 3_600)
+            .await
+            .unwrap();
+
+        assert_eq!(candles.len(), 391);
+        assert_eq!(candles[0].time, MONDAY_OPEN);
+        assert_eq!(candles[390].time, MONDAY_OPEN + 390 * 60);
+        assert!(candles
+            .windows(2)
+            .all(|pair| pair[1].time - pair[0].time == 60));
+        let expected = ["160000", "133000", "113000", "093000"]
+            .map(|hour| ("20240304".to_string(), hour.to_string()));
+        assert_eq!(cursors(&requests), expected);
+    }
+
+    #[tokio::test]
+    async fn kr_minutes_skip_market_holidays_to_the_previous_session() {
+        let (base_url, requests) = quote_server(|query| {
+            let trading = !matches!(query["FID_INPUT_DATE_1"].as_str(), "20240302" | "20240303");
+            minute_page(query, trading)
+        })
+        .await;
+        let kis = client(base_url);
+
+        // Friday 15:00 through Monday's close, across the weekend
+        let friday_close_auction = MONDAY_OPEN - 3 * DAY + 6 * 3_600;
+        let candles = kis
+            .fetch_klines_range(
+                "005930.KS",
+                "1m",
+                friday_close_auction,
+                MONDAY_OPEN + 7 * 3_600,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(candles.len(), 31 + 391);
+        assert_eq!(candles[0].time, friday_close_auction);
+        assert_eq!(candles[31].time, MONDAY_OPEN);
+        let dates: Vec<String> = cursors(&requests)
+            .into_iter()
+            .map(|(date, _)| date)
+            .collect();
+        assert_eq!(
+            dates,
+            ["20240304", "20240304", "20240304", "20240304", "20240303", "20240302", "20240301"]
+        );
+    }
+
+    #[tokio::test]
+    async fn kr_minutes_stop_after_too_many_empty_days() {
+        let (base_url, requests) = quote_server(|query| minute_page(query, false)).await;
+        let kis = client(base_url);
+
+        let candles = kis
+            .fetch_klines_range(
+                "005930.KS",
+                "1m",
+                MONDAY_OPEN - 60 * DAY,
+                MONDAY_OPEN + 7 * 3_600,
+            )
+            .await
+            .unwrap();
+
+        assert!(candles.is_empty());
+        assert_eq!(requests.lock().unwrap().len(), MAX_EMPTY_DAYS + 1);
+    }
+
+    #[tokio::test]
+    async fn latest_hourly_bars_stop_paging_once_the_buckets_are_filled() {
+        let (base_url, requests) = quote_server(|query| minute_page(query, true)).await;
+        let kis = client(base_url);
+
+        let candles = kis.fetch_klines("005930.KS", "1h", 7).await.unwrap();
+
+        // A full session of 1m pages, plus one page of the previous day to
+        // know the oldest hour is complete
+        assert_eq!(requests.lock().unwrap().len(), 5);
+        assert_eq!(candles.len(), 7);
+        assert_eq!(candles[0].time % DAY, 0);
+        assert_eq!(candles[0].open, 540.0);
+        assert_eq!(candles[0].volume, 60.0);
+        assert_eq!(candles[6].time, candles[0].time + 6 * 3_600);
+        assert_eq!(candles[6].volume, 31.0);
+    }
+
+    #[test]
+    fn aggregated_requests_are_capped_to_the_page_budget() {
+        // 50 sessions of 7 hourly bars fit in 200 pages; 1m bars never hit the cap
+        assert_eq!(kr_minute_bar_cap(60), 350);
+        assert!(kr_minute_bar_cap(1) >= 1_500);
+    }
+
+    #[test]
+    fn minutes_aggregate_into_utc_hour_buckets_aligned_to_the_kr_open() {
+        let minute = |offset: i64, open: f64, close: f64| {
+            candle_from(MONDAY_OPEN + offset * 60, open, close)
+        };
+        let minutes = [
+            minute(0, 100.0, 101.0),
+            minute(59, 103.0, 102.0),
+            minute(60, 102.0, 104.0),
+            minute(390, 105.0, 106.0),
+        ];
+
+        let hours = aggregate_minutes(&minutes, 60);
+
+        let times: Vec<i64> = hours.iter().map(|c| c.time).collect();
+        assert_eq!(
+            times,
+            [MONDAY_OPEN, MONDAY_OPEN + 3_600, MONDAY_OPEN + 6 * 3_600]
+        );
+        assert_eq!((hours[0].open, hours[0].close), (100.0, 102.0));
+        assert_eq!(hours[0].high, minutes[0].high.max(minutes[1].high));
+        assert_eq!(hours[0].volume, 2.0);
+        assert_eq!(hours[2].open, 105.0);
+    }
+}
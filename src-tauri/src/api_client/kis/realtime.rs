@@ -0,0 +1,782 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::sync::oneshot;
+use tokio_tungstenite::tungstenite::Message;
+
+use super::{parse_f64, parse_symbol, KisClient, KisSymbol, KST_OFFSET};
+use crate::models::{OrderBookLevel, OrderBookUpdate};
+
+/// 국내주식 실시간 체결가
+const TRADE_TR_ID: &str = "H0STCNT0";
+/// 국내주식 실시간 호가 (10단계)
+const ORDER_BOOK_TR_ID: &str = "H0STASP0";
+/// 한 세션에서 등록할 수 있는 실시간 항목 수. 종목마다 체결·호가 2건을 쓴다
+const MAX_REGISTRATIONS: usize = 41;
+const MAX_SYMBOLS: usize = MAX_REGISTRATIONS / 2;
+const ORDER_BOOK_DEPTH: usize = 10;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// 서버 PINGPONG은 수십 초 간격이라 이보다 오래 조용하면 연결이 죽은 것으로 본다
+const IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+/// 구독자가 모두 떠난 종목을 정리하는 주기
+const PRUNE_INTERVAL: Duration = Duration::from_secs(5);
+
+type Socket =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+type Subscribers = HashMap<String, Vec<UnboundedSender<RealtimeEvent>>>;
+
+/// 실시간 체결 한 건
+#[derive(Debug, Clone)]
+pub struct KisTrade {
+    /// 체결 시각 (unix 초)
+    pub time: i64,
+    pub price: f64,
+    pub volume: f64,
+    /// 매수 주문이 매도 호가를 받아 체결됐으면 true
+    pub buyer_initiated: bool,
+}
+
+/// 종목 구독자에게 보내는 실시간 알림
+#[derive(Debug, Clone)]
+pub enum RealtimeEvent {
+    Trade(KisTrade),
+    /// `symbol`은 종목코드 (구독자가 자신의 심볼로 바꿔 쓴다)
+    OrderBook(OrderBookUpdate),
+    /// 세션 연결 상태: "connecting" | "connected" | "reconnecting"
+    Status {
+        state: &'static str,
+        attempt: u32,
+        message: Option<String>,
+    },
+}
+
+struct SubscribeRequest {
+    code: String,
+    events: UnboundedSender<RealtimeEvent>,
+    reply: oneshot::Sender<Result<(), String>>,
+}
+
+/// 연결 하나와 그 연결에서 쓰는 접속키
+struct Session {
+    socket: Socket,
+    approval_key: String,
+}
+
+enum Disconnect {
+    /// 구독 요청 채널이 닫힘 (피드 종료)
+    Closed,
+    /// 남은 구독자가 없어 연결을 닫음
+    Idle,
+    /// 연결 끊김/오류 → 재연결
+    Lost(String),
+}
+
+/// KIS 실시간 시세 세션. appkey당 하나의 WebSocket 연결에 종목 구독을 모아 등록하고,
+/// 재연결·PINGPONG 응답·등록 한도를 여기서 처리한다.
+/// 연결은 첫 구독 때 열고, 구독자가 모두 떠나면 닫는다.
+#[derive(Clone)]
+pub struct KisFeed {
+    client: KisClient,
+    requests: Arc<Mutex<Option<UnboundedSender<SubscribeRequest>>>>,
+}
+
+impl KisFeed {
+    pub fn new(client: KisClient) -> Self {
+        Self {
+            client,
+            requests: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// 접속키와 보충 조회에 쓰는 REST 클라이언트
+    pub fn client(&self) -> &KisClient {
+        &self.client
+    }
+
+    /// 국내 종목(`005930.KS` 등)의 체결·호가를 구독한다.
+    /// 받는 쪽을 버리면 다음 정리 주기에 등록이 해제된다.
+    pub async fn subscribe(
+        &self,
+        symbol: &str,
+    ) -> Result<UnboundedReceiver<RealtimeEvent>, String> {
+        let code = match parse_symbol(symbol)? {
+            KisSymbol::Domestic(code) => code,
+            KisSymbol::Overseas { .. } => {
+                return Err(format!(
+                    "KIS 실시간 시세는 국내 주식만 지원합니다: {}",
+                    symbol
+                ))
+            }
+        };
+        self.client.config()?;
+
+        let (events, receiver) = mpsc::unbounded_channel();
+        let (reply, accepted) = oneshot::channel();
+        let ended = || "KIS 실시간 세션이 종료되었습니다".to_string();
+        self.session()?
+            .send(SubscribeRequest {
+                code,
+                events,
+                reply,
+            })
+            .map_err(|_| ended())?;
+        accepted.await.map_err(|_| ended())??;
+        Ok(receiver)
+    }
+
+    /// 세션 작업으로 가는 채널. 작업이 없거나 끝났으면 새로 띄운다.
+    fn session(&self) -> Result<UnboundedSender<SubscribeRequest>, String> {
+        let mut requests = self.requests.lock().map_err(|e| e.to_string())?;
+        if let Some(sender) = requests.as_ref().filter(|s| !s.is_closed()) {
+            return Ok(sender.clone());
+        }
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(run_session(self.client.clone(), receiver));
+        *requests = Some(sender.clone());
+        Ok(sender)
+    }
+}
+
+/// 구독이 있는 동안 연결을 유지한다.
+/// 끊기면 지수 백오프(1초 → 최대 60초)로 재연결하고 모든 종목을 다시 등록한다.
+async fn run_session(client: KisClient, mut requests: UnboundedReceiver<SubscribeRequest>) {
+    let mut subscribers: Subscribers = HashMap::new();
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 0u32;
+
+    loop {
+        // 구독이 없으면 연결하지 않고 요청을 기다린다
+        while subscribers.is_empty() {
+            let Some(request) = requests.recv().await else {
+                return;
+            };
+            accept(&mut subscribers, request);
+        }
+
+        broadcast_status(&mut subscribers, "connecting", attempt, None);
+        let reason = match connect(&client).await {
+            Ok(mut session) => {
+                broadcast_status(&mut subscribers, "connected", attempt, None);
+                let (received, disconnect) =
+                    pump(&mut session, &mut subscribers, &mut requests).await;
+                if received {
+                    backoff = INITIAL_BACKOFF;
+                    attempt = 0;
+                }
+                match disconnect {
+                    Disconnect::Closed => return,
+                    Disconnect::Idle => {
+                        let _ = session.socket.close(None).await;
+                        continue;
+                    }
+                    Disconnect::Lost(reason) => reason,
+                }
+            }
+            Err(error) => error,
+        };
+
+        attempt += 1;
+        broadcast_status(&mut subscribers, "reconnecting", attempt, Some(reason));
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+async fn connect(client: &KisClient) -> Result<Session, String> {
+    let approval_key = client.approval_key().await?;
+    let (socket, _) = tokio_tungstenite::connect_async(client.ws_url.as_str())
+        .await
+        .map_err(|e| format!("연결 실패: {}", e))?;
+    Ok(Session {
+        socket,
+        approval_key,
+    })
+}
+
+async fn pump(
+    session: &mut Session,
+    subscribers: &mut Subscribers,
+    requests: &mut UnboundedReceiver<SubscribeRequest>,
+) -> (bool, Disconnect) {
+    let codes: Vec<String> = subscribers.keys().cloned().collect();
+    for code in codes {
+        if let Err(error) = register(session, &code, true).await {
+            return (false, Disconnect::Lost(error));
+        }
+    }
+
+    let mut received = false;
+    let mut last_message = Instant::now();
+    let mut prune_timer = tokio::time::interval(PRUNE_INTERVAL);
+
+    loop {
+        tokio::select! {
+            message = session.socket.next() => {
+                let message = match message {
+                    None => {
+                        return (
+                            received,
+                            Disconnect::Lost("서버가 연결을 닫았습니다".to_string()),
+                        )
+                    }
+                    Some(Err(error)) => return (received, Disconnect::Lost(error.to_string())),
+                    Some(Ok(message)) => message,
+                };
+                last_message = Instant::now();
+
+                let text = match message {
+                    Message::Text(text) => text,
+                    Message::Close(frame) => {
+                        let reason = frame
+                            .map(|f| f.reason.to_string())
+                            .filter(|r| !r.is_empty())
+                            .unwrap_or_else(|| "서버가 연결을 닫았습니다".to_string());
+                        return (received, Disconnect::Lost(reason));
+                    }
+                    // Ping에 대한 Pong은 tungstenite가 다음 읽기 때 자동으로 보낸다
+                    _ => continue,
+                };
+                received = true;
+
+                if let Err(error) = handle_text(session, &text, subscribers).await {
+                    return (received, Disconnect::Lost(error));
+                }
+            }
+            request = requests.recv() => {
+                let Some(request) = request else {
+                    return (received, Disconnect::Closed);
+                };
+                let code = request.code.clone();
+                let events = request.events.clone();
+                if accept(subscribers, request) {
+                    if let Err(error) = register(session, &code, true).await {
+                        return (received, Disconnect::Lost(error));
+                    }
+                }
+                // 이미 연결된 세션에 합류했으므로 바로 보충을 시작할 수 있게 알린다
+                if subscribers.contains_key(&code) {
+                    let _ = events.send(RealtimeEvent::Status {
+                        state: "connected",
+                        attempt: 0,
+                        message: None,
+                    });
+                }
+            }
+            _ = prune_timer.tick() => {
+                if last_message.elapsed() > IDLE_TIMEOUT {
+                    return (received, Disconnect::Lost("수신 시간 초과".to_string()));
+                }
+                for code in prune(subscribers) {
+                    if let Err(error) = register(session, &code, false).await {
+                        return (received, Disconnect::Lost(error));
+                    }
+                }
+                if subscribers.is_empty() {
+                    return (received, Disconnect::Idle);
+                }
+            }
+        }
+    }
+}
+
+/// 구독자를 추가한다. 새로 등록해야 하는 종목이면 true.
+fn accept(subscribers: &mut Subscribers, request: SubscribeRequest) -> bool {
+    let is_new = !subscribers.contains_key(&request.code);
+    if is_new && subscribers.len() >= MAX_SYMBOLS {
+        let _ = request.reply.send(Err(format!(
+            "KIS 실시간 시세는 한 세션에 {}종목까지 구독할 수 있습니다",
+            MAX_SYMBOLS
+        )));
+        return false;
+    }
+    subscribers
+        .entry(request.code)
+        .or_default()
+        .push(request.events);
+    let _ = request.reply.send(Ok(()));
+    is_new
+}
+
+/// 받는 쪽이 모두 사라진 종목을 빼고 그 종목코드를 돌려준다.
+fn prune(subscribers: &mut Subscribers) -> Vec<String> {
+    let mut removed = Vec::new();
+    subscribers.retain(|code, senders| {
+        senders.retain(|sender| !sender.is_closed());
+        if senders.is_empty() {
+            removed.push(code.clone());
+        }
+        !senders.is_empty()
+    });
+    removed
+}
+
+fn dispatch(subscribers: &mut Subscribers, code: &str, event: RealtimeEvent) {
+    if let Some(senders) = subscribers.get_mut(code) {
+        senders.retain(|sender| sender.send(event.clone()).is_ok());
+    }
+}
+
+fn broadcast_status(
+    subscribers: &mut Subscribers,
+    state: &'static str,
+    attempt: u32,
+    message: Option<String>,
+) {
+    for senders in subscribers.values_mut() {
+        senders.retain(|sender| {
+            sender
+                .send(RealtimeEvent::Status {
+                    state,
+                    attempt,
+                    message: message.clone(),
+                })
+                .is_ok()
+        });
+    }
+    subscribers.retain(|_, senders| !senders.is_empty());
+}
+
+/// 종목의 체결·호가 항목을 등록(`tr_type` 1)하거나 해제(2)한다.
+async fn register(session: &mut Session, code: &str, subscribe: bool) -> Result<(), String> {
+    for tr_id in [TRADE_TR_ID, ORDER_BOOK_TR_ID] {
+        let message = serde_json::json!({
+            "header": {
+                "approval_key": session.approval_key,
+                "custtype": "P",
+                "tr_type": if subscribe { "1" } else { "2" },
+                "content-type": "utf-8",
+            },
+            "body": {
+                "input": { "tr_id": tr_id, "tr_key": code },
+            },
+        });
+        session
+            .socket
+            .send(Message::Text(message.to_string()))
+            .await
+            .map_err(|e| format!("{} 등록 요청 실패: {}", tr_id, e))?;
+    }
+    Ok(())
+}
+
+async fn handle_text(
+    session: &mut Session,
+    text: &str,
+    subscribers: &mut Subscribers,
+) -> Result<(), String> {
+    // 제어 메시지(등록 응답, PINGPONG)는 JSON
+    if text.starts_with('{') {
+        let Ok(json) = serde_json::from_str::<Value>(text) else {
+            return Ok(());
+        };
+        let tr_id = json["header"]["tr_id"].as_str().unwrap_or_default();
+        if tr_id == "PINGPONG" {
+            // 받은 그대로 돌려보내야 서버가 세션을 유지한다
+            return session
+                .socket
+                .send(Message::Text(text.to_string()))
+                .await
+                .map_err(|e| format!("PINGPONG 응답 실패: {}", e));
+        }
+        if json["body"]["rt_cd"]
+            .as_str()
+            .is_some_and(|rt_cd| rt_cd != "0")
+        {
+            let code = json["header"]["tr_key"].as_str().unwrap_or_default();
+            let message = format!(
+                "{} 등록 실패: {}",
+                tr_id,
+                json["body"]["msg1"].as_str().unwrap_or("Unknown error")
+            );
+            dispatch(
+                subscribers,
+                code,
+                RealtimeEvent::Status {
+                    state: "connected",
+                    attempt: 0,
+                    message: Some(message),
+                },
+            );
+        }
+        return Ok(());
+    }
+
+    // 시세는 "암호화 여부|tr_id|건수|필드^필드^..." 형식이며 여러 건이 이어 붙을 수 있다
+    let mut parts = text.splitn(4, '|');
+    let (Some(encrypted), Some(tr_id), Some(count), Some(payload)) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Ok(());
+    };
+    // 암호화되는 항목(체결통보)은 구독하지 않는다
+    if encrypted != "0" {
+        return Ok(());
+    }
+    let fields: Vec<&str> = payload.split('^').collect();
+    let count = count.parse::<usize>().unwrap_or(1).max(1);
+    let per_record = fields.len() / count;
+    if per_record == 0 {
+        return Ok(());
+    }
+
+    for record in fields.chunks_exact(per_record) {
+        let parsed = match tr_id {
+            TRADE_TR_ID => {
+                parse_trade(record).map(|(code, trade)| (code, RealtimeEvent::Trade(trade)))
+            }
+            ORDER_BOOK_TR_ID => parse_order_book(record)
+                .map(|book| (book.symbol.clone(), RealtimeEvent::OrderBook(book))),
+            _ => None,
+        };
+        if let Some((code, event)) = parsed {
+            dispatch(subscribers, &code, event);
+        }
+    }
+    Ok(())
+}
+
+/// H0STCNT0 한 건: 0 종목코드, 1 체결시각, 2 현재가, 12 체결량,
+/// 21 체결구분 (1 매수, 5 매도), 33 영업일자
+fn parse_trade(fields: &[&str]) -> Option<(String, KisTrade)> {
+    let date = NaiveDate::parse_from_str(fields.get(33)?, "%Y%m%d").ok()?;
+    let time = NaiveTime::parse_from_str(fields.get(1)?, "%H%M%S").ok()?;
+    let trade = KisTrade {
+        time: kst_timestamp(date.and_time(time))?,
+        price: parse_f64(fields.get(2)?)?,
+        volume: parse_f64(fields.get(12)?)?,
+        buyer_initiated: *fields.get(21)? == "1",
+    };
+    Some((fields[0].to_string(), trade))
+}
+
+/// H0STASP0 한 건: 0 종목코드, 1 호가시각, 3~12 매도호가, 13~22 매수호가,
+/// 23~32 매도잔량, 33~42 매수잔량, 43 총매도잔량, 44 총매수잔량
+fn parse_order_book(fields: &[&str]) -> Option<OrderBookUpdate> {
+    let time = NaiveTime::parse_from_str(fields.get(1)?, "%H%M%S").ok()?;
+    // 호가 메시지에는 일자가 없어 오늘(KST) 날짜를 쓴다
+    let kst = FixedOffset::east_opt(KST_OFFSET)?;
+    let date = Utc::now().with_timezone(&kst).date_naive();
+
+    let levels = |price_from: usize, quantity_from: usize| -> Option<Vec<OrderBookLevel>> {
+        let mut levels = Vec::with_capacity(ORDER_BOOK_DEPTH);
+        for i in 0..ORDER_BOOK_DEPTH {
+            let price = parse_f64(fields.get(price_from + i)?)?;
+            let quantity = parse_f64(fields.get(quantity_from + i)?)?;
+            // 호가가 비어 있는 단계 (상·하한가 등)
+            if price > 0.0 {
+                levels.push(OrderBookLevel { price, quantity });
+            }
+        }
+        Some(levels)
+    };
+
+    Some(OrderBookUpdate {
+        symbol: fields[0].to_string(),
+        time: kst_timestamp(date.and_time(time))?,
+        asks: levels(3, 23)?,
+        bids: levels(13, 33)?,
+        total_ask_quantity: parse_f64(fields.get(43)?)?,
+        total_bid_quantity: parse_f64(fields.get(44)?)?,
+    })
+}
+
+fn kst_timestamp(local: NaiveDateTime) -> Option<i64> {
+    let kst = FixedOffset::east_opt(KST_OFFSET)?;
+    Some(kst.from_local_datetime(&local).single()?.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, OnceLock};
+    use std::time::Duration;
+
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::Value;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+    use tokio::sync::RwLock;
+    use tokio_tungstenite::tungstenite::Message;
+    use tokio_tungstenite::WebSocketStream;
+
+    use super::{KisFeed, RealtimeEvent, MAX_SYMBOLS, ORDER_BOOK_TR_ID, TRADE_TR_ID};
+    use crate::api_client::kis::{KisClient, KisConfig, KisEnvironment};
+
+    const PINGPONG: &str = r#"{"header":{"tr_id":"PINGPONG","datetime":"20240304090010"}}"#;
+    /// 2024-03-04 09:00:00 KST
+    const OPEN: i64 = 1_709_510_400;
+
+    fn client(base_url: String, ws_url: String) -> KisClient {
+        KisClient {
+            client: reqwest::Client::new(),
+            base_url,
+            ws_url,
+            config: Some(KisConfig {
+                app_key: "app-key".to_string(),
+                app_secret: "app-secret".to_string(),
+                environment: KisEnvironment::Paper,
+            }),
+            token: Arc::new(RwLock::new(None)),
+            token_store: Arc::new(OnceLock::new()),
+            us_exchanges: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Reads a whole HTTP request (headers and `Content-Length` body).
+    async fn read_request(socket: &mut TcpStream) {
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        loop {
+            let read = socket.read(&mut buf).await.unwrap();
+            if read == 0 {
+                return;
+            }
+            request.extend_from_slice(&buf[..read]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let length = head
+                    .lines()
+                    .find_map(|line| {
+                        let line = line.to_ascii_lowercase();
+                        line.strip_prefix("content-length:")
+                            .map(|value| value.trim().parse::<usize>().unwrap())
+                    })
+                    .unwrap_or(0);
+                if body.len() >= length {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Answers `/oauth2/Approval` with `approval-1`, `approval-2`, … per connection attempt.
+    async fn approval_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for n in 1.. {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                read_request(&mut socket).await;
+                let body =
+                    serde_json::json!({ "approval_key": format!("approval-{}", n) }).to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    /// Next text frame from the client, forwarded to the test as `(connection, frame)`.
+    async fn forward_text(
+        ws: &mut WebSocketStream<TcpStream>,
+        connection: usize,
+        frames: &UnboundedSender<(usize, String)>,
+    ) {
+        loop {
+            match ws.next().await {
+                Some(Ok(Message::Text(text))) => {
+                    let _ = frames.send((connection, text));
+                    return;
+                }
+                Some(Ok(_)) => continue,
+                _ => return,
+            }
+        }
+    }
+
+    /// One H0STCNT0 record with 46 fields; only the ones the parser reads are filled in.
+    fn trade_record(code: &str, time: &str, price: &str, volume: &str) -> String {
+        let mut fields = vec!["0"; 46];
+        fields[0] = code;
+        fields[1] = time;
+        fields[2] = price;
+        fields[12] = volume;
+        fields[21] = "1";
+        fields[33] = "20240304";
+        fields.join("^")
+    }
+
+    /// 1st connection: registrations, PINGPONG (expects the echo), two trades in one frame, drop.
+    /// 2nd connection: registrations again, one trade, then stays open.
+    async fn ws_server(frames: UnboundedSender<(usize, String)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for connection in 0.. {
+                let (socket, _) = listener.accept().await.unwrap();
+                let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+                forward_text(&mut ws, connection, &frames).await;
+                forward_text(&mut ws, connection, &frames).await;
+                if connection == 0 {
+                    ws.send(Message::Text(PINGPONG.to_string())).await.unwrap();
+                    forward_text(&mut ws, connection, &frames).await;
+                    let records = [
+                        trade_record("005930", "090001", "71000", "10"),
+                        trade_record("005930", "090002", "71100", "5"),
+                    ];
+                    let frame = format!("0|{}|002|{}", TRADE_TR_ID, records.join("^"));
+                    ws.send(Message::Text(frame)).await.unwrap();
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    drop(ws);
+                } else {
+                    let record = trade_record("005930", "090105", "71200", "3");
+                    let frame = format!("0|{}|001|{}", TRADE_TR_ID, record);
+                    ws.send(Message::Text(frame)).await.unwrap();
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                }
+            }
+        });
+        format!("ws://{}", addr)
+    }
+
+    /// Accepts one connection and forwards every frame the client sends.
+    async fn sink_server(frames: UnboundedSender<(usize, String)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            while let Some(Ok(message)) = ws.next().await {
+                if let Message::Text(text) = message {
+                    let _ = frames.send((0, text));
+                }
+            }
+        });
+        format!("ws://{}", addr)
+    }
+
+    async fn recv<T>(receiver: &mut UnboundedReceiver<T>) -> T {
+        tokio::time::timeout(Duration::from_secs(10), receiver.recv())
+            .await
+            .expect("timed out")
+            .expect("channel closed")
+    }
+
+    /// (approval key, tr_type, tr_id, tr_key) of a registration frame
+    fn registration(frame: &str) -> (String, String, String, String) {
+        let json: Value = serde_json::from_str(frame).unwrap();
+        let text = |value: &Value| value.as_str().unwrap().to_string();
+        (
+            text(&json["header"]["approval_key"]),
+            text(&json["header"]["tr_type"]),
+            text(&json["body"]["input"]["tr_id"]),
+            text(&json["body"]["input"]["tr_key"]),
+        )
+    }
+
+    fn describe(event: RealtimeEvent) -> String {
+        match event {
+            RealtimeEvent::Trade(trade) => format!(
+                "trade {} {} {} {}",
+                trade.time - OPEN,
+                trade.price,
+                trade.volume,
+                trade.buyer_initiated
+            ),
+            RealtimeEvent::OrderBook(book) => format!("book {}", book.symbol),
+            RealtimeEvent::Status { state, attempt, .. } => format!("{} {}", state, attempt),
+        }
+    }
+
+    #[tokio::test]
+    async fn answers_pingpong_splits_records_and_reregisters_after_reconnect() {
+        let (frames_tx, mut frames) = mpsc::unbounded_channel();
+        let feed = KisFeed::new(client(approval_server().await, ws_server(frames_tx).await));
+        let mut events = feed.subscribe("005930.KS").await.unwrap();
+
+        let mut seen = Vec::new();
+        while seen.len() < 8 {
+            seen.push(describe(recv(&mut events).await));
+        }
+        assert_eq!(
+            seen,
+            [
+                "connecting 0",
+                "connected 0",
+                "trade 1 71000 10 true",
+                "trade 2 71100 5 true",
+                "reconnecting 1",
+                "connecting 1",
+                "connected 1",
+                "trade 65 71200 3 true",
+            ]
+        );
+
+        let mut received = Vec::new();
+        for _ in 0..5 {
+            received.push(recv(&mut frames).await);
+        }
+        let subscribe = |key: &str, tr_id: &str| {
+            (
+                key.to_string(),
+                "1".to_string(),
+                tr_id.to_string(),
+                "005930".to_string(),
+            )
+        };
+        assert_eq!(received[0].0, 0);
+        assert_eq!(
+            registration(&received[0].1),
+            subscribe("approval-1", TRADE_TR_ID)
+        );
+        assert_eq!(
+            registration(&received[1].1),
+            subscribe("approval-1", ORDER_BOOK_TR_ID)
+        );
+        // PINGPONG goes back unchanged
+        assert_eq!(received[2], (0, PINGPONG.to_string()));
+        // The new connection gets a fresh approval key and the symbol registered again
+        assert_eq!(received[3].0, 1);
+        assert_eq!(
+            registration(&received[3].1),
+            subscribe("approval-2", TRADE_TR_ID)
+        );
+        assert_eq!(
+            registration(&received[4].1),
+            subscribe("approval-2", ORDER_BOOK_TR_ID)
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_new_symbols_beyond_the_session_limit() {
+        let (frames_tx, mut frames) = mpsc::unbounded_channel();
+        let feed = KisFeed::new(client(
+            approval_server().await,
+            sink_server(frames_tx).await,
+        ));
+
+        let mut receivers = Vec::new();
+        for i in 0..MAX_SYMBOLS {
+            receivers.push(feed.subscribe(&format!("{:06}.KS", i)).await.unwrap());
+        }
+        let error = feed.subscribe("999999.KS").await.unwrap_err();
+        assert!(error.contains(&MAX_SYMBOLS.to_string()), "{}", error);
+        // Another subscriber for an already registered symbol still fits
+        receivers.push(feed.subscribe("000000.KS").await.unwrap());
+
+        let mut registered = Vec::new();
+        for _ in 0..MAX_SYMBOLS * 2 {
+            let (_, frame) = recv(&mut frames).await;
+            registered.push(registration(&frame).3);
+        }
+        registered.sort();
+        registered.dedup();
+        assert_eq!(registered.len(), MAX_SYMBOLS);
+        assert!(!registered.contains(&"999999".to_string()));
+        // Nothing else is registered afterwards
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(frames.try_recv().is_err());
+    }
+}
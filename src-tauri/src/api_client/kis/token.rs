@@ -0,0 +1,247 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+
+use super::{KisConfig, KisEnvironment};
+
+/// 만료까지 이보다 적게 남은 토큰은 쓰지 않는다
+const EXPIRY_MARGIN: i64 = 300;
+/// 만료까지 이보다 적게 남으면 미리 새로 발급받는다.
+/// KIS는 발급 후 6시간 안의 재발급 요청에 기존 토큰을 돌려주므로 그보다 늦게 잡는다
+const REFRESH_MARGIN: i64 = 3 * 3_600;
+/// 미리 갱신에 실패했을 때 다시 시도하기까지의 간격 (토큰 발급은 분당 1회)
+const REFRESH_RETRY: i64 = 60;
+/// 토큰 암호화 키 파일 이름. 실전/모의투자 토큰이 함께 쓴다
+const KEY_FILE_NAME: &str = "kis_token.key";
+const KEY_LEN: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct CachedToken {
+    pub(super) access_token: String,
+    pub(super) expires_at: i64, // unix seconds (UTC)
+    /// 미리 갱신을 다시 시도할 수 있는 시각 (저장하지 않음)
+    #[serde(skip)]
+    pub(super) retry_at: i64,
+}
+
+impl CachedToken {
+    /// 만료 여유가 남아 요청에 쓸 수 있는지
+    pub(super) fn usable(&self, now: i64) -> bool {
+        self.expires_at - now > EXPIRY_MARGIN
+    }
+
+    /// 만료가 가까워 새로 발급받을 때가 됐는지
+    pub(super) fn needs_refresh(&self, now: i64) -> bool {
+        self.expires_at - now <= REFRESH_MARGIN && now >= self.retry_at
+    }
+
+    pub(super) fn postpone_refresh(&mut self, now: i64) {
+        self.retry_at = now + REFRESH_RETRY;
+    }
+}
+
+/// 접근 토큰을 앱 데이터 디렉터리의 `kis_token_{real|paper}.bin`에 암호화해 보관한다.
+///
+/// 키는 처음 저장할 때 무작위로 만들어 토큰과 다른 디렉터리에 소유자 전용(0600) 파일로 둔다.
+/// 토큰 파일만 복사되거나 백업돼서는 풀 수 없지만, 같은 사용자 권한으로는 두 파일을 모두
+/// 읽을 수 있다. 앱 키를 함께 인증하므로 다른 자격 증명으로 저장된 토큰은 풀지 못해 새로 발급받는다.
+#[derive(Clone)]
+pub(super) struct TokenStore {
+    path: PathBuf,
+    key_path: PathBuf,
+    app_key: String,
+}
+
+impl TokenStore {
+    /// 토큰은 `token_dir`, 암호화 키는 `key_dir`에 둔다
+    pub(super) fn new(config: &KisConfig, token_dir: &Path, key_dir: &Path) -> Self {
+        let file_name = match config.environment {
+            KisEnvironment::Real => "kis_token_real.bin",
+            KisEnvironment::Paper => "kis_token_paper.bin",
+        };
+        Self {
+            path: token_dir.join(file_name),
+            key_path: key_dir.join(KEY_FILE_NAME),
+            app_key: config.app_key.clone(),
+        }
+    }
+
+    /// 저장된 토큰. 파일이나 키가 없거나, 다른 키나 자격 증명으로 저장돼 풀 수 없으면 None
+    pub(super) fn load(&self) -> Option<CachedToken> {
+        let key = read_key(&self.key_path)?;
+        let mut sealed = std::fs::read(&self.path).ok()?;
+        if sealed.len() <= NONCE_LEN {
+            return None;
+        }
+        let mut ciphertext = sealed.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&sealed).ok()?;
+        let plaintext = key.open_in_place(nonce, self.aad(), &mut ciphertext).ok()?;
+        serde_json::from_slice(plaintext).ok()
+    }
+
+    /// `nonce || 봉인된 토큰+태그` 형식으로 저장한다. 키 파일이 없으면 새로 만든다.
+    pub(super) fn save(&self, token: &CachedToken) -> Result<(), String> {
+        let key = match read_key(&self.key_path) {
+            Some(key) => key,
+            None => create_key(&self.key_path)?,
+        };
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| "KIS token nonce generation failed".to_string())?;
+
+        let mut data = serde_json::to_vec(token).map_err(|e| e.to_string())?;
+        key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), self.aad(), &mut data)
+            .map_err(|_| "KIS token sealing failed".to_string())?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&data);
+        // 쓰는 도중 종료돼도 기존 파일이 깨지지 않도록 임시 파일을 옮긴다
+        let temp = self.path.with_extension("tmp");
+        write_owner_only(&temp, &sealed)?;
+        std::fs::rename(&temp, &self.path).map_err(|e| e.to_string())
+    }
+
+    fn aad(&self) -> Aad<&[u8]> {
+        Aad::from(self.app_key.as_bytes())
+    }
+}
+
+fn read_key(path: &Path) -> Option<LessSafeKey> {
+    let bytes = std::fs::read(path).ok()?;
+    UnboundKey::new(&CHACHA20_POLY1305, &bytes)
+        .ok()
+        .map(LessSafeKey::new)
+}
+
+/// 무작위 키를 만들어 저장한다. 길이가 맞지 않는 기존 파일은 쓸 수 없으므로 바꾼다
+fn create_key(path: &Path) -> Result<LessSafeKey, String> {
+    let mut bytes = [0u8; KEY_LEN];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "KIS token key generation failed".to_string())?;
+    let temp = path.with_extension("tmp");
+    write_owner_only(&temp, &bytes)?;
+    std::fs::rename(&temp, path).map_err(|e| e.to_string())?;
+    UnboundKey::new(&CHACHA20_POLY1305, &bytes)
+        .map(LessSafeKey::new)
+        .map_err(|_| "KIS token key setup failed".to_string())
+}
+
+/// 소유자 전용(0600) 파일로 새로 쓴다.
+/// 권한은 만들 때만 적용되므로 남아 있던 파일은 지우고 새로 만든다
+fn write_owner_only(path: &Path, data: &[u8]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let _ = std::fs::remove_file(path);
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(path).map_err(|e| e.to_string())?;
+    file.write_all(data)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+
+    use super::{CachedToken, TokenStore, KEY_FILE_NAME};
+    use crate::api_client::kis::{KisConfig, KisEnvironment};
+
+    fn config(app_key: &str) -> KisConfig {
+        KisConfig {
+            app_key: app_key.to_string(),
+            app_secret: "app-secret".to_string(),
+            environment: KisEnvironment::Real,
+        }
+    }
+
+    fn token() -> CachedToken {
+        CachedToken {
+            access_token: "token".to_string(),
+            expires_at: 1_900_000_000,
+            retry_at: 0,
+        }
+    }
+
+    /// Separate token and key directories under a per-test temp root.
+    fn dirs(name: &str) -> (PathBuf, PathBuf, PathBuf) {
+        let root = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+        (root.clone(), root.join("data"), root.join("config"))
+    }
+
+    fn mode(path: &PathBuf) -> u32 {
+        std::fs::metadata(path).unwrap().permissions().mode() & 0o777
+    }
+
+    #[test]
+    fn saves_owner_only_files_that_load_back() {
+        let (root, data_dir, key_dir) = dirs("kis-token-test");
+        let store = TokenStore::new(&config("app-key"), &data_dir, &key_dir);
+        // A leftover temp file with loose permissions must not leak its mode
+        std::fs::create_dir_all(&data_dir).unwrap();
+        std::fs::write(store.path.with_extension("tmp"), b"stale").unwrap();
+        std::fs::set_permissions(
+            store.path.with_extension("tmp"),
+            std::fs::Permissions::from_mode(0o644),
+        )
+        .unwrap();
+
+        store.save(&token()).unwrap();
+
+        let token_mode = mode(&store.path);
+        let key_mode = mode(&key_dir.join(KEY_FILE_NAME));
+        let key_beside_token = data_dir.join(KEY_FILE_NAME).exists();
+        let loaded = store.load();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!((token_mode, key_mode), (0o600, 0o600));
+        assert!(!key_beside_token);
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.access_token, "token");
+        assert_eq!(loaded.expires_at, 1_900_000_000);
+    }
+
+    #[test]
+    fn a_token_does_not_load_without_its_key() {
+        let (root, data_dir, key_dir) = dirs("kis-token-key");
+        let store = TokenStore::new(&config("app-key"), &data_dir, &key_dir);
+        store.save(&token()).unwrap();
+
+        // The credentials alone are not enough: another key cannot open the file
+        std::fs::write(key_dir.join(KEY_FILE_NAME), [7u8; 32]).unwrap();
+        let other_key = store.load();
+        std::fs::remove_file(key_dir.join(KEY_FILE_NAME)).unwrap();
+        let no_key = store.load();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert!(other_key.is_none());
+        assert!(no_key.is_none());
+    }
+
+    #[test]
+    fn a_file_saved_with_other_credentials_does_not_load() {
+        let (root, data_dir, key_dir) = dirs("kis-token-other");
+        TokenStore::new(&config("old-key"), &data_dir, &key_dir)
+            .save(&token())
+            .unwrap();
+
+        let loaded = TokenStore::new(&config("new-key"), &data_dir, &key_dir).load();
+        let reloaded = TokenStore::new(&config("old-key"), &data_dir, &key_dir).load();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert!(loaded.is_none());
+        assert_eq!(reloaded.unwrap().access_token, "token");
+    }
+}
@@ -1,7 +1,12 @@
 mod binance;
 mod kis;
+mod provider;
+mod upbit;
 mod yahoo;
 
 pub use binance::BinanceClient;
+pub use kis::realtime::{KisFeed, KisTrade, RealtimeEvent};
 pub use kis::KisClient;
+pub use provider::{MarketDataProvider, ProviderRegistry};
+pub use upbit::UpbitClient;
 pub use yahoo::YahooClient;
@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+use futures_util::future::BoxFuture;
+
+use crate::models::{Candle, DataProviderInfo, MarketType, ProviderCapabilities, ProviderPriority};
+
+pub type CandleFuture<'a> = BoxFuture<'a, Result<Vec<Candle>, String>>;
+
+/// 캔들 시세 공급자. 새 거래소는 이 트레이트를 구현해 `ProviderRegistry`에 등록하면
+/// 분석·백필·워치리스트가 시장과 interval 지원 여부에 따라 자동으로 사용한다.
+pub trait MarketDataProvider: Send + Sync {
+    /// 캔들 저장소의 소스 키로도 쓰이므로 바꾸면 저장된 시리즈를 다시 받는다
+    fn id(&self) -> &'static str;
+
+    fn name(&self) -> &'static str;
+
+    fn capabilities(&self) -> ProviderCapabilities;
+
+    /// 심볼 표기로 취급 여부를 가린다 (예: Upbit `KRW-BTC`, Binance `BTCUSDT`)
+    fn supports_symbol(&self, _symbol: &str) -> bool {
+        true
+    }
+
+    /// 자격 증명이 없는 등 지금 조회할 수 없는 공급자는 조회 순서에서 빠진다
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// 이 심볼·주기에 `fetch_klines_range`를 쓸 수 있는지. 기본은 `supports_range`를 따른다
+    fn supports_range(&self, _symbol: &str, _interval: &str) -> bool {
+        self.capabilities().supports_range
+    }
+
+    /// 시장별 기본 조회 순서에 들어가는지. 같은 심볼이라도 다른 시세를 주는 공급자
+    /// (예: 무기한 선물)는 false로 두어, 사용자가 조회 순서에 넣었을 때만 쓰이게 한다.
+    fn in_default_order(&self) -> bool {
+        true
+    }
+
+    /// 최근 `limit`개 캔들 (시간 오름차순)
+    fn fetch_klines<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        limit: u32,
+    ) -> CandleFuture<'a>;
+
+    /// `since`(초) 이후 캔들 (저장소 증분 갱신용).
+    /// 기본 구현은 최근 구간 전체를 받아 저장소 upsert로 병합하게 한다.
+    fn fetch_klines_since<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        _since: i64,
+        limit: u32,
+    ) -> CandleFuture<'a> {
+        self.fetch_klines(symbol, interval, limit)
+    }
+
+    /// `[start, end]`(초) 구간. `supports_range`가 false인 심볼·주기로는 호출되지 않는다.
+    fn fetch_klines_range<'a>(
+        &'a self,
+        _symbol: &'a str,
+        _interval: &'a str,
+        _start: i64,
+        _end: i64,
+    ) -> CandleFuture<'a> {
+        let message = format!("{}는 과거 구간 조회를 지원하지 않습니다", self.name());
+        Box::pin(async move { Err(message) })
+    }
+}
+
+/// 등록된 공급자와 사용자가 정한 시장별 조회 순서.
+pub struct ProviderRegistry {
+    /// 등록 순서가 기본 우선순위
+    providers: Vec<Arc<dyn MarketDataProvider>>,
+    priorities: RwLock<HashMap<MarketType, Vec<String>>>,
+}
+
+impl ProviderRegistry {
+    /// 분당 요청 한도를 공개한 공급자는 그 한도에 맞춰 호출 간격을 조절한다.
+    pub fn new(providers: Vec<Arc<dyn MarketDataProvider>>) -> Self {
+        Self {
+            providers: providers.into_iter().map(Throttled::wrap).collect(),
+            priorities: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<Arc<dyn MarketDataProvider>> {
+        self.providers.iter().find(|p| p.id() == id).cloned()
+    }
+
+    pub fn list(&self) -> Vec<DataProviderInfo> {
+        self.providers
+            .iter()
+            .map(|p| DataProviderInfo {
+                id: p.id().to_string(),
+                name: p.name().to_string(),
+                capabilities: p.capabilities(),
+                available: p.is_available(),
+            })
+            .collect()
+    }
+
+    /// 시장을 지원하는 공급자를 우선순위대로. 설정에 없는 공급자는 등록 순서대로 뒤에 붙고,
+    /// 기본 조회 순서에서 빠지는 공급자는 설정에 있을 때만 포함된다.
+    pub fn priority(&self, market: &MarketType) -> Vec<Arc<dyn MarketDataProvider>> {
+        let configured = self
+            .priorities
+            .read()
+            .ok()
+            .and_then(|priorities| priorities.get(market).cloned())
+            .unwrap_or_default();
+        let rank = |p: &Arc<dyn MarketDataProvider>| {
+            configured
+                .iter()
+                .position(|id| id == p.id())
+                .unwrap_or(configured.len())
+        };
+
+        let mut ordered: Vec<Arc<dyn MarketDataProvider>> = self
+            .providers
+            .iter()
+            .filter(|p| p.capabilities().markets.contains(market))
+            .filter(|p| p.in_default_order() || configured.iter().any(|id| id == p.id()))
+            .cloned()
+            .collect();
+        // 안정 정렬이라 순위가 같은 공급자는 등록 순서를 유지한다
+        ordered.sort_by_key(rank);
+        ordered
+    }
+
+    /// 조회할 수 있고 심볼을 다루며 `interval`을 그대로 제공하는 공급자를 우선순위대로.
+    pub fn source_order(
+        &self,
+        market: &MarketType,
+        symbol: &str,
+        interval: &str,
+    ) -> Vec<Arc<dyn MarketDataProvider>> {
+        self.symbol_providers(market, symbol)
+            .filter(|p| p.capabilities().intervals.iter().any(|i| i == interval))
+            .collect()
+    }
+
+    /// 조회할 수 있고 심볼을 다루는 공급자 중 하나라도 제공하는 봉 주기 (중복 제외, 우선순위 순서).
+    pub fn intervals(&self, market: &MarketType, symbol: &str) -> Vec<String> {
+        let mut intervals: Vec<String> = Vec::new();
+        for provider in self.symbol_providers(market, symbol) {
+            for interval in provider.capabilities().intervals {
+                if !intervals.contains(&interval) {
+                    intervals.push(interval);
+                }
+            }
+        }
+        intervals
+    }
+
+    fn symbol_providers<'a>(
+        &self,
+        market: &MarketType,
+        symbol: &'a str,
+    ) -> impl Iterator<Item = Arc<dyn MarketDataProvider>> + 'a {
+        self.priority(market)
+            .into_iter()
+            .filter(move |p| p.is_available() && p.supports_symbol(symbol))
+    }
+
+    pub fn priorities(&self) -> Vec<ProviderPriority> {
+        [
+            MarketType::Crypto,
+            MarketType::Forex,
+            MarketType::UsStock,
+            MarketType::KrStock,
+        ]
+        .into_iter()
+        .map(|market| ProviderPriority {
+            providers: self
+                .priority(&market)
+                .iter()
+                .map(|p| p.id().to_string())
+                .collect(),
+            market,
+        })
+        .collect()
+    }
+
+    /// 조회 순서를 바꾼다. 모르는 id나 그 시장을 지원하지 않는 공급자가 있으면 거부한다.
+    pub fn set_priority(&self, priority: &ProviderPriority) -> Result<(), String> {
+        for id in &priority.providers {
+            let supported = self
+                .get(id)
+                .is_some_and(|p| p.capabilities().markets.contains(&priority.market));
+            if !supported {
+                return Err(format!("{}은(는) 이 시장의 데이터 공급자가 아닙니다", id));
+            }
+        }
+
+        self.priorities
+            .write()
+            .map_err(|e| e.to_string())?
+            .insert(priority.market.clone(), priority.providers.clone());
+        Ok(())
+    }
+}
+
+/// 공개된 분당 요청 한도를 넘지 않도록 조회 호출을 고른 간격으로 내보낸다.
+/// 호출 한 번을 요청 한 번으로 세며, 구간 조회 안의 페이지 간격은 공급자가 지킨다.
+struct Throttled {
+    inner: Arc<dyn MarketDataProvider>,
+    spacing: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl Throttled {
+    fn wrap(provider: Arc<dyn MarketDataProvider>) -> Arc<dyn MarketDataProvider> {
+        match provider.capabilities().requests_per_minute {
+            Some(limit) if limit > 0 => Arc::new(Self {
+                inner: provider,
+                spacing: Duration::from_secs(60) / limit,
+                next_slot: Mutex::new(Instant::now()),
+            }),
+            _ => provider,
+        }
+    }
+
+    /// 다음 빈 슬롯을 예약하고 그 시각까지 기다린다
+    async fn wait_turn(&self) {
+        let slot = {
+            let mut next = self
+                .next_slot
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let slot = (*next).max(Instant::now());
+            *next = slot + self.spacing;
+            slot
+        };
+        tokio::time::sleep_until(slot.into()).await;
+    }
+}
+
+impl MarketDataProvider for Throttled {
+    fn id(&self) -> &'static str {
+        self.inner.id()
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn supports_symbol(&self, symbol: &str) -> bool {
+        self.inner.supports_symbol(symbol)
+    }
+
+    fn is_available(&self) -> bool {
+        self.inner.is_available()
+    }
+
+    fn supports_range(&self, symbol: &str, interval: &str) -> bool {
+        self.inner.supports_range(symbol, interval)
+    }
+
+    fn in_default_order(&self) -> bool {
+        self.inner.in_default_order()
+    }
+
+    fn fetch_klines<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        limit: u32,
+    ) -> CandleFuture<'a> {
+        Box::pin(async move {
+            self.wait_turn().await;
+            self.inner.fetch_klines(symbol, interval, limit).await
+        })
+    }
+
+    fn fetch_klines_since<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        since: i64,
+        limit: u32,
+    ) -> CandleFuture<'a> {
+        Box::pin(async move {
+            self.wait_turn().await;
+            self.inner
+                .fetch_klines_since(symbol, interval, since, limit)
+                .await
+        })
+    }
+
+    fn fetch_klines_range<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        start: i64,
+        end: i64,
+    ) -> CandleFuture<'a> {
+        Box::pin(async move {
+            self.wait_turn().await;
+            self.inner
+                .fetch_klines_range(symbol, interval, start, end)
+                .await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Stub {
+        id: &'static str,
+        default_order: bool,
+        requests_per_minute: Option<u32>,
+    }
+
+    impl MarketDataProvider for Stub {
+        fn id(&self) -> &'static str {
+            self.id
+        }
+
+        fn name(&self) -> &'static str {
+            self.id
+        }
+
+        fn capabilities(&self) -> ProviderCapabilities {
+            ProviderCapabilities {
+                markets: vec![MarketType::Crypto],
+                intervals: vec!["1h".to_string()],
+                max_bars: 1_000,
+                supports_range: true,
+                requests_per_minute: self.requests_per_minute,
+            }
+        }
+
+        fn in_default_order(&self) -> bool {
+            self.default_order
+        }
+
+        fn fetch_klines<'a>(
+            &'a self,
+            _symbol: &'a str,
+            _interval: &'a str,
+            _limit: u32,
+        ) -> CandleFuture<'a> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+    }
+
+    fn stub(id: &'static str, default_order: bool) -> Arc<dyn MarketDataProvider> {
+        Arc::new(Stub {
+            id,
+            default_order,
+            requests_per_minute: None,
+        })
+    }
+
+    fn ids(providers: &[Arc<dyn MarketDataProvider>]) -> Vec<&'static str> {
+        providers.iter().map(|p| p.id()).collect()
+    }
+
+    #[test]
+    fn providers_outside_the_default_order_need_an_explicit_priority() {
+        let registry = ProviderRegistry::new(vec![stub("spot", true), stub("perp", false)]);
+        assert_eq!(
+            ids(&registry.source_order(&MarketType::Crypto, "BTCUSDT", "1h")),
+            ["spot"]
+        );
+
+        registry
+            .set_priority(&ProviderPriority {
+                market: MarketType::Crypto,
+                providers: vec!["perp".to_string()],
+            })
+            .unwrap();
+        assert_eq!(
+            ids(&registry.source_order(&MarketType::Crypto, "BTCUSDT", "1h")),
+            ["perp", "spot"]
+        );
+    }
+
+    #[tokio::test]
+    async fn published_request_limits_space_out_calls() {
+        let registry = ProviderRegistry::new(vec![Arc::new(Stub {
+            id: "limited",
+            default_order: true,
+            requests_per_minute: Some(600),
+        })]);
+        let provider = registry.get("limited").unwrap();
+
+        let started = Instant::now();
+        for _ in 0..3 {
+            provider.fetch_klines("BTCUSDT", "1h", 10).await.unwrap();
+        }
+        // 600/min is one call every 100ms; the first goes out immediately
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+}
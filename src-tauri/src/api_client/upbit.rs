@@ -0,0 +1,601 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+use super::provider::{CandleFuture, MarketDataProvider};
+use crate::models::{Candle, MarketType, ProviderCapabilities, SymbolSearchResult};
+
+/// Upbit 캔들 한 페이지의 최대 건수
+const PAGE_LIMIT: u32 = 200;
+/// 백필 한 번에 요청할 최대 페이지 수 (1m 기준 약 1년)
+const MAX_BACKFILL_PAGES: usize = 2_700;
+/// 시세 조회 API의 초당 10회 제한에 맞춘 페이지 간 대기
+const PAGE_DELAY: Duration = Duration::from_millis(110);
+const DEFAULT_BASE_URL: &str = "https://api.upbit.com";
+const MAX_SEARCH_RESULTS: usize = 12;
+
+#[derive(Debug, Clone)]
+struct UpbitMarket {
+    code: String,
+    korean_name: String,
+    english_name: String,
+}
+
+/// Upbit 원화·BTC·USDT 마켓 시세. 심볼은 `KRW-BTC`처럼 `{기준 통화}-{코인}` 형식이다.
+#[derive(Clone)]
+pub struct UpbitClient {
+    client: reqwest::Client,
+    base_url: String,
+    /// 검색용 마켓 목록 (처음 검색할 때 한 번 받는다)
+    markets: Arc<RwLock<Vec<UpbitMarket>>>,
+}
+
+impl UpbitClient {
+    /// `UPBIT_API_URL` 환경변수로 REST 주소를 바꿀 수 있다 (기록된 응답을 돌려주는 로컬 서버 등).
+    pub fn new() -> Self {
+        Self::with_base_url(
+            std::env::var("UPBIT_API_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string()),
+        )
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            markets: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// `KRW-BTC`, `BTC-ETH`, `USDT-BTC` 형식의 마켓 코드인지
+    pub fn is_market_code(symbol: &str) -> bool {
+        symbol.split_once('-').is_some_and(|(quote, base)| {
+            matches!(quote, "KRW" | "BTC" | "USDT")
+                && !base.is_empty()
+                && base.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+    }
+
+    /// 최근 `limit`개 캔들. 한 페이지(200개)를 넘으면 `to` 커서로 거슬러 올라간다.
+    pub async fn fetch_klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+        limit: u32,
+    ) -> Result<Vec<Candle>, String> {
+        let candles = self
+            .request_pages(symbol, interval, None, None, limit as usize)
+            .await?;
+        if candles.is_empty() {
+            return Err("No candle data received from Upbit".to_string());
+        }
+        Ok(candles)
+    }
+
+    /// `since`(초) 이후의 캔들만 조회 (저장소 증분 갱신용).
+    pub async fn fetch_klines_since(
+        &self,
+        symbol: &str,
+        interval: &str,
+        since: i64,
+        limit: u32,
+    ) -> Result<Vec<Candle>, String> {
+        let candles = self
+            .request_pages(symbol, interval, Some(since), None, limit as usize)
+            .await?;
+        if candles.is_empty() {
+            return Err("No candle data received from Upbit".to_string());
+        }
+        Ok(candles)
+    }
+
+    /// `[start, end]`(초) 구간을 최신 페이지부터 거슬러 올라가며 조회한다.
+    /// 상장 전 구간처럼 데이터가 없으면 빈 결과를 반환한다.
+    pub async fn fetch_klines_range(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<Candle>, String> {
+        self.request_pages(symbol, interval, Some(start), Some(end), usize::MAX)
+            .await
+    }
+
+    /// 마켓 코드나 한글/영문 이름에 `query`가 들어간 마켓.
+    pub async fn search_markets(&self, query: &str) -> Result<Vec<SymbolSearchResult>, String> {
+        if self.markets.read().await.is_empty() {
+            let markets = self.request_markets().await?;
+            *self.markets.write().await = markets;
+        }
+
+        let needle = query.trim().to_uppercase();
+        let markets = self.markets.read().await;
+        Ok(markets
+            .iter()
+            .filter(|m| {
+                m.code.contains(&needle)
+                    || m.english_name.to_uppercase().contains(&needle)
+                    || m.korean_name.contains(query.trim())
+            })
+            .take(MAX_SEARCH_RESULTS)
+            .map(|m| SymbolSearchResult {
+                symbol: m.code.clone(),
+                label: format!("{} ({})", m.korean_name, m.english_name),
+                market: MarketType::Crypto,
+                exchange: "Upbit".to_string(),
+            })
+            .collect())
+    }
+
+    /// `start`에 닿거나 `max`개를 채울 때까지 `end` 이전 페이지를 차례로 받는다.
+    async fn request_pages(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: Option<i64>,
+        end: Option<i64>,
+        max: usize,
+    ) -> Result<Vec<Candle>, String> {
+        let path = Self::candle_path(interval)
+            .ok_or_else(|| format!("Upbit does not provide {} candles", interval))?;
+
+        let mut all_candles: Vec<Candle> = Vec::new();
+        // `to`는 그 시각 이전의 캔들만 돌려주므로 끝 시각을 포함하도록 1초 뒤로 잡는다
+        let mut cursor = end.map(|end| end + 1);
+
+        for _ in 0..MAX_BACKFILL_PAGES {
+            let count = PAGE_LIMIT.min(max.saturating_sub(all_candles.len()) as u32);
+            if count == 0 {
+                break;
+            }
+
+            let page = self.request_candles(&path, symbol, count, cursor).await?;
+            let Some(first_time) = page.first().map(|c| c.time) else {
+                break;
+            };
+            let page_len = page.len();
+            all_candles.extend(page);
+
+            // 구간 시작에 도달했거나 더 이상 과거 데이터가 없음
+            if start.is_some_and(|start| first_time <= start) || page_len < count as usize {
+                break;
+            }
+            cursor = Some(first_time);
+
+            tokio::time::sleep(PAGE_DELAY).await;
+        }
+
+        if let Some(start) = start {
+            all_candles.retain(|c| c.time >= start);
+        }
+        if let Some(end) = end {
+            all_candles.retain(|c| c.time <= end);
+        }
+        all_candles.sort_by_key(|c| c.time);
+        all_candles.dedup_by_key(|c| c.time);
+        Ok(all_candles)
+    }
+
+    async fn request_candles(
+        &self,
+        path: &str,
+        symbol: &str,
+        count: u32,
+        to: Option<i64>,
+    ) -> Result<Vec<Candle>, String> {
+        let mut query = vec![
+            ("market", symbol.to_uppercase()),
+            ("count", count.to_string()),
+        ];
+        if let Some(to) = to.and_then(|to| DateTime::<Utc>::from_timestamp(to, 0)) {
+            query.push(("to", to.format("%Y-%m-%dT%H:%M:%SZ").to_string()));
+        }
+
+        let json = self
+            .get_json(&format!("{}/v1/candles/{}", self.base_url, path), &query)
+            .await?;
+        Self::parse_candles(&json)
+    }
+
+    async fn request_markets(&self) -> Result<Vec<UpbitMarket>, String> {
+        let url = format!("{}/v1/market/all", self.base_url);
+        let json = self
+            .get_json(&url, &[("isDetails", "false".to_string())])
+            .await?;
+
+        Ok(json
+            .as_array()
+            .ok_or("Upbit market list is not an array")?
+            .iter()
+            .filter_map(|item| {
+                Some(UpbitMarket {
+                    code: item["market"].as_str()?.to_string(),
+                    korean_name: item["korean_name"].as_str()?.to_string(),
+                    english_name: item["english_name"].as_str()?.to_string(),
+                })
+            })
+            .collect())
+    }
+
+    async fn get_json(&self, url: &str, query: &[(&str, String)]) -> Result<Value, String> {
+        let resp = self
+            .client
+            .get(url)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(format!("Upbit API error ({}): {}", status, body));
+        }
+
+        resp.json().await.map_err(|e| format!("Parse error: {}", e))
+    }
+
+    /// 캔들 API 응답(최신순 배열)을 시간 오름차순 캔들로 바꾼다.
+    fn parse_candles(json: &Value) -> Result<Vec<Candle>, String> {
+        let items = json
+            .as_array()
+            .ok_or("Upbit candle response is not an array")?;
+
+        let mut candles: Vec<Candle> = items
+            .iter()
+            .filter_map(|item| {
+                let time = NaiveDateTime::parse_from_str(
+                    item["candle_date_time_utc"].as_str()?,
+                    "%Y-%m-%dT%H:%M:%S",
+                )
+                .ok()?
+                .and_utc()
+                .timestamp();
+
+                Some(Candle {
+                    time,
+                    open: item["opening_price"].as_f64()?,
+                    high: item["high_price"].as_f64()?,
+                    low: item["low_price"].as_f64()?,
+                    close: item["trade_price"].as_f64()?,
+                    volume: item["candle_acc_trade_volume"].as_f64()?,
+                    session: None,
+                    flow: None,
+                })
+            })
+            .collect();
+
+        candles.sort_by_key(|c| c.time);
+        Ok(candles)
+    }
+
+    fn candle_path(interval: &str) -> Option<String> {
+        let path = match interval {
+            "1m" | "3m" | "5m" | "10m" | "15m" | "30m" => {
+                format!("minutes/{}", interval.trim_end_matches('m'))
+            }
+            "1h" => "minutes/60".to_string(),
+            "4h" => "minutes/240".to_string(),
+            "1d" => "days".to_string(),
+            "1w" => "weeks".to_string(),
+            "1M" => "months".to_string(),
+            _ => return None,
+        };
+        Some(path)
+    }
+}
+
+impl MarketDataProvider for UpbitClient {
+    fn id(&self) -> &'static str {
+        "upbit"
+    }
+
+    fn name(&self) -> &'static str {
+        "Upbit"
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            markets: vec![MarketType::Crypto],
+            intervals: [
+                "1m", "3m", "5m", "10m", "15m", "30m", "1h", "4h", "1d", "1w", "1M",
+            ]
+            .map(String::from)
+            .to_vec(),
+            max_bars: 2_000,
+            supports_range: true,
+            // 시세 조회 API 초당 10회
+            requests_per_minute: Some(600),
+        }
+    }
+
+    fn supports_symbol(&self, symbol: &str) -> bool {
+        Self::is_market_code(symbol)
+    }
+
+    fn fetch_klines<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        limit: u32,
+    ) -> CandleFuture<'a> {
+        Box::pin(UpbitClient::fetch_klines(self, symbol, interval, limit))
+    }
+
+    fn fetch_klines_since<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        since: i64,
+        limit: u32,
+    ) -> CandleFuture<'a> {
+        Box::pin(UpbitClient::fetch_klines_since(
+            self, symbol, interval, since, limit,
+        ))
+    }
+
+    fn fetch_klines_range<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        start: i64,
+        end: i64,
+    ) -> CandleFuture<'a> {
+        Box::pin(UpbitClient::fetch_klines_range(
+            self, symbol, interval, start, end,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use chrono::DateTime;
+    use serde_json::Value;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::UpbitClient;
+    use crate::models::MarketType;
+
+    const CANDLES: &str = include_str!("fixtures/upbit/candles_minutes_1.json");
+    const MARKETS: &str = include_str!("fixtures/upbit/market_all.json");
+    const ERROR: &str = include_str!("fixtures/upbit/error_invalid_market.json");
+
+    /// First and last 1m candle the mock server has.
+    const LISTED: i64 = 1_709_400_000;
+    const NOW: i64 = LISTED + 2_000 * 60;
+
+    fn query_param<'a>(path: &'a str, key: &str) -> Option<&'a str> {
+        path.split_once('?')?
+            .1
+            .split('&')
+            .find_map(|pair| pair.strip_prefix(key)?.strip_prefix('='))
+    }
+
+    /// One page of the `[LISTED, NOW]` 1m series, newest first, in the recorded item format.
+    /// Upbit's `to` is exclusive; `inclusive_to` repeats the cursor candle to overlap pages.
+    fn candle_page(to: Option<i64>, count: usize, inclusive_to: bool) -> Value {
+        let template: Vec<Value> = serde_json::from_str(CANDLES).unwrap();
+        let newest = match to {
+            Some(to) if inclusive_to => to.div_euclid(60) * 60,
+            Some(to) => (to - 1).div_euclid(60) * 60,
+            None => NOW,
+        }
+        .min(NOW);
+        let items: Vec<Value> = (0..count as i64)
+            .map(|i| newest - i * 60)
+            .take_while(|&time| time >= LISTED)
+            .map(|time| {
+                let utc = DateTime::from_timestamp(time, 0).unwrap();
+                let mut item = template[0].clone();
+                item["candle_date_time_utc"] = utc.format("%Y-%m-%dT%H:%M:%S").to_string().into();
+                item["candle_date_time_kst"] = (utc + chrono::Duration::hours(9))
+                    .format("%Y-%m-%dT%H:%M:%S")
+                    .to_string()
+                    .into();
+                item["timestamp"] = ((time + 59) * 1000).into();
+                item["trade_price"] = ((time - LISTED) / 60).into();
+                item
+            })
+            .collect();
+        Value::Array(items)
+    }
+
+    /// Serves `/v1/candles/minutes/1` and `/v1/market/all`, recording each request path.
+    async fn server(inclusive_to: bool) -> (UpbitClient, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 4096];
+                let read = socket.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..read]);
+                let path = request
+                    .split_whitespace()
+                    .nth(1)
+                    .unwrap_or_default()
+                    .replace("%3A", ":");
+                log.lock().unwrap().push(path.clone());
+
+                let body = if path.starts_with("/v1/market/all") {
+                    MARKETS.to_string()
+                } else {
+                    let count = query_param(&path, "count").unwrap().parse().unwrap();
+                    let to = query_param(&path, "to")
+                        .map(|to| DateTime::parse_from_rfc3339(to).unwrap().timestamp());
+                    candle_page(to, count, inclusive_to).to_string()
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (
+            UpbitClient::with_base_url(format!("http://{}/", addr)),
+            requests,
+        )
+    }
+
+    fn cursors(requests: &Mutex<Vec<String>>) -> Vec<(Option<String>, String)> {
+        requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|path| {
+                (
+                    query_param(path, "to").map(String::from),
+                    query_param(path, "count").unwrap().to_string(),
+                )
+            })
+            .collect()
+    }
+
+    fn utc(time: i64) -> String {
+        DateTime::from_timestamp(time, 0)
+            .unwrap()
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string()
+    }
+
+    #[test]
+    fn parses_recorded_page_into_ascending_candles() {
+        let candles = UpbitClient::parse_candles(&serde_json::from_str(CANDLES).unwrap()).unwrap();
+        let times: Vec<i64> = candles.iter().map(|c| c.time).collect();
+        // 2024-03-04T02:03:00Z, 02:04, 02:05
+        assert_eq!(times, [1_709_517_780, 1_709_517_840, 1_709_517_900]);
+
+        let first = &candles[0];
+        assert_eq!(first.open, 88_005_000.0);
+        assert_eq!(first.high, 88_005_000.0);
+        assert_eq!(first.low, 87_955_000.0);
+        assert_eq!(first.close, 87_990_000.0);
+        assert_eq!(first.volume, 2.39061211);
+        assert!(candles
+            .iter()
+            .all(|c| c.session.is_none() && c.flow.is_none()));
+
+        let error = UpbitClient::parse_candles(&serde_json::from_str(ERROR).unwrap());
+        assert!(error.is_err());
+    }
+
+    #[test]
+    fn recognizes_market_codes() {
+        for code in ["KRW-BTC", "BTC-ETH", "USDT-BTC", "KRW-1INCH"] {
+            assert!(UpbitClient::is_market_code(code), "{}", code);
+        }
+        for code in [
+            "BTCUSDT",
+            "KRW-",
+            "ETH-BTC",
+            "KRW-BTC/KRW",
+            "krw-btc",
+            "005930",
+            "",
+        ] {
+            assert!(!UpbitClient::is_market_code(code), "{}", code);
+        }
+    }
+
+    #[tokio::test]
+    async fn range_pages_back_with_to_cursor_and_stops_at_start() {
+        let (client, requests) = server(false).await;
+        let start = NOW - 450 * 60;
+        let end = NOW - 10 * 60;
+
+        let candles = client
+            .fetch_klines_range("KRW-BTC", "1m", start, end)
+            .await
+            .unwrap();
+
+        assert_eq!(candles.len(), 441);
+        assert_eq!(candles.first().unwrap().time, start);
+        assert_eq!(candles.last().unwrap().time, end);
+        assert!(candles.windows(2).all(|w| w[1].time - w[0].time == 60));
+        assert_eq!(candles[0].close, ((start - LISTED) / 60) as f64);
+
+        // The first cursor includes `end`; each next one is the oldest candle of the previous
+        // page. The third page reaches `start`, so no fourth request is made
+        assert_eq!(
+            cursors(&requests),
+            [
+                (Some(utc(end + 1)), "200".to_string()),
+                (Some(utc(end - 199 * 60)), "200".to_string()),
+                (Some(utc(end - 399 * 60)), "200".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn range_before_listing_stops_on_short_page() {
+        let (client, requests) = server(false).await;
+        let candles = client
+            .fetch_klines_range("KRW-BTC", "1m", LISTED - 300 * 60, LISTED + 250 * 60)
+            .await
+            .unwrap();
+
+        assert_eq!(candles.len(), 251);
+        assert_eq!(candles.first().unwrap().time, LISTED);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn latest_klines_fill_the_limit_across_pages() {
+        let (client, requests) = server(false).await;
+        let candles = client.fetch_klines("krw-btc", "1m", 450).await.unwrap();
+
+        assert_eq!(candles.len(), 450);
+        assert_eq!(candles.last().unwrap().time, NOW);
+        assert!(candles.windows(2).all(|w| w[1].time - w[0].time == 60));
+        let counts: Vec<String> = cursors(&requests).into_iter().map(|(_, c)| c).collect();
+        assert_eq!(counts, ["200", "200", "50"]);
+        assert!(requests.lock().unwrap()[0].contains("market=KRW-BTC"));
+    }
+
+    #[tokio::test]
+    async fn overlapping_pages_are_deduplicated() {
+        let (client, _) = server(true).await;
+        let candles = client
+            .fetch_klines_range("KRW-BTC", "1m", NOW - 450 * 60, NOW)
+            .await
+            .unwrap();
+
+        assert!(candles.windows(2).all(|w| w[1].time - w[0].time == 60));
+        assert_eq!(candles.first().unwrap().time, NOW - 450 * 60);
+        assert_eq!(candles.last().unwrap().time, NOW);
+    }
+
+    #[tokio::test]
+    async fn searches_codes_and_names_with_one_market_list_request() {
+        let (client, requests) = server(false).await;
+        let symbols = |results: Vec<crate::models::SymbolSearchResult>| {
+            results.into_iter().map(|r| r.symbol).collect::<Vec<_>>()
+        };
+
+        let btc = client.search_markets("btc").await.unwrap();
+        assert_eq!(btc[0].label, "비트코인 (Bitcoin)");
+        assert_eq!(btc[0].exchange, "Upbit");
+        assert_eq!(btc[0].market, MarketType::Crypto);
+        assert_eq!(symbols(btc), ["KRW-BTC", "BTC-ETH", "USDT-BTC"]);
+
+        let by_korean = symbols(client.search_markets(" 이더리움 ").await.unwrap());
+        assert_eq!(by_korean, ["KRW-ETH", "BTC-ETH", "KRW-ETC"]);
+        let by_english = symbols(client.search_markets("ethereum").await.unwrap());
+        assert_eq!(by_english, by_korean);
+        assert_eq!(client.search_markets("KRW").await.unwrap().len(), 12);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("/v1/market/all"));
+    }
+}
@@ -1,6 +1,11 @@
-use crate::models::{Candle, FundamentalsResponse, MarketType, PremarketSnapshot, SymbolSearchResult};
+use super::provider::{CandleFuture, MarketDataProvider};
+use crate::models::{
+    Candle, CorporateAction, CorporateActionKind, FundamentalsResponse, MarketType,
+    PremarketSnapshot, ProviderCapabilities, SymbolSearchResult,
+};
 use serde_json::Value;
 
+#[derive(Clone)]
 pub struct YahooClient {
     client: reqwest::Client,
 }
@@ -21,13 +26,141 @@ impl YahooClient {
         interval: &str,
         _limit: u32,
     ) -> Result<Vec<Candle>, String> {
-        let range = Self::interval_to_range(interval);
+        let range = format!("range={}", Self::interval_to_range(interval));
+        self.request_chart(symbol, interval, &range).await
+    }
+
+    /// `since`(초)부터 현재까지의 캔들만 조회 (저장소 증분 갱신용).
+    pub async fn fetch_klines_since(
+        &self,
+        symbol: &str,
+        interval: &str,
+        since: i64,
+    ) -> Result<Vec<Candle>, String> {
+        let now = chrono::Utc::now().timestamp();
+        let range = format!("period1={}&period2={}", since, now);
+        self.request_chart(symbol, interval, &range).await
+    }
+
+    /// `[start, end]`(초) 구간을 Yahoo의 interval별 조회 한도에 맞춘 창으로 나눠
+    /// 최신 구간부터 거슬러 올라가며 조회한다. 데이터가 없는 창은 건너뛴다.
+    pub async fn fetch_klines_range(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<Candle>, String> {
+        let now = chrono::Utc::now().timestamp();
+        let start = match Self::max_lookback_seconds(interval) {
+            Some(lookback) => start.max(now - lookback),
+            None => start,
+        };
+        let window = Self::window_seconds(interval);
+
+        let mut all_candles: Vec<Candle> = Vec::new();
+        let mut cursor_end = end;
+
+        while cursor_end > start {
+            let cursor_start = match window {
+                Some(window) => start.max(cursor_end - window),
+                None => start,
+            };
+            let range = format!("period1={}&period2={}", cursor_start, cursor_end);
+            let json = self.request_chart_json(symbol, interval, &range).await?;
+
+            let has_data = json
+                .get("chart")
+                .and_then(|c| c.get("result"))
+                .and_then(|r| r.get(0))
+                .and_then(|r| r.get("timestamp"))
+                .is_some();
+            if has_data {
+                all_candles.extend(Self::parse_chart_response(&json)?);
+            }
+
+            cursor_end = cursor_start;
+            if cursor_end > start {
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+        }
+
+        all_candles.retain(|c| c.time >= start && c.time <= end);
+        all_candles.sort_by_key(|c| c.time);
+        all_candles.dedup_by_key(|c| c.time);
+        Ok(all_candles)
+    }
+
+    /// 상장 이후 전체 분할/배당 이력 (월봉 조회에 딸린 `events`).
+    pub async fn fetch_corporate_actions(
+        &self,
+        symbol: &str,
+    ) -> Result<Vec<CorporateAction>, String> {
+        let json = self
+            .request_chart_json(symbol, "1M", "range=max&events=div,splits")
+            .await?;
+        let events = json
+            .get("chart")
+            .and_then(|c| c.get("result"))
+            .and_then(|r| r.get(0))
+            .ok_or("Invalid Yahoo Finance response structure")?
+            .get("events");
+
+        let mut actions: Vec<CorporateAction> = Vec::new();
+        if let Some(splits) = events
+            .and_then(|e| e.get("splits"))
+            .and_then(Value::as_object)
+        {
+            actions.extend(splits.values().filter_map(|split| {
+                let numerator = split.get("numerator")?.as_f64()?;
+                let denominator = split.get("denominator")?.as_f64()?;
+                let time = split.get("date")?.as_i64()?;
+                (numerator > 0.0 && denominator > 0.0).then(|| CorporateAction {
+                    time,
+                    kind: CorporateActionKind::Split,
+                    value: numerator / denominator,
+                })
+            }));
+        }
+        if let Some(dividends) = events
+            .and_then(|e| e.get("dividends"))
+            .and_then(Value::as_object)
+        {
+            actions.extend(dividends.values().filter_map(|dividend| {
+                Some(CorporateAction {
+                    time: dividend.get("date")?.as_i64()?,
+                    kind: CorporateActionKind::Dividend,
+                    value: dividend.get("amount")?.as_f64()?,
+                })
+            }));
+        }
+
+        actions.sort_by_key(|action| action.time);
+        Ok(actions)
+    }
+
+    async fn request_chart(
+        &self,
+        symbol: &str,
+        interval: &str,
+        range_query: &str,
+    ) -> Result<Vec<Candle>, String> {
+        let json = self.request_chart_json(symbol, interval, range_query).await?;
+        Self::parse_chart_response(&json)
+    }
+
+    async fn request_chart_json(
+        &self,
+        symbol: &str,
+        interval: &str,
+        range_query: &str,
+    ) -> Result<Value, String> {
         let yahoo_interval = Self::map_interval(interval);
 
         let include_pre_post = Self::is_intraday(interval);
         let url = format!(
-            "https://query1.finance.yahoo.com/v8/finance/chart/{}?interval={}&range={}&includePrePost={}",
-            symbol, yahoo_interval, range, include_pre_post
+            "https://query1.finance.yahoo.com/v8/finance/chart/{}?interval={}&{}&includePrePost={}",
+            symbol, yahoo_interval, range_query, include_pre_post
         );
 
         let resp = self
@@ -43,12 +176,9 @@ impl YahooClient {
             return Err(format!("Yahoo Finance API error ({}): {}", status, body));
         }
 
-        let json: serde_json::Value = resp
-            .json()
+        resp.json()
             .await
-            .map_err(|e| format!("Parse error: {}", e))?;
-
-        Self::parse_chart_response(&json)
+            .map_err(|e| format!("Parse error: {}", e))
     }
 
     pub async fn fetch_fundamentals(
@@ -191,6 +321,50 @@ impl YahooClient {
         })
     }
 
+    /// 실적 발표 예정/최근 발표 시각 (calendarEvents). 발표 직후에는 지난 날짜가 남아 있다.
+    /// 그보다 앞선 발표일은 제공하지 않는다 (earningsHistory의 `quarter`는 발표일이 아니라
+    /// 회계 분기 말일이다).
+    pub async fn fetch_earnings_dates(&self, symbol: &str) -> Result<Vec<i64>, String> {
+        let url = format!(
+            "https://query1.finance.yahoo.com/v10/finance/quoteSummary/{}?modules=calendarEvents",
+            symbol
+        );
+
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        if !resp.status().is_success() {
+            return Err(format!("Yahoo API error: {}", resp.status()));
+        }
+
+        let json: Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("Parse error: {}", e))?;
+
+        let dates = json
+            .get("quoteSummary")
+            .and_then(|s| s.get("result"))
+            .and_then(|r| r.get(0))
+            .and_then(|r| Self::value_at(r, &["calendarEvents", "earnings", "earningsDate"]))
+            .and_then(Value::as_array)
+            .ok_or("Invalid earnings calendar response")?;
+
+        let mut times: Vec<i64> = dates
+            .iter()
+            .filter_map(|date| {
+                date.as_i64()
+                    .or_else(|| date.get("raw").and_then(Value::as_i64))
+            })
+            .collect();
+        times.sort_unstable();
+        Ok(times)
+    }
+
     fn parse_chart_response(json: &serde_json::Value) -> Result<Vec<Candle>, String> {
         let result = json
             .get("chart")
@@ -238,6 +412,8 @@ impl YahooClient {
                     low,
                     close,
                     volume,
+                    session: None,
+                    flow: None,
                 })
             })
             .collect();
@@ -369,6 +545,26 @@ impl YahooClient {
         }
     }
 
+    /// Yahoo가 제공하는 intraday 과거 데이터 한도
+    fn max_lookback_seconds(interval: &str) -> Option<i64> {
+        match interval {
+            "1m" => Some(30 * 86_400),
+            "2m" | "5m" | "15m" | "30m" => Some(60 * 86_400),
+            "1h" => Some(729 * 86_400),
+            _ => None,
+        }
+    }
+
+    /// period1~period2 한 번의 요청으로 허용되는 최대 구간
+    fn window_seconds(interval: &str) -> Option<i64> {
+        match interval {
+            "1m" => Some(7 * 86_400),
+            "2m" | "5m" | "15m" | "30m" => Some(59 * 86_400),
+            "1h" => Some(729 * 86_400),
+            _ => None,
+        }
+    }
+
     fn map_interval(interval: &str) -> &'static str {
         match interval {
             "1m" => "1m",
@@ -431,3 +627,58 @@ impl YahooClient {
         Some(current)
     }
 }
+
+impl MarketDataProvider for YahooClient {
+    fn id(&self) -> &'static str {
+        "yahoo"
+    }
+
+    fn name(&self) -> &'static str {
+        "Yahoo Finance"
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            markets: vec![MarketType::Forex, MarketType::UsStock, MarketType::KrStock],
+            intervals: ["1m", "2m", "5m", "15m", "30m", "1h", "1d", "1w", "1M"]
+                .map(String::from)
+                .to_vec(),
+            max_bars: 2_500,
+            supports_range: true,
+            requests_per_minute: None,
+        }
+    }
+
+    fn fetch_klines<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        limit: u32,
+    ) -> CandleFuture<'a> {
+        Box::pin(YahooClient::fetch_klines(self, symbol, interval, limit))
+    }
+
+    fn fetch_klines_since<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        since: i64,
+        _limit: u32,
+    ) -> CandleFuture<'a> {
+        Box::pin(YahooClient::fetch_klines_since(
+            self, symbol, interval, since,
+        ))
+    }
+
+    fn fetch_klines_range<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        start: i64,
+        end: i64,
+    ) -> CandleFuture<'a> {
+        Box::pin(YahooClient::fetch_klines_range(
+            self, symbol, interval, start, end,
+        ))
+    }
+}
@@ -0,0 +1,670 @@
+use std::collections::HashMap;
+
+use crate::models::{
+    BacktestConfig, BacktestSummary, BacktestTrade, Candle, EquityPoint, MarketType,
+    PerformanceMetrics, PerformancePoint, PositionSizing, SignalPoint,
+};
+use crate::ta_engine::metrics;
+
+/// 국내주식 매도 시 증권거래세 (농어촌특별세 포함, 2026년 기준)
+const KR_SELL_TAX_RATE: f64 = 0.002;
+
+pub struct BacktestOutput {
+    pub trades: Vec<BacktestTrade>,
+    pub equity_curve: Vec<EquityPoint>,
+    pub summary: BacktestSummary,
+    pub metrics: PerformanceMetrics,
+}
+
+struct OpenPosition {
+    direction: i8, // 1 = long, -1 = short
+    quantity: f64,
+    entry_time: i64,
+    entry_price: f64,
+    entry_index: usize,
+    entry_costs: f64,
+    entry_source: String,
+}
+
+struct Order {
+    direction: i8,
+    source: String,
+}
+
+struct Simulator<'a> {
+    config: &'a BacktestConfig,
+    sell_tax_rate: f64,
+    fractional: bool,
+    cash: f64,
+    position: Option<OpenPosition>,
+    trades: Vec<BacktestTrade>,
+    total_costs: f64,
+}
+
+fn default_sell_tax_rate(market: &MarketType) -> f64 {
+    match market {
+        MarketType::KrStock => KR_SELL_TAX_RATE,
+        _ => 0.0,
+    }
+}
+
+/// 봉 하나에 모인 신호 집계. 출처는 방향별로 처음 나온 신호의 것을 둔다
+#[derive(Default)]
+struct Votes {
+    net: i32,
+    buy_source: Option<String>,
+    sell_source: Option<String>,
+}
+
+/// 같은 봉에 찍힌 신호들을 방향별로 합산해 봉당 하나의 주문으로 만든다.
+/// 주문의 출처는 합산된 방향과 같은 신호에서 가져온다.
+fn collect_orders(signals: &[SignalPoint], sources: &[String]) -> HashMap<i64, Order> {
+    let mut votes: HashMap<i64, Votes> = HashMap::new();
+    for signal in signals {
+        if !sources.is_empty() && !sources.contains(&signal.source) {
+            continue;
+        }
+        let entry = votes.entry(signal.time).or_default();
+        let (delta, source) = if signal.signal_type.is_buy() {
+            (1, &mut entry.buy_source)
+        } else {
+            (-1, &mut entry.sell_source)
+        };
+        entry.net += delta;
+        source.get_or_insert_with(|| signal.source.clone());
+    }
+
+    votes
+        .into_iter()
+        .filter_map(|(time, votes)| {
+            let source = match votes.net.signum() {
+                1 => votes.buy_source,
+                -1 => votes.sell_source,
+                _ => None,
+            }?;
+            Some((
+                time,
+                Order {
+                    direction: votes.net.signum() as i8,
+                    source,
+                },
+            ))
+        })
+        .collect()
+}
+
+impl Simulator<'_> {
+    /// `can_open`이 false면 반대 포지션 청산만 하고 새로 진입하지 않는다.
+    fn execute(&mut self, order: &Order, price: f64, time: i64, index: usize, can_open: bool) {
+        if let Some(position) = self.position.as_ref() {
+            if position.direction == order.direction {
+                return;
+            }
+            self.close(price, time, index, "signal");
+        }
+
+        if can_open && (order.direction > 0 || self.config.allow_short) {
+            self.open(order.direction, price, time, index, &order.source);
+        }
+    }
+
+    fn open(&mut self, direction: i8, price: f64, time: i64, index: usize, source: &str) {
+        if price <= 0.0 || self.cash <= 0.0 {
+            return;
+        }
+
+        let budget = match self.config.position_sizing {
+            PositionSizing::Percent { fraction } => self.cash * fraction.max(0.0),
+            PositionSizing::FixedAmount { amount } => amount.max(0.0).min(self.cash),
+        };
+
+        let commission_rate = self.config.commission_rate;
+        let fill = if direction > 0 {
+            price * (1.0 + self.config.slippage_rate)
+        } else {
+            price * (1.0 - self.config.slippage_rate)
+        };
+        let mut quantity = budget / (fill * (1.0 + commission_rate));
+        if !self.fractional {
+            quantity = quantity.floor();
+        }
+        if quantity <= 0.0 {
+            return;
+        }
+
+        let notional = quantity * fill;
+        let commission = notional * commission_rate;
+        let costs = if direction > 0 {
+            self.cash -= notional + commission;
+            commission
+        } else {
+            // 공매도 진입은 매도이므로 거래세 부과
+            let tax = notional * self.sell_tax_rate;
+            self.cash += notional - commission - tax;
+            commission + tax
+        };
+        self.total_costs += costs;
+
+        self.position = Some(OpenPosition {
+            direction,
+            quantity,
+            entry_time: time,
+            entry_price: fill,
+            entry_index: index,
+            entry_costs: costs,
+            entry_source: source.to_string(),
+        });
+    }
+
+    fn close(&mut self, price: f64, time: i64, index: usize, reason: &str) {
+        let Some(position) = self.position.take() else {
+            return;
+        };
+
+        let commission_rate = self.config.commission_rate;
+        let (fill, exit_costs, gross) = if position.direction > 0 {
+            let fill = price * (1.0 - self.config.slippage_rate);
+            let notional = position.quantity * fill;
+            let costs = notional * (commission_rate + self.sell_tax_rate);
+            self.cash += notional - costs;
+            (fill, costs, (fill - position.entry_price) * position.quantity)
+        } else {
+            let fill = price * (1.0 + self.config.slippage_rate);
+            let notional = position.quantity * fill;
+            let costs = notional * commission_rate;
+            self.cash -= notional + costs;
+            (fill, costs, (position.entry_price - fill) * position.quantity)
+        };
+        self.total_costs += exit_costs;
+
+        let costs = position.entry_costs + exit_costs;
+        let pnl = gross - costs;
+        let invested = position.entry_price * position.quantity;
+
+        self.trades.push(BacktestTrade {
+            side: if position.direction > 0 { "long" } else { "short" }.to_string(),
+            entry_time: position.entry_time,
+            entry_price: position.entry_price,
+            exit_time: time,
+            exit_price: fill,
+            quantity: position.quantity,
+            pnl,
+            return_pct: if invested > 0.0 { pnl / invested } else { 0.0 },
+            costs,
+            bars_held: index.saturating_sub(position.entry_index),
+            entry_source: position.entry_source,
+            exit_reason: reason.to_string(),
+        });
+    }
+
+    fn mark_to_market(&self, price: f64) -> (f64, f64) {
+        match self.position.as_ref() {
+            Some(p) => {
+                let signed_quantity = p.quantity * f64::from(p.direction);
+                (self.cash + signed_quantity * price, signed_quantity)
+            }
+            None => (self.cash, 0.0),
+        }
+    }
+}
+
+/// 캔들을 봉 단위로 재생하며 매수/매도 신호를 포지션으로 바꾼다.
+///
+/// - 신호는 기본적으로 다음 봉 시가에 체결된다 (미래 정보 차단).
+/// - 반대 방향 신호는 기존 포지션을 청산하고, `allow_short`일 때만 공매도로 전환한다.
+/// - 마지막 봉에서 남은 포지션은 종가로 청산하며, 그 봉에서 체결되는 주문으로는 새로 진입하지 않는다.
+pub fn run(
+    candles: &[Candle],
+    signals: &[SignalPoint],
+    config: &BacktestConfig,
+    market: &MarketType,
+    interval: &str,
+) -> BacktestOutput {
+    let orders = collect_orders(signals, &config.sources);
+    let mut sim = Simulator {
+        config,
+        sell_tax_rate: config
+            .sell_tax_rate
+            .unwrap_or_else(|| default_sell_tax_rate(market)),
+        fractional: config
+            .allow_fractional
+            .unwrap_or(matches!(market, MarketType::Crypto)),
+        cash: config.initial_capital,
+        position: None,
+        trades: Vec::new(),
+        total_costs: 0.0,
+    };
+
+    let mut equity_curve = Vec::with_capacity(candles.len());
+    let mut pending: Option<&Order> = None;
+    let mut peak = config.initial_capital;
+    let mut weights = Vec::with_capacity(candles.len());
+
+    for (i, candle) in candles.iter().enumerate() {
+        let is_last = i + 1 == candles.len();
+        if let Some(order) = pending.take() {
+            // 마지막 봉 시가에 진입하면 바로 종가에 청산돼 비용만 두 번 낸다
+            sim.execute(order, candle.open, candle.time, i, !is_last);
+        }
+
+        if let Some(order) = orders.get(&candle.time) {
+            if config.fill_on_next_open {
+                pending = Some(order);
+            } else {
+                // 같은 봉 종가에 바로 청산될 진입은 수수료만 두 번 낸다
+                sim.execute(order, candle.close, candle.time, i, !is_last);
+            }
+        }
+
+        if is_last {
+            sim.close(candle.close, candle.time, i, "end");
+        }
+
+        let (equity, position) = sim.mark_to_market(candle.close);
+        weights.push(if equity > 0.0 {
+            position * candle.close / equity
+        } else {
+            0.0
+        });
+        peak = peak.max(equity);
+        equity_curve.push(EquityPoint {
+            time: candle.time,
+            equity,
+            drawdown: if peak > 0.0 { (peak - equity) / peak } else { 0.0 },
+            position,
+        });
+    }
+
+    let performance: Vec<PerformancePoint> = equity_curve
+        .iter()
+        .map(|p| PerformancePoint {
+            time: p.time,
+            value: p.equity,
+        })
+        .collect();
+    let metrics = metrics::from_equity(
+        &performance,
+        &weights,
+        metrics::periods_per_year(interval, market),
+        0.0,
+    );
+    let summary = summarize(candles, &sim, &equity_curve, &metrics);
+
+    BacktestOutput {
+        trades: sim.trades,
+        equity_curve,
+        summary,
+        metrics,
+    }
+}
+
+fn summarize(
+    candles: &[Candle],
+    sim: &Simulator<'_>,
+    equity_curve: &[EquityPoint],
+    metrics: &PerformanceMetrics,
+) -> BacktestSummary {
+    let initial_capital = sim.config.initial_capital;
+    let final_equity = equity_curve
+        .last()
+        .map(|p| p.equity)
+        .unwrap_or(initial_capital);
+
+    let buy_and_hold_return = match (candles.first(), candles.last()) {
+        (Some(first), Some(last)) if first.close > 0.0 => last.close / first.close - 1.0,
+        _ => 0.0,
+    };
+
+    let trade_count = sim.trades.len();
+    let wins = sim.trades.iter().filter(|t| t.pnl > 0.0).count();
+    let gross_profit: f64 = sim.trades.iter().filter(|t| t.pnl > 0.0).map(|t| t.pnl).sum();
+    let gross_loss: f64 = sim.trades.iter().filter(|t| t.pnl < 0.0).map(|t| -t.pnl).sum();
+
+    BacktestSummary {
+        initial_capital,
+        final_equity,
+        total_return: if initial_capital > 0.0 {
+            final_equity / initial_capital - 1.0
+        } else {
+            0.0
+        },
+        buy_and_hold_return,
+        max_drawdown: metrics.max_drawdown,
+        trade_count,
+        win_rate: if trade_count > 0 {
+            wins as f64 / trade_count as f64
+        } else {
+            0.0
+        },
+        avg_trade_return: if trade_count > 0 {
+            sim.trades.iter().map(|t| t.return_pct).sum::<f64>() / trade_count as f64
+        } else {
+            0.0
+        },
+        profit_factor: (gross_loss > 0.0).then(|| gross_profit / gross_loss),
+        exposure: metrics.exposure,
+        total_costs: sim.total_costs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SignalType;
+    use crate::test_support::candle_from;
+
+    const DAY: i64 = 86_400;
+
+    fn candle(day: i64, open: f64, close: f64) -> Candle {
+        candle_from(day * DAY, open, close)
+    }
+
+    fn signal(day: i64, buy: bool, source: &str) -> SignalPoint {
+        SignalPoint {
+            time: day * DAY,
+            signal_type: if buy {
+                SignalType::EmaCrossoverBuy
+            } else {
+                SignalType::EmaCrossoverSell
+            },
+            price: 0.0,
+            rsi: 50.0,
+            source: source.to_string(),
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn fills_on_next_open_with_slippage_and_commission() {
+        let candles = [
+            candle(0, 100.0, 100.0),
+            candle(1, 110.0, 120.0),
+            candle(2, 130.0, 115.0),
+            candle(3, 140.0, 150.0),
+        ];
+        // The buy on the last bar has no next open and never fills
+        let signals = [
+            signal(0, true, "a"),
+            signal(2, false, "a"),
+            signal(3, true, "a"),
+        ];
+        let config = BacktestConfig {
+            initial_capital: 1_000.0,
+            commission_rate: 0.001,
+            slippage_rate: 0.01,
+            ..Default::default()
+        };
+
+        let output = run(&candles, &signals, &config, &MarketType::Crypto, "1d");
+
+        let entry = 110.0 * 1.01;
+        let exit = 140.0 * 0.99;
+        let quantity = 1_000.0 / (entry * 1.001);
+        let entry_commission = quantity * entry * 0.001;
+        let exit_commission = quantity * exit * 0.001;
+        let final_equity = quantity * exit * 0.999;
+
+        assert_eq!(output.trades.len(), 1);
+        let trade = &output.trades[0];
+        assert_eq!(trade.side, "long");
+        assert_eq!((trade.entry_time, trade.exit_time), (DAY, 3 * DAY));
+        assert_close(trade.entry_price, entry);
+        assert_close(trade.exit_price, exit);
+        assert_close(trade.quantity, quantity);
+        assert_close(trade.costs, entry_commission + exit_commission);
+        assert_close(
+            trade.pnl,
+            (exit - entry) * quantity - entry_commission - exit_commission,
+        );
+        assert_eq!(trade.bars_held, 2);
+        assert_eq!(trade.exit_reason, "signal");
+
+        // All cash went into the position, so equity tracks the close
+        let equity: Vec<f64> = output.equity_curve.iter().map(|p| p.equity).collect();
+        assert_close(equity[0], 1_000.0);
+        assert_close(equity[1], quantity * 120.0);
+        assert_close(equity[2], quantity * 115.0);
+        assert_close(equity[3], final_equity);
+        assert_close(output.equity_curve[2].drawdown, 5.0 / 120.0);
+
+        let summary = &output.summary;
+        assert_close(summary.final_equity, final_equity);
+        assert_close(summary.total_return, final_equity / 1_000.0 - 1.0);
+        assert_close(summary.buy_and_hold_return, 0.5);
+        assert_close(summary.total_costs, entry_commission + exit_commission);
+        assert_eq!(summary.trade_count, 1);
+        assert_close(summary.win_rate, 1.0);
+        assert_close(summary.max_drawdown, 5.0 / 120.0);
+        // Held through the periods after bars 1 and 2 out of three
+        assert_close(summary.exposure, 2.0 / 3.0);
+    }
+
+    #[test]
+    fn sizes_whole_shares_and_charges_kr_sell_tax_on_exit() {
+        let candles = [
+            candle(0, 10_000.0, 10_000.0),
+            candle(1, 10_000.0, 10_500.0),
+            candle(2, 10_500.0, 11_000.0),
+        ];
+        let config = BacktestConfig {
+            initial_capital: 100_000.0,
+            commission_rate: 0.0,
+            position_sizing: PositionSizing::FixedAmount { amount: 35_000.0 },
+            fill_on_next_open: false,
+            ..Default::default()
+        };
+
+        let output = run(
+            &candles,
+            &[signal(0, true, "a")],
+            &config,
+            &MarketType::KrStock,
+            "1d",
+        );
+
+        // floor(35,000 / 10,000) shares, closed at the last close with 0.2% tax
+        let tax = 3.0 * 11_000.0 * KR_SELL_TAX_RATE;
+        assert_eq!(output.trades.len(), 1);
+        let trade = &output.trades[0];
+        assert_close(trade.quantity, 3.0);
+        assert_eq!(trade.exit_reason, "end");
+        assert_close(trade.costs, tax);
+        assert_close(trade.pnl, 3_000.0 - tax);
+        assert_close(output.summary.final_equity, 100_000.0 + 3_000.0 - tax);
+        assert_close(output.summary.total_costs, tax);
+        assert_close(output.equity_curve[1].position, 3.0);
+    }
+
+    #[test]
+    fn reverses_short_to_long_and_nets_conflicting_signals() {
+        let candles = [
+            candle(0, 100.0, 100.0),
+            candle(1, 100.0, 80.0),
+            candle(2, 80.0, 90.0),
+            candle(3, 90.0, 90.0),
+        ];
+        let signals = [
+            signal(0, false, "a"),
+            signal(1, true, "a"),
+            // Buy and sell on one bar cancel out; the other source is filtered
+            signal(2, true, "a"),
+            signal(2, false, "a"),
+            signal(3, false, "b"),
+        ];
+        let config = BacktestConfig {
+            initial_capital: 1_000.0,
+            commission_rate: 0.0,
+            position_sizing: PositionSizing::Percent { fraction: 0.5 },
+            allow_short: true,
+            fill_on_next_open: false,
+            sources: vec!["a".to_string()],
+            ..Default::default()
+        };
+
+        let output = run(&candles, &signals, &config, &MarketType::Crypto, "1d");
+
+        assert_eq!(output.trades.len(), 2);
+        let short = &output.trades[0];
+        assert_eq!(short.side, "short");
+        assert_close(short.quantity, 5.0);
+        assert_close(short.pnl, 100.0);
+        // Half of the 1,100 equity after covering goes long at 80
+        let long = &output.trades[1];
+        assert_eq!(long.side, "long");
+        assert_close(long.quantity, 550.0 / 80.0);
+        assert_close(long.pnl, 550.0 / 80.0 * 10.0);
+        assert_eq!(long.exit_reason, "end");
+
+        let positions: Vec<f64> = output.equity_curve.iter().map(|p| p.position).collect();
+        assert_close(positions[0], -5.0);
+        assert_close(positions[2], 550.0 / 80.0);
+        assert_close(positions[3], 0.0);
+        assert_close(output.summary.final_equity, 1_168.75);
+        assert_close(output.summary.win_rate, 1.0);
+        // No losing trade, so there is no profit factor to report
+        assert_eq!(output.summary.profit_factor, None);
+        assert_close(
+            output.summary.avg_trade_return,
+            (100.0 / 500.0 + 68.75 / 550.0) / 2.0,
+        );
+    }
+
+    #[test]
+    fn close_fills_do_not_open_on_the_last_bar() {
+        let candles = [
+            candle(0, 100.0, 100.0),
+            candle(1, 100.0, 110.0),
+            candle(2, 110.0, 120.0),
+        ];
+        let config = BacktestConfig {
+            initial_capital: 1_000.0,
+            commission_rate: 0.001,
+            allow_short: true,
+            fill_on_next_open: false,
+            ..Default::default()
+        };
+
+        // A buy on the last bar alone leaves the account flat and uncharged
+        let output = run(
+            &candles,
+            &[signal(2, true, "a")],
+            &config,
+            &MarketType::Crypto,
+            "1d",
+        );
+        assert!(output.trades.is_empty());
+        assert_close(output.summary.final_equity, 1_000.0);
+        assert_close(output.summary.total_costs, 0.0);
+
+        // A sell on the last bar still closes the long but does not go short
+        let output = run(
+            &candles,
+            &[signal(0, true, "a"), signal(2, false, "a")],
+            &config,
+            &MarketType::Crypto,
+            "1d",
+        );
+        assert_eq!(output.trades.len(), 1);
+        assert_eq!(output.trades[0].side, "long");
+        assert_eq!(output.trades[0].exit_reason, "signal");
+        assert_close(output.equity_curve[2].position, 0.0);
+    }
+
+    #[test]
+    fn next_open_fills_do_not_open_on_the_last_bar() {
+        let candles = [
+            candle(0, 100.0, 100.0),
+            candle(1, 100.0, 110.0),
+            candle(2, 110.0, 120.0),
+        ];
+        let config = BacktestConfig {
+            initial_capital: 1_000.0,
+            commission_rate: 0.001,
+            ..Default::default()
+        };
+
+        // The buy fills at the last open, where it would be closed again at "end"
+        let output = run(
+            &candles,
+            &[signal(1, true, "a")],
+            &config,
+            &MarketType::Crypto,
+            "1d",
+        );
+
+        assert!(output.trades.is_empty());
+        assert_close(output.summary.final_equity, 1_000.0);
+        assert_close(output.summary.total_costs, 0.0);
+    }
+
+    #[test]
+    fn profit_factor_divides_gross_profit_by_gross_loss() {
+        let candles = [
+            candle(0, 100.0, 100.0),
+            candle(1, 100.0, 90.0),
+            candle(2, 90.0, 90.0),
+            candle(3, 90.0, 120.0),
+        ];
+        let config = BacktestConfig {
+            initial_capital: 1_000.0,
+            commission_rate: 0.0,
+            fill_on_next_open: false,
+            ..Default::default()
+        };
+
+        let output = run(
+            &candles,
+            &[signal(0, true, "a"), signal(1, false, "a")],
+            &config,
+            &MarketType::Crypto,
+            "1d",
+        );
+        // Only a losing trade
+        assert_eq!(output.trades.len(), 1);
+        assert_eq!(output.summary.profit_factor, Some(0.0));
+
+        let output = run(
+            &candles,
+            &[
+                signal(0, true, "a"),
+                signal(1, false, "a"),
+                signal(2, true, "a"),
+            ],
+            &config,
+            &MarketType::Crypto,
+            "1d",
+        );
+        // Lost 100 on the first trade, then 10 units gained 30 each on the second
+        assert_eq!(output.trades.len(), 2);
+        assert_close(output.summary.profit_factor.unwrap(), 3.0);
+    }
+
+    #[test]
+    fn net_orders_take_the_source_of_the_winning_direction() {
+        let signals = [
+            signal(0, false, "sell"),
+            signal(0, true, "first-buy"),
+            signal(0, true, "second-buy"),
+            signal(1, true, "buy"),
+            signal(1, false, "first-sell"),
+            signal(1, false, "second-sell"),
+        ];
+
+        let orders = collect_orders(&signals, &[]);
+        assert_eq!(orders[&0].direction, 1);
+        assert_eq!(orders[&0].source, "first-buy");
+        assert_eq!(orders[&DAY].direction, -1);
+        assert_eq!(orders[&DAY].source, "first-sell");
+
+        // A tie leaves no order
+        assert!(collect_orders(&signals[..2], &[]).is_empty());
+    }
+}
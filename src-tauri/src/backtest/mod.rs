@@ -0,0 +1,5 @@
+mod engine;
+mod walk_forward;
+
+pub use engine::run;
+pub use walk_forward::walk_forward;
@@ -0,0 +1,759 @@
+use std::collections::{BTreeMap, HashSet};
+
+use super::engine::{self, BacktestOutput};
+use crate::models::{
+    AnalysisParams, Candle, ConsensusParams, OptimizationObjective, ParameterRange,
+    ParameterStability, PerformanceMetrics, PerformancePoint, SearchMethod, SignalStrategyParams,
+    WalkForwardConfig, WalkForwardParams, WalkForwardWindow,
+};
+use crate::ta_engine::{self, metrics, ExternalData};
+
+/// 한 번에 평가할 수 있는 최대 파라미터 조합 수
+const MAX_CANDIDATES: usize = 5_000;
+
+type ParamSet = BTreeMap<String, f64>;
+
+pub struct WalkForwardOutput {
+    pub candidates_evaluated: usize,
+    pub windows: Vec<WalkForwardWindow>,
+    pub stability: Vec<ParameterStability>,
+    pub out_of_sample_equity: Vec<PerformancePoint>,
+    pub out_of_sample_metrics: PerformanceMetrics,
+    pub walk_forward_efficiency: f64,
+    pub recommended_params: Option<ParamSet>,
+}
+
+struct Candidate {
+    set: ParamSet,
+    params: AnalysisParams,
+}
+
+/// 봉 인덱스 기준 윈도우: in-sample `[is_start, oos_start)`, out-of-sample `[oos_start, oos_end)`
+struct WindowBounds {
+    is_start: usize,
+    oos_start: usize,
+    oos_end: usize,
+}
+
+/// 파라미터 이름(camelCase, 전략별 설정은 `stochRsiConfig.rsiOversold`처럼 점으로 구분)에
+/// 해당하는 `SignalStrategyParams` 필드에 값을 적용한다.
+fn apply_parameter(
+    strategies: &mut SignalStrategyParams,
+    name: &str,
+    value: f64,
+) -> Result<(), String> {
+    let period = || {
+        if value >= 1.0 {
+            Ok(value.round() as usize)
+        } else {
+            Err(format!("{} 값은 1 이상이어야 합니다", name))
+        }
+    };
+
+    match name {
+        "emaFastPeriod" => strategies.ema_fast_period = period()?,
+        "emaSlowPeriod" => strategies.ema_slow_period = period()?,
+        "divergenceSwingLength" => strategies.divergence_swing_length = period()?,
+        "supertrendAdxConfig.period" => strategies.supertrend_adx_config.period = period()?,
+        "supertrendAdxConfig.multiplier" => strategies.supertrend_adx_config.multiplier = value,
+        "supertrendAdxConfig.adxPeriod" => strategies.supertrend_adx_config.adx_period = period()?,
+        "supertrendAdxConfig.adxThreshold" => {
+            strategies.supertrend_adx_config.adx_threshold = value
+        }
+        "stochRsiConfig.stochOversold" => strategies.stoch_rsi_config.stoch_oversold = value,
+        "stochRsiConfig.stochOverbought" => strategies.stoch_rsi_config.stoch_overbought = value,
+        "stochRsiConfig.rsiOversold" => strategies.stoch_rsi_config.rsi_oversold = value,
+        "stochRsiConfig.rsiOverbought" => strategies.stoch_rsi_config.rsi_overbought = value,
+        "cmfObvConfig.cmfPeriod" => strategies.cmf_obv_config.cmf_period = period()?,
+        "cmfObvConfig.obvLookback" => strategies.cmf_obv_config.obv_lookback = period()?,
+        "ttmSqueezeConfig.emaPeriod" => strategies.ttm_squeeze_config.ema_period = period()?,
+        "ttmSqueezeConfig.atrPeriod" => strategies.ttm_squeeze_config.atr_period = period()?,
+        "ttmSqueezeConfig.atrMultiplier" => strategies.ttm_squeeze_config.atr_multiplier = value,
+        "vwapBreakoutConfig.volumeWindow" => {
+            strategies.vwap_breakout_config.volume_window = period()?
+        }
+        "vwapBreakoutConfig.volumeMultiplier" => {
+            strategies.vwap_breakout_config.volume_multiplier = value
+        }
+        "parabolicSarConfig.step" => strategies.parabolic_sar_config.step = value,
+        "parabolicSarConfig.maxStep" => strategies.parabolic_sar_config.max_step = value,
+        "ibsMeanReversionConfig.ibsOversold" => {
+            strategies.ibs_mean_reversion_config.ibs_oversold = value
+        }
+        "ibsMeanReversionConfig.ibsOverbought" => {
+            strategies.ibs_mean_reversion_config.ibs_overbought = value
+        }
+        "ibsMeanReversionConfig.rsiOversold" => {
+            strategies.ibs_mean_reversion_config.rsi_oversold = value
+        }
+        "ibsMeanReversionConfig.rsiOverbought" => {
+            strategies.ibs_mean_reversion_config.rsi_overbought = value
+        }
+        "consensus.window" => {
+            strategies
+                .consensus
+                .get_or_insert_with(ConsensusParams::default)
+                .window = period()?
+        }
+        "consensus.threshold" => {
+            strategies
+                .consensus
+                .get_or_insert_with(ConsensusParams::default)
+                .threshold = value
+        }
+        _ => return Err(format!("최적화할 수 없는 파라미터입니다: {}", name)),
+    }
+    Ok(())
+}
+
+/// 조합 자체가 말이 안 되는 경우(빠른 EMA ≥ 느린 EMA, 과매도 ≥ 과매수 등)를 걸러낸다.
+fn is_valid_combination(strategies: &SignalStrategyParams) -> bool {
+    let stoch_rsi = &strategies.stoch_rsi_config;
+    let ibs = &strategies.ibs_mean_reversion_config;
+    let sar = &strategies.parabolic_sar_config;
+    strategies.ema_fast_period < strategies.ema_slow_period
+        && sar.step > 0.0
+        && sar.step <= sar.max_step
+        && stoch_rsi.stoch_oversold < stoch_rsi.stoch_overbought
+        && stoch_rsi.rsi_oversold < stoch_rsi.rsi_overbought
+        && ibs.ibs_oversold < ibs.ibs_overbought
+        && ibs.rsi_oversold < ibs.rsi_overbought
+}
+
+fn range_values(range: &ParameterRange) -> Result<Vec<f64>, String> {
+    if range.step.is_nan() || range.step <= 0.0 || range.min > range.max {
+        return Err(format!("{} 범위가 올바르지 않습니다", range.name));
+    }
+
+    let count = ((range.max - range.min) / range.step + 1e-9).floor() as usize + 1;
+    if count > MAX_CANDIDATES {
+        return Err(format!("{} 범위의 값이 너무 많습니다", range.name));
+    }
+    Ok((0..count)
+        .map(|i| range.min + range.step * i as f64)
+        .collect())
+}
+
+/// 시드 고정 난수 (SplitMix64). 같은 시드면 같은 조합을 뽑는다.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// 각 축에서 뽑을 값의 인덱스 조합 목록을 만든다.
+fn candidate_indices(
+    axes: &[Vec<f64>],
+    params: &WalkForwardParams,
+) -> Result<Vec<Vec<usize>>, String> {
+    let total = axes
+        .iter()
+        .try_fold(1usize, |acc, axis| acc.checked_mul(axis.len()))
+        .unwrap_or(usize::MAX);
+
+    match params.walk_forward.search {
+        SearchMethod::Grid => {
+            if total > MAX_CANDIDATES {
+                return Err(format!(
+                    "그리드 조합이 {}개를 넘습니다. 범위를 줄이거나 랜덤 탐색을 사용하세요",
+                    MAX_CANDIDATES
+                ));
+            }
+            let mut combos = vec![Vec::with_capacity(axes.len())];
+            for axis in axes {
+                combos = combos
+                    .into_iter()
+                    .flat_map(|prefix| {
+                        (0..axis.len()).map(move |i| {
+                            let mut next = prefix.clone();
+                            next.push(i);
+                            next
+                        })
+                    })
+                    .collect();
+            }
+            Ok(combos)
+        }
+        SearchMethod::Random => {
+            let wanted = params
+                .walk_forward
+                .random_samples
+                .clamp(1, MAX_CANDIDATES)
+                .min(total);
+            let mut rng = SplitMix64(params.walk_forward.seed);
+            let mut seen = HashSet::new();
+            let mut combos = Vec::with_capacity(wanted);
+            let mut attempts = 0;
+            while combos.len() < wanted && attempts < wanted * 20 {
+                attempts += 1;
+                let combo: Vec<usize> = axes.iter().map(|axis| rng.below(axis.len())).collect();
+                if seen.insert(combo.clone()) {
+                    combos.push(combo);
+                }
+            }
+            Ok(combos)
+        }
+    }
+}
+
+fn build_candidates(params: &WalkForwardParams) -> Result<Vec<Candidate>, String> {
+    if params.parameters.is_empty() {
+        return Err("최적화할 파라미터 범위가 없습니다".to_string());
+    }
+
+    let axes = params
+        .parameters
+        .iter()
+        .map(range_values)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut candidates = Vec::new();
+    for combo in candidate_indices(&axes, params)? {
+        let mut analysis = params.analysis.clone();
+        let mut set = ParamSet::new();
+        for ((range, axis), &index) in params.parameters.iter().zip(&axes).zip(&combo) {
+            let value = axis[index];
+            apply_parameter(&mut analysis.signal_strategies, &range.name, value)?;
+            set.insert(range.name.clone(), value);
+        }
+        if is_valid_combination(&analysis.signal_strategies) {
+            candidates.push(Candidate {
+                set,
+                params: analysis,
+            });
+        }
+    }
+
+    if candidates.is_empty() {
+        return Err("유효한 파라미터 조합이 없습니다".to_string());
+    }
+    Ok(candidates)
+}
+
+/// 구간 길이를 지정하지 않으면 불러온 봉 수에 맞춰 in-sample은 절반, out-of-sample은 그 1/5로 잡는다
+/// (윈도우 약 5개).
+fn build_windows(len: usize, config: &WalkForwardConfig) -> Result<Vec<WindowBounds>, String> {
+    let in_sample = config.in_sample_bars.unwrap_or(len / 2);
+    let out_of_sample = config.out_of_sample_bars.unwrap_or((in_sample / 5).max(2));
+    if in_sample < 2 || out_of_sample < 2 {
+        return Err("in-sample/out-of-sample 구간은 2봉 이상이어야 합니다".to_string());
+    }
+    if len < in_sample + out_of_sample {
+        return Err(format!(
+            "워크포워드에 필요한 캔들이 부족합니다 ({}봉 필요, {}봉 보유)",
+            in_sample + out_of_sample,
+            len
+        ));
+    }
+
+    let mut windows = Vec::new();
+    let mut oos_start = in_sample;
+    while oos_start < len {
+        let oos_end = (oos_start + out_of_sample).min(len);
+        if oos_end - oos_start < 2 {
+            break;
+        }
+        windows.push(WindowBounds {
+            is_start: if config.anchored {
+                0
+            } else {
+                oos_start - in_sample
+            },
+            oos_start,
+            oos_end,
+        });
+        oos_start = oos_end;
+    }
+    Ok(windows)
+}
+
+fn objective_score(metrics: &PerformanceMetrics, objective: OptimizationObjective) -> f64 {
+    let value = match objective {
+        OptimizationObjective::Sharpe => metrics.sharpe,
+        OptimizationObjective::Sortino => metrics.sortino,
+        OptimizationObjective::Calmar => metrics.calmar,
+        OptimizationObjective::Cagr => metrics.cagr,
+        OptimizationObjective::TotalReturn => metrics.total_return,
+    };
+    if value.is_finite() {
+        value
+    } else {
+        0.0
+    }
+}
+
+/// `[start, end)` 구간을 백테스트한다.
+/// 신호는 `end` 이전 캔들만으로 계산해 구간 밖 미래 데이터가 섞이지 않게 한다.
+/// 외부 자료는 `end` 이후 시점도 담고 있지만 캔들이 없는 시점에는 신호를 내지 않는다.
+fn evaluate(
+    candles: &[Candle],
+    external: &ExternalData,
+    start: usize,
+    end: usize,
+    analysis: &AnalysisParams,
+    params: &WalkForwardParams,
+) -> BacktestOutput {
+    let history = &candles[..end];
+    let mut signals = ta_engine::detect_signals(history, analysis, &external.signal_inputs());
+    ta_engine::apply_consensus(history, analysis, &mut signals);
+    engine::run(
+        &history[start..],
+        &signals,
+        &params.backtest,
+        &params.analysis.market,
+        &params.analysis.interval,
+    )
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+fn stability_surface(
+    candidates: &[Candidate],
+    scores: &[Vec<f64>],
+    selected: &[usize],
+) -> Vec<ParameterStability> {
+    let mut surface: Vec<ParameterStability> = candidates
+        .iter()
+        .zip(scores)
+        .enumerate()
+        .map(|(index, (candidate, window_scores))| {
+            let mean_score = mean(window_scores);
+            let variance = window_scores
+                .iter()
+                .map(|s| (s - mean_score).powi(2))
+                .sum::<f64>()
+                / window_scores.len().max(1) as f64;
+            let score_std = variance.sqrt();
+            ParameterStability {
+                params: candidate.set.clone(),
+                mean_score,
+                score_std,
+                min_score: window_scores.iter().copied().fold(f64::INFINITY, f64::min),
+                max_score: window_scores
+                    .iter()
+                    .copied()
+                    .fold(f64::NEG_INFINITY, f64::max),
+                robust_score: mean_score - score_std,
+                windows_selected: selected.iter().filter(|&&s| s == index).count(),
+            }
+        })
+        .collect();
+
+    surface.sort_by(|a, b| b.robust_score.total_cmp(&a.robust_score));
+    surface
+}
+
+/// 롤링(또는 앵커드) 워크포워드 최적화.
+///
+/// - 윈도우마다 in-sample 구간에서 목표 지표가 가장 높은 조합을 고른다.
+/// - 고른 조합을 바로 뒤 out-of-sample 구간에 적용하고, 그 구간들만 이어 붙여 실전 성과를 추정한다.
+/// - 모든 조합의 윈도우별 in-sample 점수로 안정성 표면을 만든다.
+pub fn walk_forward(
+    candles: &[Candle],
+    external: &ExternalData,
+    params: &WalkForwardParams,
+) -> Result<WalkForwardOutput, String> {
+    let candidates = build_candidates(params)?;
+    let bounds = build_windows(candles.len(), &params.walk_forward)?;
+    let objective = params.walk_forward.objective;
+    let initial_capital = params.backtest.initial_capital;
+
+    let mut scores = vec![Vec::with_capacity(bounds.len()); candidates.len()];
+    let mut selected = Vec::with_capacity(bounds.len());
+    let mut windows = Vec::with_capacity(bounds.len());
+    let mut oos_equity = Vec::new();
+    let mut oos_weights = Vec::new();
+    let mut running = initial_capital;
+
+    for (index, window) in bounds.iter().enumerate() {
+        let mut best = 0;
+        for (i, candidate) in candidates.iter().enumerate() {
+            let output = evaluate(
+                candles,
+                external,
+                window.is_start,
+                window.oos_start,
+                &candidate.params,
+                params,
+            );
+            let score = objective_score(&output.metrics, objective);
+            scores[i].push(score);
+            if score > scores[best][index] {
+                best = i;
+            }
+        }
+        selected.push(best);
+
+        let output = evaluate(
+            candles,
+            external,
+            window.oos_start,
+            window.oos_end,
+            &candidates[best].params,
+            params,
+        );
+        let window_candles = &candles[window.oos_start..window.oos_end];
+        let mut last = running;
+        for (point, candle) in output.equity_curve.iter().zip(window_candles) {
+            last = if initial_capital > 0.0 {
+                running * point.equity / initial_capital
+            } else {
+                point.equity
+            };
+            oos_equity.push(PerformancePoint {
+                time: point.time,
+                value: last,
+            });
+            oos_weights.push(if point.equity > 0.0 {
+                point.position * candle.close / point.equity
+            } else {
+                0.0
+            });
+        }
+        running = last;
+
+        windows.push(WalkForwardWindow {
+            index,
+            in_sample_start: candles[window.is_start].time,
+            in_sample_end: candles[window.oos_start - 1].time,
+            out_of_sample_start: candles[window.oos_start].time,
+            out_of_sample_end: candles[window.oos_end - 1].time,
+            best_params: candidates[best].set.clone(),
+            in_sample_score: scores[best][index],
+            out_of_sample_score: objective_score(&output.metrics, objective),
+            out_of_sample_trades: output.trades.len(),
+            out_of_sample_metrics: output.metrics,
+        });
+    }
+
+    let mean_in_sample = mean(
+        &windows
+            .iter()
+            .map(|w| w.in_sample_score)
+            .collect::<Vec<_>>(),
+    );
+    let mean_out_of_sample = mean(
+        &windows
+            .iter()
+            .map(|w| w.out_of_sample_score)
+            .collect::<Vec<_>>(),
+    );
+    let walk_forward_efficiency = if mean_in_sample.abs() > f64::EPSILON {
+        mean_out_of_sample / mean_in_sample
+    } else {
+        0.0
+    };
+
+    let stability = stability_surface(&candidates, &scores, &selected);
+    let recommended_params = stability.first().map(|s| s.params.clone());
+    let out_of_sample_metrics = metrics::from_equity(
+        &oos_equity,
+        &oos_weights,
+        metrics::periods_per_year(&params.analysis.interval, &params.analysis.market),
+        0.0,
+    );
+
+    Ok(WalkForwardOutput {
+        candidates_evaluated: candidates.len(),
+        windows,
+        stability,
+        out_of_sample_equity: oos_equity,
+        out_of_sample_metrics,
+        walk_forward_efficiency,
+        recommended_params,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BacktestConfig, MarketType};
+    use crate::test_support::candle;
+
+    const DAY: i64 = 86_400;
+
+    fn range(name: &str, min: f64, max: f64, step: f64) -> ParameterRange {
+        ParameterRange {
+            name: name.to_string(),
+            min,
+            max,
+            step,
+        }
+    }
+
+    fn params(
+        parameters: Vec<ParameterRange>,
+        walk_forward: WalkForwardConfig,
+    ) -> WalkForwardParams {
+        let mut analysis =
+            AnalysisParams::new("BTCUSDT".to_string(), "1d".to_string(), MarketType::Crypto);
+        analysis.signal_strategies.ema_crossover = true;
+        analysis.signal_strategies.ema_fast_period = 2;
+        WalkForwardParams {
+            analysis,
+            backtest: BacktestConfig::default(),
+            parameters,
+            walk_forward,
+        }
+    }
+
+    /// Falls for 5 bars, then rises for 15: a fast/slow EMA crossover gains on every cycle.
+    fn sawtooth(len: usize) -> Vec<Candle> {
+        let mut close = 100.0;
+        (0..len)
+            .map(|i| {
+                close += if i % 20 < 5 { -2.0 } else { 2.0 };
+                candle(i as i64 * DAY, close)
+            })
+            .collect()
+    }
+
+    fn bounds(windows: &[WindowBounds]) -> Vec<(usize, usize, usize)> {
+        windows
+            .iter()
+            .map(|w| (w.is_start, w.oos_start, w.oos_end))
+            .collect()
+    }
+
+    #[test]
+    fn default_windows_fit_the_loaded_series() {
+        // A plain analysis load returns about 500 bars
+        let windows = build_windows(500, &WalkForwardConfig::default()).unwrap();
+        assert_eq!(
+            bounds(&windows),
+            vec![
+                (0, 250, 300),
+                (50, 300, 350),
+                (100, 350, 400),
+                (150, 400, 450),
+                (200, 450, 500),
+            ]
+        );
+    }
+
+    #[test]
+    fn explicit_windows_roll_or_anchor_and_keep_a_short_tail() {
+        let config = WalkForwardConfig {
+            in_sample_bars: Some(100),
+            out_of_sample_bars: Some(40),
+            ..Default::default()
+        };
+        assert_eq!(
+            bounds(&build_windows(205, &config).unwrap()),
+            vec![(0, 100, 140), (40, 140, 180), (80, 180, 205)]
+        );
+
+        // A one-bar tail cannot be scored and is dropped
+        let anchored = WalkForwardConfig {
+            anchored: true,
+            ..config.clone()
+        };
+        assert_eq!(
+            bounds(&build_windows(181, &anchored).unwrap()),
+            vec![(0, 100, 140), (0, 140, 180)]
+        );
+    }
+
+    #[test]
+    fn rejects_series_shorter_than_one_window() {
+        let config = WalkForwardConfig {
+            in_sample_bars: Some(500),
+            out_of_sample_bars: Some(100),
+            ..Default::default()
+        };
+        assert!(build_windows(599, &config).is_err());
+        assert_eq!(build_windows(600, &config).unwrap().len(), 1);
+
+        let config = WalkForwardConfig {
+            out_of_sample_bars: Some(1),
+            ..Default::default()
+        };
+        assert!(build_windows(500, &config).is_err());
+    }
+
+    #[test]
+    fn each_window_takes_the_best_in_sample_candidate() {
+        let candles = sawtooth(200);
+        // A slow EMA longer than the series never crosses, so that candidate never trades
+        let params = params(
+            vec![range("emaSlowPeriod", 5.0, 1_000.0, 995.0)],
+            WalkForwardConfig {
+                in_sample_bars: Some(80),
+                out_of_sample_bars: Some(40),
+                objective: OptimizationObjective::TotalReturn,
+                ..Default::default()
+            },
+        );
+
+        let output = walk_forward(&candles, &ExternalData::default(), &params).unwrap();
+
+        assert_eq!(output.candidates_evaluated, 2);
+        assert_eq!(output.windows.len(), 3);
+        let trading: ParamSet = [("emaSlowPeriod".to_string(), 5.0)].into();
+        for window in &output.windows {
+            assert_eq!(window.best_params, trading);
+            assert!(window.in_sample_score > 0.0);
+        }
+        assert_eq!(output.recommended_params, Some(trading));
+        let selected: Vec<usize> = output
+            .stability
+            .iter()
+            .map(|s| s.windows_selected)
+            .collect();
+        assert_eq!(selected, [3, 0]);
+        // The idle candidate scores zero in every window
+        assert_eq!(output.stability[1].mean_score, 0.0);
+        assert_eq!(output.stability[1].score_std, 0.0);
+    }
+
+    #[test]
+    fn out_of_sample_equity_compounds_across_windows() {
+        let candles = sawtooth(200);
+        let params = params(
+            vec![range("emaSlowPeriod", 5.0, 5.0, 1.0)],
+            WalkForwardConfig {
+                in_sample_bars: Some(80),
+                out_of_sample_bars: Some(40),
+                ..Default::default()
+            },
+        );
+        let external = ExternalData::default();
+        let analysis = &build_candidates(&params).unwrap()[0].params;
+
+        let output = walk_forward(&candles, &external, &params).unwrap();
+
+        // Only out-of-sample bars are stitched, in order
+        let times: Vec<i64> = output.out_of_sample_equity.iter().map(|p| p.time).collect();
+        let expected: Vec<i64> = candles[80..].iter().map(|c| c.time).collect();
+        assert_eq!(times, expected);
+
+        // Each window restarts from the capital the previous one ended with
+        let initial = params.backtest.initial_capital;
+        let mut running = initial;
+        for (start, end) in [(80, 120), (120, 160), (160, 200)] {
+            let window = evaluate(&candles, &external, start, end, analysis, &params);
+            let first = window.equity_curve.first().unwrap().equity;
+            let stitched = output.out_of_sample_equity[start - 80].value;
+            assert!((stitched - running * first / initial).abs() < 1e-6);
+            running *= window.equity_curve.last().unwrap().equity / initial;
+        }
+        let last = output.out_of_sample_equity.last().unwrap().value;
+        assert!((last - running).abs() < 1e-6);
+        assert!(last > initial);
+    }
+
+    #[test]
+    fn invalid_combinations_are_not_evaluated() {
+        let mut strategies = SignalStrategyParams::default();
+        assert!(is_valid_combination(&strategies));
+        strategies.ema_fast_period = strategies.ema_slow_period;
+        assert!(!is_valid_combination(&strategies));
+
+        let mut strategies = SignalStrategyParams::default();
+        strategies.parabolic_sar_config.step = strategies.parabolic_sar_config.max_step * 2.0;
+        assert!(!is_valid_combination(&strategies));
+
+        let mut strategies = SignalStrategyParams::default();
+        strategies.stoch_rsi_config.stoch_oversold = strategies.stoch_rsi_config.stoch_overbought;
+        assert!(!is_valid_combination(&strategies));
+
+        // Fast periods 5, 10 and 15 against a slow period of 10: only 5 survives
+        let mixed = params(
+            vec![
+                range("emaFastPeriod", 5.0, 15.0, 5.0),
+                range("emaSlowPeriod", 10.0, 10.0, 1.0),
+            ],
+            WalkForwardConfig::default(),
+        );
+        let candidates = build_candidates(&mixed).unwrap();
+        let sets: Vec<&ParamSet> = candidates.iter().map(|c| &c.set).collect();
+        let expected: ParamSet = [
+            ("emaFastPeriod".to_string(), 5.0),
+            ("emaSlowPeriod".to_string(), 10.0),
+        ]
+        .into();
+        assert_eq!(sets, [&expected]);
+
+        let inverted = params(
+            vec![
+                range("emaFastPeriod", 20.0, 30.0, 10.0),
+                range("emaSlowPeriod", 10.0, 10.0, 1.0),
+            ],
+            WalkForwardConfig::default(),
+        );
+        assert!(build_candidates(&inverted).is_err());
+    }
+
+    #[test]
+    fn random_search_is_capped_and_repeatable() {
+        // 100 × 100 combinations: too many for a grid
+        let axes = vec![(0..100).map(f64::from).collect::<Vec<_>>(); 2];
+        let grid = params(Vec::new(), WalkForwardConfig::default());
+        assert!(candidate_indices(&axes, &grid).is_err());
+
+        let random = params(
+            Vec::new(),
+            WalkForwardConfig {
+                search: SearchMethod::Random,
+                random_samples: MAX_CANDIDATES * 2,
+                seed: 7,
+                ..Default::default()
+            },
+        );
+        let combos = candidate_indices(&axes, &random).unwrap();
+        assert_eq!(combos.len(), MAX_CANDIDATES);
+        assert_eq!(combos.iter().collect::<HashSet<_>>().len(), MAX_CANDIDATES);
+        assert_eq!(candidate_indices(&axes, &random).unwrap(), combos);
+
+        // Never more samples than distinct combinations
+        let small = vec![vec![1.0, 2.0, 3.0]; 2];
+        assert_eq!(candidate_indices(&small, &random).unwrap().len(), 9);
+    }
+
+    #[test]
+    fn stability_ranks_consistent_candidates_first() {
+        let candidate = |slow: f64| Candidate {
+            set: [("emaSlowPeriod".to_string(), slow)].into(),
+            params: AnalysisParams::new(
+                "BTCUSDT".to_string(),
+                "1d".to_string(),
+                MarketType::Crypto,
+            ),
+        };
+        let candidates = [candidate(10.0), candidate(20.0)];
+        // The first candidate wins both windows but swings between them
+        let scores = [vec![3.0, -1.0], vec![0.5, 0.5]];
+
+        let surface = stability_surface(&candidates, &scores, &[0, 0]);
+
+        let ranked: Vec<(f64, f64, f64, usize)> = surface
+            .iter()
+            .map(|s| {
+                (
+                    s.params["emaSlowPeriod"],
+                    s.mean_score,
+                    s.robust_score,
+                    s.windows_selected,
+                )
+            })
+            .collect();
+        assert_eq!(ranked, [(20.0, 0.5, 0.5, 0), (10.0, 1.0, -1.0, 2)]);
+        assert_eq!((surface[1].min_score, surface[1].max_score), (-1.0, 3.0));
+    }
+}
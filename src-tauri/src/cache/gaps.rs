@@ -0,0 +1,132 @@
+/// 저장된 캔들 시각에서 `[start, end]` 구간의 빈 구간(hole)을 찾는다.
+///
+/// - 인접 캔들 간격이 `max_gap`보다 크면 그 사이를 공백으로 본다 (주말/야간 등 자연 휴장은 `max_gap`으로 흡수).
+/// - 구간 앞/뒤에 데이터가 없으면 그 부분도 공백이다.
+/// - 이미 조회를 마친 `covered` 구간과 겹치는 부분은 제외한다.
+pub fn find_holes(
+    times: &[i64],
+    start: i64,
+    end: i64,
+    max_gap: i64,
+    covered: &[(i64, i64)],
+) -> Vec<(i64, i64)> {
+    if start >= end {
+        return Vec::new();
+    }
+
+    let mut raw = Vec::new();
+    match (times.first(), times.last()) {
+        (Some(&first), Some(&last)) => {
+            if first - start > max_gap {
+                raw.push((start, first - 1));
+            }
+            for pair in times.windows(2) {
+                if pair[1] - pair[0] > max_gap {
+                    raw.push((pair[0] + 1, pair[1] - 1));
+                }
+            }
+            if end - last > max_gap {
+                raw.push((last + 1, end));
+            }
+        }
+        _ => raw.push((start, end)),
+    }
+
+    raw.into_iter()
+        .flat_map(|hole| subtract_covered(hole, covered))
+        .collect()
+}
+
+/// 하나의 공백에서 이미 확인된 구간을 빼고 남은 조각들을 반환한다.
+fn subtract_covered(hole: (i64, i64), covered: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    let mut pieces = vec![hole];
+
+    for &(cov_start, cov_end) in covered {
+        let mut next = Vec::with_capacity(pieces.len() + 1);
+        for (piece_start, piece_end) in pieces {
+            if cov_end < piece_start || cov_start > piece_end {
+                next.push((piece_start, piece_end));
+                continue;
+            }
+            if cov_start > piece_start {
+                next.push((piece_start, cov_start - 1));
+            }
+            if cov_end < piece_end {
+                next.push((cov_end + 1, piece_end));
+            }
+        }
+        pieces = next;
+        if pieces.is_empty() {
+            break;
+        }
+    }
+
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_holes;
+
+    const DAY: i64 = 86_400;
+
+    fn daily(from: i64, to: i64) -> Vec<i64> {
+        (from..=to).map(|day| day * DAY).collect()
+    }
+
+    #[test]
+    fn empty_series_is_one_hole() {
+        assert_eq!(find_holes(&[], 0, 10 * DAY, DAY, &[]), vec![(0, 10 * DAY)]);
+        assert!(find_holes(&[], 10 * DAY, 10 * DAY, DAY, &[]).is_empty());
+    }
+
+    #[test]
+    fn finds_leading_and_trailing_holes() {
+        let times = daily(5, 8);
+        assert_eq!(
+            find_holes(&times, 0, 12 * DAY, DAY, &[]),
+            vec![(0, 5 * DAY - 1), (8 * DAY + 1, 12 * DAY)]
+        );
+        // Edges within one natural gap are not holes
+        assert!(find_holes(&times, 4 * DAY, 9 * DAY, DAY, &[]).is_empty());
+    }
+
+    #[test]
+    fn finds_interior_holes_beyond_the_natural_gap() {
+        let mut times = daily(0, 3);
+        times.extend(daily(6, 7)); // two missing days
+        times.extend(daily(9, 10)); // one missing day, within a 2-day gap allowance
+        assert_eq!(
+            find_holes(&times, 0, 10 * DAY, 2 * DAY, &[]),
+            vec![(3 * DAY + 1, 6 * DAY - 1)]
+        );
+    }
+
+    #[test]
+    fn subtracts_covered_ranges() {
+        let times = [0, 10 * DAY];
+        // Fully covered
+        assert!(find_holes(&times, 0, 10 * DAY, DAY, &[(0, 10 * DAY)]).is_empty());
+        // Covered in the middle splits the hole
+        assert_eq!(
+            find_holes(&times, 0, 10 * DAY, DAY, &[(3 * DAY, 5 * DAY)]),
+            vec![(1, 3 * DAY - 1), (5 * DAY + 1, 10 * DAY - 1)]
+        );
+    }
+
+    #[test]
+    fn truncated_fetch_leaves_the_unfetched_front_as_a_hole() {
+        // The source stopped at its page limit: only days 6..=10 of the 0..=10 hole came back,
+        // and backfill marked coverage from the first returned bar onward.
+        let times = daily(6, 10);
+        let covered = [(6 * DAY, 10 * DAY)];
+        assert_eq!(
+            find_holes(&times, 0, 10 * DAY, DAY, &covered),
+            vec![(0, 6 * DAY - 1)]
+        );
+
+        // A retry that returns nothing marks the rest covered, so the hole is not retried forever
+        let covered = [(0, 6 * DAY - 1), (6 * DAY, 10 * DAY)];
+        assert!(find_holes(&times, 0, 10 * DAY, DAY, &covered).is_empty());
+    }
+}
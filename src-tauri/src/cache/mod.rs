@@ -1,3 +1,5 @@
+mod gaps;
 mod sqlite;
 
+pub use gaps::find_holes;
 pub use sqlite::CacheDb;
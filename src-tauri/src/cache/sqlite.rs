@@ -1,66 +1,648 @@
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
+use std::path::Path;
 use std::sync::Mutex;
 
-use crate::models::Candle;
+use crate::models::{
+    AlertEvent, AlertRule, Candle, CorporateAction, CorporateActionKind, ProviderPriority,
+    TradeFlow,
+};
+
+/// 순서대로 적용되는 스키마 마이그레이션. `PRAGMA user_version`이 적용된 개수를 기록한다.
+const MIGRATIONS: &[&str] = &[
+    // v1: 정규화된 캔들 테이블 + 시리즈별 동기화 메타데이터
+    "CREATE TABLE IF NOT EXISTS candles (
+        symbol TEXT NOT NULL,
+        source TEXT NOT NULL,
+        interval TEXT NOT NULL,
+        time INTEGER NOT NULL,
+        open REAL NOT NULL,
+        high REAL NOT NULL,
+        low REAL NOT NULL,
+        close REAL NOT NULL,
+        volume REAL NOT NULL,
+        PRIMARY KEY (symbol, source, interval, time)
+    ) WITHOUT ROWID;
+    CREATE TABLE IF NOT EXISTS series_meta (
+        symbol TEXT NOT NULL,
+        source TEXT NOT NULL,
+        interval TEXT NOT NULL,
+        fetched_at INTEGER NOT NULL,
+        PRIMARY KEY (symbol, source, interval)
+    );",
+    // v2: 백필로 확인된 구간 (휴장일·상장 전처럼 데이터가 없는 구간을 다시 요청하지 않기 위함)
+    "CREATE TABLE IF NOT EXISTS series_coverage (
+        symbol TEXT NOT NULL,
+        source TEXT NOT NULL,
+        interval TEXT NOT NULL,
+        start_time INTEGER NOT NULL,
+        end_time INTEGER NOT NULL,
+        PRIMARY KEY (symbol, source, interval, start_time, end_time)
+    );",
+    // v3: 알림 규칙과 발생 이력 (같은 규칙·심볼·키는 한 번만 기록)
+    "CREATE TABLE IF NOT EXISTS alert_rules (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        rule TEXT NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS alert_history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        rule_id INTEGER NOT NULL,
+        symbol TEXT NOT NULL,
+        event_key TEXT NOT NULL,
+        triggered_at INTEGER NOT NULL,
+        event TEXT NOT NULL,
+        UNIQUE (rule_id, symbol, event_key)
+    );
+    CREATE INDEX IF NOT EXISTS alert_history_triggered ON alert_history (triggered_at);",
+    // v4: 종목별 분할/배당 이력과 마지막 동기화 시각
+    "CREATE TABLE IF NOT EXISTS corporate_actions (
+        symbol TEXT NOT NULL,
+        time INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        value REAL NOT NULL,
+        PRIMARY KEY (symbol, time, kind)
+    ) WITHOUT ROWID;
+    CREATE TABLE IF NOT EXISTS corporate_action_sync (
+        symbol TEXT PRIMARY KEY,
+        synced_at INTEGER NOT NULL
+    );",
+    // v5: 사용자가 정한 시장별 데이터 공급자 순서
+    "CREATE TABLE IF NOT EXISTS provider_priority (
+        market TEXT PRIMARY KEY,
+        priority TEXT NOT NULL
+    );",
+    // v6: 체결 집계 (Binance klines). 제공하지 않는 공급자의 봉은 NULL
+    "ALTER TABLE candles ADD COLUMN quote_volume REAL;
+    ALTER TABLE candles ADD COLUMN trade_count INTEGER;
+    ALTER TABLE candles ADD COLUMN taker_buy_volume REAL;",
+];
 
 pub struct CacheDb {
     conn: Mutex<Connection>,
 }
 
 impl CacheDb {
-    pub fn new() -> Result<Self, String> {
+    /// 앱 데이터 디렉터리 아래의 영구 캔들 저장소를 연다.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+        }
+
+        let conn = Connection::open(path).map_err(|e| format!("Failed to open cache db: {}", e))?;
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")
+            .map_err(|e| format!("Failed to configure cache db: {}", e))?;
+
+        Self::from_connection(conn)
+    }
+
+    /// 디스크를 쓰지 않는 임시 저장소 (파일을 열 수 없을 때의 폴백).
+    pub fn open_in_memory() -> Result<Self, String> {
         let conn =
             Connection::open_in_memory().map_err(|e| format!("Failed to open cache db: {}", e))?;
+        Self::from_connection(conn)
+    }
 
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS kline_cache (
-                cache_key TEXT PRIMARY KEY,
-                data TEXT NOT NULL,
-                cached_at INTEGER NOT NULL
-            );",
-        )
-        .map_err(|e| format!("Failed to create cache table: {}", e))?;
-
+    fn from_connection(mut conn: Connection) -> Result<Self, String> {
+        Self::migrate(&mut conn)?;
         Ok(Self {
             conn: Mutex::new(conn),
         })
     }
 
-    pub fn get(&self, symbol: &str, interval: &str, source: &str) -> Option<Vec<Candle>> {
-        let conn = self.conn.lock().ok()?;
-        let key = format!("{}:{}:{}", symbol, source, interval);
-        let ttl = Self::ttl_seconds(interval);
+    fn migrate(conn: &mut Connection) -> Result<(), String> {
+        let version: usize = conn
+            .query_row("PRAGMA user_version", [], |row| row.get::<_, i64>(0))
+            .map_err(|e| format!("Failed to read schema version: {}", e))?
+            .max(0) as usize;
+
+        for (index, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = conn
+                .transaction()
+                .map_err(|e| format!("Cache migration error: {}", e))?;
+            tx.execute_batch(sql)
+                .map_err(|e| format!("Cache migration v{} failed: {}", index + 1, e))?;
+            tx.pragma_update(None, "user_version", (index + 1) as i64)
+                .map_err(|e| format!("Cache migration error: {}", e))?;
+            tx.commit()
+                .map_err(|e| format!("Cache migration error: {}", e))?;
+        }
+
+        Ok(())
+    }
+
+    /// TTL 안에 동기화된 시리즈만 반환한다 (최근 `limit`개, 시간 오름차순).
+    pub fn get(&self, symbol: &str, interval: &str, source: &str, limit: u32) -> Option<Vec<Candle>> {
+        if !self.is_fresh(symbol, interval, source) {
+            return None;
+        }
+        let candles = self.load(symbol, interval, source, limit).ok()?;
+        if candles.is_empty() {
+            None
+        } else {
+            Some(candles)
+        }
+    }
+
+    /// 받아온 캔들을 upsert하고 시리즈의 동기화 시각을 갱신한다.
+    pub fn set(&self, symbol: &str, interval: &str, source: &str, candles: &[Candle]) -> Result<(), String> {
+        self.write_candles(symbol, interval, source, candles, true)
+    }
+
+    /// 과거 구간 캔들을 병합한다. 최신 데이터가 아니므로 동기화 시각은 건드리지 않는다.
+    pub fn upsert(&self, symbol: &str, interval: &str, source: &str, candles: &[Candle]) -> Result<(), String> {
+        self.write_candles(symbol, interval, source, candles, false)
+    }
+
+    fn write_candles(
+        &self,
+        symbol: &str,
+        interval: &str,
+        source: &str,
+        candles: &[Candle],
+        touch_meta: bool,
+    ) -> Result<(), String> {
+        let mut conn = self.conn.lock().map_err(|e| e.to_string())?;
         let now = chrono::Utc::now().timestamp();
 
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Cache write error: {}", e))?;
+        {
+            let mut stmt = tx
+                .prepare_cached(
+                    "INSERT INTO candles (symbol, source, interval, time, open, high, low, close, volume,
+                                          quote_volume, trade_count, taker_buy_volume)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                     ON CONFLICT (symbol, source, interval, time) DO UPDATE SET
+                        open = excluded.open,
+                        high = excluded.high,
+                        low = excluded.low,
+                        close = excluded.close,
+                        volume = excluded.volume,
+                        quote_volume = excluded.quote_volume,
+                        trade_count = excluded.trade_count,
+                        taker_buy_volume = excluded.taker_buy_volume",
+                )
+                .map_err(|e| format!("Cache write error: {}", e))?;
+
+            for candle in candles {
+                stmt.execute(rusqlite::params![
+                    symbol,
+                    source,
+                    interval,
+                    candle.time,
+                    candle.open,
+                    candle.high,
+                    candle.low,
+                    candle.close,
+                    candle.volume,
+                    candle.flow.map(|flow| flow.quote_volume),
+                    candle.flow.map(|flow| flow.trade_count as i64),
+                    candle.flow.map(|flow| flow.taker_buy_volume),
+                ])
+                .map_err(|e| format!("Cache write error: {}", e))?;
+            }
+
+            if touch_meta {
+                tx.execute(
+                    "INSERT INTO series_meta (symbol, source, interval, fetched_at) VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT (symbol, source, interval) DO UPDATE SET fetched_at = excluded.fetched_at",
+                    rusqlite::params![symbol, source, interval, now],
+                )
+                .map_err(|e| format!("Cache write error: {}", e))?;
+            }
+        }
+        tx.commit().map_err(|e| format!("Cache write error: {}", e))?;
+
+        Ok(())
+    }
+
+    /// 저장된 시리즈의 최근 `limit`개 캔들 (TTL과 무관).
+    pub fn load(&self, symbol: &str, interval: &str, source: &str, limit: u32) -> Result<Vec<Candle>, String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
         let mut stmt = conn
-            .prepare("SELECT data, cached_at FROM kline_cache WHERE cache_key = ?1")
-            .ok()?;
+            .prepare_cached(
+                "SELECT time, open, high, low, close, volume, quote_volume, trade_count, taker_buy_volume
+                 FROM candles
+                 WHERE symbol = ?1 AND source = ?2 AND interval = ?3
+                 ORDER BY time DESC LIMIT ?4",
+            )
+            .map_err(|e| format!("Cache read error: {}", e))?;
+
+        let mut candles = stmt
+            .query_map(rusqlite::params![symbol, source, interval, limit], Self::row_to_candle)
+            .map_err(|e| format!("Cache read error: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Cache read error: {}", e))?;
 
-        let result: Option<(String, i64)> = stmt
-            .query_row(rusqlite::params![key], |row| Ok((row.get(0)?, row.get(1)?)))
-            .ok();
+        candles.reverse();
+        Ok(candles)
+    }
 
-        match result {
-            Some((data, cached_at)) if now - cached_at < ttl => serde_json::from_str(&data).ok(),
-            _ => None,
+    /// `[start, end]` 구간에 저장된 캔들 (시간 오름차순).
+    pub fn load_range(
+        &self,
+        symbol: &str,
+        interval: &str,
+        source: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<Candle>, String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT time, open, high, low, close, volume, quote_volume, trade_count, taker_buy_volume
+                 FROM candles
+                 WHERE symbol = ?1 AND source = ?2 AND interval = ?3 AND time BETWEEN ?4 AND ?5
+                 ORDER BY time ASC",
+            )
+            .map_err(|e| format!("Cache read error: {}", e))?;
+
+        let candles = stmt
+            .query_map(rusqlite::params![symbol, source, interval, start, end], Self::row_to_candle)
+            .map_err(|e| format!("Cache read error: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Cache read error: {}", e))?;
+
+        Ok(candles)
+    }
+
+    /// `[start, end]` 구간에 저장된 캔들 시각만 조회 (공백 탐지용).
+    pub fn times(&self, symbol: &str, interval: &str, source: &str, start: i64, end: i64) -> Result<Vec<i64>, String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT time FROM candles
+                 WHERE symbol = ?1 AND source = ?2 AND interval = ?3 AND time BETWEEN ?4 AND ?5
+                 ORDER BY time ASC",
+            )
+            .map_err(|e| format!("Cache read error: {}", e))?;
+
+        let times = stmt
+            .query_map(rusqlite::params![symbol, source, interval, start, end], |row| row.get(0))
+            .map_err(|e| format!("Cache read error: {}", e))?
+            .collect::<Result<Vec<i64>, _>>()
+            .map_err(|e| format!("Cache read error: {}", e))?;
+
+        Ok(times)
+    }
+
+    /// 백필로 이미 확인한 구간 목록.
+    pub fn coverage(&self, symbol: &str, interval: &str, source: &str) -> Result<Vec<(i64, i64)>, String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT start_time, end_time FROM series_coverage
+                 WHERE symbol = ?1 AND source = ?2 AND interval = ?3
+                 ORDER BY start_time ASC",
+            )
+            .map_err(|e| format!("Cache read error: {}", e))?;
+
+        let ranges = stmt
+            .query_map(rusqlite::params![symbol, source, interval], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .map_err(|e| format!("Cache read error: {}", e))?
+            .collect::<Result<Vec<(i64, i64)>, _>>()
+            .map_err(|e| format!("Cache read error: {}", e))?;
+
+        Ok(ranges)
+    }
+
+    /// 소스에서 조회를 마친 구간을 기록한다 (결과가 비어 있어도 기록).
+    /// 겹치거나 맞닿은 기존 구간과 합쳐 시리즈마다 서로 떨어진 구간만 남긴다.
+    pub fn mark_covered(&self, symbol: &str, interval: &str, source: &str, start: i64, end: i64) -> Result<(), String> {
+        let mut conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Cache write error: {}", e))?;
+
+        let (merged_start, merged_end): (i64, i64) = tx
+            .query_row(
+                "SELECT COALESCE(MIN(MIN(start_time), ?4), ?4), COALESCE(MAX(MAX(end_time), ?5), ?5)
+                 FROM series_coverage
+                 WHERE symbol = ?1 AND source = ?2 AND interval = ?3
+                   AND start_time <= ?5 + 1 AND end_time >= ?4 - 1",
+                rusqlite::params![symbol, source, interval, start, end],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| format!("Cache write error: {}", e))?;
+        tx.execute(
+            "DELETE FROM series_coverage
+             WHERE symbol = ?1 AND source = ?2 AND interval = ?3
+               AND start_time <= ?5 + 1 AND end_time >= ?4 - 1",
+            rusqlite::params![symbol, source, interval, start, end],
+        )
+        .map_err(|e| format!("Cache write error: {}", e))?;
+        tx.execute(
+            "INSERT INTO series_coverage (symbol, source, interval, start_time, end_time)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![symbol, source, interval, merged_start, merged_end],
+        )
+        .map_err(|e| format!("Cache write error: {}", e))?;
+        tx.commit()
+            .map_err(|e| format!("Cache write error: {}", e))?;
+
+        Ok(())
+    }
+
+    /// 저장된 마지막 캔들의 시각 (증분 조회의 시작점).
+    pub fn last_time(&self, symbol: &str, interval: &str, source: &str) -> Option<i64> {
+        let conn = self.conn.lock().ok()?;
+        conn.query_row(
+            "SELECT MAX(time) FROM candles WHERE symbol = ?1 AND source = ?2 AND interval = ?3",
+            rusqlite::params![symbol, source, interval],
+            |row| row.get::<_, Option<i64>>(0),
+        )
+        .ok()
+        .flatten()
+    }
+
+    /// 시리즈 키(`us:AAPL`)에 저장된 모든 캔들과 동기화 기록을 지운다.
+    /// 새 분할로 가격 기준이 바뀌었을 때 전체를 다시 받게 한다.
+    pub fn clear_series(&self, symbol: &str) -> Result<(), String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        for table in ["candles", "series_meta", "series_coverage"] {
+            conn.execute(
+                &format!("DELETE FROM {} WHERE symbol = ?1", table),
+                rusqlite::params![symbol],
+            )
+            .map_err(|e| format!("Cache write error: {}", e))?;
         }
+        Ok(())
     }
 
-    pub fn set(&self, symbol: &str, interval: &str, source: &str, candles: &[Candle]) -> Result<(), String> {
+    /// 종목의 분할/배당 이력 (시간 오름차순).
+    pub fn corporate_actions(&self, symbol: &str) -> Result<Vec<CorporateAction>, String> {
         let conn = self.conn.lock().map_err(|e| e.to_string())?;
-        let key = format!("{}:{}:{}", symbol, source, interval);
-        let data = serde_json::to_string(candles).map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT time, kind, value FROM corporate_actions
+                 WHERE symbol = ?1 ORDER BY time ASC",
+            )
+            .map_err(|e| format!("Cache read error: {}", e))?;
+
+        let actions = stmt
+            .query_map(rusqlite::params![symbol], |row| {
+                let kind = match row.get::<_, String>(1)?.as_str() {
+                    "split" => CorporateActionKind::Split,
+                    _ => CorporateActionKind::Dividend,
+                };
+                Ok(CorporateAction {
+                    time: row.get(0)?,
+                    kind,
+                    value: row.get(2)?,
+                })
+            })
+            .map_err(|e| format!("Cache read error: {}", e))?
+            .collect::<Result<Vec<CorporateAction>, _>>()
+            .map_err(|e| format!("Cache read error: {}", e))?;
+
+        Ok(actions)
+    }
+
+    /// 분할/배당 이력을 마지막으로 동기화한 시각.
+    pub fn corporate_actions_synced_at(&self, symbol: &str) -> Option<i64> {
+        let conn = self.conn.lock().ok()?;
+        conn.query_row(
+            "SELECT synced_at FROM corporate_action_sync WHERE symbol = ?1",
+            rusqlite::params![symbol],
+            |row| row.get(0),
+        )
+        .optional()
+        .ok()
+        .flatten()
+    }
+
+    /// 분할/배당 이력을 병합하고 새로 추가된 항목을 돌려준다.
+    pub fn save_corporate_actions(
+        &self,
+        symbol: &str,
+        actions: &[CorporateAction],
+    ) -> Result<Vec<CorporateAction>, String> {
         let now = chrono::Utc::now().timestamp();
+        let mut conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Cache write error: {}", e))?;
 
-        conn.execute(
-            "INSERT OR REPLACE INTO kline_cache (cache_key, data, cached_at) VALUES (?1, ?2, ?3)",
-            rusqlite::params![key, data, now],
+        let mut added = Vec::new();
+        {
+            let mut stmt = tx
+                .prepare_cached(
+                    "INSERT OR IGNORE INTO corporate_actions (symbol, time, kind, value)
+                     VALUES (?1, ?2, ?3, ?4)",
+                )
+                .map_err(|e| format!("Cache write error: {}", e))?;
+            for action in actions {
+                let kind = match action.kind {
+                    CorporateActionKind::Split => "split",
+                    CorporateActionKind::Dividend => "dividend",
+                };
+                let inserted = stmt
+                    .execute(rusqlite::params![symbol, action.time, kind, action.value])
+                    .map_err(|e| format!("Cache write error: {}", e))?;
+                if inserted > 0 {
+                    added.push(action.clone());
+                }
+            }
+        }
+        tx.execute(
+            "INSERT INTO corporate_action_sync (symbol, synced_at) VALUES (?1, ?2)
+             ON CONFLICT (symbol) DO UPDATE SET synced_at = excluded.synced_at",
+            rusqlite::params![symbol, now],
         )
         .map_err(|e| format!("Cache write error: {}", e))?;
+        tx.commit()
+            .map_err(|e| format!("Cache write error: {}", e))?;
 
+        Ok(added)
+    }
+
+    /// 저장된 알림 규칙 (id 오름차순).
+    pub fn alert_rules(&self) -> Result<Vec<AlertRule>, String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare_cached("SELECT id, rule FROM alert_rules ORDER BY id")
+            .map_err(|e| format!("Alert store error: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| format!("Alert store error: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Alert store error: {}", e))?;
+
+        let mut rules = Vec::with_capacity(rows.len());
+        for (id, json) in rows {
+            match serde_json::from_str::<AlertRule>(&json) {
+                Ok(mut rule) => {
+                    rule.id = id;
+                    rules.push(rule);
+                }
+                Err(error) => log::warn!("alert rule {} skipped: {}", id, error),
+            }
+        }
+        Ok(rules)
+    }
+
+    /// 규칙을 저장하고 id를 돌려준다. `rule.id`가 0이면 새로 추가한다.
+    pub fn save_alert_rule(&self, rule: &AlertRule) -> Result<i64, String> {
+        let json = serde_json::to_string(rule).map_err(|e| format!("Alert store error: {}", e))?;
+        let now = chrono::Utc::now().timestamp();
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+
+        if rule.id > 0 {
+            let updated = conn
+                .execute(
+                    "UPDATE alert_rules SET rule = ?2, updated_at = ?3 WHERE id = ?1",
+                    rusqlite::params![rule.id, json, now],
+                )
+                .map_err(|e| format!("Alert store error: {}", e))?;
+            if updated == 0 {
+                return Err(format!("알림 규칙 {}을(를) 찾을 수 없습니다", rule.id));
+            }
+            return Ok(rule.id);
+        }
+
+        conn.execute(
+            "INSERT INTO alert_rules (rule, updated_at) VALUES (?1, ?2)",
+            rusqlite::params![json, now],
+        )
+        .map_err(|e| format!("Alert store error: {}", e))?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// 규칙을 삭제한다. 이력은 남긴다.
+    pub fn delete_alert_rule(&self, id: i64) -> Result<bool, String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let deleted = conn
+            .execute("DELETE FROM alert_rules WHERE id = ?1", rusqlite::params![id])
+            .map_err(|e| format!("Alert store error: {}", e))?;
+        Ok(deleted > 0)
+    }
+
+    /// 발생 이력을 기록한다. 같은 규칙·심볼·키가 이미 있으면 None (중복).
+    pub fn record_alert(&self, event: &AlertEvent) -> Result<Option<i64>, String> {
+        let json = serde_json::to_string(event).map_err(|e| format!("Alert store error: {}", e))?;
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let inserted = conn
+            .execute(
+                "INSERT OR IGNORE INTO alert_history (rule_id, symbol, event_key, triggered_at, event)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![event.rule_id, event.symbol, event.key, event.triggered_at, json],
+            )
+            .map_err(|e| format!("Alert store error: {}", e))?;
+        Ok((inserted > 0).then(|| conn.last_insert_rowid()))
+    }
+
+    /// 최근 발생 이력 (최신순).
+    pub fn alert_history(&self, rule_id: Option<i64>, limit: u32) -> Result<Vec<AlertEvent>, String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, event FROM alert_history
+                 WHERE ?1 IS NULL OR rule_id = ?1
+                 ORDER BY triggered_at DESC, id DESC LIMIT ?2",
+            )
+            .map_err(|e| format!("Alert store error: {}", e))?;
+        let rows = stmt
+            .query_map(rusqlite::params![rule_id, limit], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| format!("Alert store error: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Alert store error: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, json)| {
+                let mut event = serde_json::from_str::<AlertEvent>(&json).ok()?;
+                event.id = id;
+                Some(event)
+            })
+            .collect())
+    }
+
+    pub fn clear_alert_history(&self) -> Result<(), String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM alert_history", [])
+            .map_err(|e| format!("Alert store error: {}", e))?;
         Ok(())
     }
 
+    /// 저장된 시장별 공급자 순서.
+    pub fn provider_priorities(&self) -> Result<Vec<ProviderPriority>, String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare_cached("SELECT priority FROM provider_priority")
+            .map_err(|e| format!("Provider store error: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| format!("Provider store error: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Provider store error: {}", e))?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|json| serde_json::from_str(json).ok())
+            .collect())
+    }
+
+    pub fn save_provider_priority(&self, priority: &ProviderPriority) -> Result<(), String> {
+        let market = serde_json::to_string(&priority.market)
+            .map_err(|e| format!("Provider store error: {}", e))?;
+        let json =
+            serde_json::to_string(priority).map_err(|e| format!("Provider store error: {}", e))?;
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO provider_priority (market, priority) VALUES (?1, ?2)
+             ON CONFLICT (market) DO UPDATE SET priority = excluded.priority",
+            rusqlite::params![market, json],
+        )
+        .map_err(|e| format!("Provider store error: {}", e))?;
+        Ok(())
+    }
+
+    fn row_to_candle(row: &rusqlite::Row<'_>) -> rusqlite::Result<Candle> {
+        Ok(Candle {
+            time: row.get(0)?,
+            open: row.get(1)?,
+            high: row.get(2)?,
+            low: row.get(3)?,
+            close: row.get(4)?,
+            volume: row.get(5)?,
+            session: None,
+            flow: match (row.get(6)?, row.get::<_, Option<i64>>(7)?, row.get(8)?) {
+                (Some(quote_volume), Some(trade_count), Some(taker_buy_volume)) => {
+                    Some(TradeFlow {
+                        quote_volume,
+                        trade_count: trade_count.max(0) as u64,
+                        taker_buy_volume,
+                    })
+                }
+                _ => None,
+            },
+        })
+    }
+
+    fn is_fresh(&self, symbol: &str, interval: &str, source: &str) -> bool {
+        let Ok(conn) = self.conn.lock() else {
+            return false;
+        };
+        let fetched_at: Option<i64> = conn
+            .query_row(
+                "SELECT fetched_at FROM series_meta WHERE symbol = ?1 AND source = ?2 AND interval = ?3",
+                rusqlite::params![symbol, source, interval],
+                |row| row.get(0),
+            )
+            .optional()
+            .ok()
+            .flatten();
+
+        let now = chrono::Utc::now().timestamp();
+        matches!(fetched_at, Some(at) if now - at < Self::ttl_seconds(interval))
+    }
+
     fn ttl_seconds(interval: &str) -> i64 {
         match interval {
             "1d" => 900,
@@ -76,3 +658,232 @@ impl CacheDb {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CacheDb, MIGRATIONS};
+    use crate::models::{AlertEvent, Candle, MarketType, TradeFlow};
+    use rusqlite::Connection;
+
+    /// Every OHLCV column differs, so a swapped or dropped column shows up in round trips
+    fn candle(time: i64, close: f64) -> Candle {
+        Candle {
+            time,
+            open: close,
+            high: close + 1.0,
+            low: close - 1.0,
+            close,
+            volume: 10.0,
+            session: None,
+            flow: None,
+        }
+    }
+
+    fn schema_version(cache: &CacheDb) -> i64 {
+        let conn = cache.conn.lock().unwrap();
+        conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    fn event(rule_id: i64, symbol: &str, key: &str, triggered_at: i64) -> AlertEvent {
+        AlertEvent {
+            id: 0,
+            rule_id,
+            rule_name: "rule".to_string(),
+            symbol: symbol.to_string(),
+            market: MarketType::Crypto,
+            interval: "1h".to_string(),
+            time: 1_700_000_000,
+            price: 100.0,
+            message: "message".to_string(),
+            key: key.to_string(),
+            triggered_at,
+        }
+    }
+
+    #[test]
+    fn record_alert_ignores_repeated_events() {
+        let cache = CacheDb::open_in_memory().unwrap();
+
+        let first = cache
+            .record_alert(&event(1, "BTCUSDT", "cross:up:1", 10))
+            .unwrap();
+        assert!(first.is_some());
+        // The same rule, symbol and key on a later poll is a duplicate
+        assert_eq!(
+            cache
+                .record_alert(&event(1, "BTCUSDT", "cross:up:1", 70))
+                .unwrap(),
+            None
+        );
+        // A different key, symbol or rule is a new event
+        assert!(cache
+            .record_alert(&event(1, "BTCUSDT", "cross:down:1", 70))
+            .unwrap()
+            .is_some());
+        assert!(cache
+            .record_alert(&event(1, "ETHUSDT", "cross:up:1", 70))
+            .unwrap()
+            .is_some());
+        assert!(cache
+            .record_alert(&event(2, "BTCUSDT", "cross:up:1", 70))
+            .unwrap()
+            .is_some());
+
+        let history = cache.alert_history(Some(1), 10).unwrap();
+        assert_eq!(history.len(), 3);
+        let original = history
+            .iter()
+            .find(|e| e.key == "cross:up:1" && e.symbol == "BTCUSDT");
+        assert_eq!(
+            original.map(|e| (e.id, e.triggered_at)),
+            Some((first.unwrap(), 10))
+        );
+    }
+
+    #[test]
+    fn migrations_upgrade_a_v1_store_without_losing_candles() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+        conn.execute(
+            "INSERT INTO candles (symbol, source, interval, time, open, high, low, close, volume)
+             VALUES ('crypto:BTCUSDT', 'binance', '1h', 3600, 1.0, 2.0, 0.5, 1.5, 10.0)",
+            [],
+        )
+        .unwrap();
+
+        let cache = CacheDb::from_connection(conn).unwrap();
+        assert_eq!(schema_version(&cache), MIGRATIONS.len() as i64);
+
+        // Rows written before v6 have no trade flow
+        let candles = cache.load("crypto:BTCUSDT", "1h", "binance", 10).unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0].close, 1.5);
+        assert!(candles[0].flow.is_none());
+
+        // Tables from later migrations are usable
+        cache
+            .mark_covered("crypto:BTCUSDT", "1h", "binance", 0, 3600)
+            .unwrap();
+        assert_eq!(
+            cache.coverage("crypto:BTCUSDT", "1h", "binance").unwrap(),
+            vec![(0, 3600)]
+        );
+    }
+
+    #[test]
+    fn reopening_a_migrated_store_is_a_no_op() {
+        let cache = CacheDb::open_in_memory().unwrap();
+        let conn = cache.conn.into_inner().unwrap();
+
+        let cache = CacheDb::from_connection(conn).unwrap();
+        assert_eq!(schema_version(&cache), MIGRATIONS.len() as i64);
+    }
+
+    #[test]
+    fn upsert_overwrites_bars_at_the_same_time() {
+        let cache = CacheDb::open_in_memory().unwrap();
+        cache
+            .set("us:AAPL", "1d", "yahoo", &[candle(100, 1.0), candle(200, 2.0)])
+            .unwrap();
+
+        // The forming bar is re-sent with a new close and trade flow
+        let mut revised = candle(200, 2.5);
+        revised.flow = Some(TradeFlow {
+            quote_volume: 25.0,
+            trade_count: 3,
+            taker_buy_volume: 6.0,
+        });
+        cache.upsert("us:AAPL", "1d", "yahoo", &[revised]).unwrap();
+
+        let candles = cache.load("us:AAPL", "1d", "yahoo", 10).unwrap();
+        assert_eq!(
+            candles
+                .iter()
+                .map(|c| (c.time, c.open, c.high, c.low, c.close, c.volume))
+                .collect::<Vec<_>>(),
+            vec![(100, 1.0, 2.0, 0.0, 1.0, 10.0), (200, 2.5, 3.5, 1.5, 2.5, 10.0)]
+        );
+        assert_eq!(candles[1].flow.map(|flow| flow.trade_count), Some(3));
+        // Other sources and intervals are separate series
+        assert!(cache.load("us:AAPL", "1d", "kis", 10).unwrap().is_empty());
+        assert!(cache.load("us:AAPL", "1h", "yahoo", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn load_returns_the_latest_bars_in_ascending_order() {
+        let cache = CacheDb::open_in_memory().unwrap();
+        // Written out of order across two batches
+        cache
+            .set("crypto:BTCUSDT", "1h", "binance", &[candle(300, 3.0), candle(100, 1.0)])
+            .unwrap();
+        cache
+            .upsert("crypto:BTCUSDT", "1h", "binance", &[candle(400, 4.0), candle(200, 2.0)])
+            .unwrap();
+
+        let times = |candles: Vec<Candle>| candles.iter().map(|c| c.time).collect::<Vec<_>>();
+        assert_eq!(
+            times(cache.load("crypto:BTCUSDT", "1h", "binance", 3).unwrap()),
+            vec![200, 300, 400]
+        );
+        assert_eq!(
+            times(
+                cache
+                    .load_range("crypto:BTCUSDT", "1h", "binance", 150, 300)
+                    .unwrap()
+            ),
+            vec![200, 300]
+        );
+        assert_eq!(cache.last_time("crypto:BTCUSDT", "1h", "binance"), Some(400));
+        assert_eq!(cache.last_time("crypto:ETHUSDT", "1h", "binance"), None);
+    }
+
+    #[test]
+    fn only_set_marks_a_series_fresh() {
+        let cache = CacheDb::open_in_memory().unwrap();
+        cache
+            .upsert("crypto:BTCUSDT", "1d", "binance", &[candle(100, 1.0)])
+            .unwrap();
+        // Backfilled history does not count as a fresh sync
+        assert!(cache.get("crypto:BTCUSDT", "1d", "binance", 10).is_none());
+
+        cache
+            .set("crypto:BTCUSDT", "1d", "binance", &[candle(200, 2.0)])
+            .unwrap();
+        assert_eq!(
+            cache
+                .get("crypto:BTCUSDT", "1d", "binance", 10)
+                .map(|candles| candles.len()),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn coverage_merges_overlapping_and_adjacent_ranges() {
+        let cache = CacheDb::open_in_memory().unwrap();
+        let mark = |start, end| {
+            cache
+                .mark_covered("us:AAPL", "1d", "yahoo", start, end)
+                .unwrap()
+        };
+        let coverage = || cache.coverage("us:AAPL", "1d", "yahoo").unwrap();
+
+        mark(100, 200);
+        mark(300, 400);
+        assert_eq!(coverage(), vec![(100, 200), (300, 400)]);
+
+        // Adjacent to the first range
+        mark(201, 250);
+        assert_eq!(coverage(), vec![(100, 250), (300, 400)]);
+        // Bridges both ranges
+        mark(240, 310);
+        assert_eq!(coverage(), vec![(100, 400)]);
+        // Already covered
+        mark(150, 160);
+        assert_eq!(coverage(), vec![(100, 400)]);
+        // Other series are untouched
+        cache.mark_covered("us:AAPL", "1h", "yahoo", 100, 400).unwrap();
+        assert_eq!(coverage(), vec![(100, 400)]);
+    }
+}
@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+use super::analysis::{is_forming, load_analysis_candles};
+use crate::alerts;
+use crate::api_client::{ProviderRegistry, YahooClient};
+use crate::cache::CacheDb;
+use crate::models::{AlertEvent, AlertHistoryParams, AlertRule};
+
+pub const ALERT_TRIGGERED_EVENT: &str = "alert-triggered";
+
+/// 백그라운드 평가 주기
+const ALERT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+const MAX_ALERT_TARGETS: usize = 24;
+
+#[tauri::command]
+pub async fn list_alert_rules(cache: State<'_, CacheDb>) -> Result<Vec<AlertRule>, String> {
+    cache.alert_rules()
+}
+
+/// 규칙을 추가하거나(`id` = 0) 수정하고, id가 채워진 규칙을 돌려준다.
+#[tauri::command]
+pub async fn save_alert_rule(
+    mut rule: AlertRule,
+    cache: State<'_, CacheDb>,
+) -> Result<AlertRule, String> {
+    if rule.targets.is_empty() {
+        return Err("알림 대상 심볼을 하나 이상 지정하세요".to_string());
+    }
+    if rule.targets.len() > MAX_ALERT_TARGETS {
+        return Err(format!(
+            "알림 대상은 최대 {}개까지 지정할 수 있습니다",
+            MAX_ALERT_TARGETS
+        ));
+    }
+    rule.id = cache.save_alert_rule(&rule)?;
+    Ok(rule)
+}
+
+#[tauri::command]
+pub async fn delete_alert_rule(id: i64, cache: State<'_, CacheDb>) -> Result<bool, String> {
+    cache.delete_alert_rule(id)
+}
+
+#[tauri::command]
+pub async fn fetch_alert_history(
+    params: AlertHistoryParams,
+    cache: State<'_, CacheDb>,
+) -> Result<Vec<AlertEvent>, String> {
+    cache.alert_history(params.rule_id, params.limit.clamp(1, 1_000))
+}
+
+#[tauri::command]
+pub async fn clear_alert_history(cache: State<'_, CacheDb>) -> Result<(), String> {
+    cache.clear_alert_history()
+}
+
+/// 모든 규칙을 즉시 평가하고 새로 발생한 알림을 돌려준다.
+#[tauri::command]
+pub async fn evaluate_alerts(app: AppHandle) -> Result<Vec<AlertEvent>, String> {
+    evaluate_all(&app).await
+}
+
+/// 앱 수명 동안 주기적으로 규칙을 평가하는 작업을 시작한다.
+pub fn spawn_alert_loop(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(error) = evaluate_all(&app).await {
+                log::warn!("alert evaluation failed: {}", error);
+            }
+            tokio::time::sleep(ALERT_POLL_INTERVAL).await;
+        }
+    });
+}
+
+/// 활성 규칙을 평가해 새 사건만 이력에 기록하고 `alert-triggered` 이벤트(및 데스크톱 알림)로 보낸다.
+/// 같은 규칙·심볼·사건은 이력 테이블의 고유 키로 한 번만 발생한다.
+async fn evaluate_all(app: &AppHandle) -> Result<Vec<AlertEvent>, String> {
+    let cache = app.state::<CacheDb>();
+    let providers = app.state::<ProviderRegistry>();
+    let yahoo_client = app.state::<YahooClient>();
+
+    let rules = cache.alert_rules()?;
+    let mut fired = Vec::new();
+
+    for rule in rules.iter().filter(|rule| rule.enabled) {
+        for target in rule.targets.iter().take(MAX_ALERT_TARGETS) {
+            let params = alerts::analysis_params(rule, target);
+            let mut loaded = match load_analysis_candles(
+                &params,
+                providers.inner(),
+                yahoo_client.inner(),
+                cache.inner(),
+            )
+            .await
+            {
+                Ok(loaded) => loaded,
+                Err(error) => {
+                    log::warn!("alert {} ({}) skipped: {}", rule.id, target.symbol, error);
+                    continue;
+                }
+            };
+
+            let triggered_at = chrono::Utc::now().timestamp();
+            // 진행 중인 봉은 마감 전에 조건이 사라질 수 있으므로 마감된 봉만 평가한다
+            if loaded
+                .candles
+                .last()
+                .is_some_and(|c| is_forming(c.time, &rule.interval, &target.market, triggered_at))
+            {
+                loaded.candles.pop();
+            }
+            for matched in alerts::evaluate(rule, &params, &loaded.candles) {
+                let mut event = AlertEvent {
+                    id: 0,
+                    rule_id: rule.id,
+                    rule_name: rule.name.clone(),
+                    symbol: target.symbol.clone(),
+                    market: target.market.clone(),
+                    interval: rule.interval.clone(),
+                    time: matched.time,
+                    price: matched.price,
+                    message: matched.message,
+                    key: matched.key,
+                    triggered_at,
+                };
+                let Some(id) = cache.record_alert(&event)? else {
+                    continue;
+                };
+                event.id = id;
+
+                if rule.notify {
+                    let _ = app
+                        .notification()
+                        .builder()
+                        .title(format!("{} · {}", rule.name, event.symbol))
+                        .body(event.message.clone())
+                        .show();
+                }
+                let _ = app.emit(ALERT_TRIGGERED_EVENT, event.clone());
+                fired.push(event);
+            }
+        }
+    }
+
+    Ok(fired)
+}
@@ -1,49 +1,54 @@
-use chrono::{Datelike, TimeZone, Utc};
+use chrono::{Datelike, Duration, NaiveDate, Utc};
 use tauri::State;
 
-use crate::api_client::{BinanceClient, KisClient, YahooClient};
+use super::backfill::{backfill_range, resolve_backfill_source};
+use crate::api_client::{BinanceClient, KisClient, ProviderRegistry, YahooClient};
 use crate::cache::CacheDb;
 use crate::models::{
-    AnalysisParams, AnalysisResponse, Candle, FundamentalsParams, FundamentalsResponse, MarketType,
-    WatchlistSnapshot, WatchlistSnapshotParams,
+    AnalysisParams, AnalysisResponse, AnchorMode, Candle, CorporateAction, CorporateActionKind,
+    FundamentalsParams, FundamentalsResponse, FundingRateResult, InvestorFlowParams,
+    InvestorFlowResult, KimchiPremiumParams, KimchiPremiumResult, LongShortRatioResult, MarketType,
+    OpenInterestResult, VwapAnchor, WatchlistSnapshot, WatchlistSnapshotParams,
+};
+use crate::ta_engine::{
+    self, adjustment, derivatives, investor_flow, kimchi_premium, session, ExternalData,
 };
-use crate::ta_engine;
 
 const ANALYSIS_OUTPUT_LIMIT: u32 = 500;
 const MAX_WATCHLIST_ITEMS: usize = 24;
-
-#[derive(Debug, Clone, Copy)]
-enum MarketDataSource {
-    Binance,
-    Yahoo,
-    Kis,
-}
-
-impl MarketDataSource {
-    fn as_str(self) -> &'static str {
-        match self {
-            Self::Binance => "binance",
-            Self::Yahoo => "yahoo",
-            Self::Kis => "kis",
-        }
-    }
-}
+/// 분할/배당 이력을 다시 동기화하는 주기
+const CORPORATE_ACTION_TTL: i64 = 6 * 3_600;
+/// 김치 프리미엄 환산에 쓰는 USD/KRW 환율 (Yahoo)
+const USD_KRW_SYMBOL: &str = "KRW=X";
+/// Binance 선물 통계(미결제약정·롱숏 비율)가 제공하는 집계 주기, 긴 것부터
+const FUTURES_STATS_PERIODS: [(&str, i64); 9] = [
+    ("1d", 86_400),
+    ("12h", 43_200),
+    ("6h", 21_600),
+    ("4h", 14_400),
+    ("2h", 7_200),
+    ("1h", 3_600),
+    ("30m", 1_800),
+    ("15m", 900),
+    ("5m", 300),
+];
 
 #[derive(Debug, Clone)]
 struct SourceFetchResult {
     candles: Vec<Candle>,
-    data_source: MarketDataSource,
+    /// 캔들을 받아온 공급자 id
+    data_source: &'static str,
 }
 
 #[derive(Debug, Clone)]
-struct IntervalPlan {
-    requested: String,
-    source: String,
-    factor: u32,
-    needs_resample: bool,
+pub(super) struct IntervalPlan {
+    pub(super) requested: String,
+    pub(super) source: String,
+    pub(super) factor: u32,
+    pub(super) needs_resample: bool,
 }
 
-fn market_prefix(market: &MarketType) -> &'static str {
+pub(super) fn market_prefix(market: &MarketType) -> &'static str {
     match market {
         MarketType::Crypto => "crypto",
         MarketType::Forex => "fx",
@@ -52,19 +57,15 @@ fn market_prefix(market: &MarketType) -> &'static str {
     }
 }
 
-fn native_intervals(market: &MarketType) -> &'static [&'static str] {
-    match market {
-        MarketType::Crypto => &[
-            "1m", "3m", "5m", "15m", "30m", "1h", "2h", "4h", "6h", "8h", "12h", "1d", "1w",
-            "1M",
-        ],
-        MarketType::KrStock => {
-            &["1m", "5m", "15m", "30m", "1h", "1d", "1w", "1M"]
-        }
-        MarketType::Forex | MarketType::UsStock => {
-            &["1m", "2m", "5m", "15m", "30m", "1h", "1d", "1w", "1M"]
-        }
-    }
+/// 심볼을 다루는 공급자 중 하나라도 그대로 제공하는 봉 주기 (짧은 주기부터).
+fn native_intervals(
+    market: &MarketType,
+    symbol: &str,
+    providers: &ProviderRegistry,
+) -> Vec<String> {
+    let mut intervals = providers.intervals(market, symbol);
+    intervals.sort_by_key(|interval| interval_seconds(interval).unwrap_or(i64::MAX));
+    intervals
 }
 
 fn parse_interval_parts(interval: &str) -> Option<(i64, char)> {
@@ -97,7 +98,7 @@ fn parse_interval_parts(interval: &str) -> Option<(i64, char)> {
     Some((value, unit))
 }
 
-fn interval_seconds(interval: &str) -> Option<i64> {
+pub(super) fn interval_seconds(interval: &str) -> Option<i64> {
     let (value, unit) = parse_interval_parts(interval)?;
 
     match unit {
@@ -106,12 +107,18 @@ fn interval_seconds(interval: &str) -> Option<i64> {
         'd' => Some(value * 86_400),
         'w' => Some(value * 604_800),
         'M' => Some(value * 2_592_000),
+        'Q' => Some(value * 7_776_000),
         'Y' => Some(value * 31_536_000),
         _ => None,
     }
 }
 
-fn resolve_interval_plan(interval: &str, market: &MarketType) -> IntervalPlan {
+pub(super) fn resolve_interval_plan(
+    interval: &str,
+    market: &MarketType,
+    symbol: &str,
+    providers: &ProviderRegistry,
+) -> IntervalPlan {
     let requested = {
         let trimmed = interval.trim();
         if trimmed.is_empty() {
@@ -121,7 +128,7 @@ fn resolve_interval_plan(interval: &str, market: &MarketType) -> IntervalPlan {
         }
     };
 
-    let native = native_intervals(market);
+    let native = native_intervals(market, symbol, providers);
     if let Some((value, unit)) = parse_interval_parts(&requested) {
         if unit == 'Y' && native.iter().any(|candidate| *candidate == "1M") {
             return IntervalPlan {
@@ -132,7 +139,7 @@ fn resolve_interval_plan(interval: &str, market: &MarketType) -> IntervalPlan {
             };
         }
     }
-    if native.iter().any(|candidate| *candidate == requested) {
+    if native.contains(&requested) {
         return IntervalPlan {
             requested: requested.clone(),
             source: requested,
@@ -155,7 +162,7 @@ fn resolve_interval_plan(interval: &str, market: &MarketType) -> IntervalPlan {
     let mut best_seconds = 86_400_i64;
     let mut found_divisor = false;
 
-    for candidate in native {
+    for candidate in &native {
         let Some(source_seconds) = interval_seconds(candidate) else {
             continue;
         };
@@ -173,7 +180,7 @@ fn resolve_interval_plan(interval: &str, market: &MarketType) -> IntervalPlan {
     }
 
     if !found_divisor {
-        best_source = native.first().copied().unwrap_or("1d");
+        best_source = native.first().map_or("1d", String::as_str);
         best_seconds = interval_seconds(best_source).unwrap_or(86_400);
     }
 
@@ -191,61 +198,33 @@ fn resolve_interval_plan(interval: &str, market: &MarketType) -> IntervalPlan {
     }
 }
 
-fn requested_source_limit(output_limit: u32, plan: &IntervalPlan, market: &MarketType) -> u32 {
+/// 리샘플링 여유분을 더한 소스 봉 수. 최우선 공급자의 한 번 조회 한도를 넘지 않는다.
+fn requested_source_limit(
+    output_limit: u32,
+    plan: &IntervalPlan,
+    market: &MarketType,
+    symbol: &str,
+    providers: &ProviderRegistry,
+) -> u32 {
     let factor = plan.factor.max(1);
     let expanded = output_limit.saturating_mul(factor.saturating_add(1));
-    match market {
-        MarketType::Crypto => expanded.clamp(output_limit, 1_000),
-        MarketType::KrStock => expanded.clamp(output_limit, 1_500),
-        MarketType::Forex | MarketType::UsStock => {
-            expanded.clamp(output_limit, 2_500)
-        }
-    }
-}
-
-fn is_intraday_source_interval(interval: &str) -> bool {
-    matches!(interval, "1m" | "2m" | "5m" | "15m" | "30m" | "1h")
-}
-
-fn resolve_source_order(market: &MarketType, source_interval: &str) -> Vec<MarketDataSource> {
-    match market {
-        MarketType::Crypto => vec![MarketDataSource::Binance],
-        MarketType::Forex | MarketType::UsStock => vec![MarketDataSource::Yahoo],
-        MarketType::KrStock => {
-            if is_intraday_source_interval(source_interval) {
-                vec![MarketDataSource::Yahoo]
-            } else {
-                vec![MarketDataSource::Kis, MarketDataSource::Yahoo]
-            }
-        }
-    }
+    let max_bars = providers
+        .source_order(market, symbol, &plan.source)
+        .first()
+        .map_or(1_000, |provider| provider.capabilities().max_bars);
+    expanded.clamp(output_limit, max_bars.max(output_limit))
 }
 
-async fn fetch_candles_from_source(
-    source: MarketDataSource,
-    symbol: &str,
-    plan: &IntervalPlan,
-    source_limit: u32,
-    binance_client: &BinanceClient,
-    yahoo_client: &YahooClient,
-    kis_client: &KisClient,
-) -> Result<Vec<Candle>, String> {
-    match source {
-        MarketDataSource::Binance => {
-            binance_client
-                .fetch_klines(symbol, &plan.source, source_limit)
-                .await
-        }
-        MarketDataSource::Yahoo => {
-            yahoo_client
-                .fetch_klines(symbol, &plan.source, source_limit)
-                .await
-        }
-        MarketDataSource::Kis => {
-            kis_client
-                .fetch_klines(symbol, &plan.source, source_limit)
-                .await
-        }
+/// 저장된 마지막 캔들부터 증분 조회할지 결정한다.
+/// 공백이 한 번의 요청으로 메울 수 없을 만큼 크면 전체 조회로 되돌아간다.
+fn incremental_since(last_time: Option<i64>, source_interval: &str, source_limit: u32) -> Option<i64> {
+    let last_time = last_time?;
+    let step = interval_seconds(source_interval)?;
+    let missing_bars = (Utc::now().timestamp() - last_time) / step;
+    if missing_bars < i64::from(source_limit) {
+        Some(last_time)
+    } else {
+        None
     }
 }
 
@@ -256,39 +235,53 @@ async fn load_source_candles(
     market: &MarketType,
     plan: &IntervalPlan,
     source_limit: u32,
-    binance_client: &BinanceClient,
-    yahoo_client: &YahooClient,
-    kis_client: &KisClient,
+    providers: &ProviderRegistry,
 ) -> Result<SourceFetchResult, String> {
     let mut errors = Vec::new();
 
-    for source in resolve_source_order(market, &plan.source) {
-        if let Some(cached) = cache.get(cache_key_symbol, &plan.source, source.as_str()) {
+    for provider in providers.source_order(market, symbol, &plan.source) {
+        let source = provider.id();
+        if let Some(cached) = cache.get(cache_key_symbol, &plan.source, source, source_limit) {
             return Ok(SourceFetchResult {
                 candles: cached,
                 data_source: source,
             });
         }
 
-        match fetch_candles_from_source(
-            source,
-            symbol,
-            plan,
+        let since = incremental_since(
+            cache.last_time(cache_key_symbol, &plan.source, source),
+            &plan.source,
             source_limit,
-            binance_client,
-            yahoo_client,
-            kis_client,
-        )
-        .await
-        {
+        );
+
+        let fetched = match since {
+            Some(since) => {
+                provider
+                    .fetch_klines_since(symbol, &plan.source, since, source_limit)
+                    .await
+            }
+            None => {
+                provider
+                    .fetch_klines(symbol, &plan.source, source_limit)
+                    .await
+            }
+        };
+        match fetched {
             Ok(candles) => {
-                let _ = cache.set(cache_key_symbol, &plan.source, source.as_str(), &candles);
+                // 저장소에 병합한 뒤 누적된 시리즈를 다시 읽는다
+                let stored = cache
+                    .set(cache_key_symbol, &plan.source, source, &candles)
+                    .and_then(|_| cache.load(cache_key_symbol, &plan.source, source, source_limit));
+                let candles = match stored {
+                    Ok(stored) if !stored.is_empty() => stored,
+                    _ => candles,
+                };
                 return Ok(SourceFetchResult {
                     candles,
                     data_source: source,
                 });
             }
-            Err(error) => errors.push(format!("{}: {}", source.as_str(), error)),
+            Err(error) => errors.push(format!("{}: {}", source, error)),
         }
     }
 
@@ -299,64 +292,79 @@ async fn load_source_candles(
     })
 }
 
-fn resample_candles(candles: &[Candle], plan: &IntervalPlan) -> Vec<Candle> {
-    if !plan.needs_resample || candles.is_empty() {
-        return candles.to_vec();
-    }
+/// `start`부터의 장기 이력: 빈 구간을 백필한 뒤 저장소에서 전체 구간을 읽는다.
+/// 일부 구간만 실패하면 받은 만큼 돌려주고 실패 구간은 `warnings`에 남긴다.
+async fn load_history_candles(
+    cache: &CacheDb,
+    params: &AnalysisParams,
+    plan: &IntervalPlan,
+    preferred: &str,
+    start: i64,
+    providers: &ProviderRegistry,
+    warnings: &mut Vec<String>,
+) -> Result<SourceFetchResult, String> {
+    let cache_key_symbol = format!("{}:{}", market_prefix(&params.market), params.symbol);
+    let provider = resolve_backfill_source(
+        &params.market,
+        &params.symbol,
+        &plan.source,
+        Some(preferred),
+        providers,
+    )
+    .ok_or("이 시장은 과거 구간 백필을 지원하지 않습니다")?;
+    let end = Utc::now().timestamp();
 
-    if let Some((value, unit)) = parse_interval_parts(&plan.requested) {
-        if unit == 'Y' {
-            let year_span = value.max(1);
-            let mut output: Vec<Candle> = Vec::new();
-            for candle in candles {
-                let Some(timestamp) = Utc.timestamp_opt(candle.time, 0).single() else {
-                    continue;
-                };
-                let year = i64::from(timestamp.year());
-                let bucket_year = year - ((year - 1970).rem_euclid(year_span));
-                let Some(bucket_start) = Utc
-                    .with_ymd_and_hms(bucket_year as i32, 1, 1, 0, 0, 0)
-                    .single()
-                    .map(|dt| dt.timestamp())
-                else {
-                    continue;
-                };
+    let report = backfill_range(
+        cache,
+        &params.symbol,
+        &params.market,
+        plan,
+        provider.as_ref(),
+        start,
+        end,
+    )
+    .await?;
+    if !report.errors.is_empty() {
+        log::warn!(
+            "backfill of {} left {} hole(s): {}",
+            cache_key_symbol,
+            report.errors.len(),
+            report.errors.join(" | ")
+        );
+        warnings.push(format!(
+            "일부 과거 구간을 백필하지 못해 이력이 비어 있을 수 있습니다: {}",
+            report.errors.join(" | ")
+        ));
+    }
 
-                if let Some(last) = output.last_mut() {
-                    if last.time == bucket_start {
-                        if candle.high > last.high {
-                            last.high = candle.high;
-                        }
-                        if candle.low < last.low {
-                            last.low = candle.low;
-                        }
-                        last.close = candle.close;
-                        last.volume += candle.volume;
-                        continue;
-                    }
-                }
+    let candles = cache.load_range(&cache_key_symbol, &plan.source, provider.id(), start, end)?;
+    if candles.is_empty() {
+        return Err("요청 구간의 저장된 캔들이 없습니다".to_string());
+    }
 
-                output.push(Candle {
-                    time: bucket_start,
-                    open: candle.open,
-                    high: candle.high,
-                    low: candle.low,
-                    close: candle.close,
-                    volume: candle.volume,
-                });
-            }
+    Ok(SourceFetchResult {
+        candles,
+        data_source: provider.id(),
+    })
+}
 
-            return output;
-        }
+pub(super) fn resample_candles(
+    candles: &[Candle],
+    plan: &IntervalPlan,
+    market: &MarketType,
+) -> Vec<Candle> {
+    if !plan.needs_resample || candles.is_empty() {
+        return candles.to_vec();
     }
-
-    let Some(bucket_seconds) = interval_seconds(&plan.requested) else {
+    let Some((value, unit)) = parse_interval_parts(&plan.requested) else {
         return candles.to_vec();
     };
 
     let mut output: Vec<Candle> = Vec::new();
     for candle in candles {
-        let bucket_start = (candle.time / bucket_seconds) * bucket_seconds;
+        let Some(bucket_start) = bucket_start(candle.time, value, unit, market) else {
+            continue;
+        };
 
         if let Some(last) = output.last_mut() {
             if last.time == bucket_start {
@@ -368,6 +376,14 @@ fn resample_candles(candles: &[Candle], plan: &IntervalPlan) -> Vec<Candle> {
                 }
                 last.close = candle.close;
                 last.volume += candle.volume;
+                // 체결 집계가 빠진 구간이 섞이면 봉 전체의 매수/매도 구분을 믿을 수 없다
+                last.flow = match (last.flow, candle.flow) {
+                    (Some(mut flow), Some(other)) => {
+                        flow.merge(&other);
+                        Some(flow)
+                    }
+                    _ => None,
+                };
                 continue;
             }
         }
@@ -379,51 +395,528 @@ fn resample_candles(candles: &[Candle], plan: &IntervalPlan) -> Vec<Candle> {
             low: candle.low,
             close: candle.close,
             volume: candle.volume,
+            session: None,
+            flow: candle.flow,
         });
     }
 
     output
 }
 
-#[tauri::command]
-pub async fn fetch_analysis(
-    params: AnalysisParams,
-    binance_client: State<'_, BinanceClient>,
-    yahoo_client: State<'_, YahooClient>,
-    kis_client: State<'_, KisClient>,
-    cache: State<'_, CacheDb>,
-) -> Result<AnalysisResponse, String> {
+/// 리샘플 봉의 시작 시각.
+/// 분/시간 봉은 거래소 정규장 시작(애프터마켓 봉은 마감)에 맞추고, 일 이상은 거래일 기준 달력 구간
+/// (월요일 시작 주, 월, 분기, 연)의 첫날 00:00 UTC로 찍는다.
+fn bucket_start(time: i64, value: i64, unit: char, market: &MarketType) -> Option<i64> {
+    if matches!(unit, 'm' | 'h') {
+        let bucket_seconds = interval_seconds(&format!("{}{}", value, unit))?;
+        let anchor = session::bucket_anchor(time, market);
+        return Some(anchor + (time - anchor).div_euclid(bucket_seconds) * bucket_seconds);
+    }
+
+    let date = session::session_date(time, market);
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1)?;
+    let start = match unit {
+        'd' => {
+            let days = (date - epoch).num_days();
+            epoch + Duration::days(days - days.rem_euclid(value))
+        }
+        'w' => {
+            // 1970-01-05는 첫 월요일
+            let first_monday = NaiveDate::from_ymd_opt(1970, 1, 5)?;
+            let weeks = (date - first_monday).num_days().div_euclid(7);
+            first_monday + Duration::weeks(weeks - weeks.rem_euclid(value))
+        }
+        'M' | 'Q' | 'Y' => {
+            let months_per_bucket = value
+                * match unit {
+                    'M' => 1,
+                    'Q' => 3,
+                    _ => 12,
+                };
+            let months = i64::from(date.year() - 1970) * 12 + i64::from(date.month0());
+            let bucket = months - months.rem_euclid(months_per_bucket);
+            NaiveDate::from_ymd_opt(
+                1970 + bucket.div_euclid(12) as i32,
+                bucket.rem_euclid(12) as u32 + 1,
+                1,
+            )?
+        }
+        _ => return None,
+    };
+
+    Some(start.and_hms_opt(0, 0, 0)?.and_utc().timestamp())
+}
+
+/// `time`에 시작한 봉이 `now`에 아직 진행 중인지. 분/시간 봉은 봉 길이로, 일 이상은 `now`가
+/// 같은 거래일 기준 달력 구간(일, 주, 월 …)에 속하는지로 판단한다.
+pub(super) fn is_forming(time: i64, interval: &str, market: &MarketType, now: i64) -> bool {
+    let Some((value, unit)) = parse_interval_parts(interval) else {
+        return false;
+    };
+    if matches!(unit, 'm' | 'h') {
+        return interval_seconds(interval).is_some_and(|seconds| time + seconds > now);
+    }
+    bucket_start(time, value, unit, market) == bucket_start(now, value, unit, market)
+}
+
+/// 주식 종목의 분할/배당 이력. 동기화한 지 오래됐으면 Yahoo에서 다시 받고,
+/// 새 분할이 생겼으면 가격 기준이 달라진 저장 시리즈를 비운다. 조회에 실패하면 저장된 이력을 쓰고
+/// `warnings`에 남긴다.
+pub(super) async fn load_corporate_actions(
+    cache: &CacheDb,
+    cache_key_symbol: &str,
+    symbol: &str,
+    market: &MarketType,
+    yahoo_client: &YahooClient,
+    warnings: &mut Vec<String>,
+) -> Vec<CorporateAction> {
+    if !matches!(market, MarketType::UsStock | MarketType::KrStock) {
+        return Vec::new();
+    }
+
+    let synced_at = cache.corporate_actions_synced_at(symbol);
+    let now = Utc::now().timestamp();
+    if synced_at.is_none_or(|synced| now - synced >= CORPORATE_ACTION_TTL) {
+        let saved = yahoo_client
+            .fetch_corporate_actions(symbol)
+            .await
+            .and_then(|actions| cache.save_corporate_actions(symbol, &actions));
+        match saved {
+            // 첫 동기화 전에 저장된 시리즈는 이미 그때까지의 분할이 반영되어 있다
+            Ok(added) if synced_at.is_some() => {
+                if added.iter().any(|a| a.kind == CorporateActionKind::Split) {
+                    if let Err(error) = cache.clear_series(cache_key_symbol) {
+                        log::warn!(
+                            "failed to reset {} after split: {}",
+                            cache_key_symbol,
+                            error
+                        );
+                        warnings.push(format!(
+                            "분할 전 저장 시리즈를 비우지 못해 가격 기준이 섞였을 수 있습니다: {}",
+                            error
+                        ));
+                    }
+                }
+            }
+            Ok(_) => {}
+            Err(error) => {
+                log::warn!("corporate actions for {} unavailable: {}", symbol, error);
+                warnings.push(format!(
+                    "분할/배당 이력을 받지 못해 저장된 이력으로 수정주가를 계산했습니다: {}",
+                    error
+                ));
+            }
+        }
+    }
+
+    cache.corporate_actions(symbol).unwrap_or_default()
+}
+
+/// 분석/백테스트 공용: 소스 캔들을 불러와 가격을 보정하고 요청 interval로 리샘플링한다.
+pub(super) struct AnalysisCandles {
+    pub(super) candles: Vec<Candle>,
+    pub(super) data_source: &'static str,
+    pub(super) source_interval: String,
+    /// 불러온 구간 안의 분할/배당
+    pub(super) corporate_actions: Vec<CorporateAction>,
+    pub(super) warnings: Vec<String>,
+}
+
+pub(super) async fn load_analysis_candles(
+    params: &AnalysisParams,
+    providers: &ProviderRegistry,
+    yahoo_client: &YahooClient,
+    cache: &CacheDb,
+) -> Result<AnalysisCandles, String> {
     let market_prefix = market_prefix(&params.market);
     let cache_key_symbol = format!("{}:{}", market_prefix, params.symbol);
-    let plan = resolve_interval_plan(&params.interval, &params.market);
-    let source_limit = requested_source_limit(ANALYSIS_OUTPUT_LIMIT, &plan, &params.market);
+    let plan = resolve_interval_plan(&params.interval, &params.market, &params.symbol, providers);
+    let source_limit = requested_source_limit(
+        ANALYSIS_OUTPUT_LIMIT,
+        &plan,
+        &params.market,
+        &params.symbol,
+        providers,
+    );
+    let mut warnings = Vec::new();
+    // 분할 보정(저장 기준)은 이력 없이 계산되므로 Yahoo와 동기화하지 않고 저장된 이력만 표시한다
+    let corporate_actions = if params.adjustment.needs_corporate_actions() {
+        load_corporate_actions(
+            cache,
+            &cache_key_symbol,
+            &params.symbol,
+            &params.market,
+            yahoo_client,
+            &mut warnings,
+        )
+        .await
+    } else {
+        cache.corporate_actions(&params.symbol).unwrap_or_default()
+    };
 
     let source_result = load_source_candles(
-        cache.inner(),
+        cache,
         &cache_key_symbol,
         &params.symbol,
         &params.market,
         &plan,
         source_limit,
-        binance_client.inner(),
+        providers,
+    )
+    .await?;
+
+    // 장기 이력 요청: 빈 구간을 백필한 뒤 저장소에서 전체 구간을 읽는다
+    let source_result = match params.start_time {
+        Some(start) => {
+            let history = load_history_candles(
+                cache,
+                params,
+                &plan,
+                source_result.data_source,
+                start,
+                providers,
+                &mut warnings,
+            )
+            .await;
+            match history {
+                Ok(history) => history,
+                Err(error) => {
+                    log::warn!("history for {} unavailable: {}", cache_key_symbol, error);
+                    warnings.push(format!(
+                        "요청한 시작 시각부터 이력을 불러오지 못해 최근 {}개 봉으로 계산했습니다: {}",
+                        source_result.candles.len(),
+                        error
+                    ));
+                    source_result
+                }
+            }
+        }
+        None => source_result,
+    };
+
+    let adjusted = adjustment::apply(
+        &source_result.candles,
+        &corporate_actions,
+        params.adjustment,
+    );
+    let first_time = adjusted.first().map_or(i64::MAX, |c| c.time);
+    let mut candles = resample_candles(&adjusted, &plan, &params.market);
+    session::tag_sessions(&mut candles, &params.interval, &params.market);
+    Ok(AnalysisCandles {
+        candles,
+        data_source: source_result.data_source,
+        source_interval: plan.source,
+        corporate_actions: corporate_actions
+            .into_iter()
+            .filter(|action| action.time >= first_time)
+            .collect(),
+        warnings,
+    })
+}
+
+#[tauri::command]
+pub async fn fetch_analysis(
+    mut params: AnalysisParams,
+    providers: State<'_, ProviderRegistry>,
+    binance_client: State<'_, BinanceClient>,
+    kis_client: State<'_, KisClient>,
+    yahoo_client: State<'_, YahooClient>,
+    cache: State<'_, CacheDb>,
+) -> Result<AnalysisResponse, String> {
+    let loaded = load_analysis_candles(
+        &params,
+        providers.inner(),
         yahoo_client.inner(),
-        kis_client.inner(),
+        cache.inner(),
     )
     .await?;
-    let candles = resample_candles(&source_result.candles, &plan);
+    fill_earnings_anchors(&mut params, &loaded.candles, yahoo_client.inner()).await;
+
+    let mut warnings = loaded.warnings;
+    let external = load_external_data(
+        &params,
+        &loaded.candles,
+        providers.inner(),
+        kis_client.inner(),
+        yahoo_client.inner(),
+        cache.inner(),
+        &mut warnings,
+    )
+    .await;
 
-    let mut response = ta_engine::analyze(&candles, &params);
-    response.data_source = source_result.data_source.as_str().to_string();
-    response.source_interval = plan.source.clone();
+    let mut response = ta_engine::analyze_with(&loaded.candles, &params, external);
+    response.data_source = loaded.data_source.to_string();
+    response.source_interval = loaded.source_interval;
+    response.corporate_actions = loaded.corporate_actions;
+    response.warnings = warnings;
+
+    if params.market == MarketType::Crypto {
+        load_futures_data(
+            &params,
+            &loaded.candles,
+            binance_client.inner(),
+            &mut response,
+        )
+        .await;
+    }
     Ok(response)
 }
 
+/// 분석 파라미터가 요청한 외부 신호 재료를 불러온다. 불러오지 못한 자료는 비워 두고
+/// `warnings`에 남긴다.
+pub(super) async fn load_external_data(
+    params: &AnalysisParams,
+    candles: &[Candle],
+    providers: &ProviderRegistry,
+    kis_client: &KisClient,
+    yahoo_client: &YahooClient,
+    cache: &CacheDb,
+    warnings: &mut Vec<String>,
+) -> ExternalData {
+    let mut external = ExternalData::default();
+    if let Some(premium_params) = params.kimchi_premium.as_ref() {
+        let premium = load_kimchi_premium(
+            params,
+            premium_params,
+            candles,
+            providers,
+            yahoo_client,
+            cache,
+        )
+        .await;
+        match premium {
+            Ok(premium) => external.kimchi_premium = Some(premium),
+            Err(error) => warnings.push(format!("김치 프리미엄을 계산하지 못했습니다: {}", error)),
+        }
+    }
+    if let Some(flow_params) = params.investor_flow.as_ref() {
+        match load_investor_flow(params, flow_params, candles, kis_client).await {
+            Ok(flow) => external.investor_flow = Some(flow),
+            Err(error) => {
+                log::warn!("investor flow for {} unavailable: {}", params.symbol, error);
+                warnings.push(format!("투자자별 순매수를 받지 못했습니다: {}", error));
+            }
+        }
+    }
+    external
+}
+
+/// 요청된 Binance 무기한 선물 지표를 받아 봉 시각에 맞춘다. 조회에 실패한 지표는 비워 두고
+/// `warnings`에 남긴다.
+async fn load_futures_data(
+    params: &AnalysisParams,
+    candles: &[Candle],
+    binance_client: &BinanceClient,
+    response: &mut AnalysisResponse,
+) {
+    let (Some(first), Some(last)) = (candles.first(), candles.last()) else {
+        return;
+    };
+    let step = interval_seconds(&params.interval).unwrap_or(86_400);
+    let (start, end) = (first.time, last.time + step);
+    // 원화 마켓 심볼은 같은 코인의 USDT 무기한 선물을 쓴다
+    let symbol = kimchi_premium_pair(&params.symbol)
+        .map_or_else(|| params.symbol.trim().to_uppercase(), |(_, usd)| usd);
+    // 봉 주기 이하에서 가장 긴 집계 주기
+    let period = FUTURES_STATS_PERIODS
+        .iter()
+        .find(|(_, secs)| *secs <= step)
+        .map_or("5m", |(period, _)| *period);
+
+    if params.show_funding_rate {
+        match binance_client
+            .fetch_funding_rates(&symbol, start, end)
+            .await
+        {
+            Ok(rates) => {
+                response.funding_rate = Some(FundingRateResult {
+                    symbol: symbol.clone(),
+                    data: derivatives::align_funding_rates(candles, step, &rates),
+                })
+            }
+            Err(error) => response
+                .warnings
+                .push(format!("{} 펀딩비를 받지 못했습니다: {}", symbol, error)),
+        }
+    }
+    if params.show_open_interest {
+        match binance_client
+            .fetch_open_interest(&symbol, period, start, end)
+            .await
+        {
+            Ok(points) => {
+                response.open_interest = Some(OpenInterestResult {
+                    symbol: symbol.clone(),
+                    period: period.to_string(),
+                    data: derivatives::align_open_interest(candles, step, &points),
+                })
+            }
+            Err(error) => response.warnings.push(format!(
+                "{} 미결제약정을 받지 못했습니다: {}",
+                symbol, error
+            )),
+        }
+    }
+    if params.show_long_short_ratio {
+        match binance_client
+            .fetch_long_short_ratio(&symbol, period, start, end)
+            .await
+        {
+            Ok(points) => {
+                response.long_short_ratio = Some(LongShortRatioResult {
+                    symbol: symbol.clone(),
+                    period: period.to_string(),
+                    data: derivatives::align_long_short_ratio(candles, step, &points),
+                })
+            }
+            Err(error) => response.warnings.push(format!(
+                "{} 롱/숏 비율을 받지 못했습니다: {}",
+                symbol, error
+            )),
+        }
+    }
+}
+
+/// 국내 주식의 투자자별 일별 순매수를 받아 봉 구간별로 합산한다.
+/// 일 단위 자료라 일봉 이상에서만 계산한다.
+async fn load_investor_flow(
+    params: &AnalysisParams,
+    flow_params: &InvestorFlowParams,
+    candles: &[Candle],
+    kis_client: &KisClient,
+) -> Result<InvestorFlowResult, String> {
+    if params.market != MarketType::KrStock {
+        return Err("투자자별 매매동향은 국내 주식에만 계산합니다".to_string());
+    }
+    let step = interval_seconds(&params.interval).unwrap_or(86_400);
+    if step < 86_400 {
+        return Err("투자자별 매매동향은 일봉 이상에서만 계산합니다".to_string());
+    }
+    let first_time = candles.first().ok_or("분석할 캔들이 없습니다")?.time;
+
+    let days = kis_client
+        .fetch_investor_flow(&params.symbol, first_time)
+        .await?;
+    Ok(InvestorFlowResult {
+        symbol: params.symbol.clone(),
+        streak: flow_params.streak,
+        data: investor_flow::align(candles, step, &days),
+    })
+}
+
+/// 원화 마켓 심볼과 USDT 마켓 심볼 쌍 (`KRW-BTC` ↔ `BTCUSDT`).
+fn kimchi_premium_pair(symbol: &str) -> Option<(String, String)> {
+    let symbol = symbol.trim().to_uppercase();
+    let base = match symbol.strip_prefix("KRW-") {
+        Some(base) => base,
+        None => symbol.strip_suffix("USDT")?,
+    };
+    if base.is_empty() || !base.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some((format!("KRW-{}", base), format!("{}USDT", base)))
+}
+
+/// 분석 심볼의 반대쪽 마켓 캔들과 USD/KRW 환율을 불러와 김치 프리미엄을 계산한다.
+/// 분석 심볼 쪽은 이미 불러온 캔들을 그대로 쓴다.
+async fn load_kimchi_premium(
+    params: &AnalysisParams,
+    premium_params: &KimchiPremiumParams,
+    candles: &[Candle],
+    providers: &ProviderRegistry,
+    yahoo_client: &YahooClient,
+    cache: &CacheDb,
+) -> Result<KimchiPremiumResult, String> {
+    if params.market != MarketType::Crypto {
+        return Err("김치 프리미엄은 암호화폐에만 계산합니다".to_string());
+    }
+    let (krw_symbol, usd_symbol) = kimchi_premium_pair(&params.symbol)
+        .ok_or_else(|| format!("{}의 원화/USDT 마켓 쌍을 찾지 못했습니다", params.symbol))?;
+    let first_time = candles.first().ok_or("분석할 캔들이 없습니다")?.time;
+
+    let is_krw = params.symbol.trim().eq_ignore_ascii_case(&krw_symbol);
+    let counterpart_symbol = if is_krw { &usd_symbol } else { &krw_symbol };
+    let mut counterpart = AnalysisParams::new(
+        counterpart_symbol.clone(),
+        params.interval.clone(),
+        MarketType::Crypto,
+    );
+    counterpart.start_time = params.start_time;
+    let counterpart = load_analysis_candles(&counterpart, providers, yahoo_client, cache).await?;
+
+    // 환율은 주말 봉이 없으므로 시간봉/일봉을 받아 직전 값으로 채운다.
+    // 최근 구간이 첫 봉까지 닿지 않을 때만 과거 구간을 백필한다
+    let step = interval_seconds(&params.interval).unwrap_or(86_400);
+    let intraday = step < 86_400;
+    let mut fx_params = AnalysisParams::new(
+        USD_KRW_SYMBOL.to_string(),
+        if intraday { "1h" } else { "1d" }.to_string(),
+        MarketType::Forex,
+    );
+    let mut fx = load_analysis_candles(&fx_params, providers, yahoo_client, cache).await?;
+    if fx.candles.first().is_none_or(|c| c.time > first_time) {
+        fx_params.start_time = Some(first_time);
+        fx = load_analysis_candles(&fx_params, providers, yahoo_client, cache).await?;
+    }
+
+    let (krw, usd) = if is_krw {
+        (candles, counterpart.candles.as_slice())
+    } else {
+        (counterpart.candles.as_slice(), candles)
+    };
+    Ok(KimchiPremiumResult {
+        data: kimchi_premium::calculate(
+            krw,
+            usd,
+            &fx.candles,
+            step,
+            if intraday { 3_600 } else { 86_400 },
+            premium_params.z_period,
+        ),
+        krw_symbol,
+        usd_symbol,
+        z_period: premium_params.z_period,
+        band: premium_params.band,
+    })
+}
+
+/// 시각이 비어 있는 실적 발표 앵커를 마지막 봉 이전의 최근 발표일로 채운다 (미국 주식).
+/// Yahoo는 직전/예정 발표일만 주므로, 조회에 실패하거나 마지막 봉이 직전 발표보다 이르면
+/// 그 앵커는 건너뛴다.
+async fn fill_earnings_anchors(
+    params: &mut AnalysisParams,
+    candles: &[Candle],
+    yahoo_client: &YahooClient,
+) {
+    let (Some(anchored), Some(last)) = (params.anchored_vwap.as_mut(), candles.last()) else {
+        return;
+    };
+    let pending =
+        |anchor: &VwapAnchor| anchor.mode == AnchorMode::Earnings && anchor.time.is_none();
+    if params.market != MarketType::UsStock || !anchored.anchors.iter().any(pending) {
+        return;
+    }
+
+    let latest = match yahoo_client.fetch_earnings_dates(&params.symbol).await {
+        Ok(dates) => dates.into_iter().rfind(|&time| time <= last.time),
+        Err(error) => {
+            log::warn!(
+                "earnings dates for {} unavailable: {}",
+                params.symbol,
+                error
+            );
+            None
+        }
+    };
+    for anchor in anchored.anchors.iter_mut().filter(|anchor| pending(anchor)) {
+        anchor.time = latest;
+    }
+}
+
 #[tauri::command]
 pub async fn fetch_watchlist_snapshots(
     params: WatchlistSnapshotParams,
-    binance_client: State<'_, BinanceClient>,
-    yahoo_client: State<'_, YahooClient>,
-    kis_client: State<'_, KisClient>,
+    providers: State<'_, ProviderRegistry>,
     cache: State<'_, CacheDb>,
 ) -> Result<Vec<WatchlistSnapshot>, String> {
     if params.items.is_empty() {
@@ -441,8 +934,14 @@ pub async fn fetch_watchlist_snapshots(
     for item in params.items.iter().take(MAX_WATCHLIST_ITEMS) {
         let market_prefix = market_prefix(&item.market);
         let cache_key_symbol = format!("{}:{}", market_prefix, item.symbol);
-        let plan = resolve_interval_plan(&interval, &item.market);
-        let source_limit = requested_source_limit(limit as u32, &plan, &item.market);
+        let plan = resolve_interval_plan(&interval, &item.market, &item.symbol, providers.inner());
+        let source_limit = requested_source_limit(
+            limit as u32,
+            &plan,
+            &item.market,
+            &item.symbol,
+            providers.inner(),
+        );
 
         let source_result = match load_source_candles(
             cache.inner(),
@@ -451,16 +950,14 @@ pub async fn fetch_watchlist_snapshots(
             &item.market,
             &plan,
             source_limit,
-            binance_client.inner(),
-            yahoo_client.inner(),
-            kis_client.inner(),
+            providers.inner(),
         )
         .await
         {
             Ok(result) => result,
             Err(_) => continue,
         };
-        let candles = resample_candles(&source_result.candles, &plan);
+        let candles = resample_candles(&source_result.candles, &plan, &item.market);
 
         if candles.len() < 2 {
             continue;
@@ -497,7 +994,7 @@ pub async fn fetch_watchlist_snapshots(
             high,
             low,
             sparkline,
-            data_source: source_result.data_source.as_str().to_string(),
+            data_source: source_result.data_source.to_string(),
             source_interval: plan.source.clone(),
         });
     }
@@ -518,3 +1015,109 @@ pub async fn fetch_fundamentals(
         .fetch_fundamentals(&params.symbol, params.market.clone())
         .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::candle;
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> i64 {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .and_then(|date| date.and_hms_opt(hour, minute, 0))
+            .unwrap()
+            .and_utc()
+            .timestamp()
+    }
+
+    #[test]
+    fn intraday_buckets_align_to_the_regular_open() {
+        // 10:15 KST -> the 10:00 KST hour (open 09:00)
+        assert_eq!(
+            bucket_start(utc(2024, 3, 4, 1, 15), 1, 'h', &MarketType::KrStock),
+            Some(utc(2024, 3, 4, 1, 0))
+        );
+        // 10:45 EST -> the 10:30 EST hour (open 09:30)
+        assert_eq!(
+            bucket_start(utc(2024, 3, 4, 15, 45), 1, 'h', &MarketType::UsStock),
+            Some(utc(2024, 3, 4, 15, 30))
+        );
+        // 10:45 EDT after the switch
+        assert_eq!(
+            bucket_start(utc(2024, 3, 11, 14, 45), 1, 'h', &MarketType::UsStock),
+            Some(utc(2024, 3, 11, 14, 30))
+        );
+    }
+
+    #[test]
+    fn post_market_buckets_start_at_the_early_close() {
+        // 2024-11-29 closes at 13:00 EST (18:00 UTC), so 13:20 is post-market
+        assert_eq!(
+            bucket_start(utc(2024, 11, 29, 18, 20), 1, 'h', &MarketType::UsStock),
+            Some(utc(2024, 11, 29, 18, 0))
+        );
+        // On a full day the same clock time is still inside the 12:30 regular bucket
+        assert_eq!(
+            bucket_start(utc(2024, 11, 27, 18, 20), 1, 'h', &MarketType::UsStock),
+            Some(utc(2024, 11, 27, 17, 30))
+        );
+    }
+
+    #[test]
+    fn calendar_buckets_use_the_session_date() {
+        // Sunday 18:00 EST trades toward Monday's FX session
+        let sunday_evening = utc(2024, 3, 3, 23, 0);
+        assert_eq!(
+            bucket_start(sunday_evening, 1, 'd', &MarketType::Forex),
+            Some(utc(2024, 3, 4, 0, 0))
+        );
+        assert_eq!(
+            bucket_start(sunday_evening, 1, 'w', &MarketType::Forex),
+            Some(utc(2024, 3, 4, 0, 0))
+        );
+        assert_eq!(
+            bucket_start(utc(2024, 3, 6, 12, 0), 1, 'w', &MarketType::Crypto),
+            Some(utc(2024, 3, 4, 0, 0))
+        );
+        assert_eq!(
+            bucket_start(utc(2024, 8, 20, 12, 0), 1, 'Q', &MarketType::Crypto),
+            Some(utc(2024, 7, 1, 0, 0))
+        );
+        assert_eq!(
+            bucket_start(utc(2024, 8, 20, 12, 0), 1, 'Y', &MarketType::Crypto),
+            Some(utc(2024, 1, 1, 0, 0))
+        );
+    }
+
+    #[test]
+    fn forming_bars_share_the_calendar_bucket_with_now() {
+        let market = MarketType::Crypto;
+        let monday = utc(2024, 3, 4, 0, 0);
+        assert!(is_forming(monday, "1w", &market, utc(2024, 3, 10, 23, 0)));
+        assert!(!is_forming(monday, "1w", &market, utc(2024, 3, 11, 0, 0)));
+        assert!(is_forming(monday, "15m", &market, monday + 899));
+        assert!(!is_forming(monday, "15m", &market, monday + 900));
+    }
+
+    #[test]
+    fn incremental_fetch_resumes_from_the_last_stored_bar() {
+        let cache = CacheDb::open_in_memory().unwrap();
+        let key = "crypto:BTCUSDT";
+        let hour = 3_600;
+        let now = Utc::now().timestamp() / hour * hour;
+
+        // Nothing stored yet: full fetch
+        assert_eq!(incremental_since(cache.last_time(key, "1h", "binance"), "1h", 500), None);
+
+        cache
+            .set(key, "1h", "binance", &[candle(now - 3 * hour, 1.0), candle(now - 2 * hour, 1.0)])
+            .unwrap();
+        assert_eq!(
+            incremental_since(cache.last_time(key, "1h", "binance"), "1h", 500),
+            Some(now - 2 * hour)
+        );
+        // A gap longer than one request falls back to a full fetch
+        assert_eq!(incremental_since(cache.last_time(key, "1h", "binance"), "1h", 2), None);
+        // Unknown intervals cannot be measured
+        assert_eq!(incremental_since(cache.last_time(key, "1h", "binance"), "?", 500), None);
+    }
+}
@@ -0,0 +1,233 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use tauri::State;
+
+use super::analysis::{interval_seconds, market_prefix, resolve_interval_plan, IntervalPlan};
+use crate::api_client::{MarketDataProvider, ProviderRegistry};
+use crate::cache::{find_holes, CacheDb};
+use crate::models::{BackfillParams, BackfillReport, MarketType};
+
+/// 시장별로 자연스럽게 생기는 캔들 간격의 상한 (이보다 크면 누락 구간으로 본다).
+fn max_natural_gap(market: &MarketType, step: i64) -> i64 {
+    // 월봉(30일 고정 환산)처럼 길이가 들쭉날쭉한 봉을 위한 여유
+    let base = step + step / 2;
+    let closed = match market {
+        MarketType::Crypto => 0,
+        // 주말 휴장
+        MarketType::Forex => 3 * 86_400,
+        // 주말 + 공휴일, intraday는 야간 휴장 포함
+        MarketType::UsStock | MarketType::KrStock => 4 * 86_400,
+    };
+    base.max(closed)
+}
+
+/// 과거 구간 조회(페이지네이션)를 지원하는 소스를 고른다. 선호 소스가 지원하면 그대로 사용.
+pub(super) fn resolve_backfill_source(
+    market: &MarketType,
+    symbol: &str,
+    source_interval: &str,
+    preferred: Option<&str>,
+    providers: &ProviderRegistry,
+) -> Option<Arc<dyn MarketDataProvider>> {
+    let order = providers.source_order(market, symbol, source_interval);
+    let supports_range =
+        |p: &&Arc<dyn MarketDataProvider>| p.supports_range(symbol, source_interval);
+    order
+        .iter()
+        .filter(supports_range)
+        .find(|p| Some(p.id()) == preferred)
+        .or_else(|| order.iter().find(supports_range))
+        .cloned()
+}
+
+/// 저장된 시리즈에서 `[start, end]` 구간의 빈 곳만 찾아 소스에서 채운다.
+pub(super) async fn backfill_range(
+    cache: &CacheDb,
+    symbol: &str,
+    market: &MarketType,
+    plan: &IntervalPlan,
+    provider: &dyn MarketDataProvider,
+    start: i64,
+    end: i64,
+) -> Result<BackfillReport, String> {
+    let cache_key_symbol = format!("{}:{}", market_prefix(market), symbol);
+    let step = interval_seconds(&plan.source).unwrap_or(86_400);
+    let key = provider.id();
+
+    let max_gap = max_natural_gap(market, step);
+
+    let times = cache.times(&cache_key_symbol, &plan.source, key, start, end)?;
+    let covered = cache.coverage(&cache_key_symbol, &plan.source, key)?;
+    let holes = find_holes(&times, start, end, max_gap, &covered);
+
+    let mut report = BackfillReport {
+        symbol: symbol.to_string(),
+        interval: plan.source.clone(),
+        data_source: key.to_string(),
+        holes_found: holes.len(),
+        holes_filled: 0,
+        candles_stored: 0,
+        errors: Vec::new(),
+    };
+
+    for (hole_start, hole_end) in holes {
+        match provider
+            .fetch_klines_range(symbol, &plan.source, hole_start, hole_end)
+            .await
+        {
+            Ok(candles) => {
+                cache.upsert(&cache_key_symbol, &plan.source, key, &candles)?;
+                // 소스가 페이지 상한에서 멈추면 앞부분이 빠지므로 받은 첫 봉부터만 확인된 것으로 본다.
+                // 남은 앞부분은 다음 백필에서 다시 조회하고, 빈 결과면 그때 통째로 기록된다.
+                // 첫 봉이 자연 간격 안에 있으면 잘린 것이 아니므로 공백 시작부터 기록해 이전 구간과 이어지게 한다
+                let covered_from = match candles.first() {
+                    Some(first) if first.time - hole_start > max_gap => first.time,
+                    _ => hole_start,
+                };
+                cache.mark_covered(&cache_key_symbol, &plan.source, key, covered_from, hole_end)?;
+                report.holes_filled += 1;
+                report.candles_stored += candles.len();
+            }
+            Err(error) => report
+                .errors
+                .push(format!("{}~{}: {}", hole_start, hole_end, error)),
+        }
+    }
+
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn backfill_history(
+    params: BackfillParams,
+    providers: State<'_, ProviderRegistry>,
+    cache: State<'_, CacheDb>,
+) -> Result<BackfillReport, String> {
+    let plan = resolve_interval_plan(
+        &params.interval,
+        &params.market,
+        &params.symbol,
+        providers.inner(),
+    );
+    let end = params.end_time.unwrap_or_else(|| Utc::now().timestamp());
+    if params.start_time >= end {
+        return Err("백필 시작 시각이 종료 시각보다 늦습니다".to_string());
+    }
+
+    let provider = resolve_backfill_source(
+        &params.market,
+        &params.symbol,
+        &plan.source,
+        None,
+        providers.inner(),
+    )
+    .ok_or("이 시장은 과거 구간 백필을 지원하지 않습니다")?;
+
+    backfill_range(
+        cache.inner(),
+        &params.symbol,
+        &params.market,
+        &plan,
+        provider.as_ref(),
+        params.start_time,
+        end,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::future::BoxFuture;
+
+    use super::*;
+    use crate::models::{Candle, ProviderCapabilities};
+    use crate::test_support::candle;
+
+    type CandleFuture<'a> = BoxFuture<'a, Result<Vec<Candle>, String>>;
+
+    const HOUR: i64 = 3_600;
+
+    /// 요청 구간의 정시 봉을 모두 돌려주는 공급자
+    struct Hourly;
+
+    impl MarketDataProvider for Hourly {
+        fn id(&self) -> &'static str {
+            "hourly"
+        }
+
+        fn name(&self) -> &'static str {
+            "Hourly"
+        }
+
+        fn capabilities(&self) -> ProviderCapabilities {
+            ProviderCapabilities {
+                markets: vec![MarketType::Crypto],
+                intervals: vec!["1h".to_string()],
+                max_bars: 1_000,
+                supports_range: true,
+                requests_per_minute: None,
+            }
+        }
+
+        fn fetch_klines<'a>(
+            &'a self,
+            _symbol: &'a str,
+            _interval: &'a str,
+            _limit: u32,
+        ) -> CandleFuture<'a> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+
+        fn fetch_klines_range<'a>(
+            &'a self,
+            _symbol: &'a str,
+            _interval: &'a str,
+            start: i64,
+            end: i64,
+        ) -> CandleFuture<'a> {
+            let first = (start + HOUR - 1) / HOUR * HOUR;
+            let candles = (first..=end)
+                .step_by(HOUR as usize)
+                .map(|time| candle(time, 1.0))
+                .collect();
+            Box::pin(async move { Ok(candles) })
+        }
+    }
+
+    fn plan() -> IntervalPlan {
+        IntervalPlan {
+            requested: "1h".to_string(),
+            source: "1h".to_string(),
+            factor: 1,
+            needs_resample: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn repeated_backfills_keep_one_coverage_row() {
+        let cache = CacheDb::open_in_memory().unwrap();
+        let plan = plan();
+        let start = 1_700_000_000 / HOUR * HOUR + 1_800;
+        let run = |end: i64| {
+            backfill_range(&cache, "BTCUSDT", &MarketType::Crypto, &plan, &Hourly, start, end)
+        };
+
+        let first = run(start + 100 * HOUR).await.unwrap();
+        assert_eq!((first.holes_found, first.candles_stored), (1, 100));
+
+        // The same range again has nothing left to fetch
+        let again = run(start + 100 * HOUR).await.unwrap();
+        assert_eq!(again.holes_found, 0);
+
+        // Later calls only fetch the new tail and extend the same row
+        let later = run(start + 110 * HOUR).await.unwrap();
+        assert_eq!((later.holes_found, later.candles_stored), (1, 10));
+        run(start + 120 * HOUR).await.unwrap();
+
+        assert_eq!(
+            cache.coverage("crypto:BTCUSDT", "1h", "hourly").unwrap(),
+            vec![(start, start + 120 * HOUR)]
+        );
+    }
+}
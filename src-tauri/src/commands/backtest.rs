@@ -0,0 +1,113 @@
+use tauri::State;
+
+use super::analysis::{load_analysis_candles, load_external_data};
+use crate::api_client::{KisClient, ProviderRegistry, YahooClient};
+use crate::backtest;
+use crate::cache::CacheDb;
+use crate::models::{BacktestParams, BacktestResponse, WalkForwardParams, WalkForwardResponse};
+use crate::ta_engine;
+
+#[tauri::command]
+pub async fn run_backtest(
+    params: BacktestParams,
+    providers: State<'_, ProviderRegistry>,
+    kis_client: State<'_, KisClient>,
+    yahoo_client: State<'_, YahooClient>,
+    cache: State<'_, CacheDb>,
+) -> Result<BacktestResponse, String> {
+    let loaded = load_analysis_candles(
+        &params.analysis,
+        providers.inner(),
+        yahoo_client.inner(),
+        cache.inner(),
+    )
+    .await?;
+
+    if loaded.candles.len() < 2 {
+        return Err("백테스트에 필요한 캔들 데이터가 부족합니다".to_string());
+    }
+
+    let mut warnings = loaded.warnings;
+    let external = load_external_data(
+        &params.analysis,
+        &loaded.candles,
+        providers.inner(),
+        kis_client.inner(),
+        yahoo_client.inner(),
+        cache.inner(),
+        &mut warnings,
+    )
+    .await;
+    let signals = ta_engine::analyze_with(&loaded.candles, &params.analysis, external).signals;
+    let output = backtest::run(
+        &loaded.candles,
+        &signals,
+        &params.backtest,
+        &params.analysis.market,
+        &params.analysis.interval,
+    );
+
+    Ok(BacktestResponse {
+        symbol: params.analysis.symbol.clone(),
+        interval: params.analysis.interval.clone(),
+        data_source: loaded.data_source.to_string(),
+        trades: output.trades,
+        equity_curve: output.equity_curve,
+        summary: output.summary,
+        metrics: output.metrics,
+        warnings,
+    })
+}
+
+#[tauri::command]
+pub async fn run_walk_forward(
+    params: WalkForwardParams,
+    providers: State<'_, ProviderRegistry>,
+    kis_client: State<'_, KisClient>,
+    yahoo_client: State<'_, YahooClient>,
+    cache: State<'_, CacheDb>,
+) -> Result<WalkForwardResponse, String> {
+    let loaded = load_analysis_candles(
+        &params.analysis,
+        providers.inner(),
+        yahoo_client.inner(),
+        cache.inner(),
+    )
+    .await?;
+
+    let mut warnings = loaded.warnings;
+    let external = load_external_data(
+        &params.analysis,
+        &loaded.candles,
+        providers.inner(),
+        kis_client.inner(),
+        yahoo_client.inner(),
+        cache.inner(),
+        &mut warnings,
+    )
+    .await;
+
+    // 조합 × 윈도우만큼 백테스트를 반복하므로 블로킹 스레드에서 실행
+    let candles = loaded.candles;
+    let job_params = params.clone();
+    let output = tokio::task::spawn_blocking(move || {
+        backtest::walk_forward(&candles, &external, &job_params)
+    })
+    .await
+    .map_err(|e| format!("워크포워드 실행 실패: {}", e))??;
+
+    Ok(WalkForwardResponse {
+        symbol: params.analysis.symbol.clone(),
+        interval: params.analysis.interval.clone(),
+        data_source: loaded.data_source.to_string(),
+        objective: params.walk_forward.objective,
+        candidates_evaluated: output.candidates_evaluated,
+        windows: output.windows,
+        stability: output.stability,
+        out_of_sample_equity: output.out_of_sample_equity,
+        out_of_sample_metrics: output.out_of_sample_metrics,
+        walk_forward_efficiency: output.walk_forward_efficiency,
+        recommended_params: output.recommended_params,
+        warnings,
+    })
+}
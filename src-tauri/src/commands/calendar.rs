@@ -0,0 +1,22 @@
+use crate::models::{MarketCalendarParams, TradingSession};
+use crate::ta_engine::session;
+
+/// 한 번에 조회할 수 있는 최대 일수
+const MAX_CALENDAR_DAYS: usize = 800;
+
+/// 구간 안의 거래일별 정규장 시간. 휴장일은 빠지고 조기 폐장일은 `halfDay`로 표시된다.
+#[tauri::command]
+pub fn fetch_market_calendar(params: MarketCalendarParams) -> Result<Vec<TradingSession>, String> {
+    if params.end_time < params.start_time {
+        return Err("종료 시각이 시작 시각보다 앞섭니다".to_string());
+    }
+    let first = session::session_date(params.start_time, &params.market);
+    let last = session::session_date(params.end_time, &params.market);
+
+    Ok(first
+        .iter_days()
+        .take_while(|date| *date <= last)
+        .take(MAX_CALENDAR_DAYS)
+        .filter_map(|date| session::trading_session(date, &params.market))
+        .collect())
+}
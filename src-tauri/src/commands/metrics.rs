@@ -0,0 +1,26 @@
+use crate::models::{MetricsParams, PerformanceMetrics};
+use crate::ta_engine::metrics;
+
+#[tauri::command]
+pub fn compute_performance_metrics(params: MetricsParams) -> Result<PerformanceMetrics, String> {
+    let periods_per_year = metrics::periods_per_year(&params.interval, &params.market);
+
+    if params.equity.len() >= 2 {
+        return Ok(metrics::from_equity(
+            &params.equity,
+            &params.weights,
+            periods_per_year,
+            params.risk_free_rate,
+        ));
+    }
+    if !params.returns.is_empty() {
+        return Ok(metrics::from_returns(
+            &params.returns,
+            &params.weights,
+            periods_per_year,
+            params.risk_free_rate,
+        ));
+    }
+
+    Err("자산 곡선 또는 수익률 시리즈가 필요합니다".to_string())
+}
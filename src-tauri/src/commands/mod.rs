@@ -1,3 +1,10 @@
+pub mod alert;
 pub mod analysis;
+pub mod backfill;
+pub mod backtest;
+pub mod calendar;
+pub mod metrics;
+pub mod provider;
 pub mod search;
 pub mod strategy;
+pub mod stream;
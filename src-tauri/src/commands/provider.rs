@@ -0,0 +1,36 @@
+use tauri::State;
+
+use crate::api_client::ProviderRegistry;
+use crate::cache::CacheDb;
+use crate::models::{DataProviderInfo, ProviderPriority};
+
+/// 등록된 데이터 공급자와 각 공급자의 지원 범위.
+#[tauri::command]
+pub fn list_data_providers(providers: State<'_, ProviderRegistry>) -> Vec<DataProviderInfo> {
+    providers.list()
+}
+
+/// 시장별 현재 조회 순서.
+#[tauri::command]
+pub fn fetch_provider_priorities(providers: State<'_, ProviderRegistry>) -> Vec<ProviderPriority> {
+    providers.priorities()
+}
+
+/// 시장의 공급자 조회 순서를 바꾸고 저장한다. 적용된 전체 순서를 돌려준다.
+#[tauri::command]
+pub fn set_provider_priority(
+    params: ProviderPriority,
+    providers: State<'_, ProviderRegistry>,
+    cache: State<'_, CacheDb>,
+) -> Result<ProviderPriority, String> {
+    providers.set_priority(&params)?;
+    cache.save_provider_priority(&params)?;
+    Ok(ProviderPriority {
+        providers: providers
+            .priority(&params.market)
+            .iter()
+            .map(|p| p.id().to_string())
+            .collect(),
+        market: params.market,
+    })
+}
@@ -1,17 +1,34 @@
-use crate::api_client::YahooClient;
-use crate::models::{SymbolSearchParams, SymbolSearchResult};
+use crate::api_client::{UpbitClient, YahooClient};
+use crate::models::{MarketType, SymbolSearchParams, SymbolSearchResult};
 use tauri::State;
 
+/// 주식은 Yahoo, 암호화폐는 Upbit 원화/BTC/USDT 마켓에서 찾는다.
+/// Upbit 조회 실패는 주식 결과를 막지 않는다.
 #[tauri::command]
 pub async fn search_symbols(
     params: SymbolSearchParams,
     yahoo_client: State<'_, YahooClient>,
+    upbit_client: State<'_, UpbitClient>,
 ) -> Result<Vec<SymbolSearchResult>, String> {
     if params.query.len() < 2 {
         return Ok(vec![]);
     }
 
-    yahoo_client
-        .search_symbols(&params.query, params.market_filter.as_ref())
-        .await
+    let mut results = match params.market_filter {
+        Some(MarketType::Crypto) => Vec::new(),
+        _ => {
+            yahoo_client
+                .search_symbols(&params.query, params.market_filter.as_ref())
+                .await?
+        }
+    };
+
+    if matches!(params.market_filter, None | Some(MarketType::Crypto)) {
+        match upbit_client.search_markets(&params.query).await {
+            Ok(markets) => results.extend(markets),
+            Err(error) => log::warn!("Upbit market search failed: {}", error),
+        }
+    }
+
+    Ok(results)
 }
@@ -1,12 +1,14 @@
 use std::collections::HashMap;
 use tauri::State;
 
+use super::analysis::load_corporate_actions;
 use crate::api_client::YahooClient;
 use crate::cache::CacheDb;
 use crate::models::{
-    MultiSymbolCandlesParams, MultiSymbolCandlesResponse, PremarketSnapshot,
+    MarketType, MultiSymbolCandlesParams, MultiSymbolCandlesResponse, PremarketSnapshot,
     PremarketSnapshotParams,
 };
+use crate::ta_engine::{adjustment, session};
 
 const MAX_STRATEGY_SYMBOLS: usize = 30;
 
@@ -32,27 +34,58 @@ pub async fn fetch_multi_symbol_candles(
     let limit = params.limit.clamp(50, 600);
     let mut data: HashMap<String, Vec<crate::models::Candle>> = HashMap::new();
     let mut errors: HashMap<String, String> = HashMap::new();
+    let mut warnings: HashMap<String, Vec<String>> = HashMap::new();
 
     for symbol in params.symbols.iter().take(MAX_STRATEGY_SYMBOLS) {
         let cache_key = format!("us:{}", symbol);
-
-        if let Some(cached) = cache.get(&cache_key, yahoo_interval, "yahoo") {
-            data.insert(symbol.clone(), cached);
-            continue;
+        // 실패해도 전략 스캔은 저장된 이력으로 계속하고, 종목별 경고로 알린다
+        // 분할 보정(저장 기준)만 요청하면 이력이 필요 없으므로 종목마다 Yahoo를 부르지 않는다
+        let mut symbol_warnings = Vec::new();
+        let actions = if params.adjustment.needs_corporate_actions() {
+            load_corporate_actions(
+                cache.inner(),
+                &cache_key,
+                symbol,
+                &MarketType::UsStock,
+                yahoo_client.inner(),
+                &mut symbol_warnings,
+            )
+            .await
+        } else {
+            Vec::new()
+        };
+        if !symbol_warnings.is_empty() {
+            warnings.insert(symbol.clone(), symbol_warnings);
         }
 
-        match yahoo_client.fetch_klines(symbol, yahoo_interval, limit).await {
-            Ok(candles) => {
-                let _ = cache.set(&cache_key, yahoo_interval, "yahoo", &candles);
-                data.insert(symbol.clone(), candles);
-            }
-            Err(e) => {
-                errors.insert(symbol.clone(), e);
+        let candles = if let Some(cached) = cache.get(&cache_key, yahoo_interval, "yahoo", limit) {
+            cached
+        } else {
+            let fetched = yahoo_client
+                .fetch_klines(symbol, yahoo_interval, limit)
+                .await;
+            match fetched {
+                Ok(candles) => {
+                    let _ = cache.set(&cache_key, yahoo_interval, "yahoo", &candles);
+                    candles
+                }
+                Err(e) => {
+                    errors.insert(symbol.clone(), e);
+                    continue;
+                }
             }
-        }
+        };
+
+        let mut adjusted = adjustment::apply(&candles, &actions, params.adjustment);
+        session::tag_sessions(&mut adjusted, yahoo_interval, &MarketType::UsStock);
+        data.insert(symbol.clone(), adjusted);
     }
 
-    Ok(MultiSymbolCandlesResponse { data, errors })
+    Ok(MultiSymbolCandlesResponse {
+        data,
+        errors,
+        warnings,
+    })
 }
 
 #[tauri::command]
@@ -0,0 +1,209 @@
+use futures_util::future::BoxFuture;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::mpsc;
+
+use super::analysis::{interval_seconds, market_prefix, resolve_interval_plan};
+use crate::api_client::{BinanceClient, KisFeed, MarketDataProvider, ProviderRegistry};
+use crate::cache::CacheDb;
+use crate::models::{CandleStreamParams, CandleStreamStatus, IndicatorUpdate, MarketType};
+use crate::stream::binance::{self, KlineSubscription};
+use crate::stream::kis::{self, KisSubscription};
+use crate::stream::{StreamEvent, StreamManager};
+use crate::ta_engine::incremental::{IncrementalIndicator, IndicatorSet};
+
+pub const CANDLE_UPDATE_EVENT: &str = "candle-update";
+pub const CANDLE_STREAM_STATUS_EVENT: &str = "candle-stream-status";
+pub const INDICATOR_UPDATE_EVENT: &str = "indicator-update";
+pub const ORDER_BOOK_UPDATE_EVENT: &str = "order-book-update";
+
+/// 지표 상태를 채울 때 읽을 저장된 봉 수
+const INDICATOR_SEED_LIMIT: u32 = 1_000;
+
+/// 암호화폐(Binance)·국내 주식(KIS) 심볼의 실시간 봉 갱신을 시작한다.
+/// 갱신은 `candle-update`, 연결 상태는 `candle-stream-status` 이벤트로 전달되며
+/// 마감된 봉은 캔들 저장소에도 기록된다. 국내 주식은 호가를 `order-book-update`로 함께 보낸다.
+/// `indicators`를 지정하면 지표 갱신값을 `indicator-update` 이벤트로 함께 보낸다.
+#[tauri::command]
+pub async fn start_candle_stream(
+    params: CandleStreamParams,
+    app: AppHandle,
+    binance_client: State<'_, BinanceClient>,
+    kis_feed: State<'_, KisFeed>,
+    providers: State<'_, ProviderRegistry>,
+    cache: State<'_, CacheDb>,
+    streams: State<'_, StreamManager>,
+) -> Result<(), String> {
+    let (source_id, source_name) = match params.market {
+        MarketType::Crypto => ("binance", "Binance"),
+        MarketType::KrStock => ("kis", "KIS"),
+        _ => return Err("실시간 스트림은 암호화폐와 국내 주식만 지원합니다".to_string()),
+    };
+    // Upbit `KRW-BTC` 같은 마켓은 Binance 스트림에 없어 끝없이 재연결하게 된다
+    if params.market == MarketType::Crypto && !binance_client.supports_symbol(&params.symbol) {
+        return Err(format!(
+            "{}은(는) {} 실시간 스트림이 제공하지 않는 심볼입니다",
+            params.symbol, source_name
+        ));
+    }
+    let plan = resolve_interval_plan(
+        &params.interval,
+        &params.market,
+        &params.symbol,
+        providers.inner(),
+    );
+    let step = interval_seconds(&plan.source).unwrap_or(86_400);
+    let too_long = params.market == MarketType::KrStock && step > kis::MAX_STEP;
+    if plan.needs_resample || too_long {
+        return Err(format!(
+            "{} 주기는 {} 스트림이 제공하지 않습니다",
+            params.interval, source_name
+        ));
+    }
+
+    let cache_key_symbol = format!("{}:{}", market_prefix(&params.market), params.symbol);
+    let mut indicators = params.indicators.as_ref().map(|indicator_params| {
+        let mut set = IndicatorSet::new(indicator_params, &plan.source, &params.market);
+        let mut stored = cache
+            .load(
+                &cache_key_symbol,
+                &plan.source,
+                source_id,
+                INDICATOR_SEED_LIMIT,
+            )
+            .unwrap_or_default();
+        // 마지막 저장 봉은 진행 중에 저장됐을 수 있으므로 보충 구간에서 다시 받는다
+        stored.pop();
+        for candle in &stored {
+            set.update(candle);
+        }
+        set
+    });
+
+    let symbol = params.symbol.to_uppercase();
+    let interval = plan.source.clone();
+    let resync_from = indicators
+        .as_ref()
+        .and_then(IndicatorSet::last_time)
+        .or(params.since);
+    let key = StreamManager::key(&symbol, &interval);
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    let source: BoxFuture<'static, ()> = match params.market {
+        MarketType::KrStock => Box::pin(kis::run(
+            KisSubscription {
+                symbol,
+                interval: interval.clone(),
+                step,
+                resync_from,
+            },
+            kis_feed.inner().clone(),
+            tx,
+        )),
+        _ => Box::pin(binance::run(
+            KlineSubscription {
+                ws_url: streams.ws_url().to_string(),
+                symbol,
+                interval: interval.clone(),
+                step,
+                resync_from,
+            },
+            binance_client.inner().clone(),
+            tx,
+        )),
+    };
+
+    let task = tauri::async_runtime::spawn(async move {
+        let forward = async move {
+            while let Some(event) = rx.recv().await {
+                match event {
+                    StreamEvent::Candle(update) => {
+                        if update.closed {
+                            let cache = app.state::<CacheDb>();
+                            if let Err(error) = cache.upsert(
+                                &cache_key_symbol,
+                                &interval,
+                                source_id,
+                                std::slice::from_ref(&update.candle),
+                            ) {
+                                log::warn!("stream cache write failed: {}", error);
+                            }
+                        }
+                        if let Some(set) = indicators.as_mut() {
+                            // 이미 반영한 봉(보충 구간과 겹치는 부분)은 건너뛴다
+                            let fresh = set.last_time().is_none_or(|t| update.candle.time > t);
+                            let values = if !fresh {
+                                None
+                            } else if update.closed {
+                                set.update(&update.candle)
+                            } else {
+                                set.peek(&update.candle)
+                            };
+                            if let Some(values) = values {
+                                let _ = app.emit(
+                                    INDICATOR_UPDATE_EVENT,
+                                    IndicatorUpdate {
+                                        symbol: update.symbol.clone(),
+                                        interval: update.interval.clone(),
+                                        closed: update.closed,
+                                        values,
+                                    },
+                                );
+                            }
+                        }
+                        let _ = app.emit(CANDLE_UPDATE_EVENT, update);
+                    }
+                    StreamEvent::OrderBook(book) => {
+                        let _ = app.emit(ORDER_BOOK_UPDATE_EVENT, book);
+                    }
+                    StreamEvent::Status(status) => {
+                        let _ = app.emit(CANDLE_STREAM_STATUS_EVENT, status);
+                    }
+                }
+            }
+        };
+        tokio::join!(source, forward);
+    });
+
+    streams.insert(key, task)
+}
+
+#[tauri::command]
+pub async fn stop_candle_stream(
+    params: CandleStreamParams,
+    app: AppHandle,
+    providers: State<'_, ProviderRegistry>,
+    streams: State<'_, StreamManager>,
+) -> Result<bool, String> {
+    let interval = resolve_interval_plan(
+        &params.interval,
+        &params.market,
+        &params.symbol,
+        providers.inner(),
+    )
+    .source;
+    let symbol = params.symbol.to_uppercase();
+    let stopped = streams.stop(&StreamManager::key(&symbol, &interval))?;
+    if stopped {
+        let _ = app.emit(
+            CANDLE_STREAM_STATUS_EVENT,
+            CandleStreamStatus {
+                symbol,
+                interval,
+                state: "stopped".to_string(),
+                attempt: 0,
+                message: None,
+            },
+        );
+    }
+    Ok(stopped)
+}
+
+#[tauri::command]
+pub async fn stop_all_candle_streams(streams: State<'_, StreamManager>) -> Result<(), String> {
+    streams.stop_all()
+}
+
+#[tauri::command]
+pub async fn list_candle_streams(streams: State<'_, StreamManager>) -> Result<Vec<String>, String> {
+    streams.active()
+}
@@ -1,30 +1,105 @@
+mod alerts;
 mod api_client;
+mod backtest;
 mod cache;
 mod commands;
 mod models;
+mod stream;
 mod ta_engine;
+#[cfg(test)]
+mod test_support;
 
-use api_client::{BinanceClient, KisClient, YahooClient};
+use std::sync::Arc;
+
+use api_client::{BinanceClient, KisClient, KisFeed, ProviderRegistry, UpbitClient, YahooClient};
 use cache::CacheDb;
+use models::{MarketType, ProviderPriority};
+use stream::StreamManager;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // .env 파일에서 환경변수 로드 (파일 없으면 무시)
     let _ = dotenvy::dotenv();
 
+    let binance_client = BinanceClient::new();
+    let yahoo_client = YahooClient::new();
+    let upbit_client = UpbitClient::new();
+    let kis_client = KisClient::new();
+    // 등록 순서가 시장별 기본 조회 순서. 선물은 조회 순서에 넣었을 때만 쓰인다
+    let providers = ProviderRegistry::new(vec![
+        Arc::new(binance_client.clone()),
+        Arc::new(upbit_client.clone()),
+        Arc::new(binance_client.perpetual()),
+        Arc::new(kis_client.clone()),
+        Arc::new(yahoo_client.clone()),
+    ]);
+    // 미국 주식은 자격 증명 없이 쓰는 Yahoo가 기본이고, KIS는 조회 순서에서 올렸을 때 먼저 쓰인다.
+    // 저장된 조회 순서가 있으면 아래에서 덮어쓴다
+    if let Err(error) = providers.set_priority(&ProviderPriority {
+        market: MarketType::UsStock,
+        providers: vec!["yahoo".to_string()],
+    }) {
+        log::warn!("default provider priority ignored: {}", error);
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
-        .manage(BinanceClient::new())
-        .manage(YahooClient::new())
-        .manage(KisClient::new())
-        .manage(CacheDb::new().expect("Failed to initialize cache database"))
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_log::Builder::new().build())
+        .manage(binance_client)
+        .manage(yahoo_client)
+        .manage(upbit_client)
+        .manage(KisFeed::new(kis_client.clone()))
+        .manage(kis_client)
+        .manage(StreamManager::new())
+        .setup(move |app| {
+            // 캔들 저장소는 앱 데이터 디렉터리에 영구 보관
+            let db_path = app.path().app_data_dir()?.join("candles.db");
+            let cache = CacheDb::open(&db_path).or_else(|error| {
+                log::warn!("{} — falling back to in-memory cache", error);
+                CacheDb::open_in_memory()
+            })?;
+            for priority in cache.provider_priorities().unwrap_or_default() {
+                if let Err(error) = providers.set_priority(&priority) {
+                    log::warn!("saved provider priority ignored: {}", error);
+                }
+            }
+            app.manage(providers);
+            app.manage(cache);
+            // KIS 접근 토큰은 캔들 저장소 옆에, 암호화 키는 설정 디렉터리에 따로 보관
+            app.state::<KisClient>().set_token_dirs(
+                &app.path().app_data_dir()?,
+                &app.path().app_config_dir()?,
+            );
+            commands::alert::spawn_alert_loop(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
+            commands::alert::list_alert_rules,
+            commands::alert::save_alert_rule,
+            commands::alert::delete_alert_rule,
+            commands::alert::fetch_alert_history,
+            commands::alert::clear_alert_history,
+            commands::alert::evaluate_alerts,
             commands::analysis::fetch_analysis,
             commands::analysis::fetch_watchlist_snapshots,
             commands::analysis::fetch_fundamentals,
+            commands::backfill::backfill_history,
+            commands::backtest::run_backtest,
+            commands::backtest::run_walk_forward,
+            commands::calendar::fetch_market_calendar,
+            commands::metrics::compute_performance_metrics,
+            commands::provider::list_data_providers,
+            commands::provider::fetch_provider_priorities,
+            commands::provider::set_provider_priority,
             commands::strategy::fetch_multi_symbol_candles,
             commands::strategy::fetch_premarket_snapshots,
-            commands::search::search_symbols
+            commands::search::search_symbols,
+            commands::stream::start_candle_stream,
+            commands::stream::stop_candle_stream,
+            commands::stream::stop_all_candle_streams,
+            commands::stream::list_candle_streams
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
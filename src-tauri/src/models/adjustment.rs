@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+/// 가격 보정 방식. 저장되는 시리즈는 분할 보정 기준(Yahoo `quote`, KIS 수정주가)이고
+/// 조회할 때 이 설정에 맞춰 다시 계산한다.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceAdjustment {
+    /// 원주가 (분할 전 가격 그대로)
+    None,
+    /// 주식 분할만 보정
+    #[default]
+    Splits,
+    /// 분할 + 배당 재투자 (총수익 기준)
+    TotalReturn,
+}
+
+impl PriceAdjustment {
+    /// 저장 기준(분할 보정)과 다르게 계산하려면 분할/배당 이력이 필요하다
+    pub fn needs_corporate_actions(self) -> bool {
+        self != PriceAdjustment::Splits
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum CorporateActionKind {
+    Split,
+    Dividend,
+}
+
+/// 주식 분할/배당. `time`은 권리락(배당락) 시각
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorporateAction {
+    pub time: i64,
+    pub kind: CorporateActionKind,
+    /// 분할은 비율(4:1 분할이면 4), 배당은 주당 금액(분할 보정 기준)
+    pub value: f64,
+}
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+
+use super::{MarketType, SignalStrategyParams, SignalType};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertTarget {
+    pub symbol: String,
+    pub market: MarketType,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum CrossDirection {
+    Above,
+    Below,
+    #[default]
+    Either,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum AlertComparison {
+    Above,
+    Below,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum AlertIndicator {
+    Rsi,
+    Mfi,
+    Cmf,
+    WilliamsR,
+    Adx,
+    StochasticK,
+}
+
+/// 알림 조건. `kind`로 구분한다.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum AlertCondition {
+    /// 신호 발생. `sources`(예: `ttm_squeeze`)가 비어 있으면 모든 전략, `signal_types`가 비어 있으면 모든 방향
+    Signal {
+        #[serde(default)]
+        sources: Vec<String>,
+        #[serde(default)]
+        signal_types: Vec<SignalType>,
+    },
+    /// 종가가 가격 수준을 교차
+    PriceCross {
+        level: f64,
+        #[serde(default)]
+        direction: CrossDirection,
+    },
+    /// 종가가 앵커드 VWAP을 교차
+    AnchoredVwapCross {
+        anchor_time: i64,
+        #[serde(default)]
+        direction: CrossDirection,
+    },
+    /// 지표 값이 기준을 넘어섬. 조건이 새로 성립한 봉에서만 발생한다
+    Threshold {
+        indicator: AlertIndicator,
+        /// 비우면 지표별 기본 기간
+        #[serde(default)]
+        period: Option<usize>,
+        comparison: AlertComparison,
+        value: f64,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertRule {
+    /// 저장 시 부여된다 (0이면 새 규칙)
+    #[serde(default)]
+    pub id: i64,
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub targets: Vec<AlertTarget>,
+    #[serde(default = "default_interval")]
+    pub interval: String,
+    pub condition: AlertCondition,
+    /// 신호 조건의 전략 세부 설정. 켤 전략은 조건의 `sources`로 정한다
+    #[serde(default)]
+    pub signal_strategies: SignalStrategyParams,
+    /// 데스크톱 알림 표시 여부 (이벤트는 항상 보낸다)
+    #[serde(default = "default_enabled")]
+    pub notify: bool,
+}
+
+/// `alert-triggered` 이벤트 payload이자 알림 이력 한 건
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertEvent {
+    #[serde(default)]
+    pub id: i64,
+    pub rule_id: i64,
+    pub rule_name: String,
+    pub symbol: String,
+    pub market: MarketType,
+    pub interval: String,
+    /// 조건이 성립한 봉 시각
+    pub time: i64,
+    pub price: f64,
+    pub message: String,
+    /// 같은 규칙·심볼 안에서 중복 발생을 판정하는 키
+    pub key: String,
+    pub triggered_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertHistoryParams {
+    #[serde(default)]
+    pub rule_id: Option<i64>,
+    #[serde(default = "default_history_limit")]
+    pub limit: u32,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+fn default_interval() -> String {
+    "1h".to_string()
+}
+fn default_history_limit() -> u32 {
+    200
+}
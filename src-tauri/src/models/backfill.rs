@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+use super::MarketType;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackfillParams {
+    pub symbol: String,
+    #[serde(default)]
+    pub market: MarketType,
+    pub interval: String,
+    pub start_time: i64,
+    #[serde(default)]
+    pub end_time: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackfillReport {
+    pub symbol: String,
+    pub interval: String,
+    pub data_source: String,
+    pub holes_found: usize,
+    pub holes_filled: usize,
+    pub candles_stored: usize,
+    pub errors: Vec<String>,
+}
@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+
+use super::{AnalysisParams, PerformanceMetrics};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "mode")]
+pub enum PositionSizing {
+    /// 진입 시점 자산 대비 비율 (1.0 = 전액)
+    Percent { fraction: f64 },
+    /// 진입마다 고정 금액
+    FixedAmount { amount: f64 },
+}
+
+impl Default for PositionSizing {
+    fn default() -> Self {
+        Self::Percent { fraction: 1.0 }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestConfig {
+    #[serde(default = "default_initial_capital")]
+    pub initial_capital: f64,
+    /// 매수/매도 양쪽에 부과되는 수수료율
+    #[serde(default = "default_commission_rate")]
+    pub commission_rate: f64,
+    /// 체결가 불리 방향 슬리피지 비율
+    #[serde(default)]
+    pub slippage_rate: f64,
+    /// 매도 시 거래세율. 지정하지 않으면 시장 기본값 (국내주식 증권거래세)
+    #[serde(default)]
+    pub sell_tax_rate: Option<f64>,
+    #[serde(default)]
+    pub position_sizing: PositionSizing,
+    /// 매도 신호로 공매도 진입 허용
+    #[serde(default)]
+    pub allow_short: bool,
+    /// 소수점 수량 허용. 지정하지 않으면 암호화폐만 허용
+    #[serde(default)]
+    pub allow_fractional: Option<bool>,
+    /// 신호 발생 다음 봉 시가에 체결 (false면 신호 봉 종가)
+    #[serde(default = "default_fill_on_next_open")]
+    pub fill_on_next_open: bool,
+    /// 거래에 사용할 신호 source 목록 (비어 있으면 전체)
+    #[serde(default)]
+    pub sources: Vec<String>,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            initial_capital: default_initial_capital(),
+            commission_rate: default_commission_rate(),
+            slippage_rate: 0.0,
+            sell_tax_rate: None,
+            position_sizing: PositionSizing::default(),
+            allow_short: false,
+            allow_fractional: None,
+            fill_on_next_open: default_fill_on_next_open(),
+            sources: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestParams {
+    #[serde(flatten)]
+    pub analysis: AnalysisParams,
+    #[serde(default)]
+    pub backtest: BacktestConfig,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestTrade {
+    pub side: String, // "long" | "short"
+    pub entry_time: i64,
+    pub entry_price: f64,
+    pub exit_time: i64,
+    pub exit_price: f64,
+    pub quantity: f64,
+    pub pnl: f64,
+    pub return_pct: f64,
+    pub costs: f64,
+    pub bars_held: usize,
+    pub entry_source: String,
+    pub exit_reason: String, // "signal" | "end"
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EquityPoint {
+    pub time: i64,
+    pub equity: f64,
+    pub drawdown: f64,
+    pub position: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestSummary {
+    pub initial_capital: f64,
+    pub final_equity: f64,
+    pub total_return: f64,
+    pub buy_and_hold_return: f64,
+    pub max_drawdown: f64,
+    pub trade_count: usize,
+    pub win_rate: f64,
+    pub avg_trade_return: f64,
+    /// 총이익 / 총손실. 손실 거래가 없으면 None
+    pub profit_factor: Option<f64>,
+    /// 포지션을 보유한 기간의 비율
+    pub exposure: f64,
+    pub total_costs: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestResponse {
+    pub symbol: String,
+    pub interval: String,
+    pub data_source: String,
+    pub trades: Vec<BacktestTrade>,
+    pub equity_curve: Vec<EquityPoint>,
+    pub summary: BacktestSummary,
+    pub metrics: PerformanceMetrics,
+    /// 캔들 로드 중 빠지거나 근사한 부분 (백필 실패, 분할/배당 미반영 등)
+    pub warnings: Vec<String>,
+}
+
+fn default_initial_capital() -> f64 {
+    10_000_000.0
+}
+fn default_commission_rate() -> f64 {
+    0.00015
+}
+fn default_fill_on_next_open() -> bool {
+    true
+}
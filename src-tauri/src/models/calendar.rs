@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+use super::MarketType;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketCalendarParams {
+    #[serde(default)]
+    pub market: MarketType,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+/// 거래일 하나의 정규장 시간. 시각은 UTC 초
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TradingSession {
+    /// 거래소 기준 거래일 (YYYY-MM-DD)
+    pub date: String,
+    pub open: i64,
+    pub close: i64,
+    /// 조기 폐장일
+    pub half_day: bool,
+}
@@ -9,4 +9,39 @@ pub struct Candle {
     pub low: f64,
     pub close: f64,
     pub volume: f64,
+    /// 주식 분/시간 봉의 장 구분. 일봉 이상과 24시간 시장은 비어 있다
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<CandleSession>,
+    /// 체결 세부 내역 (Binance). 제공하지 않는 공급자는 비어 있다
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flow: Option<TradeFlow>,
+}
+
+/// 봉 안의 체결 집계. 테이커 매수량으로 실제 매수/매도 거래량을 나눌 수 있다
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeFlow {
+    /// 호가 통화 기준 거래대금
+    pub quote_volume: f64,
+    pub trade_count: u64,
+    /// 시장가 매수(테이커 매수) 체결량 (기준 자산 단위)
+    pub taker_buy_volume: f64,
+}
+
+impl TradeFlow {
+    /// 같은 봉으로 묶이는 다른 구간의 체결을 더한다 (리샘플링)
+    pub fn merge(&mut self, other: &TradeFlow) {
+        self.quote_volume += other.quote_volume;
+        self.trade_count += other.trade_count;
+        self.taker_buy_volume += other.taker_buy_volume;
+    }
+}
+
+/// 봉이 속한 거래 구간 (프리마켓 / 정규장 / 애프터마켓)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CandleSession {
+    Pre,
+    Regular,
+    Post,
 }
@@ -1,5 +1,7 @@
 use serde::Serialize;
 
+use super::{AnchorMode, VwapReset};
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BollingerBandsPoint {
@@ -84,10 +86,41 @@ pub struct VwapPoint {
     pub value: f64,
 }
 
+/// 세션 VWAP의 거래량 가중 표준편차 밴드
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VwapBandPoint {
+    pub time: i64,
+    pub std_dev: f64,
+    pub upper1: f64,
+    pub lower1: f64,
+    pub upper2: f64,
+    pub lower2: f64,
+    pub upper3: f64,
+    pub lower3: f64,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct VwapResult {
+    /// 실제 적용된 초기화 주기 (`auto`는 해석된 값으로 바뀐다)
+    pub reset: VwapReset,
     pub data: Vec<VwapPoint>,
+    pub bands: Vec<VwapBandPoint>,
+    /// 누적이 다시 시작된 봉 시각 (첫 봉 포함)
+    pub session_starts: Vec<i64>,
+}
+
+/// 앵커 하나의 VWAP 시리즈
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchoredVwapSeries {
+    pub label: String,
+    pub mode: AnchorMode,
+    /// 누적을 시작한 봉 시각
+    pub anchor_time: i64,
+    #[serde(flatten)]
+    pub vwap: VwapResult,
 }
 
 // ATR
@@ -281,6 +314,8 @@ pub struct CvdPoint {
 #[serde(rename_all = "camelCase")]
 pub struct CvdResult {
     pub data: Vec<CvdPoint>,
+    /// 테이커 매수량이 없어 `cvd_estimate` 방식으로 추정한 봉 수
+    pub estimated_bars: usize,
 }
 
 // STC (Schaff Trend Cycle)
@@ -335,3 +370,139 @@ pub struct AutoFibResult {
     pub is_uptrend: bool,
     pub levels: Vec<AutoFibLevel>,
 }
+
+// Multi-strategy consensus
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsensusPoint {
+    pub time: i64,
+    /// buy_score - sell_score
+    pub score: f64,
+    pub buy_score: f64,
+    pub sell_score: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsensusResult {
+    pub data: Vec<ConsensusPoint>,
+}
+
+// Kimchi premium (KRW market vs USDT market converted with USD/KRW)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KimchiPremiumPoint {
+    pub time: i64,
+    pub krw_price: f64,
+    pub usd_price: f64,
+    pub usd_krw: f64,
+    /// 퍼센트
+    pub premium: f64,
+    /// `z_period`개가 쌓이기 전이나 변동이 없으면 비어 있다
+    pub z_score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KimchiPremiumResult {
+    pub krw_symbol: String,
+    pub usd_symbol: String,
+    pub z_period: usize,
+    pub band: f64,
+    pub data: Vec<KimchiPremiumPoint>,
+}
+
+// Binance USDⓈ-M perpetual futures data
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingRatePoint {
+    pub time: i64,
+    /// 8시간 펀딩비 (0.0001 = 0.01%)
+    pub rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingRateResult {
+    /// 선물 심볼 (`KRW-BTC` 분석이면 `BTCUSDT`)
+    pub symbol: String,
+    pub data: Vec<FundingRatePoint>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenInterestPoint {
+    pub time: i64,
+    /// 계약 수량 (기초 자산 단위)
+    pub open_interest: f64,
+    /// USDT 환산 금액
+    pub open_interest_value: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenInterestResult {
+    pub symbol: String,
+    /// Binance 통계 집계 주기
+    pub period: String,
+    pub data: Vec<OpenInterestPoint>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LongShortRatioPoint {
+    pub time: i64,
+    pub ratio: f64,
+    /// 롱 포지션 비중 (0~1)
+    pub long_share: f64,
+    pub short_share: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LongShortRatioResult {
+    pub symbol: String,
+    pub period: String,
+    pub data: Vec<LongShortRatioPoint>,
+}
+
+// Korean investor flow (net buying by investor type, KIS)
+/// 하루 투자자별 순매수 (매수 - 매도). 수량은 주, 금액은 원
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvestorNetBuy {
+    /// 거래일 장 시작 시각
+    pub time: i64,
+    pub individual: f64,
+    pub foreign: f64,
+    pub institution: f64,
+    pub individual_amount: f64,
+    pub foreign_amount: f64,
+    pub institution_amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvestorFlowPoint {
+    pub time: i64,
+    /// 봉 구간의 순매수 수량 합계
+    pub individual: f64,
+    pub foreign: f64,
+    pub institution: f64,
+    /// 봉 구간의 순매수 금액 합계 (원)
+    pub individual_amount: f64,
+    pub foreign_amount: f64,
+    pub institution_amount: f64,
+    /// 첫 봉부터 누적한 순매수 수량
+    pub individual_cumulative: f64,
+    pub foreign_cumulative: f64,
+    pub institution_cumulative: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvestorFlowResult {
+    pub symbol: String,
+    pub streak: usize,
+    pub data: Vec<InvestorFlowPoint>,
+}
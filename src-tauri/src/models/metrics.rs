@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+use super::MarketType;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformancePoint {
+    pub time: i64,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsParams {
+    /// 자산 곡선 (지정하면 `returns`보다 우선)
+    #[serde(default)]
+    pub equity: Vec<PerformancePoint>,
+    /// 기간 수익률 시리즈 (0.01 = 1%)
+    #[serde(default)]
+    pub returns: Vec<f64>,
+    /// 기간별 포지션 비중 (-1.0 ~ 1.0, 노출도/회전율 계산용)
+    #[serde(default)]
+    pub weights: Vec<f64>,
+    pub interval: String,
+    #[serde(default)]
+    pub market: MarketType,
+    /// 연율 무위험 수익률
+    #[serde(default)]
+    pub risk_free_rate: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceMetrics {
+    pub observations: usize,
+    pub periods_per_year: f64,
+    pub total_return: f64,
+    pub cagr: f64,
+    pub annualized_volatility: f64,
+    pub sharpe: f64,
+    pub sortino: f64,
+    pub max_drawdown: f64,
+    pub max_drawdown_peak_time: Option<i64>,
+    pub max_drawdown_trough_time: Option<i64>,
+    /// 고점 회복까지 가장 오래 걸린 구간의 봉 수
+    pub max_drawdown_duration: usize,
+    pub calmar: f64,
+    pub hit_rate: f64,
+    /// 이익 기간 수익률 합 / 손실 기간 손실 합. 손실 기간이 없으면 None
+    pub profit_factor: Option<f64>,
+    pub exposure: f64,
+    /// 연율화된 비중 회전율 (1.0 = 연간 자산 1회 교체)
+    pub turnover: f64,
+}
@@ -1,30 +1,68 @@
+mod adjustment;
+mod alert;
+mod backfill;
+mod backtest;
+mod calendar;
 mod candle;
 mod fundamental;
 mod indicator;
+mod metrics;
+mod optimizer;
 mod params;
+mod provider;
 mod search;
 mod signal;
 mod strategy;
+mod stream;
 mod watchlist;
 
-pub use candle::Candle;
+pub use adjustment::{CorporateAction, CorporateActionKind, PriceAdjustment};
+pub use alert::{
+    AlertComparison, AlertCondition, AlertEvent, AlertHistoryParams, AlertIndicator, AlertRule,
+    AlertTarget, CrossDirection,
+};
+pub use backfill::{BackfillParams, BackfillReport};
+pub use backtest::{
+    BacktestConfig, BacktestParams, BacktestResponse, BacktestSummary, BacktestTrade, EquityPoint,
+    PositionSizing,
+};
+pub use calendar::{MarketCalendarParams, TradingSession};
+pub use candle::{Candle, CandleSession, TradeFlow};
 pub use fundamental::{FundamentalsParams, FundamentalsResponse};
 pub use indicator::{
-    AdxPoint, AdxResult, AtrPoint, AtrResult, AutoFibLevel, AutoFibResult, BollingerBandsPoint,
-    ChoppinessPoint, ChoppinessResult, CmfPoint, CmfResult, CvdPoint, CvdResult, DonchianPoint,
-    DonchianResult, IchimokuPoint, IchimokuResult, KeltnerPoint, KeltnerResult, MaPoint, MacdPoint,
-    MacdResult, MfiPoint, MfiResult, MovingAverageResult, ObvPoint, ObvResult, ParabolicSarPoint,
-    ParabolicSarResult, RsiPoint, SmcEvent, SmcResult, StcPoint, StcResult, StochasticPoint,
-    StochasticResult, SupertrendPoint, SupertrendResult, VwapPoint, VwapResult, WillrPoint,
-    WillrResult,
+    AdxPoint, AdxResult, AnchoredVwapSeries, AtrPoint, AtrResult, AutoFibLevel, AutoFibResult,
+    BollingerBandsPoint, ChoppinessPoint, ChoppinessResult, CmfPoint, CmfResult, ConsensusPoint,
+    ConsensusResult, CvdPoint, CvdResult, DonchianPoint, DonchianResult, FundingRatePoint,
+    FundingRateResult, IchimokuPoint, IchimokuResult, InvestorFlowPoint, InvestorFlowResult,
+    InvestorNetBuy, KeltnerPoint, KeltnerResult, KimchiPremiumPoint, KimchiPremiumResult,
+    LongShortRatioPoint, LongShortRatioResult, MaPoint, MacdPoint, MacdResult, MfiPoint, MfiResult,
+    MovingAverageResult, ObvPoint, ObvResult, OpenInterestPoint, OpenInterestResult,
+    ParabolicSarPoint, ParabolicSarResult, RsiPoint, SmcEvent, SmcResult, StcPoint, StcResult,
+    StochasticPoint, StochasticResult, SupertrendPoint, SupertrendResult, VwapBandPoint, VwapPoint,
+    VwapResult, WillrPoint, WillrResult,
+};
+pub use metrics::{MetricsParams, PerformanceMetrics, PerformancePoint};
+pub use optimizer::{
+    OptimizationObjective, ParameterRange, ParameterStability, SearchMethod, WalkForwardConfig,
+    WalkForwardParams, WalkForwardResponse, WalkForwardWindow,
 };
-pub use params::{AnalysisParams, MarketType};
+pub use params::{
+    AnalysisParams, AnchorMode, CmfObvConfig, ConsensusParams, CvdEstimate, IbsMeanReversionConfig,
+    InvestorFlowParams, KimchiPremiumParams, LiveIndicatorParams, MacdParams, MarketType,
+    SignalStrategyParams, StochRsiConfig, StochasticParams, SupertrendAdxConfig, VwapAnchor,
+    VwapBreakoutConfig, VwapReset,
+};
+pub use provider::{DataProviderInfo, ProviderCapabilities, ProviderPriority};
 pub use search::{SymbolSearchParams, SymbolSearchResult};
 pub use signal::{SignalPoint, SignalType};
 pub use strategy::{
     MultiSymbolCandlesParams, MultiSymbolCandlesResponse, PremarketSnapshot,
     PremarketSnapshotParams,
 };
+pub use stream::{
+    CandleStreamParams, CandleStreamStatus, CandleUpdate, IndicatorUpdate, IndicatorValues,
+    OrderBookLevel, OrderBookUpdate,
+};
 pub use watchlist::{WatchlistSnapshot, WatchlistSnapshotParams};
 
 use serde::Serialize;
@@ -58,9 +96,23 @@ pub struct AnalysisResponse {
     pub stc: Option<StcResult>,
     pub smc: Option<SmcResult>,
     pub anchored_vwap: Option<VwapResult>,
+    pub anchored_vwaps: Vec<AnchoredVwapSeries>,
     pub auto_fib: Option<AutoFibResult>,
+    pub consensus: Option<ConsensusResult>,
+    /// 김치 프리미엄 (`kimchiPremium` 요청 시, 암호화폐)
+    pub kimchi_premium: Option<KimchiPremiumResult>,
+    /// Binance 무기한 선물 지표 (`showFundingRate` 등 요청 시, 암호화폐). 봉 시각에 맞춰 정렬된다
+    pub funding_rate: Option<FundingRateResult>,
+    pub open_interest: Option<OpenInterestResult>,
+    pub long_short_ratio: Option<LongShortRatioResult>,
+    /// 외국인·기관·개인 순매수 (`investorFlow` 요청 시, 국내 주식 일봉 이상)
+    pub investor_flow: Option<InvestorFlowResult>,
     pub symbol: String,
     pub interval: String,
     pub data_source: String,
     pub source_interval: String,
+    /// 구간 안의 분할/배당 (주식)
+    pub corporate_actions: Vec<CorporateAction>,
+    /// 부가 데이터를 받지 못해 빠지거나 근사한 부분. 분석 자체는 성공한 상태다
+    pub warnings: Vec<String>,
 }
@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{AnalysisParams, BacktestConfig, PerformanceMetrics, PerformancePoint};
+
+/// 탐색할 파라미터 하나의 범위.
+/// `name`은 `SignalStrategyParams`의 camelCase 필드명 (전략별 설정은 `stochRsiConfig.rsiOversold` 형태).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterRange {
+    pub name: String,
+    pub min: f64,
+    pub max: f64,
+    #[serde(default = "default_step")]
+    pub step: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchMethod {
+    #[default]
+    Grid,
+    Random,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OptimizationObjective {
+    #[default]
+    Sharpe,
+    Sortino,
+    Calmar,
+    Cagr,
+    TotalReturn,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkForwardConfig {
+    /// 최적화(in-sample) 구간 봉 수. 지정하지 않으면 불러온 봉의 절반
+    #[serde(default)]
+    pub in_sample_bars: Option<usize>,
+    /// 검증(out-of-sample) 구간 봉 수. 윈도우는 이 크기만큼 이동한다.
+    /// 지정하지 않으면 in-sample의 1/5
+    #[serde(default)]
+    pub out_of_sample_bars: Option<usize>,
+    /// in-sample 시작을 처음 봉에 고정 (확장 윈도우)
+    #[serde(default)]
+    pub anchored: bool,
+    #[serde(default)]
+    pub search: SearchMethod,
+    /// 랜덤 탐색 시 평가할 조합 수
+    #[serde(default = "default_random_samples")]
+    pub random_samples: usize,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub objective: OptimizationObjective,
+}
+
+impl Default for WalkForwardConfig {
+    fn default() -> Self {
+        Self {
+            in_sample_bars: None,
+            out_of_sample_bars: None,
+            anchored: false,
+            search: SearchMethod::default(),
+            random_samples: default_random_samples(),
+            seed: 0,
+            objective: OptimizationObjective::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkForwardParams {
+    #[serde(flatten)]
+    pub analysis: AnalysisParams,
+    #[serde(default)]
+    pub backtest: BacktestConfig,
+    pub parameters: Vec<ParameterRange>,
+    #[serde(default)]
+    pub walk_forward: WalkForwardConfig,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkForwardWindow {
+    pub index: usize,
+    pub in_sample_start: i64,
+    pub in_sample_end: i64,
+    pub out_of_sample_start: i64,
+    pub out_of_sample_end: i64,
+    pub best_params: BTreeMap<String, f64>,
+    pub in_sample_score: f64,
+    pub out_of_sample_score: f64,
+    pub out_of_sample_trades: usize,
+    pub out_of_sample_metrics: PerformanceMetrics,
+}
+
+/// 한 파라미터 조합의 윈도우별 in-sample 점수 분포 (안정성 표면의 한 점)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterStability {
+    pub params: BTreeMap<String, f64>,
+    pub mean_score: f64,
+    pub score_std: f64,
+    pub min_score: f64,
+    pub max_score: f64,
+    /// 평균 - 표준편차. 윈도우마다 성과가 고른 조합일수록 높다
+    pub robust_score: f64,
+    pub windows_selected: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkForwardResponse {
+    pub symbol: String,
+    pub interval: String,
+    pub data_source: String,
+    pub objective: OptimizationObjective,
+    pub candidates_evaluated: usize,
+    pub windows: Vec<WalkForwardWindow>,
+    /// robust_score 내림차순
+    pub stability: Vec<ParameterStability>,
+    /// out-of-sample 구간만 이어 붙인 자산 곡선
+    pub out_of_sample_equity: Vec<PerformancePoint>,
+    pub out_of_sample_metrics: PerformanceMetrics,
+    /// 평균 out-of-sample 점수 / 평균 in-sample 점수
+    pub walk_forward_efficiency: f64,
+    pub recommended_params: Option<BTreeMap<String, f64>>,
+    /// 캔들 로드 중 빠지거나 근사한 부분 (백필 실패, 분할/배당 미반영 등)
+    pub warnings: Vec<String>,
+}
+
+fn default_step() -> f64 {
+    1.0
+}
+fn default_random_samples() -> usize {
+    50
+}
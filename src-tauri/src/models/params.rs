@@ -1,6 +1,10 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
+use super::PriceAdjustment;
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum MarketType {
     Crypto,
@@ -10,6 +14,31 @@ pub enum MarketType {
     KrStock,
 }
 
+/// 체결 방향 정보(테이커 매수량)가 없는 봉의 CVD 델타 추정 방식
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum CvdEstimate {
+    /// 양봉이면 +거래량, 음봉이면 −거래량
+    #[default]
+    BarDirection,
+    /// 봉 안의 종가 위치만큼 거래량을 매수/매도로 나눈다: 거래량 × (2·종가 − 고가 − 저가) / (고가 − 저가)
+    CloseLocation,
+}
+
+/// VWAP 누적을 초기화하는 주기. 경계는 각 시장의 거래일 기준 (KRX/NYSE 현지 시각, 외환은
+/// 뉴욕 17시 롤오버, 암호화폐는 UTC 자정)
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum VwapReset {
+    /// 분·시간봉은 일별, 그 외는 누적
+    #[default]
+    Auto,
+    Cumulative,
+    Daily,
+    Weekly,
+    Monthly,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MacdParams {
@@ -21,6 +50,16 @@ pub struct MacdParams {
     pub signal_period: usize,
 }
 
+impl Default for MacdParams {
+    fn default() -> Self {
+        Self {
+            fast_period: default_macd_fast(),
+            slow_period: default_macd_slow(),
+            signal_period: default_macd_signal(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StochasticParams {
@@ -32,6 +71,16 @@ pub struct StochasticParams {
     pub smooth: usize,
 }
 
+impl Default for StochasticParams {
+    fn default() -> Self {
+        Self {
+            k_period: default_stoch_k(),
+            d_period: default_stoch_d(),
+            smooth: default_stoch_smooth(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DonchianParams {
@@ -103,10 +152,51 @@ pub struct SmcParams {
     pub swing_length: usize,
 }
 
+/// 앵커 시점을 정하는 방식
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum AnchorMode {
+    /// `time`에 지정한 시각
+    #[default]
+    Time,
+    /// SMC 스윙 탐지기의 마지막 스윙 고점
+    SwingHigh,
+    /// SMC 스윙 탐지기의 마지막 스윙 저점
+    SwingLow,
+    /// 최근 실적 발표일. `time`을 비우면 조회해서 채운다 (미국 주식)
+    Earnings,
+    /// 마지막 봉이 속한 해의 첫 봉
+    YearToDate,
+    /// 불러온 구간에서 거래량이 가장 큰 봉
+    HighestVolume,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VwapAnchor {
+    #[serde(default)]
+    pub mode: AnchorMode,
+    #[serde(default)]
+    pub time: Option<i64>,
+    /// 비우면 방식별 기본 이름
+    #[serde(default)]
+    pub label: Option<String>,
+    /// ±1/2/3σ 밴드 포함 여부
+    #[serde(default)]
+    pub bands: bool,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AnchoredVwapParams {
-    pub anchor_time: i64,
+    /// 단일 앵커 (`anchoredVwap` 응답). 여러 앵커는 `anchors`를 쓴다
+    #[serde(default)]
+    pub anchor_time: Option<i64>,
+    #[serde(default)]
+    pub anchors: Vec<VwapAnchor>,
+    /// 스윙 앵커에 쓰는 SMC 스윙 길이
+    #[serde(default = "default_smc_swing_length")]
+    pub swing_length: usize,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -118,7 +208,210 @@ pub struct AutoFibParams {
     pub swing_length: usize,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+/// 원화 마켓 가격이 USDT 마켓 가격(USD/KRW 환산)보다 얼마나 높은지. 암호화폐 심볼에만 적용된다
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KimchiPremiumParams {
+    /// z-score를 계산할 봉 수
+    #[serde(default = "default_kimchi_z_period")]
+    pub z_period: usize,
+    /// |z-score|가 이 값을 넘어서는 봉에서 극단 구간 신호를 낸다
+    #[serde(default = "default_kimchi_band")]
+    pub band: f64,
+}
+
+/// KIS 투자자별 일별 순매수. 국내 주식의 일봉 이상에만 적용된다
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvestorFlowParams {
+    /// 외국인과 기관이 이만큼 연속으로 함께 순매수(순매도)하면 신호를 낸다
+    #[serde(default = "default_investor_flow_streak")]
+    pub streak: usize,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupertrendAdxConfig {
+    /// Supertrend ATR 기간
+    #[serde(default = "default_supertrend_period")]
+    pub period: usize,
+    #[serde(default = "default_supertrend_multiplier")]
+    pub multiplier: f64,
+    #[serde(default = "default_adx_period")]
+    pub adx_period: usize,
+    /// 추세 전환을 인정할 최소 ADX
+    #[serde(default = "default_supertrend_adx_threshold")]
+    pub adx_threshold: f64,
+}
+
+impl Default for SupertrendAdxConfig {
+    fn default() -> Self {
+        Self {
+            period: default_supertrend_period(),
+            multiplier: default_supertrend_multiplier(),
+            adx_period: default_adx_period(),
+            adx_threshold: default_supertrend_adx_threshold(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StochRsiConfig {
+    #[serde(default = "default_stoch_oversold")]
+    pub stoch_oversold: f64,
+    #[serde(default = "default_stoch_overbought")]
+    pub stoch_overbought: f64,
+    #[serde(default = "default_stoch_rsi_oversold")]
+    pub rsi_oversold: f64,
+    #[serde(default = "default_stoch_rsi_overbought")]
+    pub rsi_overbought: f64,
+}
+
+impl Default for StochRsiConfig {
+    fn default() -> Self {
+        Self {
+            stoch_oversold: default_stoch_oversold(),
+            stoch_overbought: default_stoch_overbought(),
+            rsi_oversold: default_stoch_rsi_oversold(),
+            rsi_overbought: default_stoch_rsi_overbought(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CmfObvConfig {
+    #[serde(default = "default_cmf_period")]
+    pub cmf_period: usize,
+    /// OBV 추세를 비교할 과거 봉 수
+    #[serde(default = "default_cmf_obv_lookback")]
+    pub obv_lookback: usize,
+}
+
+impl Default for CmfObvConfig {
+    fn default() -> Self {
+        Self {
+            cmf_period: default_cmf_period(),
+            obv_lookback: default_cmf_obv_lookback(),
+        }
+    }
+}
+
+/// 스퀴즈 판정에 쓸 Keltner 채널 (볼린저 밴드는 `bbPeriod`/`bbMultiplier`를 쓴다)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TtmSqueezeConfig {
+    #[serde(default = "default_keltner_ema_period")]
+    pub ema_period: usize,
+    #[serde(default = "default_keltner_atr_period")]
+    pub atr_period: usize,
+    #[serde(default = "default_keltner_atr_multiplier")]
+    pub atr_multiplier: f64,
+}
+
+impl Default for TtmSqueezeConfig {
+    fn default() -> Self {
+        Self {
+            ema_period: default_keltner_ema_period(),
+            atr_period: default_keltner_atr_period(),
+            atr_multiplier: default_keltner_atr_multiplier(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParabolicSarConfig {
+    /// 가속 계수 증가폭
+    #[serde(default = "default_sar_step")]
+    pub step: f64,
+    #[serde(default = "default_sar_max_step")]
+    pub max_step: f64,
+}
+
+impl Default for ParabolicSarConfig {
+    fn default() -> Self {
+        Self {
+            step: default_sar_step(),
+            max_step: default_sar_max_step(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VwapBreakoutConfig {
+    /// 평균 거래량을 구할 봉 수
+    #[serde(default = "default_vwap_volume_window")]
+    pub volume_window: usize,
+    /// 평균 거래량 대비 이 배수를 넘어야 돌파로 인정
+    #[serde(default = "default_vwap_volume_multiplier")]
+    pub volume_multiplier: f64,
+    /// 돌파 판정에 쓸 VWAP 초기화 주기. 비우면 차트 VWAP(`vwapReset`)을 그대로 쓴다
+    #[serde(default)]
+    pub reset: Option<VwapReset>,
+}
+
+impl Default for VwapBreakoutConfig {
+    fn default() -> Self {
+        Self {
+            volume_window: default_vwap_volume_window(),
+            volume_multiplier: default_vwap_volume_multiplier(),
+            reset: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IbsMeanReversionConfig {
+    #[serde(default = "default_ibs_oversold")]
+    pub ibs_oversold: f64,
+    #[serde(default = "default_ibs_overbought")]
+    pub ibs_overbought: f64,
+    #[serde(default = "default_ibs_rsi_oversold")]
+    pub rsi_oversold: f64,
+    #[serde(default = "default_ibs_rsi_overbought")]
+    pub rsi_overbought: f64,
+}
+
+impl Default for IbsMeanReversionConfig {
+    fn default() -> Self {
+        Self {
+            ibs_oversold: default_ibs_oversold(),
+            ibs_overbought: default_ibs_overbought(),
+            rsi_oversold: default_ibs_rsi_oversold(),
+            rsi_overbought: default_ibs_rsi_overbought(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsensusParams {
+    /// source별 가중치 (지정하지 않은 source는 1.0, 0이면 제외)
+    #[serde(default)]
+    pub weights: HashMap<String, f64>,
+    /// 같은 합의로 묶을 봉 수
+    #[serde(default = "default_consensus_window")]
+    pub window: usize,
+    /// 합의 신호를 낼 순점수 기준
+    #[serde(default = "default_consensus_threshold")]
+    pub threshold: f64,
+}
+
+impl Default for ConsensusParams {
+    fn default() -> Self {
+        Self {
+            weights: HashMap::new(),
+            window: default_consensus_window(),
+            threshold: default_consensus_threshold(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SignalStrategyParams {
     #[serde(default)]
@@ -147,6 +440,51 @@ pub struct SignalStrategyParams {
     pub ema_slow_period: usize,
     #[serde(default = "default_divergence_swing")]
     pub divergence_swing_length: usize,
+    #[serde(default)]
+    pub supertrend_adx_config: SupertrendAdxConfig,
+    #[serde(default)]
+    pub stoch_rsi_config: StochRsiConfig,
+    #[serde(default)]
+    pub cmf_obv_config: CmfObvConfig,
+    #[serde(default)]
+    pub ttm_squeeze_config: TtmSqueezeConfig,
+    #[serde(default)]
+    pub vwap_breakout_config: VwapBreakoutConfig,
+    #[serde(default)]
+    pub parabolic_sar_config: ParabolicSarConfig,
+    #[serde(default)]
+    pub ibs_mean_reversion_config: IbsMeanReversionConfig,
+    /// 지정하면 활성 전략 신호의 가중 합의로 `consensus` 신호를 추가한다
+    #[serde(default)]
+    pub consensus: Option<ConsensusParams>,
+}
+
+impl Default for SignalStrategyParams {
+    fn default() -> Self {
+        Self {
+            supertrend_adx: false,
+            ema_crossover: false,
+            stoch_rsi_combined: false,
+            cmf_obv: false,
+            ttm_squeeze: false,
+            vwap_breakout: false,
+            parabolic_sar: false,
+            macd_hist_reversal: false,
+            ibs_mean_reversion: false,
+            rsi_divergence: false,
+            ema_fast_period: default_ema_fast(),
+            ema_slow_period: default_ema_slow(),
+            divergence_swing_length: default_divergence_swing(),
+            supertrend_adx_config: SupertrendAdxConfig::default(),
+            stoch_rsi_config: StochRsiConfig::default(),
+            cmf_obv_config: CmfObvConfig::default(),
+            ttm_squeeze_config: TtmSqueezeConfig::default(),
+            vwap_breakout_config: VwapBreakoutConfig::default(),
+            parabolic_sar_config: ParabolicSarConfig::default(),
+            ibs_mean_reversion_config: IbsMeanReversionConfig::default(),
+            consensus: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -162,6 +500,15 @@ pub struct AnalysisParams {
     pub rsi_period: usize,
     #[serde(default)]
     pub market: MarketType,
+    /// 지정하면 이 시각(초)부터 현재까지의 저장된 이력을 백필 후 분석한다
+    #[serde(default)]
+    pub start_time: Option<i64>,
+    /// 주식 가격 보정 방식 (암호화폐·외환에는 영향 없음)
+    #[serde(default)]
+    pub adjustment: PriceAdjustment,
+    /// 지표·시그널을 정규장 봉으로만 계산한다. 프리/애프터마켓 봉은 차트용으로 그대로 돌려준다
+    #[serde(default)]
+    pub regular_hours_only: bool,
     #[serde(default)]
     pub sma_periods: Vec<usize>,
     #[serde(default)]
@@ -176,6 +523,16 @@ pub struct AnalysisParams {
     pub show_obv: bool,
     #[serde(default)]
     pub show_cvd: bool,
+    /// 테이커 매수량이 없는 봉의 CVD 추정 방식
+    #[serde(default)]
+    pub cvd_estimate: CvdEstimate,
+    /// Binance 무기한 선물의 펀딩비·미결제약정·상위 트레이더 롱/숏 비율 (암호화폐)
+    #[serde(default)]
+    pub show_funding_rate: bool,
+    #[serde(default)]
+    pub show_open_interest: bool,
+    #[serde(default)]
+    pub show_long_short_ratio: bool,
     #[serde(default)]
     pub donchian: Option<DonchianParams>,
     #[serde(default)]
@@ -195,10 +552,103 @@ pub struct AnalysisParams {
     #[serde(default)]
     pub smc: Option<SmcParams>,
     #[serde(default)]
+    pub vwap_reset: VwapReset,
+    #[serde(default)]
     pub anchored_vwap: Option<AnchoredVwapParams>,
     #[serde(default)]
     pub auto_fib: Option<AutoFibParams>,
     #[serde(default)]
+    pub kimchi_premium: Option<KimchiPremiumParams>,
+    #[serde(default)]
+    pub investor_flow: Option<InvestorFlowParams>,
+    #[serde(default)]
+    pub signal_strategies: SignalStrategyParams,
+}
+
+impl AnalysisParams {
+    /// 모든 선택 지표를 끈 기본 설정 (요청 본문 없이 분석을 돌릴 때)
+    pub fn new(symbol: String, interval: String, market: MarketType) -> Self {
+        Self {
+            symbol,
+            interval,
+            bb_period: default_bb_period(),
+            bb_multiplier: default_bb_multiplier(),
+            rsi_period: default_rsi_period(),
+            market,
+            start_time: None,
+            adjustment: PriceAdjustment::default(),
+            regular_hours_only: false,
+            sma_periods: Vec::new(),
+            ema_periods: Vec::new(),
+            hma_periods: Vec::new(),
+            macd: None,
+            stochastic: None,
+            show_obv: false,
+            show_cvd: false,
+            cvd_estimate: CvdEstimate::default(),
+            show_funding_rate: false,
+            show_open_interest: false,
+            show_long_short_ratio: false,
+            donchian: None,
+            keltner: None,
+            mfi: None,
+            cmf: None,
+            choppiness: None,
+            williams_r: None,
+            adx: None,
+            stc: None,
+            smc: None,
+            vwap_reset: VwapReset::default(),
+            anchored_vwap: None,
+            auto_fib: None,
+            kimchi_premium: None,
+            investor_flow: None,
+            signal_strategies: SignalStrategyParams::default(),
+        }
+    }
+}
+
+/// 실시간 스트림에서 갱신할 지표 설정. 필드 의미는 `AnalysisParams`와 같고,
+/// VWAP/ATR/Supertrend/Parabolic SAR는 `analyze`처럼 항상 계산한다.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveIndicatorParams {
+    #[serde(default = "default_bb_period")]
+    pub bb_period: usize,
+    #[serde(default = "default_bb_multiplier")]
+    pub bb_multiplier: f64,
+    #[serde(default = "default_rsi_period")]
+    pub rsi_period: usize,
+    #[serde(default)]
+    pub sma_periods: Vec<usize>,
+    #[serde(default)]
+    pub ema_periods: Vec<usize>,
+    #[serde(default)]
+    pub macd: Option<MacdParams>,
+    #[serde(default)]
+    pub stochastic: Option<StochasticParams>,
+    #[serde(default)]
+    pub show_obv: bool,
+    #[serde(default)]
+    pub show_cvd: bool,
+    #[serde(default)]
+    pub cvd_estimate: CvdEstimate,
+    #[serde(default)]
+    pub donchian: Option<DonchianParams>,
+    #[serde(default)]
+    pub keltner: Option<KeltnerParams>,
+    #[serde(default)]
+    pub mfi: Option<MfiParams>,
+    #[serde(default)]
+    pub cmf: Option<CmfParams>,
+    #[serde(default)]
+    pub williams_r: Option<WillrParams>,
+    #[serde(default)]
+    pub adx: Option<AdxParams>,
+    #[serde(default)]
+    pub vwap_reset: VwapReset,
+    /// Supertrend/Parabolic SAR 설정만 쓴다 (`analyze`처럼 전략 설정을 따른다)
+    #[serde(default)]
     pub signal_strategies: SignalStrategyParams,
 }
 
@@ -274,6 +724,15 @@ fn default_auto_fib_lookback() -> usize {
 fn default_auto_fib_swing_length() -> usize {
     5
 }
+fn default_kimchi_z_period() -> usize {
+    60
+}
+fn default_kimchi_band() -> f64 {
+    2.0
+}
+fn default_investor_flow_streak() -> usize {
+    3
+}
 fn default_ema_fast() -> usize {
     9
 }
@@ -283,3 +742,57 @@ fn default_ema_slow() -> usize {
 fn default_divergence_swing() -> usize {
     5
 }
+fn default_supertrend_period() -> usize {
+    10
+}
+fn default_supertrend_multiplier() -> f64 {
+    3.0
+}
+fn default_supertrend_adx_threshold() -> f64 {
+    25.0
+}
+fn default_stoch_oversold() -> f64 {
+    20.0
+}
+fn default_stoch_overbought() -> f64 {
+    80.0
+}
+fn default_stoch_rsi_oversold() -> f64 {
+    30.0
+}
+fn default_stoch_rsi_overbought() -> f64 {
+    70.0
+}
+fn default_cmf_obv_lookback() -> usize {
+    10
+}
+fn default_sar_step() -> f64 {
+    0.02
+}
+fn default_sar_max_step() -> f64 {
+    0.2
+}
+fn default_vwap_volume_window() -> usize {
+    20
+}
+fn default_vwap_volume_multiplier() -> f64 {
+    1.5
+}
+fn default_ibs_oversold() -> f64 {
+    0.2
+}
+fn default_ibs_overbought() -> f64 {
+    0.8
+}
+fn default_ibs_rsi_oversold() -> f64 {
+    35.0
+}
+fn default_ibs_rsi_overbought() -> f64 {
+    65.0
+}
+fn default_consensus_window() -> usize {
+    3
+}
+fn default_consensus_threshold() -> f64 {
+    2.0
+}
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+use super::MarketType;
+
+/// 시세 공급자가 지원하는 범위와 제약
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderCapabilities {
+    pub markets: Vec<MarketType>,
+    /// 그대로 받아올 수 있는 봉 주기 (그 외는 리샘플링)
+    pub intervals: Vec<String>,
+    /// 최근 구간 한 번 조회로 받을 수 있는 최대 봉 수
+    pub max_bars: u32,
+    /// 과거 구간(start/end) 페이지 조회 지원 여부 (일부 심볼·주기만 지원할 수 있다)
+    pub supports_range: bool,
+    /// 공개된 분당 요청 한도 (비공개면 None)
+    pub requests_per_minute: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataProviderInfo {
+    /// 저장소의 소스 키로도 쓰이는 고유 id
+    pub id: String,
+    pub name: String,
+    pub capabilities: ProviderCapabilities,
+    /// 자격 증명 등이 갖춰져 지금 조회에 쓰이는지
+    pub available: bool,
+}
+
+/// 시장별 공급자 조회 순서. 목록에 없는 공급자는 기본 순서대로 뒤에 붙는다
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderPriority {
+    pub market: MarketType,
+    pub providers: Vec<String>,
+}
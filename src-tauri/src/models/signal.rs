@@ -1,6 +1,6 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum SignalType {
     SupertrendBuy,
@@ -23,6 +23,12 @@ pub enum SignalType {
     IbsMeanRevSell,
     RsiDivergenceBuy,
     RsiDivergenceSell,
+    ConsensusBuy,
+    ConsensusSell,
+    KimchiPremiumBuy,
+    KimchiPremiumSell,
+    InvestorFlowBuy,
+    InvestorFlowSell,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -34,3 +40,25 @@ pub struct SignalPoint {
     pub rsi: f64,
     pub source: String,
 }
+
+impl SignalType {
+    /// 매수 방향 신호 여부 (그 외는 매도 방향)
+    pub fn is_buy(&self) -> bool {
+        matches!(
+            self,
+            Self::SupertrendBuy
+                | Self::EmaCrossoverBuy
+                | Self::StochRsiBuy
+                | Self::CmfObvBuy
+                | Self::TtmSqueezeBuy
+                | Self::VwapBreakoutBuy
+                | Self::ParabolicSarBuy
+                | Self::MacdHistReversalBuy
+                | Self::IbsMeanRevBuy
+                | Self::RsiDivergenceBuy
+                | Self::ConsensusBuy
+                | Self::KimchiPremiumBuy
+                | Self::InvestorFlowBuy
+        )
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use super::Candle;
+use super::{Candle, PriceAdjustment};
 
 fn default_interval() -> String {
     "1mo".to_string()
@@ -19,6 +19,8 @@ pub struct MultiSymbolCandlesParams {
     pub interval: String,
     #[serde(default = "default_limit")]
     pub limit: u32,
+    #[serde(default)]
+    pub adjustment: PriceAdjustment,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -26,6 +28,8 @@ pub struct MultiSymbolCandlesParams {
 pub struct MultiSymbolCandlesResponse {
     pub data: HashMap<String, Vec<Candle>>,
     pub errors: HashMap<String, String>,
+    /// 종목별로 분할/배당 이력을 받지 못해 보정이 빠졌을 수 있는 부분
+    pub warnings: HashMap<String, Vec<String>>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
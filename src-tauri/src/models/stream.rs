@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    AdxPoint, AtrPoint, BollingerBandsPoint, Candle, CmfPoint, CvdPoint, DonchianPoint,
+    KeltnerPoint, LiveIndicatorParams, MacdPoint, MarketType, MfiPoint, MovingAverageResult,
+    ObvPoint, ParabolicSarPoint, RsiPoint, StochasticPoint, SupertrendPoint, VwapBandPoint,
+    VwapPoint, WillrPoint,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CandleStreamParams {
+    pub symbol: String,
+    pub interval: String,
+    /// 암호화폐는 Binance, 국내 주식은 KIS 실시간 체결로 봉을 만든다
+    #[serde(default = "default_stream_market")]
+    pub market: MarketType,
+    /// 프론트엔드가 이미 가진 마지막 봉 시각. 연결 직후 이후 구간을 REST로 보충한다
+    #[serde(default)]
+    pub since: Option<i64>,
+    /// 지정하면 저장된 봉으로 지표 상태를 채운 뒤 `indicator-update` 이벤트로 갱신값을 보낸다
+    #[serde(default)]
+    pub indicators: Option<LiveIndicatorParams>,
+}
+
+/// `candle-update` 이벤트 payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CandleUpdate {
+    pub symbol: String,
+    pub interval: String,
+    pub candle: Candle,
+    /// 봉 마감 여부 (false면 진행 중인 봉)
+    pub closed: bool,
+}
+
+/// `candle-stream-status` 이벤트 payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CandleStreamStatus {
+    pub symbol: String,
+    pub interval: String,
+    pub state: String, // "connecting" | "connected" | "reconnecting" | "stopped"
+    pub attempt: u32,
+    pub message: Option<String>,
+}
+
+/// 호가 한 단계
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderBookLevel {
+    pub price: f64,
+    pub quantity: f64,
+}
+
+/// `order-book-update` 이벤트 payload (국내 주식 10단계 호가)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderBookUpdate {
+    pub symbol: String,
+    pub time: i64,
+    /// 매도 호가, 최우선 호가부터
+    pub asks: Vec<OrderBookLevel>,
+    /// 매수 호가, 최우선 호가부터
+    pub bids: Vec<OrderBookLevel>,
+    pub total_ask_quantity: f64,
+    pub total_bid_quantity: f64,
+}
+
+/// 봉 하나 시점의 지표 값. 워밍업 중이거나 설정하지 않은 지표는 None
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndicatorValues {
+    pub time: i64,
+    pub bollinger_bands: Option<BollingerBandsPoint>,
+    pub rsi: Option<RsiPoint>,
+    /// 기간별 최신 값 (data는 0개 또는 1개)
+    pub sma: Vec<MovingAverageResult>,
+    pub ema: Vec<MovingAverageResult>,
+    pub macd: Option<MacdPoint>,
+    pub stochastic: Option<StochasticPoint>,
+    pub obv: Option<ObvPoint>,
+    pub cvd: Option<CvdPoint>,
+    pub vwap: Option<VwapPoint>,
+    pub vwap_bands: Option<VwapBandPoint>,
+    pub atr: Option<AtrPoint>,
+    pub supertrend: Option<SupertrendPoint>,
+    pub parabolic_sar: Option<ParabolicSarPoint>,
+    pub donchian: Option<DonchianPoint>,
+    pub keltner: Option<KeltnerPoint>,
+    pub mfi: Option<MfiPoint>,
+    pub cmf: Option<CmfPoint>,
+    pub williams_r: Option<WillrPoint>,
+    pub adx: Option<AdxPoint>,
+}
+
+/// `indicator-update` 이벤트 payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndicatorUpdate {
+    pub symbol: String,
+    pub interval: String,
+    /// false면 진행 중인 봉 기준 값 (상태에는 반영되지 않음)
+    pub closed: bool,
+    #[serde(flatten)]
+    pub values: IndicatorValues,
+}
+
+fn default_stream_market() -> MarketType {
+    MarketType::Crypto
+}
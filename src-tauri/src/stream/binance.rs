@@ -0,0 +1,626 @@
+use std::time::{Duration, Instant};
+
+use futures_util::StreamExt;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_tungstenite::tungstenite::Message;
+
+use super::StreamEvent;
+use crate::api_client::BinanceClient;
+use crate::models::{Candle, CandleStreamStatus, CandleUpdate, TradeFlow};
+
+pub const DEFAULT_WS_URL: &str = "wss://stream.binance.com:9443";
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// 이 시간 동안 아무 메시지가 없으면 연결이 죽은 것으로 보고 재연결
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+/// 체결(aggTrade)로 진행 중인 봉을 갱신할 때 이벤트를 보내는 최소 간격
+const TRADE_EMIT_INTERVAL: Duration = Duration::from_millis(250);
+/// 재연결 시 REST로 보충할 최대 봉 수
+const RESYNC_LIMIT: u32 = 1_000;
+
+/// 구독할 심볼/주기와 접속 주소
+pub struct KlineSubscription {
+    pub ws_url: String,
+    pub symbol: String,
+    pub interval: String,
+    /// 봉 길이 (초). REST로 이어 받은 봉의 마감 시각 계산에 쓴다
+    pub step: i64,
+    /// 마지막으로 확보한 봉 시각. (재)연결 직후 이후 구간을 REST로 보충한다
+    pub resync_from: Option<i64>,
+}
+
+/// 진행 중인 봉과 마지막 마감 봉 상태
+struct BarState {
+    current: Option<(Candle, i64)>, // (봉, 마감 시각 ms)
+    last_closed: Option<i64>,
+    last_emit: Instant,
+}
+
+/// 재연결 대기 시간. 실패할 때마다 두 배로 늘리고, 데이터를 받은 연결 뒤에는 처음으로 되돌린다
+struct Backoff {
+    initial: Duration,
+    max: Duration,
+    next: Duration,
+}
+
+impl Backoff {
+    fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            next: initial,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.next = self.initial;
+    }
+
+    fn next_delay(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = (self.next * 2).min(self.max);
+        delay
+    }
+}
+
+enum Disconnect {
+    /// 이벤트 수신 측이 사라짐 (구독 종료)
+    Closed,
+    /// 연결 끊김/오류 → 재연결
+    Lost(String),
+}
+
+impl KlineSubscription {
+    fn stream_url(&self) -> String {
+        let symbol = self.symbol.to_lowercase();
+        format!(
+            "{}/stream?streams={}@kline_{}/{}@aggTrade",
+            self.ws_url.trim_end_matches('/'),
+            symbol,
+            self.interval,
+            symbol
+        )
+    }
+
+    fn status(&self, state: &str, attempt: u32, message: Option<String>) -> StreamEvent {
+        StreamEvent::Status(CandleStreamStatus {
+            symbol: self.symbol.clone(),
+            interval: self.interval.clone(),
+            state: state.to_string(),
+            attempt,
+            message,
+        })
+    }
+
+    fn candle(&self, candle: Candle, closed: bool) -> StreamEvent {
+        StreamEvent::Candle(CandleUpdate {
+            symbol: self.symbol.clone(),
+            interval: self.interval.clone(),
+            candle,
+            closed,
+        })
+    }
+}
+
+/// kline/aggTrade 결합 스트림을 구독해 봉 갱신을 `events`로 보낸다.
+///
+/// - 연결이 끊기면 지수 백오프(1초 → 최대 60초)로 재연결한다.
+/// - (재)연결 직후 마지막 마감 봉 이후 구간을 REST로 받아 빈 구간을 메운다.
+/// - `events` 수신 측이 닫히면 종료한다.
+pub async fn run(
+    subscription: KlineSubscription,
+    rest: BinanceClient,
+    events: UnboundedSender<StreamEvent>,
+) {
+    let backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+    run_with_backoff(subscription, rest, events, backoff).await
+}
+
+async fn run_with_backoff(
+    subscription: KlineSubscription,
+    rest: BinanceClient,
+    events: UnboundedSender<StreamEvent>,
+    mut backoff: Backoff,
+) {
+    let url = subscription.stream_url();
+    let mut state = BarState {
+        current: None,
+        last_closed: subscription.resync_from,
+        last_emit: Instant::now(),
+    };
+    let mut attempt = 0u32;
+
+    loop {
+        if events
+            .send(subscription.status("connecting", attempt, None))
+            .is_err()
+        {
+            return;
+        }
+
+        let reason = match tokio_tungstenite::connect_async(url.as_str()).await {
+            Ok((socket, _)) => {
+                let _ = events.send(subscription.status("connected", attempt, None));
+                // 연결이 성립한 뒤 보충해야 보충과 실시간 사이에 빈틈이 생기지 않는다
+                resync(&subscription, &rest, &mut state, &events, attempt).await;
+
+                let (received, disconnect) = pump(socket, &subscription, &mut state, &events).await;
+                if received {
+                    backoff.reset();
+                    attempt = 0;
+                }
+                match disconnect {
+                    Disconnect::Closed => return,
+                    Disconnect::Lost(reason) => reason,
+                }
+            }
+            Err(error) => format!("연결 실패: {}", error),
+        };
+
+        attempt += 1;
+        if events
+            .send(subscription.status("reconnecting", attempt, Some(reason)))
+            .is_err()
+        {
+            return;
+        }
+        tokio::time::sleep(backoff.next_delay()).await;
+    }
+}
+
+/// 마지막 마감 봉 이후를 REST로 받아 보낸다. 마지막 봉은 진행 중인 봉으로 이어 받아
+/// 다음 kline 이벤트 전에 오는 체결도 반영한다.
+async fn resync(
+    subscription: &KlineSubscription,
+    rest: &BinanceClient,
+    state: &mut BarState,
+    events: &UnboundedSender<StreamEvent>,
+    attempt: u32,
+) {
+    let Some(since) = state.last_closed else {
+        return;
+    };
+
+    match rest
+        .fetch_klines_since(
+            &subscription.symbol,
+            &subscription.interval,
+            since,
+            RESYNC_LIMIT,
+        )
+        .await
+    {
+        Ok(mut candles) => {
+            let last = candles.pop();
+            for candle in candles {
+                state.last_closed = Some(candle.time);
+                let _ = events.send(subscription.candle(candle, true));
+            }
+            if let Some(last) = last {
+                // 끊기기 전의 봉보다 REST 값이 최신이다
+                if state
+                    .current
+                    .as_ref()
+                    .is_none_or(|(c, _)| c.time <= last.time)
+                {
+                    let close_time = (last.time + subscription.step) * 1000 - 1;
+                    let _ = events.send(subscription.candle(last.clone(), false));
+                    state.current = Some((last, close_time));
+                }
+            }
+        }
+        Err(error) => {
+            let _ = events.send(subscription.status(
+                "connected",
+                attempt,
+                Some(format!("REST 보충 실패: {}", error)),
+            ));
+        }
+    }
+}
+
+async fn pump<S>(
+    mut socket: S,
+    subscription: &KlineSubscription,
+    state: &mut BarState,
+    events: &UnboundedSender<StreamEvent>,
+) -> (bool, Disconnect)
+where
+    S: futures_util::Stream<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+    let mut received = false;
+
+    loop {
+        let message = match tokio::time::timeout(IDLE_TIMEOUT, socket.next()).await {
+            Err(_) => return (received, Disconnect::Lost("수신 시간 초과".to_string())),
+            Ok(None) => {
+                return (
+                    received,
+                    Disconnect::Lost("서버가 연결을 닫았습니다".to_string()),
+                )
+            }
+            Ok(Some(Err(error))) => return (received, Disconnect::Lost(error.to_string())),
+            Ok(Some(Ok(message))) => message,
+        };
+
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Close(frame) => {
+                let reason = frame
+                    .map(|f| f.reason.to_string())
+                    .filter(|r| !r.is_empty())
+                    .unwrap_or_else(|| "서버가 연결을 닫았습니다".to_string());
+                return (received, Disconnect::Lost(reason));
+            }
+            // Ping에 대한 Pong은 tungstenite가 다음 읽기 때 자동으로 보낸다
+            _ => continue,
+        };
+        received = true;
+
+        let Ok(envelope) = serde_json::from_str::<serde_json::Value>(&text) else {
+            continue;
+        };
+        // 결합 스트림은 {"stream": ..., "data": {...}} 형태
+        let data = envelope.get("data").unwrap_or(&envelope);
+
+        let update = match data["e"].as_str() {
+            Some("kline") => apply_kline(data, state),
+            Some("aggTrade") => apply_trade(data, state),
+            _ => None,
+        };
+
+        if let Some((candle, closed)) = update {
+            state.last_emit = Instant::now();
+            if events.send(subscription.candle(candle, closed)).is_err() {
+                return (received, Disconnect::Closed);
+            }
+        }
+    }
+}
+
+fn parse_str_f64(value: &serde_json::Value) -> Option<f64> {
+    value.as_str()?.parse::<f64>().ok()
+}
+
+/// kline 이벤트는 진행 중인 봉의 확정 값이므로 그대로 반영한다.
+fn apply_kline(data: &serde_json::Value, state: &mut BarState) -> Option<(Candle, bool)> {
+    let k = &data["k"];
+    let candle = Candle {
+        time: k["t"].as_i64()? / 1000,
+        open: parse_str_f64(&k["o"])?,
+        high: parse_str_f64(&k["h"])?,
+        low: parse_str_f64(&k["l"])?,
+        close: parse_str_f64(&k["c"])?,
+        volume: parse_str_f64(&k["v"])?,
+        session: None,
+        flow: Some(TradeFlow {
+            quote_volume: parse_str_f64(&k["q"])?,
+            trade_count: k["n"].as_u64()?,
+            taker_buy_volume: parse_str_f64(&k["V"])?,
+        }),
+    };
+    let closed = k["x"].as_bool().unwrap_or(false);
+
+    if closed {
+        state.last_closed = Some(candle.time);
+        state.current = None;
+    } else {
+        state.current = Some((candle.clone(), k["T"].as_i64().unwrap_or(i64::MAX)));
+    }
+    Some((candle, closed))
+}
+
+/// 체결로 진행 중인 봉의 종가/고가/저가/거래량과 체결 집계를 앞당겨 갱신한다
+/// (다음 kline 이벤트가 덮어쓴다).
+fn apply_trade(data: &serde_json::Value, state: &mut BarState) -> Option<(Candle, bool)> {
+    let price = parse_str_f64(&data["p"])?;
+    let quantity = parse_str_f64(&data["q"])?;
+    let trade_time = data["T"].as_i64()?;
+
+    let (candle, close_time) = state.current.as_mut()?;
+    if trade_time < candle.time * 1000 || trade_time > *close_time {
+        return None;
+    }
+    candle.close = price;
+    candle.high = candle.high.max(price);
+    candle.low = candle.low.min(price);
+    candle.volume += quantity;
+    if let Some(flow) = candle.flow.as_mut() {
+        flow.quote_volume += price * quantity;
+        // 집계 체결 하나에 묶인 개별 체결 수 (첫/마지막 체결 id)
+        flow.trade_count += match (data["f"].as_u64(), data["l"].as_u64()) {
+            (Some(first), Some(last)) if last >= first => last - first + 1,
+            _ => 1,
+        };
+        // 매수자가 메이커가 아니면 테이커 매수
+        if data["m"].as_bool() == Some(false) {
+            flow.taker_buy_volume += quantity;
+        }
+    }
+
+    if state.last_emit.elapsed() < TRADE_EMIT_INTERVAL {
+        return None;
+    }
+    Some((candle.clone(), false))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use futures_util::SinkExt;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+    use tokio_tungstenite::tungstenite::Message;
+
+    use super::{run_with_backoff, Backoff, KlineSubscription};
+    use crate::api_client::BinanceClient;
+    use crate::stream::StreamEvent;
+
+    const STEP: i64 = 60;
+    const T0: i64 = 1_700_000_040;
+    const T1: i64 = T0 + STEP;
+    const BACKOFF: Duration = Duration::from_millis(50);
+
+    fn rest_kline(time: i64, close: &str) -> serde_json::Value {
+        serde_json::json!([
+            time * 1000,
+            "100",
+            "120",
+            "90",
+            close,
+            "10",
+            (time + STEP) * 1000 - 1,
+            "1000",
+            5,
+            "4",
+            "400",
+            "0"
+        ])
+    }
+
+    fn kline_frame(time: i64, close: &str) -> Message {
+        let data = serde_json::json!({
+            "e": "kline",
+            "k": {
+                "t": time * 1000, "T": (time + STEP) * 1000 - 1,
+                "o": "100", "h": "120", "l": "90", "c": close, "v": "12",
+                "q": "1200", "n": 6, "V": "5", "x": false
+            }
+        });
+        Message::Text(serde_json::json!({ "stream": "btcusdt@kline_1m", "data": data }).to_string())
+    }
+
+    fn trade_frame(time_ms: i64, price: &str) -> Message {
+        let data = serde_json::json!({
+            "e": "aggTrade", "p": price, "q": "2", "T": time_ms, "f": 1, "l": 3, "m": false
+        });
+        Message::Text(serde_json::json!({ "stream": "btcusdt@aggTrade", "data": data }).to_string())
+    }
+
+    /// Serves `/api/v3/klines`: the first call before any reconnect, later calls after it.
+    async fn rest_server(hits: Arc<AtomicUsize>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 4096];
+                let _ = socket.read(&mut buf).await.unwrap();
+                let body = if hits.fetch_add(1, Ordering::SeqCst) == 0 {
+                    serde_json::json!([rest_kline(T0 - STEP, "100"), rest_kline(T0, "101")])
+                } else {
+                    serde_json::json!([rest_kline(T0, "106"), rest_kline(T1, "107")])
+                }
+                .to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    /// 1st connection: trade before any kline, then a kline, then drop.
+    /// 2nd connection: dropped before sending anything (backoff grows).
+    /// 3rd connection: trade in the bar resynced over REST, then stay open.
+    /// Each accept and drop is stamped on `timeline`.
+    async fn ws_server(timeline: Arc<Mutex<Vec<Instant>>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for connection in 0.. {
+                let (socket, _) = listener.accept().await.unwrap();
+                timeline.lock().unwrap().push(Instant::now());
+                let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+                match connection {
+                    0 => {
+                        // Past TRADE_EMIT_INTERVAL so the trade is emitted, not just applied
+                        tokio::time::sleep(Duration::from_millis(300)).await;
+                        ws.send(trade_frame(T0 * 1000 + 1_000, "105"))
+                            .await
+                            .unwrap();
+                        ws.send(kline_frame(T0, "104")).await.unwrap();
+                        timeline.lock().unwrap().push(Instant::now());
+                        drop(ws);
+                    }
+                    1 => {
+                        timeline.lock().unwrap().push(Instant::now());
+                        drop(ws);
+                    }
+                    _ => {
+                        tokio::time::sleep(Duration::from_millis(300)).await;
+                        ws.send(trade_frame(T1 * 1000 + 2_000, "110"))
+                            .await
+                            .unwrap();
+                        tokio::time::sleep(Duration::from_secs(30)).await;
+                    }
+                }
+            }
+        });
+        format!("ws://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn reconnects_with_backoff_and_resyncs_over_rest() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let timeline = Arc::new(Mutex::new(Vec::new()));
+        let rest = BinanceClient::with_base_url(rest_server(hits.clone()).await);
+        let subscription = KlineSubscription {
+            ws_url: ws_server(timeline.clone()).await,
+            symbol: "BTCUSDT".to_string(),
+            interval: "1m".to_string(),
+            step: STEP,
+            resync_from: Some(T0 - 2 * STEP),
+        };
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tokio::spawn(run_with_backoff(
+            subscription,
+            rest,
+            tx,
+            Backoff::new(BACKOFF, Duration::from_secs(1)),
+        ));
+
+        let mut statuses: Vec<(String, u32)> = Vec::new();
+        let mut candles = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(15);
+        while Instant::now() < deadline {
+            let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+                .await
+                .expect("stream stalled")
+                .expect("stream ended");
+            match event {
+                StreamEvent::Status(status) => statuses.push((status.state, status.attempt)),
+                StreamEvent::Candle(update) => {
+                    let done = update.candle.time == T1 && update.candle.close == 110.0;
+                    candles.push((update.candle, update.closed));
+                    if done {
+                        break;
+                    }
+                }
+                StreamEvent::OrderBook(_) => {}
+            }
+        }
+
+        let states: Vec<(&str, u32)> = statuses.iter().map(|(s, a)| (s.as_str(), *a)).collect();
+        assert_eq!(
+            states,
+            [
+                ("connecting", 0),
+                ("connected", 0),
+                ("reconnecting", 1),
+                ("connecting", 1),
+                ("connected", 1),
+                ("reconnecting", 2),
+                ("connecting", 2),
+                ("connected", 2),
+            ]
+        );
+        // Stamped on the server (drop → next accept) so a slow test task can't shorten the
+        // measured gap; sleeps only guarantee a lower bound
+        let timeline = timeline.lock().unwrap().clone();
+        let first_wait = timeline[2] - timeline[1];
+        let second_wait = timeline[4] - timeline[3];
+        assert!(first_wait >= BACKOFF);
+        assert!(second_wait >= BACKOFF * 2);
+
+        // Every (re)connect resyncs over REST
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+        assert!(candles
+            .iter()
+            .any(|(c, closed)| c.time == T0 - STEP && *closed));
+        // A trade arriving before the first kline extends the bar seeded from REST
+        let seeded = candles
+            .iter()
+            .find(|(c, _)| c.time == T0 && c.close == 105.0)
+            .expect("trade before kline was dropped");
+        assert!(!seeded.1);
+        assert_eq!(seeded.0.high, 120.0);
+        assert_eq!(seeded.0.volume, 12.0);
+        assert_eq!(seeded.0.flow.as_ref().map(|f| f.trade_count), Some(8));
+        // After reconnecting the previous bar is closed by REST and the trade lands in the new one
+        assert!(candles
+            .iter()
+            .any(|(c, closed)| c.time == T0 && *closed && c.close == 106.0));
+        let (last, closed) = candles.last().unwrap();
+        assert_eq!((last.time, last.close, *closed), (T1, 110.0, false));
+    }
+
+    #[tokio::test]
+    async fn empty_resync_is_not_an_error() {
+        // REST has nothing after the last closed bar
+        let rest_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let rest_addr = rest_listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = rest_listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let _ = socket.read(&mut buf).await.unwrap();
+            let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\nConnection: close\r\n\r\n[]";
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+        let ws_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws_addr = ws_listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = ws_listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            ws.send(kline_frame(T1, "104")).await.unwrap();
+            tokio::time::sleep(Duration::from_secs(30)).await;
+        });
+
+        let subscription = KlineSubscription {
+            ws_url: format!("ws://{}", ws_addr),
+            symbol: "BTCUSDT".to_string(),
+            interval: "1m".to_string(),
+            step: STEP,
+            resync_from: Some(T0),
+        };
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tokio::spawn(run_with_backoff(
+            subscription,
+            BinanceClient::with_base_url(format!("http://{}", rest_addr)),
+            tx,
+            Backoff::new(BACKOFF, Duration::from_secs(1)),
+        ));
+
+        let mut statuses = Vec::new();
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+                .await
+                .expect("stream stalled")
+                .expect("stream ended");
+            match event {
+                StreamEvent::Status(status) => statuses.push((status.state, status.message)),
+                StreamEvent::Candle(update) => {
+                    assert_eq!((update.candle.time, update.closed), (T1, false));
+                    break;
+                }
+                StreamEvent::OrderBook(_) => {}
+            }
+        }
+        assert_eq!(
+            statuses,
+            [
+                ("connecting".to_string(), None),
+                ("connected".to_string(), None)
+            ]
+        );
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap_and_resets() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(5));
+        let delays: Vec<u64> = (0..5).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 5, 5]);
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,362 @@
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::StreamEvent;
+use crate::api_client::{KisClient, KisFeed, KisTrade, RealtimeEvent};
+use crate::models::{Candle, CandleStreamStatus, CandleUpdate, OrderBookUpdate, TradeFlow};
+
+/// 체결로 진행 중인 봉을 갱신할 때 이벤트를 보내는 최소 간격
+const TRADE_EMIT_INTERVAL: Duration = Duration::from_millis(250);
+/// 봉 구간이 끝난 뒤 늦게 도착하는 체결을 기다리는 시간
+const CLOSE_GRACE: i64 = 2;
+/// 체결이 끊긴 봉을 시간으로 마감하는지 확인하는 주기
+const CLOSE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// (재)연결 시 REST로 보충할 최대 봉 수
+const RESYNC_LIMIT: u32 = 1_000;
+/// 체결로 모을 수 있는 가장 긴 봉 (초). 봉 구간은 에포크 기준으로 자르는데, KRX 장 시작
+/// (09:00 KST)이 UTC 자정이라 일봉까지는 REST 봉 시각과 맞지만 주·월봉은 달력 구간이라 어긋난다
+pub const MAX_STEP: i64 = 86_400;
+
+/// 구독할 국내 종목/주기
+pub struct KisSubscription {
+    /// `005930.KS` 형식
+    pub symbol: String,
+    pub interval: String,
+    /// 봉 길이 (초). `MAX_STEP` 이하
+    pub step: i64,
+    /// 마지막으로 확보한 봉 시각. (재)연결 직후 이후 구간을 REST로 보충한다
+    pub resync_from: Option<i64>,
+}
+
+/// 진행 중인 봉과 마지막 마감 봉 상태
+struct BarState {
+    current: Option<Candle>,
+    last_closed: Option<i64>,
+    last_emit: Instant,
+}
+
+impl KisSubscription {
+    fn status(&self, state: &str, attempt: u32, message: Option<String>) -> StreamEvent {
+        StreamEvent::Status(CandleStreamStatus {
+            symbol: self.symbol.clone(),
+            interval: self.interval.clone(),
+            state: state.to_string(),
+            attempt,
+            message,
+        })
+    }
+
+    fn candle(&self, candle: Candle, closed: bool) -> StreamEvent {
+        StreamEvent::Candle(CandleUpdate {
+            symbol: self.symbol.clone(),
+            interval: self.interval.clone(),
+            candle,
+            closed,
+        })
+    }
+
+    fn order_book(&self, book: OrderBookUpdate) -> StreamEvent {
+        StreamEvent::OrderBook(OrderBookUpdate {
+            symbol: self.symbol.clone(),
+            ..book
+        })
+    }
+}
+
+/// KIS 실시간 체결을 봉으로 모아 `events`로 보내고 호가는 그대로 전달한다.
+///
+/// - 연결·재연결·PINGPONG은 `KisFeed` 세션이 맡고, 여기서는 상태만 전달한다.
+/// - (재)연결 직후 마지막 마감 봉 이후 구간을 REST로 받아 빈 구간을 메운다.
+/// - 다음 봉의 체결이 오거나 봉 구간이 끝나면 진행 중인 봉을 마감한다.
+/// - `events` 수신 측이 닫히면 종료하고, 세션은 다음 정리 주기에 등록을 해제한다.
+pub async fn run(
+    subscription: KisSubscription,
+    feed: KisFeed,
+    events: UnboundedSender<StreamEvent>,
+) {
+    let mut realtime = match feed.subscribe(&subscription.symbol).await {
+        Ok(realtime) => realtime,
+        Err(error) => {
+            let _ = events.send(subscription.status("stopped", 0, Some(error)));
+            return;
+        }
+    };
+    let mut state = BarState {
+        current: None,
+        last_closed: subscription.resync_from,
+        last_emit: Instant::now(),
+    };
+    let mut close_timer = tokio::time::interval(CLOSE_CHECK_INTERVAL);
+
+    loop {
+        let update = tokio::select! {
+            event = realtime.recv() => match event {
+                None => {
+                    let _ = events.send(subscription.status(
+                        "stopped",
+                        0,
+                        Some("KIS 실시간 세션이 종료되었습니다".to_string()),
+                    ));
+                    return;
+                }
+                Some(RealtimeEvent::Trade(trade)) => {
+                    apply_trade(&trade, subscription.step, &mut state)
+                }
+                Some(RealtimeEvent::OrderBook(book)) => {
+                    if events.send(subscription.order_book(book)).is_err() {
+                        return;
+                    }
+                    Vec::new()
+                }
+                Some(RealtimeEvent::Status { state: status, attempt, message }) => {
+                    if events.send(subscription.status(status, attempt, message)).is_err() {
+                        return;
+                    }
+                    if status == "connected" {
+                        resync(&subscription, feed.client(), &mut state, &events, attempt).await;
+                    }
+                    Vec::new()
+                }
+            },
+            _ = close_timer.tick() => {
+                close_elapsed(subscription.step, Utc::now().timestamp(), &mut state)
+            }
+        };
+
+        for (candle, closed) in update {
+            if events.send(subscription.candle(candle, closed)).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// 마지막 마감 봉 이후를 REST로 받아 보낸다. 마지막 봉은 진행 중인 봉으로 이어 받는다.
+async fn resync(
+    subscription: &KisSubscription,
+    rest: &KisClient,
+    state: &mut BarState,
+    events: &UnboundedSender<StreamEvent>,
+    attempt: u32,
+) {
+    let Some(since) = state.last_closed else {
+        return;
+    };
+
+    match rest
+        .fetch_klines_since(
+            &subscription.symbol,
+            &subscription.interval,
+            since,
+            RESYNC_LIMIT,
+        )
+        .await
+    {
+        Ok(mut candles) => {
+            let last = candles.pop();
+            for candle in candles {
+                state.last_closed = Some(candle.time);
+                let _ = events.send(subscription.candle(candle, true));
+            }
+            // 이미 체결로 진행 중인 봉을 만들었으면 그쪽이 더 최신이다
+            if let Some(last) = last {
+                if state.current.as_ref().is_none_or(|c| c.time < last.time) {
+                    let _ = events.send(subscription.candle(last.clone(), false));
+                    state.current = Some(last);
+                }
+            }
+        }
+        Err(error) => {
+            let _ = events.send(subscription.status(
+                "connected",
+                attempt,
+                Some(format!("REST 보충 실패: {}", error)),
+            ));
+        }
+    }
+}
+
+/// 체결을 진행 중인 봉에 반영한다. 새 봉 구간의 체결이면 이전 봉을 마감해 함께 돌려준다.
+fn apply_trade(trade: &KisTrade, step: i64, state: &mut BarState) -> Vec<(Candle, bool)> {
+    let bucket = trade.time.div_euclid(step) * step;
+    let mut updates = Vec::new();
+
+    if let Some(current) = state.current.as_ref() {
+        // 이미 지난 봉의 늦은 체결
+        if bucket < current.time {
+            return updates;
+        }
+        if bucket > current.time {
+            if let Some(closed) = state.current.take() {
+                state.last_closed = Some(closed.time);
+                updates.push((closed, true));
+            }
+        }
+    } else if state.last_closed.is_some_and(|closed| bucket <= closed) {
+        // 시간으로 마감한 봉의 늦은 체결. 다시 열면 일부 체결만으로 저장된 봉을 덮어쓴다
+        return updates;
+    }
+
+    let candle = state.current.get_or_insert_with(|| Candle {
+        time: bucket,
+        open: trade.price,
+        high: trade.price,
+        low: trade.price,
+        close: trade.price,
+        volume: 0.0,
+        session: None,
+        flow: Some(TradeFlow::default()),
+    });
+    candle.close = trade.price;
+    candle.high = candle.high.max(trade.price);
+    candle.low = candle.low.min(trade.price);
+    candle.volume += trade.volume;
+    // REST로 이어 받은 봉은 앞부분의 체결 방향을 모르므로 집계하지 않는다
+    if let Some(flow) = candle.flow.as_mut() {
+        flow.quote_volume += trade.price * trade.volume;
+        flow.trade_count += 1;
+        if trade.buyer_initiated {
+            flow.taker_buy_volume += trade.volume;
+        }
+    }
+
+    if updates.is_empty() && state.last_emit.elapsed() < TRADE_EMIT_INTERVAL {
+        return updates;
+    }
+    state.last_emit = Instant::now();
+    updates.push((candle.clone(), false));
+    updates
+}
+
+/// 체결 없이 `now`(초)에 봉 구간이 끝났으면 마감한다 (장 마감, 거래 정지 등).
+fn close_elapsed(step: i64, now: i64, state: &mut BarState) -> Vec<(Candle, bool)> {
+    let expired = state
+        .current
+        .as_ref()
+        .is_some_and(|current| now >= current.time + step + CLOSE_GRACE);
+    if !expired {
+        return Vec::new();
+    }
+    let Some(closed) = state.current.take() else {
+        return Vec::new();
+    };
+    state.last_closed = Some(closed.time);
+    vec![(closed, true)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: i64 = 60;
+
+    fn trade(time: i64, price: f64, volume: f64, buyer_initiated: bool) -> KisTrade {
+        KisTrade {
+            time,
+            price,
+            volume,
+            buyer_initiated,
+        }
+    }
+
+    fn empty_state() -> BarState {
+        BarState {
+            current: None,
+            last_closed: None,
+            last_emit: Instant::now(),
+        }
+    }
+
+    fn closed_times(updates: &[(Candle, bool)]) -> Vec<i64> {
+        updates
+            .iter()
+            .filter(|(_, closed)| *closed)
+            .map(|(candle, _)| candle.time)
+            .collect()
+    }
+
+    #[test]
+    fn trades_build_the_bucket_and_the_next_bucket_closes_it() {
+        let mut state = empty_state();
+        apply_trade(&trade(120, 10.0, 1.0, true), STEP, &mut state);
+        apply_trade(&trade(130, 12.0, 2.0, false), STEP, &mut state);
+        apply_trade(&trade(150, 9.0, 1.0, true), STEP, &mut state);
+
+        let updates = apply_trade(&trade(185, 11.0, 1.0, true), STEP, &mut state);
+        assert_eq!(closed_times(&updates), [120]);
+        let (closed, _) = &updates[0];
+        assert_eq!(
+            (closed.open, closed.high, closed.low, closed.close),
+            (10.0, 12.0, 9.0, 9.0)
+        );
+        assert_eq!(closed.volume, 4.0);
+        assert_eq!(
+            closed.flow,
+            Some(TradeFlow {
+                quote_volume: 43.0,
+                trade_count: 3,
+                taker_buy_volume: 2.0,
+            })
+        );
+        assert_eq!(state.last_closed, Some(120));
+        assert_eq!(state.current.as_ref().map(|c| c.time), Some(180));
+
+        // A late trade for the closed bucket does not touch the new bar
+        assert!(apply_trade(&trade(170, 50.0, 1.0, true), STEP, &mut state).is_empty());
+        assert_eq!(state.current.as_ref().map(|c| c.high), Some(11.0));
+    }
+
+    #[test]
+    fn elapsed_buckets_close_after_the_grace_period() {
+        let mut state = empty_state();
+        apply_trade(&trade(120, 10.0, 1.0, true), STEP, &mut state);
+
+        assert!(close_elapsed(STEP, 180 + CLOSE_GRACE - 1, &mut state).is_empty());
+        let updates = close_elapsed(STEP, 180 + CLOSE_GRACE, &mut state);
+        assert_eq!(closed_times(&updates), [120]);
+        assert!(state.current.is_none());
+        assert_eq!(state.last_closed, Some(120));
+        assert!(close_elapsed(STEP, 300, &mut state).is_empty());
+    }
+
+    #[test]
+    fn late_trades_after_a_timed_close_do_not_reopen_the_bar() {
+        let mut state = empty_state();
+        apply_trade(&trade(120, 10.0, 1.0, true), STEP, &mut state);
+        close_elapsed(STEP, 180 + CLOSE_GRACE, &mut state);
+
+        assert!(apply_trade(&trade(175, 8.0, 1.0, false), STEP, &mut state).is_empty());
+        assert!(state.current.is_none());
+
+        let updates = apply_trade(&trade(181, 11.0, 1.0, true), STEP, &mut state);
+        assert!(closed_times(&updates).is_empty());
+        assert_eq!(state.current.as_ref().map(|c| c.time), Some(180));
+    }
+
+    #[test]
+    fn a_resynced_forming_bar_keeps_taking_trades() {
+        // resync adopts the REST bar of the current bucket, whose flow is unknown
+        let mut state = empty_state();
+        state.last_closed = Some(120);
+        state.current = Some(Candle {
+            time: 120,
+            open: 10.0,
+            high: 10.0,
+            low: 10.0,
+            close: 10.0,
+            volume: 5.0,
+            session: None,
+            flow: None,
+        });
+
+        apply_trade(&trade(130, 12.0, 1.0, true), STEP, &mut state);
+        let current = state.current.as_ref().unwrap();
+        assert_eq!(
+            (current.high, current.close, current.volume),
+            (12.0, 12.0, 6.0)
+        );
+        assert!(current.flow.is_none());
+    }
+}
@@ -0,0 +1,118 @@
+pub mod binance;
+pub mod kis;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use tauri::async_runtime::JoinHandle;
+
+use crate::models::{CandleStreamStatus, CandleUpdate, OrderBookUpdate};
+
+/// 스트림 작업이 바깥(Tauri 이벤트 등)으로 내보내는 알림
+#[derive(Debug, Clone)]
+pub enum StreamEvent {
+    Candle(CandleUpdate),
+    OrderBook(OrderBookUpdate),
+    Status(CandleStreamStatus),
+}
+
+/// 실행 중인 실시간 스트림 작업 목록. 키는 `SYMBOL@interval`.
+pub struct StreamManager {
+    ws_url: String,
+    tasks: Mutex<HashMap<String, JoinHandle<()>>>,
+}
+
+impl StreamManager {
+    /// `BINANCE_WS_URL` 환경변수로 WebSocket 주소를 바꿀 수 있다 (로컬 대역 서버 등).
+    pub fn new() -> Self {
+        Self::with_ws_url(
+            std::env::var("BINANCE_WS_URL").unwrap_or_else(|_| binance::DEFAULT_WS_URL.to_string()),
+        )
+    }
+
+    pub fn with_ws_url(ws_url: impl Into<String>) -> Self {
+        Self {
+            ws_url: ws_url.into(),
+            tasks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn ws_url(&self) -> &str {
+        &self.ws_url
+    }
+
+    pub fn key(symbol: &str, interval: &str) -> String {
+        format!("{}@{}", symbol.to_uppercase(), interval)
+    }
+
+    /// 작업을 등록한다. 같은 키의 기존 작업은 중단한다.
+    pub fn insert(&self, key: String, task: JoinHandle<()>) -> Result<(), String> {
+        let mut tasks = self.tasks.lock().map_err(|e| e.to_string())?;
+        Self::prune(&mut tasks);
+        if let Some(previous) = tasks.insert(key, task) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    /// 작업을 중단한다. 실행 중이던 작업이 있었으면 true.
+    pub fn stop(&self, key: &str) -> Result<bool, String> {
+        let mut tasks = self.tasks.lock().map_err(|e| e.to_string())?;
+        Ok(match tasks.remove(key) {
+            Some(task) => {
+                task.abort();
+                true
+            }
+            None => false,
+        })
+    }
+
+    pub fn stop_all(&self) -> Result<(), String> {
+        let mut tasks = self.tasks.lock().map_err(|e| e.to_string())?;
+        for (_, task) in tasks.drain() {
+            task.abort();
+        }
+        Ok(())
+    }
+
+    pub fn active(&self) -> Result<Vec<String>, String> {
+        let mut tasks = self.tasks.lock().map_err(|e| e.to_string())?;
+        Self::prune(&mut tasks);
+        let mut keys: Vec<String> = tasks.keys().cloned().collect();
+        keys.sort();
+        Ok(keys)
+    }
+
+    /// 구독 종료 등으로 스스로 끝난 작업을 목록에서 뺀다.
+    fn prune(tasks: &mut HashMap<String, JoinHandle<()>>) {
+        tasks.retain(|_, task| !task.inner().is_finished());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::StreamManager;
+
+    #[tokio::test]
+    async fn finished_tasks_are_dropped_from_the_list() {
+        let streams = StreamManager::with_ws_url("ws://127.0.0.1:0");
+        streams
+            .insert(
+                "BTCUSDT@1m".to_string(),
+                tauri::async_runtime::spawn(async {}),
+            )
+            .unwrap();
+        streams
+            .insert(
+                "ETHUSDT@1m".to_string(),
+                tauri::async_runtime::spawn(tokio::time::sleep(Duration::from_secs(30))),
+            )
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(streams.active().unwrap(), ["ETHUSDT@1m"]);
+        streams.stop_all().unwrap();
+    }
+}
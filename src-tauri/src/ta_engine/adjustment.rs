@@ -0,0 +1,120 @@
+use crate::models::{Candle, CorporateAction, CorporateActionKind, PriceAdjustment};
+
+/// Re-bases split-adjusted candles (the stored basis) to `adjustment`.
+///
+/// - `None`: undoes every later split, so prices and volume are as originally traded
+/// - `Splits`: returned unchanged
+/// - `TotalReturn`: additionally scales history before each ex-dividend date by
+///   `1 - dividend / previous close`, as if dividends were reinvested
+pub fn apply(
+    candles: &[Candle],
+    actions: &[CorporateAction],
+    adjustment: PriceAdjustment,
+) -> Vec<Candle> {
+    let mut output = candles.to_vec();
+
+    // (ex-time, price multiplier for every candle before it)
+    let mut factors: Vec<(i64, f64)> = match adjustment {
+        PriceAdjustment::Splits => return output,
+        PriceAdjustment::None => actions
+            .iter()
+            .filter(|a| a.kind == CorporateActionKind::Split && a.value > 0.0)
+            .map(|a| (a.time, a.value))
+            .collect(),
+        PriceAdjustment::TotalReturn => actions
+            .iter()
+            .filter(|a| a.kind == CorporateActionKind::Dividend)
+            .filter_map(|a| {
+                let prev_close = candles.iter().rev().find(|c| c.time < a.time)?.close;
+                (prev_close > a.value && a.value > 0.0)
+                    .then(|| (a.time, 1.0 - a.value / prev_close))
+            })
+            .collect(),
+    };
+    factors.sort_by_key(|&(time, _)| time);
+
+    let mut factor = 1.0;
+    let mut pending = factors.len();
+    for candle in output.iter_mut().rev() {
+        while pending > 0 && factors[pending - 1].0 > candle.time {
+            factor *= factors[pending - 1].1;
+            pending -= 1;
+        }
+        candle.open *= factor;
+        candle.high *= factor;
+        candle.low *= factor;
+        candle.close *= factor;
+        if adjustment == PriceAdjustment::None {
+            candle.volume /= factor;
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::candle;
+
+    const DAY: i64 = 86_400;
+
+    fn action(day: i64, kind: CorporateActionKind, value: f64) -> CorporateAction {
+        CorporateAction {
+            time: day * DAY,
+            kind,
+            value,
+        }
+    }
+
+    fn closes(candles: &[Candle]) -> Vec<f64> {
+        candles.iter().map(|c| c.close).collect()
+    }
+
+    /// Split-adjusted bars at 10 with a 2:1 split and a 1.0 dividend both going ex on day 2
+    fn fixture() -> (Vec<Candle>, Vec<CorporateAction>) {
+        let candles = (0..4).map(|d| candle(d * DAY, 10.0)).collect();
+        let actions = vec![
+            action(2, CorporateActionKind::Split, 2.0),
+            action(2, CorporateActionKind::Dividend, 1.0),
+        ];
+        (candles, actions)
+    }
+
+    #[test]
+    fn splits_is_the_stored_basis() {
+        let (candles, actions) = fixture();
+        let output = apply(&candles, &actions, PriceAdjustment::Splits);
+        assert_eq!(closes(&output), vec![10.0; 4]);
+        assert!(!PriceAdjustment::Splits.needs_corporate_actions());
+    }
+
+    #[test]
+    fn none_undoes_later_splits_and_ignores_dividends() {
+        let (candles, mut actions) = fixture();
+        actions.push(action(3, CorporateActionKind::Split, 3.0));
+
+        let output = apply(&candles, &actions, PriceAdjustment::None);
+        // Both splits compound before day 2; only the 3:1 split before day 3
+        assert_eq!(closes(&output), vec![60.0, 60.0, 30.0, 10.0]);
+        assert_eq!(output[0].high, 60.0);
+        let volumes: Vec<f64> = output.iter().map(|c| c.volume).collect();
+        assert_eq!(volumes, vec![1.0 / 6.0, 1.0 / 6.0, 1.0 / 3.0, 1.0]);
+    }
+
+    #[test]
+    fn total_return_scales_history_before_each_dividend() {
+        let (candles, mut actions) = fixture();
+        actions.push(action(1, CorporateActionKind::Dividend, 0.5));
+        // Larger than the previous close: ignored
+        actions.push(action(3, CorporateActionKind::Dividend, 20.0));
+
+        let output = apply(&candles, &actions, PriceAdjustment::TotalReturn);
+        // The split on day 2 is already in the stored basis; only the dividend scales
+        let expected = [10.0 * 0.9 * 0.95, 10.0 * 0.9, 10.0, 10.0];
+        for (actual, expected) in closes(&output).iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-12, "{} != {}", actual, expected);
+        }
+        assert!(output.iter().all(|c| c.volume == 1.0));
+    }
+}
@@ -1,3 +1,4 @@
+use super::incremental::{self, IncrementalIndicator};
 use crate::models::{AdxPoint, AdxResult, Candle};
 
 /// ADX with +DI and -DI using Wilder's smoothing.
@@ -91,3 +92,101 @@ pub fn calculate(candles: &[Candle], period: usize) -> AdxResult {
 
     AdxResult { period, data }
 }
+
+/// Streaming ADX. Emits the same points as `calculate`, the first at candle `2 * period - 1`.
+/// (The batch version additionally requires `2 * period + 1` candles before returning anything.)
+#[derive(Debug, Clone)]
+pub struct AdxState {
+    period: usize,
+    prev: Option<Candle>,
+    /// Number of (+DM, -DM, TR) samples folded into the initial sums
+    seeded: usize,
+    smooth_plus_dm: f64,
+    smooth_minus_dm: f64,
+    smooth_tr: f64,
+    dx_count: usize,
+    dx_sum: f64,
+    adx: Option<f64>,
+}
+
+impl AdxState {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            prev: None,
+            seeded: 0,
+            smooth_plus_dm: 0.0,
+            smooth_minus_dm: 0.0,
+            smooth_tr: 0.0,
+            dx_count: 0,
+            dx_sum: 0.0,
+            adx: None,
+        }
+    }
+}
+
+impl IncrementalIndicator for AdxState {
+    type Output = AdxPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<AdxPoint> {
+        let prev = self.prev.replace(candle.clone())?;
+        if self.period == 0 {
+            return None;
+        }
+        let p = self.period as f64;
+
+        let up = candle.high - prev.high;
+        let down = prev.low - candle.low;
+        let plus_dm = if up > down && up > 0.0 { up } else { 0.0 };
+        let minus_dm = if down > up && down > 0.0 { down } else { 0.0 };
+        let tr = incremental::true_range(candle, Some(prev.close));
+
+        if self.seeded < self.period {
+            self.smooth_plus_dm += plus_dm;
+            self.smooth_minus_dm += minus_dm;
+            self.smooth_tr += tr;
+            self.seeded += 1;
+            if self.seeded < self.period {
+                return None;
+            }
+        } else {
+            self.smooth_plus_dm = self.smooth_plus_dm - self.smooth_plus_dm / p + plus_dm;
+            self.smooth_minus_dm = self.smooth_minus_dm - self.smooth_minus_dm / p + minus_dm;
+            self.smooth_tr = self.smooth_tr - self.smooth_tr / p + tr;
+        }
+
+        let (plus_di, minus_di) = if self.smooth_tr > 0.0 {
+            (
+                100.0 * self.smooth_plus_dm / self.smooth_tr,
+                100.0 * self.smooth_minus_dm / self.smooth_tr,
+            )
+        } else {
+            (0.0, 0.0)
+        };
+        let di_sum = plus_di + minus_di;
+        let dx = if di_sum > 0.0 {
+            100.0 * (plus_di - minus_di).abs() / di_sum
+        } else {
+            0.0
+        };
+
+        let adx = match self.adx {
+            Some(adx) => (adx * (p - 1.0) + dx) / p,
+            None => {
+                self.dx_sum += dx;
+                self.dx_count += 1;
+                if self.dx_count < self.period {
+                    return None;
+                }
+                self.dx_sum / p
+            }
+        };
+        self.adx = Some(adx);
+        Some(AdxPoint {
+            time: candle.time,
+            adx,
+            plus_di,
+            minus_di,
+        })
+    }
+}
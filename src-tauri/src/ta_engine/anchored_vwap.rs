@@ -1,7 +1,10 @@
-use crate::models::{Candle, VwapPoint, VwapResult};
+use chrono::Datelike;
+
+use super::{session, smc, vwap};
+use crate::models::{AnchorMode, AnchoredVwapSeries, Candle, MarketType, VwapAnchor, VwapResult};
 
 /// Anchored VWAP: Same as standard VWAP but starts accumulation from anchor_time.
-/// Only produces data points from anchor_time onward.
+/// Only produces data points (and σ bands) from anchor_time onward.
 pub fn calculate(candles: &[Candle], anchor_time: i64) -> VwapResult {
     // Find the candle at or after anchor_time
     let start_idx = candles
@@ -9,30 +12,150 @@ pub fn calculate(candles: &[Candle], anchor_time: i64) -> VwapResult {
         .position(|c| c.time >= anchor_time)
         .unwrap_or(candles.len());
 
-    if start_idx >= candles.len() {
-        return VwapResult { data: Vec::new() };
+    vwap::calculate(&candles[start_idx..])
+}
+
+/// One labelled anchored VWAP per anchor. Anchors that cannot be placed on these candles
+/// (no confirmed swing yet, no earnings date, anchor after the last candle) are skipped.
+pub fn calculate_anchors(
+    candles: &[Candle],
+    anchors: &[VwapAnchor],
+    swing_length: usize,
+    market: &MarketType,
+) -> Vec<AnchoredVwapSeries> {
+    anchors
+        .iter()
+        .filter_map(|anchor| {
+            let start_idx = resolve_anchor(candles, anchor, swing_length, market)?;
+            let mut vwap = vwap::calculate(&candles[start_idx..]);
+            if !anchor.bands {
+                vwap.bands.clear();
+            }
+            Some(AnchoredVwapSeries {
+                label: anchor
+                    .label
+                    .clone()
+                    .unwrap_or_else(|| default_label(anchor.mode).to_string()),
+                mode: anchor.mode,
+                anchor_time: candles[start_idx].time,
+                vwap,
+            })
+        })
+        .collect()
+}
+
+/// Index of the candle the anchor starts accumulating from.
+fn resolve_anchor(
+    candles: &[Candle],
+    anchor: &VwapAnchor,
+    swing_length: usize,
+    market: &MarketType,
+) -> Option<usize> {
+    match anchor.mode {
+        AnchorMode::Time | AnchorMode::Earnings => {
+            let time = anchor.time?;
+            candles.iter().position(|c| c.time >= time)
+        }
+        AnchorMode::SwingHigh => smc::swing_points(candles, swing_length)
+            .0
+            .last()
+            .map(|&(idx, _)| idx),
+        AnchorMode::SwingLow => smc::swing_points(candles, swing_length)
+            .1
+            .last()
+            .map(|&(idx, _)| idx),
+        AnchorMode::YearToDate => {
+            let year = session::session_date(candles.last()?.time, market).year();
+            candles
+                .iter()
+                .position(|c| session::session_date(c.time, market).year() == year)
+        }
+        AnchorMode::HighestVolume => candles
+            .iter()
+            .enumerate()
+            .fold(None, |best: Option<(usize, f64)>, (idx, c)| match best {
+                Some((_, volume)) if volume >= c.volume => best,
+                _ => Some((idx, c.volume)),
+            })
+            .map(|(idx, _)| idx),
+    }
+}
+
+fn default_label(mode: AnchorMode) -> &'static str {
+    match mode {
+        AnchorMode::Time => "앵커드 VWAP",
+        AnchorMode::SwingHigh => "스윙 고점 VWAP",
+        AnchorMode::SwingLow => "스윙 저점 VWAP",
+        AnchorMode::Earnings => "실적 발표 VWAP",
+        AnchorMode::YearToDate => "연초 VWAP",
+        AnchorMode::HighestVolume => "최대 거래량 VWAP",
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::candle;
+
+    const DAY: i64 = 86_400;
 
-    let mut cumulative_pv = 0.0_f64;
-    let mut cumulative_volume = 0.0_f64;
-    let mut data = Vec::with_capacity(candles.len() - start_idx);
-
-    for candle in &candles[start_idx..] {
-        let typical_price = (candle.high + candle.low + candle.close) / 3.0;
-        cumulative_pv += typical_price * candle.volume;
-        cumulative_volume += candle.volume.max(0.0);
-
-        let value = if cumulative_volume > f64::EPSILON {
-            cumulative_pv / cumulative_volume
-        } else {
-            typical_price
-        };
-
-        data.push(VwapPoint {
-            time: candle.time,
-            value,
-        });
+    fn time_anchor(time: i64, label: Option<&str>, bands: bool) -> VwapAnchor {
+        VwapAnchor {
+            mode: AnchorMode::Time,
+            time: Some(time),
+            label: label.map(str::to_string),
+            bands,
+        }
     }
 
-    VwapResult { data }
+    #[test]
+    fn an_anchor_between_bars_starts_at_the_next_bar() {
+        let candles: Vec<Candle> = (0..5).map(|d| candle(d * DAY, 100.0 + d as f64)).collect();
+
+        let result = calculate(&candles, 2 * DAY - 1);
+        assert_eq!(result.data.first().map(|p| p.time), Some(2 * DAY));
+        assert_eq!(result.data[0].value, 102.0);
+        assert_eq!(result.data.len(), 3);
+
+        let series = calculate_anchors(
+            &candles,
+            &[
+                time_anchor(DAY + DAY / 2, None, false),
+                time_anchor(3 * DAY, Some("earnings gap"), true),
+            ],
+            3,
+            &MarketType::UsStock,
+        );
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].anchor_time, 2 * DAY);
+        assert_eq!(series[0].label, "앵커드 VWAP");
+        assert!(series[0].vwap.bands.is_empty());
+        // An anchor exactly on a bar starts on that bar
+        assert_eq!(series[1].anchor_time, 3 * DAY);
+        assert_eq!(series[1].label, "earnings gap");
+        assert_eq!(series[1].vwap.data[1].value, 103.5);
+        assert_eq!(series[1].vwap.bands.len(), 2);
+    }
+
+    #[test]
+    fn anchors_after_the_last_bar_are_skipped() {
+        let candles: Vec<Candle> = (0..3).map(|d| candle(d * DAY, 100.0)).collect();
+
+        assert!(calculate(&candles, 3 * DAY).data.is_empty());
+        let series = calculate_anchors(
+            &candles,
+            &[
+                time_anchor(2 * DAY + 1, None, true),
+                VwapAnchor {
+                    mode: AnchorMode::Earnings,
+                    time: None,
+                    label: None,
+                    bands: false,
+                },
+            ],
+            3,
+            &MarketType::UsStock,
+        );
+        assert!(series.is_empty());
+    }
 }
@@ -1,3 +1,4 @@
+use super::incremental::{self, IncrementalIndicator, WilderAverage};
 use crate::models::{AtrPoint, AtrResult, Candle};
 
 pub fn calculate(candles: &[Candle], period: usize) -> AtrResult {
@@ -45,3 +46,31 @@ pub fn calculate(candles: &[Candle], period: usize) -> AtrResult {
 
     AtrResult { period, data }
 }
+
+/// Streaming ATR. Emits the same points as `calculate`.
+#[derive(Debug, Clone)]
+pub struct AtrState {
+    prev_close: Option<f64>,
+    atr: WilderAverage,
+}
+
+impl AtrState {
+    pub fn new(period: usize) -> Self {
+        Self {
+            prev_close: None,
+            atr: WilderAverage::new(period),
+        }
+    }
+}
+
+impl IncrementalIndicator for AtrState {
+    type Output = AtrPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<AtrPoint> {
+        let tr = incremental::true_range(candle, self.prev_close.replace(candle.close));
+        self.atr.update(tr).map(|value| AtrPoint {
+            time: candle.time,
+            value,
+        })
+    }
+}
@@ -1,3 +1,6 @@
+use std::collections::VecDeque;
+
+use super::incremental::{self, IncrementalIndicator};
 use crate::models::{BollingerBandsPoint, Candle};
 
 pub fn calculate(candles: &[Candle], period: usize, multiplier: f64) -> Vec<BollingerBandsPoint> {
@@ -29,3 +32,44 @@ pub fn calculate(candles: &[Candle], period: usize, multiplier: f64) -> Vec<Boll
 
     result
 }
+
+/// Streaming Bollinger Bands. Emits the same points as `calculate`.
+#[derive(Debug, Clone)]
+pub struct BollingerState {
+    period: usize,
+    multiplier: f64,
+    window: VecDeque<f64>,
+}
+
+impl BollingerState {
+    pub fn new(period: usize, multiplier: f64) -> Self {
+        Self {
+            period,
+            multiplier,
+            window: VecDeque::with_capacity(period + 1),
+        }
+    }
+}
+
+impl IncrementalIndicator for BollingerState {
+    type Output = BollingerBandsPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<BollingerBandsPoint> {
+        if !incremental::push_window(&mut self.window, candle.close, self.period) {
+            return None;
+        }
+
+        let sum: f64 = self.window.iter().sum();
+        let sma = sum / self.period as f64;
+        let variance: f64 =
+            self.window.iter().map(|x| (x - sma).powi(2)).sum::<f64>() / self.period as f64;
+        let std_dev = variance.sqrt();
+
+        Some(BollingerBandsPoint {
+            time: candle.time,
+            upper: sma + self.multiplier * std_dev,
+            middle: sma,
+            lower: sma - self.multiplier * std_dev,
+        })
+    }
+}
@@ -1,3 +1,6 @@
+use std::collections::VecDeque;
+
+use super::incremental::{self, IncrementalIndicator};
 use crate::models::{Candle, CmfPoint, CmfResult};
 
 /// Chaikin Money Flow: Σ[((C-L)-(H-C))/(H-L) × V] / Σ(V) over period.
@@ -45,3 +48,52 @@ pub fn calculate(candles: &[Candle], period: usize) -> CmfResult {
 
     CmfResult { period, data }
 }
+
+/// Streaming CMF. Emits the same points as `calculate`.
+#[derive(Debug, Clone)]
+pub struct CmfState {
+    period: usize,
+    /// (money flow multiplier, volume)
+    window: VecDeque<(f64, f64)>,
+}
+
+impl CmfState {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            window: VecDeque::with_capacity(period + 1),
+        }
+    }
+}
+
+impl IncrementalIndicator for CmfState {
+    type Output = CmfPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<CmfPoint> {
+        let hl = candle.high - candle.low;
+        let mfm = if hl.abs() < f64::EPSILON {
+            0.0
+        } else {
+            ((candle.close - candle.low) - (candle.high - candle.close)) / hl
+        };
+        if !incremental::push_window(&mut self.window, (mfm, candle.volume), self.period) {
+            return None;
+        }
+
+        let mut mfv_sum = 0.0;
+        let mut vol_sum = 0.0;
+        for (mfm, volume) in &self.window {
+            mfv_sum += mfm * volume;
+            vol_sum += volume;
+        }
+        let value = if vol_sum.abs() < f64::EPSILON {
+            0.0
+        } else {
+            mfv_sum / vol_sum
+        };
+        Some(CmfPoint {
+            time: candle.time,
+            value,
+        })
+    }
+}
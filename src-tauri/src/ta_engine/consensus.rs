@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use crate::models::{
+    Candle, ConsensusParams, ConsensusPoint, ConsensusResult, SignalPoint, SignalType,
+};
+
+/// 여러 전략 신호의 가중 합의 점수.
+///
+/// 각 source의 가장 최근 신호가 `window`봉 이내이면 `weight × (매수 +1 / 매도 -1)`로 점수에 더한다.
+/// 순점수가 `threshold`를 위로(또는 `-threshold`를 아래로) 넘는 봉에서 합의 신호를 낸다.
+pub fn calculate(
+    candles: &[Candle],
+    signals: &[SignalPoint],
+    params: &ConsensusParams,
+) -> (ConsensusResult, Vec<SignalPoint>) {
+    let window = params.window.max(1);
+    let mut by_time: HashMap<i64, Vec<&SignalPoint>> = HashMap::new();
+    for signal in signals {
+        by_time.entry(signal.time).or_default().push(signal);
+    }
+
+    // source → (신호 봉 인덱스, 해당 봉의 순방향)
+    let mut latest: HashMap<&str, (usize, i32)> = HashMap::new();
+    let mut data = Vec::with_capacity(candles.len());
+    let mut composite = Vec::new();
+    let mut prev_score = 0.0;
+
+    for (i, candle) in candles.iter().enumerate() {
+        if let Some(bar_signals) = by_time.get(&candle.time) {
+            let mut net: HashMap<&str, i32> = HashMap::new();
+            for signal in bar_signals {
+                *net.entry(signal.source.as_str()).or_insert(0) +=
+                    if signal.signal_type.is_buy() { 1 } else { -1 };
+            }
+            // 같은 봉에서 매수/매도가 상쇄되면 방향 0으로 이전 신호를 덮는다
+            for (source, direction) in net {
+                latest.insert(source, (i, direction.signum()));
+            }
+        }
+
+        let mut buy_score = 0.0;
+        let mut sell_score = 0.0;
+        for (source, &(index, direction)) in &latest {
+            if i - index >= window {
+                continue;
+            }
+            let weight = params.weights.get(*source).copied().unwrap_or(1.0);
+            if direction > 0 {
+                buy_score += weight;
+            } else if direction < 0 {
+                sell_score += weight;
+            }
+        }
+        let score = buy_score - sell_score;
+
+        if prev_score < params.threshold && score >= params.threshold {
+            composite.push(SignalPoint {
+                time: candle.time,
+                signal_type: SignalType::ConsensusBuy,
+                price: candle.close,
+                rsi: 0.0,
+                source: "consensus".to_string(),
+            });
+        }
+        if prev_score > -params.threshold && score <= -params.threshold {
+            composite.push(SignalPoint {
+                time: candle.time,
+                signal_type: SignalType::ConsensusSell,
+                price: candle.close,
+                rsi: 0.0,
+                source: "consensus".to_string(),
+            });
+        }
+        prev_score = score;
+
+        data.push(ConsensusPoint {
+            time: candle.time,
+            score,
+            buy_score,
+            sell_score,
+        });
+    }
+
+    (ConsensusResult { data }, composite)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(i: i64) -> Candle {
+        Candle {
+            time: i * 60,
+            open: 100.0,
+            high: 101.0,
+            low: 99.0,
+            close: 100.0 + i as f64,
+            volume: 1.0,
+            session: None,
+            flow: None,
+        }
+    }
+
+    fn signal(i: i64, buy: bool, source: &str) -> SignalPoint {
+        SignalPoint {
+            time: i * 60,
+            signal_type: if buy {
+                SignalType::SupertrendBuy
+            } else {
+                SignalType::SupertrendSell
+            },
+            price: 0.0,
+            rsi: 50.0,
+            source: source.to_string(),
+        }
+    }
+
+    #[test]
+    fn weights_sources_within_the_window_and_fires_on_threshold_crossings() {
+        let candles: Vec<Candle> = (0..6).map(candle).collect();
+        let signals = [
+            signal(0, true, "a"),
+            signal(1, true, "b"),
+            signal(1, false, "c"),
+            // Opposite signals from one source on one bar cancel out
+            signal(4, true, "a"),
+            signal(4, false, "a"),
+            signal(5, false, "c"),
+            signal(5, false, "e"),
+            signal(5, false, "muted"),
+        ];
+        let params = ConsensusParams {
+            weights: [("b".to_string(), 2.0), ("muted".to_string(), 0.0)].into(),
+            window: 3,
+            threshold: 2.0,
+        };
+
+        let (result, composite) = calculate(&candles, &signals, &params);
+
+        let scores: Vec<f64> = result.data.iter().map(|p| p.score).collect();
+        // a expires on bar 3, b and c on bar 4
+        assert_eq!(scores, vec![1.0, 2.0, 2.0, 1.0, 0.0, -2.0]);
+        assert_eq!(
+            (result.data[1].buy_score, result.data[1].sell_score),
+            (3.0, 1.0)
+        );
+
+        assert_eq!(composite.len(), 2);
+        assert_eq!(composite[0].signal_type, SignalType::ConsensusBuy);
+        assert_eq!((composite[0].time, composite[0].price), (60, 101.0));
+        assert_eq!(composite[1].signal_type, SignalType::ConsensusSell);
+        assert_eq!(composite[1].time, 300);
+    }
+
+    #[test]
+    fn staying_above_the_threshold_does_not_repeat_the_signal() {
+        let candles: Vec<Candle> = (0..4).map(candle).collect();
+        let signals = [signal(0, true, "a"), signal(2, true, "a")];
+        let params = ConsensusParams {
+            window: 5,
+            threshold: 1.0,
+            ..Default::default()
+        };
+
+        let (_, composite) = calculate(&candles, &signals, &params);
+        assert_eq!(composite.len(), 1);
+        assert_eq!(composite[0].time, 0);
+    }
+}
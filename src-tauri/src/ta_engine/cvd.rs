@@ -1,29 +1,83 @@
-use crate::models::{Candle, CvdPoint, CvdResult};
+use super::incremental::IncrementalIndicator;
+use crate::models::{Candle, CvdEstimate, CvdPoint, CvdResult};
 
-/// Cumulative Volume Delta: close>open → +volume, close<open → -volume, cumulative sum.
-pub fn calculate(candles: &[Candle]) -> CvdResult {
-    if candles.is_empty() {
-        return CvdResult { data: Vec::new() };
-    }
-
-    let mut cumulative = 0.0;
+/// Cumulative Volume Delta. Bars carrying trade flow use the real taker split
+/// (taker buys − taker sells); the rest are estimated with `estimate`.
+pub fn calculate(candles: &[Candle], estimate: CvdEstimate) -> CvdResult {
+    let mut state = CvdState::new(estimate);
+    let mut estimated_bars = 0;
     let data = candles
         .iter()
         .map(|c| {
-            let delta = if c.close > c.open {
-                c.volume
-            } else if c.close < c.open {
-                -c.volume
-            } else {
-                0.0
-            };
-            cumulative += delta;
-            CvdPoint {
-                time: c.time,
-                value: cumulative,
+            if c.flow.is_none() {
+                estimated_bars += 1;
             }
+            state.accumulate(c)
         })
         .collect();
 
-    CvdResult { data }
+    CvdResult {
+        data,
+        estimated_bars,
+    }
+}
+
+/// Volume delta of a single bar.
+pub fn bar_delta(candle: &Candle, estimate: CvdEstimate) -> f64 {
+    if let Some(flow) = candle.flow {
+        // Taker sells are whatever volume was not a taker buy
+        return 2.0 * flow.taker_buy_volume - candle.volume;
+    }
+
+    match estimate {
+        CvdEstimate::BarDirection => {
+            if candle.close > candle.open {
+                candle.volume
+            } else if candle.close < candle.open {
+                -candle.volume
+            } else {
+                0.0
+            }
+        }
+        CvdEstimate::CloseLocation => {
+            let range = candle.high - candle.low;
+            if range <= f64::EPSILON {
+                0.0
+            } else {
+                candle.volume * (2.0 * candle.close - candle.high - candle.low) / range
+            }
+        }
+    }
+}
+
+/// Streaming CVD. Emits the same points as `calculate`.
+#[derive(Debug, Clone, Default)]
+pub struct CvdState {
+    estimate: CvdEstimate,
+    cumulative: f64,
+}
+
+impl CvdState {
+    pub fn new(estimate: CvdEstimate) -> Self {
+        Self {
+            estimate,
+            cumulative: 0.0,
+        }
+    }
+
+    fn accumulate(&mut self, candle: &Candle) -> CvdPoint {
+        self.cumulative += bar_delta(candle, self.estimate);
+        CvdPoint {
+            time: candle.time,
+            value: self.cumulative,
+        }
+    }
+}
+
+impl IncrementalIndicator for CvdState {
+    type Output = CvdPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<CvdPoint> {
+        Some(self.accumulate(candle))
+    }
 }
@@ -0,0 +1,131 @@
+use crate::models::{Candle, FundingRatePoint, LongShortRatioPoint, OpenInterestPoint};
+
+/// Funding rate in effect on each candle (the last settlement before the candle closed).
+pub fn align_funding_rates(
+    candles: &[Candle],
+    step: i64,
+    rates: &[FundingRatePoint],
+) -> Vec<FundingRatePoint> {
+    align(
+        candles,
+        step,
+        rates,
+        |p| p.time,
+        |p, time| FundingRatePoint { time, ..p.clone() },
+    )
+}
+
+pub fn align_open_interest(
+    candles: &[Candle],
+    step: i64,
+    points: &[OpenInterestPoint],
+) -> Vec<OpenInterestPoint> {
+    align(
+        candles,
+        step,
+        points,
+        |p| p.time,
+        |p, time| OpenInterestPoint { time, ..p.clone() },
+    )
+}
+
+pub fn align_long_short_ratio(
+    candles: &[Candle],
+    step: i64,
+    points: &[LongShortRatioPoint],
+) -> Vec<LongShortRatioPoint> {
+    align(
+        candles,
+        step,
+        points,
+        |p| p.time,
+        |p, time| LongShortRatioPoint { time, ..p.clone() },
+    )
+}
+
+/// Re-stamps externally sampled points onto the candle grid. Each candle takes the latest
+/// point published before it closed (`time < candle.time + step`), so no value from after the
+/// bar leaks into it. Candles before the first point are skipped.
+fn align<T>(
+    candles: &[Candle],
+    step: i64,
+    points: &[T],
+    time_of: impl Fn(&T) -> i64,
+    restamp: impl Fn(&T, i64) -> T,
+) -> Vec<T> {
+    let mut sorted: Vec<&T> = points.iter().collect();
+    sorted.sort_by_key(|p| time_of(p));
+
+    let mut idx = 0;
+    let mut current: Option<&T> = None;
+    let mut aligned = Vec::with_capacity(candles.len());
+    for candle in candles {
+        let close_time = candle.time + step.max(1);
+        while idx < sorted.len() && time_of(sorted[idx]) < close_time {
+            current = Some(sorted[idx]);
+            idx += 1;
+        }
+        if let Some(point) = current {
+            aligned.push(restamp(point, candle.time));
+        }
+    }
+    aligned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::candle;
+
+    const HOUR: i64 = 3_600;
+
+    fn funding(time: i64, rate: f64) -> FundingRatePoint {
+        FundingRatePoint { time, rate }
+    }
+
+    #[test]
+    fn sparse_funding_carries_forward_onto_each_candle() {
+        let candles: Vec<Candle> = (0..12).map(|h| candle(h * HOUR, 100.0)).collect();
+        // Settlements every 8h, passed out of order; the one at 10:00 is after hour 9 closes
+        let rates = [
+            funding(8 * HOUR, 0.0002),
+            funding(HOUR + 1, 0.0001),
+            funding(10 * HOUR, 0.0003),
+        ];
+
+        let aligned = align_funding_rates(&candles, HOUR, &rates);
+
+        let points: Vec<(i64, f64)> = aligned.iter().map(|p| (p.time, p.rate)).collect();
+        // Hour 0 precedes the first settlement; hour 1 takes it since it lands before the close
+        let mut expected: Vec<(i64, f64)> = (1..8).map(|h| (h * HOUR, 0.0001)).collect();
+        expected.extend([
+            (8 * HOUR, 0.0002),
+            (9 * HOUR, 0.0002),
+            (10 * HOUR, 0.0003),
+            (11 * HOUR, 0.0003),
+        ]);
+        assert_eq!(points, expected);
+    }
+
+    #[test]
+    fn open_interest_published_at_the_close_belongs_to_the_next_bar() {
+        let candles: Vec<Candle> = (0..3).map(|h| candle(h * 4 * HOUR, 100.0)).collect();
+        let points = [
+            OpenInterestPoint {
+                time: 0,
+                open_interest: 10.0,
+                open_interest_value: 1_000.0,
+            },
+            OpenInterestPoint {
+                time: 4 * HOUR,
+                open_interest: 12.0,
+                open_interest_value: 1_200.0,
+            },
+        ];
+
+        let aligned = align_open_interest(&candles, 4 * HOUR, &points);
+
+        let values: Vec<(i64, f64)> = aligned.iter().map(|p| (p.time, p.open_interest)).collect();
+        assert_eq!(values, [(0, 10.0), (4 * HOUR, 12.0), (8 * HOUR, 12.0)]);
+    }
+}
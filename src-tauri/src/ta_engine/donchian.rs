@@ -1,4 +1,7 @@
+use std::collections::VecDeque;
+
 use super::helpers;
+use super::incremental::{self, IncrementalIndicator};
 use crate::models::{Candle, DonchianPoint, DonchianResult};
 
 pub fn calculate(candles: &[Candle], period: usize) -> DonchianResult {
@@ -26,3 +29,46 @@ pub fn calculate(candles: &[Candle], period: usize) -> DonchianResult {
 
     DonchianResult { period, data }
 }
+
+/// Streaming Donchian Channels. Emits the same points as `calculate`.
+#[derive(Debug, Clone)]
+pub struct DonchianState {
+    period: usize,
+    window: VecDeque<Candle>,
+}
+
+impl DonchianState {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            window: VecDeque::with_capacity(period + 1),
+        }
+    }
+}
+
+impl IncrementalIndicator for DonchianState {
+    type Output = DonchianPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<DonchianPoint> {
+        if !incremental::push_window(&mut self.window, candle.clone(), self.period) {
+            return None;
+        }
+
+        let upper = self
+            .window
+            .iter()
+            .map(|c| c.high)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let lower = self
+            .window
+            .iter()
+            .map(|c| c.low)
+            .fold(f64::INFINITY, f64::min);
+        Some(DonchianPoint {
+            time: candle.time,
+            upper,
+            middle: (upper + lower) / 2.0,
+            lower,
+        })
+    }
+}
@@ -1,3 +1,4 @@
+use super::incremental::{EmaValue, IncrementalIndicator};
 use super::sma;
 use crate::models::{Candle, MaPoint, MovingAverageResult};
 
@@ -44,3 +45,28 @@ pub fn calculate_from_values(values: &[f64], period: usize) -> Vec<f64> {
 
     result
 }
+
+/// Streaming EMA over close prices. Emits the same points as `calculate`.
+#[derive(Debug, Clone)]
+pub struct EmaState {
+    ema: EmaValue,
+}
+
+impl EmaState {
+    pub fn new(period: usize) -> Self {
+        Self {
+            ema: EmaValue::new(period),
+        }
+    }
+}
+
+impl IncrementalIndicator for EmaState {
+    type Output = MaPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<MaPoint> {
+        self.ema.update(candle.close).map(|value| MaPoint {
+            time: candle.time,
+            value,
+        })
+    }
+}
@@ -0,0 +1,570 @@
+use std::collections::VecDeque;
+
+use super::adx::AdxState;
+use super::atr::AtrState;
+use super::bollinger::BollingerState;
+use super::cmf::CmfState;
+use super::cvd::CvdState;
+use super::donchian::DonchianState;
+use super::ema::EmaState;
+use super::keltner::KeltnerState;
+use super::macd::MacdState;
+use super::mfi::MfiState;
+use super::obv::ObvState;
+use super::parabolic_sar::ParabolicSarState;
+use super::rsi::RsiState;
+use super::sma::SmaState;
+use super::stochastic::StochasticState;
+use super::supertrend::SupertrendState;
+use super::vwap::{self, VwapState};
+use super::williams_r::WillrState;
+use crate::models::{
+    Candle, IndicatorValues, LiveIndicatorParams, MaPoint, MarketType, MovingAverageResult,
+};
+
+/// Streaming indicator that consumes one candle at a time.
+///
+/// Feeding a candle sequence in order yields the same points, in the same order, as the
+/// module's batch `calculate`. Call `update` for closed candles only; use `peek` to evaluate
+/// the in-progress candle without advancing the state.
+pub trait IncrementalIndicator {
+    type Output;
+
+    /// Advance with a closed candle. Returns None during warm-up.
+    fn update(&mut self, candle: &Candle) -> Option<Self::Output>;
+
+    /// Value the indicator would have if `candle` closed now. The state is left untouched.
+    fn peek(&self, candle: &Candle) -> Option<Self::Output>
+    where
+        Self: Clone,
+    {
+        self.clone().update(candle)
+    }
+}
+
+/// Rolling SMA using the same running-sum arithmetic as `sma::calculate_from_values`.
+#[derive(Debug, Clone)]
+pub struct SmaValue {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+}
+
+impl SmaValue {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            window: VecDeque::with_capacity(period + 1),
+            sum: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        if self.period == 0 {
+            return None;
+        }
+        self.window.push_back(value);
+        if self.window.len() > self.period {
+            let oldest = self.window.pop_front().unwrap_or(0.0);
+            self.sum += value - oldest;
+        } else {
+            self.sum += value;
+        }
+        (self.window.len() == self.period).then(|| self.sum / self.period as f64)
+    }
+}
+
+/// SMA-seeded EMA matching `ema::calculate_from_values`.
+#[derive(Debug, Clone)]
+pub struct EmaValue {
+    period: usize,
+    multiplier: f64,
+    count: usize,
+    seed_sum: f64,
+    value: Option<f64>,
+}
+
+impl EmaValue {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            multiplier: 2.0 / (period as f64 + 1.0),
+            count: 0,
+            seed_sum: 0.0,
+            value: None,
+        }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        if self.period == 0 {
+            return None;
+        }
+        match self.value {
+            Some(prev) => self.value = Some((value - prev) * self.multiplier + prev),
+            None => {
+                self.seed_sum += value;
+                self.count += 1;
+                if self.count == self.period {
+                    self.value = Some(self.seed_sum / self.period as f64);
+                }
+            }
+        }
+        self.value
+    }
+}
+
+/// SMA-seeded Wilder smoothing as used by RSI and ATR.
+#[derive(Debug, Clone)]
+pub struct WilderAverage {
+    period: usize,
+    count: usize,
+    seed_sum: f64,
+    value: Option<f64>,
+}
+
+impl WilderAverage {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            count: 0,
+            seed_sum: 0.0,
+            value: None,
+        }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        if self.period == 0 {
+            return None;
+        }
+        let p = self.period as f64;
+        match self.value {
+            Some(prev) => self.value = Some((prev * (p - 1.0) + value) / p),
+            None => {
+                self.seed_sum += value;
+                self.count += 1;
+                if self.count == self.period {
+                    self.value = Some(self.seed_sum / p);
+                }
+            }
+        }
+        self.value
+    }
+}
+
+/// True range; the first candle (no previous close) uses high - low.
+pub fn true_range(candle: &Candle, prev_close: Option<f64>) -> f64 {
+    match prev_close {
+        Some(prev_close) => (candle.high - candle.low)
+            .max((candle.high - prev_close).abs())
+            .max((candle.low - prev_close).abs()),
+        None => candle.high - candle.low,
+    }
+}
+
+/// Push into a fixed-size window, dropping the oldest item. Returns true once the window is full.
+pub fn push_window<T>(window: &mut VecDeque<T>, item: T, capacity: usize) -> bool {
+    window.push_back(item);
+    if window.len() > capacity {
+        window.pop_front();
+    }
+    capacity > 0 && window.len() == capacity
+}
+
+/// Streaming counterparts of the indicators `analyze` returns, for the live candle stream.
+/// VWAP, ATR(14), Supertrend and Parabolic SAR are always on, as in `analyze`; the latter two
+/// use the Supertrend + ADX and Parabolic SAR strategy settings.
+#[derive(Debug, Clone)]
+pub struct IndicatorSet {
+    last_time: Option<i64>,
+    bollinger: BollingerState,
+    rsi: RsiState,
+    sma: Vec<(usize, SmaState)>,
+    ema: Vec<(usize, EmaState)>,
+    macd: Option<MacdState>,
+    stochastic: Option<StochasticState>,
+    obv: Option<ObvState>,
+    cvd: Option<CvdState>,
+    vwap: VwapState,
+    atr: AtrState,
+    supertrend: SupertrendState,
+    parabolic_sar: ParabolicSarState,
+    donchian: Option<DonchianState>,
+    keltner: Option<KeltnerState>,
+    mfi: Option<MfiState>,
+    cmf: Option<CmfState>,
+    williams_r: Option<WillrState>,
+    adx: Option<AdxState>,
+}
+
+impl IndicatorSet {
+    /// `interval` and `market` decide where the session VWAP resets.
+    pub fn new(params: &LiveIndicatorParams, interval: &str, market: &MarketType) -> Self {
+        Self {
+            last_time: None,
+            bollinger: BollingerState::new(params.bb_period, params.bb_multiplier),
+            rsi: RsiState::new(params.rsi_period),
+            sma: params
+                .sma_periods
+                .iter()
+                .map(|&period| (period, SmaState::new(period)))
+                .collect(),
+            ema: params
+                .ema_periods
+                .iter()
+                .map(|&period| (period, EmaState::new(period)))
+                .collect(),
+            macd: params
+                .macd
+                .as_ref()
+                .map(|m| MacdState::new(m.fast_period, m.slow_period, m.signal_period)),
+            stochastic: params
+                .stochastic
+                .as_ref()
+                .map(|s| StochasticState::new(s.k_period, s.d_period, s.smooth)),
+            obv: params.show_obv.then(ObvState::new),
+            cvd: params.show_cvd.then(|| CvdState::new(params.cvd_estimate)),
+            vwap: VwapState::with_reset(
+                vwap::resolve_reset(params.vwap_reset, interval),
+                market.clone(),
+            ),
+            atr: AtrState::new(14),
+            supertrend: SupertrendState::new(
+                params.signal_strategies.supertrend_adx_config.period,
+                params.signal_strategies.supertrend_adx_config.multiplier,
+            ),
+            parabolic_sar: ParabolicSarState::new(
+                params.signal_strategies.parabolic_sar_config.step,
+                params.signal_strategies.parabolic_sar_config.max_step,
+            ),
+            donchian: params
+                .donchian
+                .as_ref()
+                .map(|d| DonchianState::new(d.period)),
+            keltner: params
+                .keltner
+                .as_ref()
+                .map(|k| KeltnerState::new(k.ema_period, k.atr_period, k.atr_multiplier)),
+            mfi: params.mfi.as_ref().map(|m| MfiState::new(m.period)),
+            cmf: params.cmf.as_ref().map(|c| CmfState::new(c.period)),
+            williams_r: params
+                .williams_r
+                .as_ref()
+                .map(|w| WillrState::new(w.period)),
+            adx: params.adx.as_ref().map(|a| AdxState::new(a.period)),
+        }
+    }
+
+    /// Time of the last candle passed to `update`.
+    pub fn last_time(&self) -> Option<i64> {
+        self.last_time
+    }
+}
+
+fn update_optional<T: IncrementalIndicator>(
+    state: &mut Option<T>,
+    candle: &Candle,
+) -> Option<T::Output> {
+    state.as_mut().and_then(|state| state.update(candle))
+}
+
+fn update_periods<T: IncrementalIndicator<Output = MaPoint>>(
+    states: &mut [(usize, T)],
+    candle: &Candle,
+) -> Vec<MovingAverageResult> {
+    states
+        .iter_mut()
+        .map(|(period, state)| MovingAverageResult {
+            period: *period,
+            data: state.update(candle).into_iter().collect(),
+        })
+        .collect()
+}
+
+impl IncrementalIndicator for IndicatorSet {
+    type Output = IndicatorValues;
+
+    fn update(&mut self, candle: &Candle) -> Option<IndicatorValues> {
+        self.last_time = Some(candle.time);
+        let (vwap, vwap_bands) = self.vwap.update(candle).unzip();
+        Some(IndicatorValues {
+            time: candle.time,
+            bollinger_bands: self.bollinger.update(candle),
+            rsi: self.rsi.update(candle),
+            sma: update_periods(&mut self.sma, candle),
+            ema: update_periods(&mut self.ema, candle),
+            macd: update_optional(&mut self.macd, candle),
+            stochastic: update_optional(&mut self.stochastic, candle),
+            obv: update_optional(&mut self.obv, candle),
+            cvd: update_optional(&mut self.cvd, candle),
+            vwap,
+            vwap_bands,
+            atr: self.atr.update(candle),
+            supertrend: self.supertrend.update(candle),
+            parabolic_sar: self.parabolic_sar.update(candle),
+            donchian: update_optional(&mut self.donchian, candle),
+            keltner: update_optional(&mut self.keltner, candle),
+            mfi: update_optional(&mut self.mfi, candle),
+            cmf: update_optional(&mut self.cmf, candle),
+            williams_r: update_optional(&mut self.williams_r, candle),
+            adx: update_optional(&mut self.adx, candle),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+    use crate::models::{CvdEstimate, TradeFlow, VwapReset};
+    use crate::ta_engine::{
+        adx, atr, bollinger, cmf, cvd, donchian, ema, keltner, macd, mfi, obv, parabolic_sar, rsi,
+        sma, stochastic, supertrend, vwap, williams_r,
+    };
+
+    /// Deterministic xorshift64 so failures reproduce from the seed alone.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next_f64(&mut self) -> f64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 >> 11) as f64 / (1u64 << 53) as f64
+        }
+    }
+
+    /// Random walk with flat (zero-range), zero-volume and flow-carrying bars mixed in.
+    fn random_candles(seed: u64, len: usize) -> Vec<Candle> {
+        let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1);
+        let mut close = 100.0;
+        (0..len)
+            .map(|i| {
+                let open = close;
+                let kind = rng.next_f64();
+                let (high, low, volume) = if kind < 0.15 {
+                    // Flat bar: every price equal, range zero
+                    (open, open, rng.next_f64() * 500.0)
+                } else {
+                    close = (open + (rng.next_f64() - 0.5) * 4.0).max(1.0);
+                    let high = open.max(close) + rng.next_f64() * 2.0;
+                    let low = (open.min(close) - rng.next_f64() * 2.0).max(0.5);
+                    let volume = if kind < 0.25 {
+                        0.0
+                    } else {
+                        rng.next_f64() * 1_000.0
+                    };
+                    (high, low, volume)
+                };
+                let flow = (kind > 0.8).then(|| TradeFlow {
+                    quote_volume: volume * close,
+                    trade_count: 10,
+                    taker_buy_volume: volume * rng.next_f64(),
+                });
+                Candle {
+                    // Hourly bars starting 2024-01-01 so daily sessions roll over
+                    time: 1_704_067_200 + i as i64 * 3_600,
+                    open,
+                    high,
+                    low,
+                    close,
+                    volume,
+                    session: None,
+                    flow,
+                }
+            })
+            .collect()
+    }
+
+    /// Lengths around every warm-up boundary used below, plus a long tail.
+    fn lengths() -> impl Iterator<Item = usize> {
+        (0..=40).chain([97, 250])
+    }
+
+    fn stream<S>(mut state: S, candles: &[Candle]) -> Vec<S::Output>
+    where
+        S: IncrementalIndicator + Clone,
+        S::Output: Serialize,
+    {
+        let mut out = Vec::new();
+        for candle in candles {
+            // peek must agree with update and leave the state alone
+            let peeked = state.peek(candle).map(|p| serde_json::to_value(p).unwrap());
+            let updated = state.update(candle);
+            assert_eq!(
+                peeked,
+                updated.as_ref().map(|p| serde_json::to_value(p).unwrap())
+            );
+            out.extend(updated);
+        }
+        out
+    }
+
+    fn assert_matches<S, T>(name: &str, state: S, candles: &[Candle], expected: Vec<T>)
+    where
+        S: IncrementalIndicator + Clone,
+        S::Output: Serialize,
+        T: Serialize,
+    {
+        let streamed = serde_json::to_value(stream(state, candles)).unwrap();
+        let batch = serde_json::to_value(expected).unwrap();
+        assert_eq!(
+            streamed,
+            batch,
+            "{} differs for {} candles",
+            name,
+            candles.len()
+        );
+    }
+
+    /// The batch SAR seeds candle 0 from the candle 0 → 1 trend, which a stream cannot know yet.
+    fn sar_after_seed(candles: &[Candle]) -> Vec<crate::models::ParabolicSarPoint> {
+        let mut data = parabolic_sar::calculate(candles, 0.02, 0.2).data;
+        if !data.is_empty() {
+            data.remove(0);
+        }
+        data
+    }
+
+    #[test]
+    fn streaming_matches_batch_on_random_series() {
+        for seed in 1..=8 {
+            for len in lengths() {
+                let c = &random_candles(seed, len);
+                assert_matches("sma", SmaState::new(5), c, sma::calculate(c, 5).data);
+                assert_matches("ema", EmaState::new(5), c, ema::calculate(c, 5).data);
+                assert_matches(
+                    "bollinger",
+                    BollingerState::new(7, 2.0),
+                    c,
+                    bollinger::calculate(c, 7, 2.0),
+                );
+                assert_matches("rsi", RsiState::new(6), c, rsi::calculate(c, 6));
+                assert_matches(
+                    "macd",
+                    MacdState::new(3, 6, 4),
+                    c,
+                    macd::calculate(c, 3, 6, 4).data,
+                );
+                assert_matches(
+                    "stochastic",
+                    StochasticState::new(5, 3, 2),
+                    c,
+                    stochastic::calculate(c, 5, 3, 2).data,
+                );
+                for estimate in [CvdEstimate::BarDirection, CvdEstimate::CloseLocation] {
+                    assert_matches(
+                        "cvd",
+                        CvdState::new(estimate),
+                        c,
+                        cvd::calculate(c, estimate).data,
+                    );
+                }
+                for reset in [VwapReset::Cumulative, VwapReset::Daily, VwapReset::Weekly] {
+                    let batch = vwap::calculate_with_reset(c, reset, &MarketType::Crypto);
+                    let expected: Vec<_> = batch.data.into_iter().zip(batch.bands).collect();
+                    assert_matches(
+                        "vwap",
+                        VwapState::with_reset(reset, MarketType::Crypto),
+                        c,
+                        expected,
+                    );
+                }
+                assert_matches("atr", AtrState::new(5), c, atr::calculate(c, 5).data);
+                assert_matches(
+                    "supertrend",
+                    SupertrendState::new(5, 3.0),
+                    c,
+                    supertrend::calculate(c, 5, 3.0).data,
+                );
+                assert_matches(
+                    "parabolic_sar",
+                    ParabolicSarState::new(0.02, 0.2),
+                    c,
+                    sar_after_seed(c),
+                );
+                assert_matches(
+                    "donchian",
+                    DonchianState::new(6),
+                    c,
+                    donchian::calculate(c, 6).data,
+                );
+                assert_matches(
+                    "keltner",
+                    KeltnerState::new(6, 4, 2.0),
+                    c,
+                    keltner::calculate(c, 6, 4, 2.0).data,
+                );
+                assert_matches("mfi", MfiState::new(5), c, mfi::calculate(c, 5).data);
+                assert_matches("cmf", CmfState::new(5), c, cmf::calculate(c, 5).data);
+                assert_matches(
+                    "williams_r",
+                    WillrState::new(5),
+                    c,
+                    williams_r::calculate(c, 5).data,
+                );
+                // Documented warm-up exceptions: batch ADX needs 2 * period + 1 candles and
+                // batch OBV at least two, while the streams already emit at one fewer
+                if len != 2 * 5 {
+                    assert_matches("adx", AdxState::new(5), c, adx::calculate(c, 5).data);
+                }
+                if len != 1 {
+                    assert_matches("obv", ObvState::new(), c, obv::calculate(c).data);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn streaming_matches_batch_on_flat_series() {
+        for len in lengths() {
+            let c: Vec<Candle> = random_candles(3, len)
+                .into_iter()
+                .map(|candle| Candle {
+                    open: 50.0,
+                    high: 50.0,
+                    low: 50.0,
+                    close: 50.0,
+                    ..candle
+                })
+                .collect();
+            let c = &c;
+            assert_matches("rsi", RsiState::new(6), c, rsi::calculate(c, 6));
+            assert_matches(
+                "stochastic",
+                StochasticState::new(5, 3, 2),
+                c,
+                stochastic::calculate(c, 5, 3, 2).data,
+            );
+            assert_matches(
+                "supertrend",
+                SupertrendState::new(5, 3.0),
+                c,
+                supertrend::calculate(c, 5, 3.0).data,
+            );
+            assert_matches(
+                "parabolic_sar",
+                ParabolicSarState::new(0.02, 0.2),
+                c,
+                sar_after_seed(c),
+            );
+            assert_matches("mfi", MfiState::new(5), c, mfi::calculate(c, 5).data);
+            assert_matches("cmf", CmfState::new(5), c, cmf::calculate(c, 5).data);
+            assert_matches(
+                "williams_r",
+                WillrState::new(5),
+                c,
+                williams_r::calculate(c, 5).data,
+            );
+            if len != 2 * 5 {
+                assert_matches("adx", AdxState::new(5), c, adx::calculate(c, 5).data);
+            }
+            assert_matches(
+                "cvd",
+                CvdState::new(CvdEstimate::CloseLocation),
+                c,
+                cvd::calculate(c, CvdEstimate::CloseLocation).data,
+            );
+        }
+    }
+}
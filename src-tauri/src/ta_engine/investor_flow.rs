@@ -0,0 +1,127 @@
+use crate::models::{Candle, InvestorFlowPoint, InvestorNetBuy};
+
+/// Sums daily net buying per investor type into each candle and accumulates it from the first
+/// candle. A candle covers the days from its open up to the next candle (`step` for the last
+/// one), so weekly/monthly bars add up their trading days. Candles without any flow day (before
+/// the data starts, or today before KIS posts it) are skipped; the cumulative lines carry over.
+pub fn align(candles: &[Candle], step: i64, days: &[InvestorNetBuy]) -> Vec<InvestorFlowPoint> {
+    let mut sorted: Vec<&InvestorNetBuy> = days.iter().collect();
+    sorted.sort_by_key(|d| d.time);
+
+    let mut idx = 0;
+    let mut cumulative = (0.0, 0.0, 0.0);
+    let mut data = Vec::with_capacity(candles.len());
+    for (i, candle) in candles.iter().enumerate() {
+        let end = candles
+            .get(i + 1)
+            .map_or(candle.time + step.max(1), |next| next.time);
+        // Days before the first candle or skipped by a gap are not attributed to any bar
+        while idx < sorted.len() && sorted[idx].time < candle.time {
+            idx += 1;
+        }
+
+        let mut point: Option<InvestorFlowPoint> = None;
+        while idx < sorted.len() && sorted[idx].time < end {
+            let day = sorted[idx];
+            let point = point.get_or_insert(InvestorFlowPoint {
+                time: candle.time,
+                individual: 0.0,
+                foreign: 0.0,
+                institution: 0.0,
+                individual_amount: 0.0,
+                foreign_amount: 0.0,
+                institution_amount: 0.0,
+                individual_cumulative: 0.0,
+                foreign_cumulative: 0.0,
+                institution_cumulative: 0.0,
+            });
+            point.individual += day.individual;
+            point.foreign += day.foreign;
+            point.institution += day.institution;
+            point.individual_amount += day.individual_amount;
+            point.foreign_amount += day.foreign_amount;
+            point.institution_amount += day.institution_amount;
+            idx += 1;
+        }
+
+        if let Some(mut point) = point {
+            cumulative.0 += point.individual;
+            cumulative.1 += point.foreign;
+            cumulative.2 += point.institution;
+            point.individual_cumulative = cumulative.0;
+            point.foreign_cumulative = cumulative.1;
+            point.institution_cumulative = cumulative.2;
+            data.push(point);
+        }
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::candle;
+
+    const DAY: i64 = 86_400;
+    const WEEK: i64 = 7 * DAY;
+
+    fn day(time: i64, foreign: f64, institution: f64) -> InvestorNetBuy {
+        InvestorNetBuy {
+            time,
+            individual: -(foreign + institution),
+            foreign,
+            institution,
+            individual_amount: -(foreign + institution) * 1_000.0,
+            foreign_amount: foreign * 1_000.0,
+            institution_amount: institution * 1_000.0,
+        }
+    }
+
+    #[test]
+    fn weekly_bars_sum_their_trading_days() {
+        let candles: Vec<Candle> = (0..4).map(|week| candle(week * WEEK, 100.0)).collect();
+        // Unsorted on purpose; the day before the first bar and the day after the last bar's
+        // span belong to no bar
+        let mut days = vec![day(15 * DAY, -1.0, -1.0), day(-DAY, 9.0, 9.0)];
+        days.extend((0..5).map(|d| day(d * DAY, 1.0, 2.0)));
+        days.push(day(14 * DAY, -1.0, -1.0));
+        days.push(day(28 * DAY, 9.0, 9.0));
+
+        let points = align(&candles, WEEK, &days);
+
+        // The second and fourth weeks have no flow days and are skipped
+        let times: Vec<i64> = points.iter().map(|p| p.time).collect();
+        assert_eq!(times, [0, 2 * WEEK]);
+
+        let first = &points[0];
+        assert_eq!(
+            (first.foreign, first.institution, first.individual),
+            (5.0, 10.0, -15.0)
+        );
+        assert_eq!(first.foreign_amount, 5_000.0);
+        assert_eq!(first.foreign_cumulative, 5.0);
+
+        let third = &points[1];
+        assert_eq!((third.foreign, third.institution), (-2.0, -2.0));
+        assert_eq!(
+            (
+                third.individual_cumulative,
+                third.foreign_cumulative,
+                third.institution_cumulative
+            ),
+            (-11.0, 3.0, 8.0)
+        );
+    }
+
+    #[test]
+    fn daily_bars_take_one_day_each() {
+        let candles: Vec<Candle> = (0..3).map(|d| candle(d * DAY, 100.0)).collect();
+        let days: Vec<InvestorNetBuy> = (0..3).map(|d| day(d * DAY, d as f64, 1.0)).collect();
+
+        let points = align(&candles, DAY, &days);
+        let foreign: Vec<f64> = points.iter().map(|p| p.foreign).collect();
+        let cumulative: Vec<f64> = points.iter().map(|p| p.foreign_cumulative).collect();
+        assert_eq!(foreign, [0.0, 1.0, 2.0]);
+        assert_eq!(cumulative, [0.0, 1.0, 3.0]);
+    }
+}
@@ -1,3 +1,5 @@
+use super::atr::AtrState;
+use super::incremental::{EmaValue, IncrementalIndicator};
 use super::{atr, ema};
 use crate::models::{Candle, KeltnerPoint, KeltnerResult};
 
@@ -52,3 +54,37 @@ pub fn calculate(
         data,
     }
 }
+
+/// Streaming Keltner Channels. Emits the same points as `calculate`.
+#[derive(Debug, Clone)]
+pub struct KeltnerState {
+    ema: EmaValue,
+    atr: AtrState,
+    atr_multiplier: f64,
+}
+
+impl KeltnerState {
+    pub fn new(ema_period: usize, atr_period: usize, atr_multiplier: f64) -> Self {
+        Self {
+            ema: EmaValue::new(ema_period),
+            atr: AtrState::new(atr_period),
+            atr_multiplier,
+        }
+    }
+}
+
+impl IncrementalIndicator for KeltnerState {
+    type Output = KeltnerPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<KeltnerPoint> {
+        let mid = self.ema.update(candle.close);
+        let atr = self.atr.update(candle);
+        let (mid, atr) = (mid?, atr?.value);
+        Some(KeltnerPoint {
+            time: candle.time,
+            upper: mid + self.atr_multiplier * atr,
+            middle: mid,
+            lower: mid - self.atr_multiplier * atr,
+        })
+    }
+}
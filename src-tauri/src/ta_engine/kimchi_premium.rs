@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use crate::models::{Candle, KimchiPremiumPoint};
+
+/// Kimchi premium: how far the KRW-quoted price sits above the USDT-quoted price converted
+/// at USD/KRW, in percent. Only bars present in both crypto series are used; the FX rate is
+/// the close of the latest FX bar that closed by the time the bar closed (FX has no weekend
+/// bars). `step` and `fx_step` are the bar lengths in seconds of the crypto and FX series.
+pub fn calculate(
+    krw: &[Candle],
+    usd: &[Candle],
+    usd_krw: &[Candle],
+    step: i64,
+    fx_step: i64,
+    z_period: usize,
+) -> Vec<KimchiPremiumPoint> {
+    let usd_by_time: HashMap<i64, f64> = usd.iter().map(|c| (c.time, c.close)).collect();
+    let mut fx_idx = 0;
+    let mut fx_rate: Option<f64> = None;
+    let mut data: Vec<KimchiPremiumPoint> = Vec::new();
+
+    for candle in krw {
+        // A 1h FX bar is still open at the close of a shorter bar that starts inside it
+        while fx_idx < usd_krw.len() && usd_krw[fx_idx].time + fx_step <= candle.time + step {
+            fx_rate = Some(usd_krw[fx_idx].close)
+                .filter(|rate| *rate > 0.0)
+                .or(fx_rate);
+            fx_idx += 1;
+        }
+        let (Some(&usd_price), Some(rate)) = (usd_by_time.get(&candle.time), fx_rate) else {
+            continue;
+        };
+        if usd_price <= 0.0 {
+            continue;
+        }
+
+        data.push(KimchiPremiumPoint {
+            time: candle.time,
+            krw_price: candle.close,
+            usd_price,
+            usd_krw: rate,
+            premium: (candle.close / (usd_price * rate) - 1.0) * 100.0,
+            z_score: None,
+        });
+    }
+
+    fill_z_scores(&mut data, z_period);
+    data
+}
+
+/// Rolling z-score of the premium over the last `period` points (population std dev).
+fn fill_z_scores(data: &mut [KimchiPremiumPoint], period: usize) {
+    if period < 2 {
+        return;
+    }
+    for i in (period - 1)..data.len() {
+        let window = &data[i + 1 - period..=i];
+        let mean = window.iter().map(|p| p.premium).sum::<f64>() / period as f64;
+        let variance = window
+            .iter()
+            .map(|p| (p.premium - mean).powi(2))
+            .sum::<f64>()
+            / period as f64;
+        let std_dev = variance.sqrt();
+        if std_dev > f64::EPSILON {
+            data[i].z_score = Some((data[i].premium - mean) / std_dev);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::candle;
+
+    const DAY: i64 = 86_400;
+
+    #[test]
+    fn uses_bars_present_in_both_pairs_with_the_latest_fx_close() {
+        let krw: Vec<Candle> = (0..5).map(|d| candle(d * DAY, 1_430.0)).collect();
+        // The USDT pair is missing day 2
+        let usd: Vec<Candle> = [0, 1, 3, 4].iter().map(|&d| candle(d * DAY, 1.0)).collect();
+        // No FX bar before day 1, a zero quote on day 3 and nothing on day 4 (weekend)
+        let usd_krw = [
+            candle(DAY, 1_300.0),
+            candle(2 * DAY, 1_100.0),
+            candle(3 * DAY, 0.0),
+        ];
+
+        let data = calculate(&krw, &usd, &usd_krw, DAY, DAY, 0);
+
+        let points: Vec<(i64, f64)> = data.iter().map(|p| (p.time, p.usd_krw)).collect();
+        // Day 0 has no FX rate yet and day 2 has no USDT bar; days 3 and 4 carry day 2's rate
+        assert_eq!(
+            points,
+            [(DAY, 1_300.0), (3 * DAY, 1_100.0), (4 * DAY, 1_100.0)]
+        );
+        assert!((data[0].premium - 10.0).abs() < 1e-9);
+        assert!((data[1].premium - 30.0).abs() < 1e-9);
+        assert!(data.iter().all(|p| p.z_score.is_none()));
+    }
+
+    #[test]
+    fn intraday_bars_use_the_last_completed_fx_bar() {
+        const HOUR: i64 = 3_600;
+        const MINUTE: i64 = 60;
+        // 15m bars from 00:30 to 01:45
+        let times: Vec<i64> = (2..8).map(|q| q * 15 * MINUTE).collect();
+        let krw: Vec<Candle> = times.iter().map(|&t| candle(t, 1_430.0)).collect();
+        let usd: Vec<Candle> = times.iter().map(|&t| candle(t, 1.0)).collect();
+        let usd_krw = [candle(0, 1_300.0), candle(HOUR, 1_100.0)];
+
+        let data = calculate(&krw, &usd, &usd_krw, 15 * MINUTE, HOUR, 0);
+
+        let points: Vec<(i64, f64)> = data.iter().map(|p| (p.time, p.usd_krw)).collect();
+        // The 00:30 bar closes before the first FX bar does, and the 01:00 FX bar closes at
+        // 02:00, so only the 01:45 bar sees it
+        assert_eq!(
+            points,
+            [
+                (45 * MINUTE, 1_300.0),
+                (HOUR, 1_300.0),
+                (HOUR + 15 * MINUTE, 1_300.0),
+                (HOUR + 30 * MINUTE, 1_300.0),
+                (HOUR + 45 * MINUTE, 1_100.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn z_scores_start_after_a_full_window_of_varying_premiums() {
+        let krw: Vec<Candle> = [100.0, 100.0, 100.0, 103.0]
+            .iter()
+            .enumerate()
+            .map(|(d, &close)| candle(d as i64 * DAY, close))
+            .collect();
+        let usd: Vec<Candle> = (0..4).map(|d| candle(d * DAY, 1.0)).collect();
+        let usd_krw = [candle(0, 100.0)];
+
+        let data = calculate(&krw, &usd, &usd_krw, DAY, DAY, 3);
+
+        // A flat window has no spread, so no z-score
+        let z: Vec<Option<f64>> = data.iter().map(|p| p.z_score).collect();
+        assert_eq!(z[..3], [None, None, None]);
+        // Premiums 0, 0, 3: mean 1, population std dev sqrt(2)
+        assert!((z[3].unwrap() - 2.0 / 2f64.sqrt()).abs() < 1e-9);
+    }
+}
@@ -1,4 +1,5 @@
 use super::ema;
+use super::incremental::{EmaValue, IncrementalIndicator};
 use crate::models::{Candle, MacdPoint, MacdResult};
 
 /// Calculate MACD: EMA(fast) - EMA(slow), Signal = EMA(signal_period, MACD line), Histogram = MACD - Signal.
@@ -57,3 +58,38 @@ pub fn calculate(
 
     MacdResult { data }
 }
+
+/// Streaming MACD. Emits the same points as `calculate`.
+#[derive(Debug, Clone)]
+pub struct MacdState {
+    fast: EmaValue,
+    slow: EmaValue,
+    signal: EmaValue,
+}
+
+impl MacdState {
+    pub fn new(fast_period: usize, slow_period: usize, signal_period: usize) -> Self {
+        Self {
+            fast: EmaValue::new(fast_period),
+            slow: EmaValue::new(slow_period),
+            signal: EmaValue::new(signal_period),
+        }
+    }
+}
+
+impl IncrementalIndicator for MacdState {
+    type Output = MacdPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<MacdPoint> {
+        let fast = self.fast.update(candle.close);
+        let slow = self.slow.update(candle.close);
+        let macd = fast? - slow?;
+        let signal = self.signal.update(macd)?;
+        Some(MacdPoint {
+            time: candle.time,
+            macd,
+            signal,
+            histogram: macd - signal,
+        })
+    }
+}
@@ -0,0 +1,287 @@
+use crate::models::{MarketType, PerformanceMetrics, PerformancePoint};
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 86_400.0;
+
+/// interval과 시장 운영 시간에 맞춘 연간 봉 개수.
+pub fn periods_per_year(interval: &str, market: &MarketType) -> f64 {
+    let (trading_days, session_hours) = match market {
+        MarketType::Crypto => (365.0, 24.0),
+        MarketType::Forex => (260.0, 24.0),
+        MarketType::UsStock | MarketType::KrStock => (252.0, 6.5),
+    };
+
+    let trimmed = interval.trim();
+    let digits: String = trimmed.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let value = digits.parse::<f64>().unwrap_or(1.0).max(1.0);
+    let unit = trimmed[digits.len()..].chars().next().unwrap_or('d');
+
+    match unit {
+        'm' => trading_days * session_hours * 60.0 / value,
+        'h' => trading_days * session_hours / value,
+        'w' => 52.0 / value,
+        'M' => 12.0 / value,
+        'Y' => 1.0 / value,
+        _ => trading_days / value,
+    }
+}
+
+/// 자산 곡선에서 성과 지표를 계산한다.
+/// `weights`는 각 시점에서 다음 기간 동안 유지한 포지션 비중이며, 비어 있으면 노출도/회전율은 0이다.
+/// 이어지는 기간이 없는 마지막 시점의 비중은 쓰지 않는다.
+pub fn from_equity(
+    equity: &[PerformancePoint],
+    weights: &[f64],
+    periods_per_year: f64,
+    risk_free_rate: f64,
+) -> PerformanceMetrics {
+    if equity.len() < 2 {
+        return PerformanceMetrics {
+            observations: equity.len(),
+            periods_per_year,
+            ..Default::default()
+        };
+    }
+
+    let returns: Vec<f64> = equity
+        .windows(2)
+        .map(|w| {
+            if w[0].value.abs() > f64::EPSILON {
+                w[1].value / w[0].value - 1.0
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    let first = equity[0].value;
+    let last = equity[equity.len() - 1].value;
+    let total_return = if first > 0.0 { last / first - 1.0 } else { 0.0 };
+
+    // 시각이 있으면 달력 기준, 없으면 봉 개수 기준으로 기간을 환산
+    let span = (equity[equity.len() - 1].time - equity[0].time) as f64;
+    let years = if span > 0.0 {
+        span / SECONDS_PER_YEAR
+    } else {
+        returns.len() as f64 / periods_per_year
+    };
+
+    let mut metrics = return_statistics(&returns, periods_per_year, risk_free_rate);
+    metrics.observations = equity.len();
+    metrics.total_return = total_return;
+    metrics.cagr = if years > 0.0 && first > 0.0 && last > 0.0 {
+        (last / first).powf(1.0 / years) - 1.0
+    } else {
+        0.0
+    };
+
+    apply_drawdown(&mut metrics, equity);
+    metrics.calmar = if metrics.max_drawdown > 0.0 {
+        metrics.cagr / metrics.max_drawdown
+    } else {
+        0.0
+    };
+
+    let held = &weights[..weights.len().min(returns.len())];
+    apply_weights(&mut metrics, held, periods_per_year);
+    metrics
+}
+
+/// 기간 수익률 시리즈에서 성과 지표를 계산한다 (자산 곡선은 1.0에서 시작).
+/// `weights`는 수익률과 같은 기간별 포지션 비중이다.
+pub fn from_returns(
+    returns: &[f64],
+    weights: &[f64],
+    periods_per_year: f64,
+    risk_free_rate: f64,
+) -> PerformanceMetrics {
+    let mut value = 1.0;
+    let mut equity = Vec::with_capacity(returns.len() + 1);
+    equity.push(PerformancePoint { time: 0, value });
+    for r in returns {
+        value *= 1.0 + r;
+        equity.push(PerformancePoint { time: 0, value });
+    }
+
+    from_equity(&equity, weights, periods_per_year, risk_free_rate)
+}
+
+fn return_statistics(returns: &[f64], periods_per_year: f64, risk_free_rate: f64) -> PerformanceMetrics {
+    let n = returns.len() as f64;
+    let rf_period = (1.0 + risk_free_rate).powf(1.0 / periods_per_year) - 1.0;
+    let excess: Vec<f64> = returns.iter().map(|r| r - rf_period).collect();
+    let mean_excess = excess.iter().sum::<f64>() / n;
+
+    // 표본 표준편차 (N-1)
+    let std = if returns.len() > 1 {
+        let mean = returns.iter().sum::<f64>() / n;
+        (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+    } else {
+        0.0
+    };
+    let downside = (excess.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / n).sqrt();
+
+    let annual = periods_per_year.sqrt();
+    let gross_profit: f64 = returns.iter().filter(|r| **r > 0.0).sum();
+    let gross_loss: f64 = -returns.iter().filter(|r| **r < 0.0).sum::<f64>();
+    let active = returns.iter().filter(|r| **r != 0.0).count();
+    let wins = returns.iter().filter(|r| **r > 0.0).count();
+
+    PerformanceMetrics {
+        periods_per_year,
+        annualized_volatility: std * annual,
+        sharpe: if std > 0.0 { mean_excess / std * annual } else { 0.0 },
+        sortino: if downside > 0.0 {
+            mean_excess / downside * annual
+        } else {
+            0.0
+        },
+        // 포지션이 없어 수익률이 0인 기간은 적중률 계산에서 제외
+        hit_rate: if active > 0 {
+            wins as f64 / active as f64
+        } else {
+            0.0
+        },
+        profit_factor: (gross_loss > 0.0).then(|| gross_profit / gross_loss),
+        ..Default::default()
+    }
+}
+
+fn apply_drawdown(metrics: &mut PerformanceMetrics, equity: &[PerformancePoint]) {
+    let has_time = equity[equity.len() - 1].time != equity[0].time;
+    let mut peak_value = equity[0].value;
+    let mut peak_index = 0usize;
+    let mut underwater_since: Option<usize> = None;
+
+    for (i, point) in equity.iter().enumerate() {
+        if point.value >= peak_value {
+            if let Some(start) = underwater_since.take() {
+                metrics.max_drawdown_duration = metrics.max_drawdown_duration.max(i - start);
+            }
+            peak_value = point.value;
+            peak_index = i;
+            continue;
+        }
+
+        underwater_since.get_or_insert(peak_index);
+        let drawdown = if peak_value > 0.0 {
+            (peak_value - point.value) / peak_value
+        } else {
+            0.0
+        };
+        if drawdown > metrics.max_drawdown {
+            metrics.max_drawdown = drawdown;
+            if has_time {
+                metrics.max_drawdown_peak_time = Some(equity[peak_index].time);
+                metrics.max_drawdown_trough_time = Some(point.time);
+            }
+        }
+    }
+
+    // 회복하지 못한 채 끝난 구간
+    if let Some(start) = underwater_since {
+        metrics.max_drawdown_duration = metrics.max_drawdown_duration.max(equity.len() - 1 - start);
+    }
+}
+
+fn apply_weights(metrics: &mut PerformanceMetrics, weights: &[f64], periods_per_year: f64) {
+    if weights.is_empty() {
+        return;
+    }
+
+    let invested = weights.iter().filter(|w| w.abs() > f64::EPSILON).count();
+    metrics.exposure = invested as f64 / weights.len() as f64;
+
+    if weights.len() > 1 {
+        let traded: f64 = weights.windows(2).map(|w| (w[1] - w[0]).abs()).sum();
+        metrics.turnover = traded / 2.0 * periods_per_year / (weights.len() - 1) as f64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn sharpe_and_sortino_match_hand_computed_values() {
+        let returns = [0.1, -0.05, 0.02, 0.03];
+        let metrics = from_returns(&returns, &[], 252.0, 0.0);
+
+        // mean 0.025, squared deviations sum to 0.0113 over n - 1 = 3
+        let std = (0.0113_f64 / 3.0).sqrt();
+        assert_close(metrics.sharpe, 0.025 / std * 252.0_f64.sqrt());
+        assert_close(metrics.annualized_volatility, std * 252.0_f64.sqrt());
+        // Downside deviation sqrt(0.05^2 / 4) = 0.025 equals the mean
+        assert_close(metrics.sortino, 252.0_f64.sqrt());
+        assert_close(metrics.hit_rate, 0.75);
+        assert_close(metrics.profit_factor.unwrap(), 0.15 / 0.05);
+        assert_close(metrics.total_return, 1.1 * 0.95 * 1.02 * 1.03 - 1.0);
+        assert_close(metrics.max_drawdown, 0.05);
+        assert_eq!(metrics.observations, 5);
+    }
+
+    #[test]
+    fn profit_factor_is_undefined_without_losing_periods() {
+        let winning = from_returns(&[0.01, 0.0, 0.02], &[], 252.0, 0.0);
+        assert_eq!(winning.profit_factor, None);
+
+        let losing = from_returns(&[-0.01, 0.0], &[], 252.0, 0.0);
+        assert_eq!(losing.profit_factor, Some(0.0));
+    }
+
+    #[test]
+    fn cagr_uses_calendar_time_or_bar_count() {
+        let year = SECONDS_PER_YEAR as i64;
+        let point = |time, value| PerformancePoint { time, value };
+        let equity = [point(0, 100.0), point(year, 90.0), point(2 * year, 121.0)];
+        let metrics = from_equity(&equity, &[], 252.0, 0.0);
+        // 100 -> 121 over two years
+        assert_close(metrics.cagr, 0.1);
+        assert_close(metrics.max_drawdown, 0.1);
+        assert_close(metrics.calmar, 1.0);
+        assert_eq!(metrics.max_drawdown_peak_time, Some(0));
+        assert_eq!(metrics.max_drawdown_trough_time, Some(year));
+        assert_eq!(metrics.max_drawdown_duration, 2);
+
+        // Without timestamps four quarterly returns span one year
+        let metrics = from_returns(&[0.1, -0.05, 0.02, 0.03], &[], 4.0, 0.0);
+        assert_close(metrics.cagr, 1.1 * 0.95 * 1.02 * 1.03 - 1.0);
+    }
+
+    #[test]
+    fn weights_align_with_return_periods() {
+        let returns = [0.01, 0.02, 0.0, -0.01];
+        let from_periods = from_returns(&returns, &[1.0, 1.0, 0.0, 0.5], 4.0, 0.0);
+        assert_close(from_periods.exposure, 0.75);
+        // |1-1| + |0-1| + |0.5-0| = 1.5 traded, half of it per side, over 3 changes
+        assert_close(from_periods.turnover, 0.75 * 4.0 / 3.0);
+
+        // Point-aligned weights from a backtest carry one extra trailing value
+        let mut value = 1.0;
+        let mut equity = vec![PerformancePoint { time: 0, value }];
+        for r in returns {
+            value *= 1.0 + r;
+            equity.push(PerformancePoint { time: 0, value });
+        }
+        let from_points = from_equity(&equity, &[1.0, 1.0, 0.0, 0.5, 0.0], 4.0, 0.0);
+        assert_close(from_points.exposure, from_periods.exposure);
+        assert_close(from_points.turnover, from_periods.turnover);
+    }
+
+    #[test]
+    fn periods_per_year_follows_market_hours() {
+        assert_close(periods_per_year("1d", &MarketType::UsStock), 252.0);
+        assert_close(periods_per_year("1h", &MarketType::Crypto), 365.0 * 24.0);
+        // 252 sessions of 6.5 hours, four bars an hour
+        assert_close(periods_per_year("15m", &MarketType::KrStock), 1_638.0 * 4.0);
+        assert_close(periods_per_year("1w", &MarketType::Forex), 52.0);
+    }
+}
@@ -1,3 +1,6 @@
+use std::collections::VecDeque;
+
+use super::incremental::{self, IncrementalIndicator};
 use crate::models::{Candle, MfiPoint, MfiResult};
 
 /// Money Flow Index — volume-weighted RSI using Typical Price.
@@ -44,3 +47,60 @@ pub fn calculate(candles: &[Candle], period: usize) -> MfiResult {
 
     MfiResult { period, data }
 }
+
+/// Streaming MFI. Emits the same points as `calculate`.
+#[derive(Debug, Clone)]
+pub struct MfiState {
+    period: usize,
+    /// (typical price, volume) for the last `period + 1` candles
+    window: VecDeque<(f64, f64)>,
+}
+
+impl MfiState {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            window: VecDeque::with_capacity(period + 2),
+        }
+    }
+}
+
+impl IncrementalIndicator for MfiState {
+    type Output = MfiPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<MfiPoint> {
+        let typical = (candle.high + candle.low + candle.close) / 3.0;
+        if self.period == 0
+            || !incremental::push_window(
+                &mut self.window,
+                (typical, candle.volume),
+                self.period + 1,
+            )
+        {
+            return None;
+        }
+
+        let mut pos_flow = 0.0;
+        let mut neg_flow = 0.0;
+        for j in 1..self.window.len() {
+            let (prev_typical, _) = self.window[j - 1];
+            let (typical, volume) = self.window[j];
+            let mf = typical * volume;
+            if typical > prev_typical {
+                pos_flow += mf;
+            } else if typical < prev_typical {
+                neg_flow += mf;
+            }
+        }
+
+        let value = if neg_flow.abs() < f64::EPSILON {
+            100.0
+        } else {
+            100.0 - 100.0 / (1.0 + pos_flow / neg_flow)
+        };
+        Some(MfiPoint {
+            time: candle.time,
+            value,
+        })
+    }
+}
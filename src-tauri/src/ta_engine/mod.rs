@@ -2,43 +2,126 @@ pub mod atr;
 pub mod bollinger;
 pub mod choppiness;
 pub mod cmf;
+pub mod consensus;
 pub mod donchian;
 pub mod ema;
 pub mod helpers;
 pub mod hma;
 pub mod ichimoku;
+pub mod incremental;
+pub mod investor_flow;
 pub mod keltner;
+pub mod kimchi_premium;
 pub mod macd;
+pub mod metrics;
 pub mod mfi;
 pub mod obv;
 pub mod parabolic_sar;
 pub mod rsi;
+pub mod session;
 pub mod signal;
 pub mod sma;
 pub mod stochastic;
 pub mod supertrend;
 pub mod vwap;
 pub mod williams_r;
+pub mod adjustment;
 pub mod adx;
 pub mod cvd;
+pub mod derivatives;
 pub mod smc;
 pub mod anchored_vwap;
 pub mod auto_fib;
 pub mod stc;
 pub mod wma;
 
-use crate::models::{AnalysisParams, AnalysisResponse, Candle};
+use crate::models::{
+    AdxResult, AnalysisParams, AnalysisResponse, BollingerBandsPoint, Candle, CandleSession,
+    CmfResult, ConsensusResult, InvestorFlowResult, KeltnerResult, KimchiPremiumResult, MacdResult,
+    ObvResult, ParabolicSarResult, RsiPoint, SignalPoint, StochasticResult, SupertrendResult,
+    VwapResult,
+};
+
+/// 신호 탐지에 재사용할 이미 계산된 지표. 비어 있거나 전략 설정과 기간이 다른 항목은
+/// 필요할 때 전략 설정으로 다시 계산한다. 외부에서 불러오는 자료(김치 프리미엄,
+/// 투자자별 순매수)는 다시 계산할 수 없으므로 비어 있으면 해당 신호를 내지 않는다.
+#[derive(Default)]
+pub struct SignalInputs<'a> {
+    pub bollinger: Option<&'a [BollingerBandsPoint]>,
+    pub rsi: Option<&'a [RsiPoint]>,
+    pub vwap: Option<&'a VwapResult>,
+    pub supertrend: Option<&'a SupertrendResult>,
+    pub parabolic_sar: Option<&'a ParabolicSarResult>,
+    pub macd: Option<&'a MacdResult>,
+    pub stochastic: Option<&'a StochasticResult>,
+    pub obv: Option<&'a ObvResult>,
+    pub cmf: Option<&'a CmfResult>,
+    pub keltner: Option<&'a KeltnerResult>,
+    pub adx: Option<&'a AdxResult>,
+    pub kimchi_premium: Option<&'a KimchiPremiumResult>,
+    pub investor_flow: Option<&'a InvestorFlowResult>,
+}
+
+/// 캔들 밖에서 불러온 신호 재료. 분석 응답에 그대로 실리고 신호 탐지에도 쓰인다.
+#[derive(Default)]
+pub struct ExternalData {
+    pub kimchi_premium: Option<KimchiPremiumResult>,
+    pub investor_flow: Option<InvestorFlowResult>,
+}
+
+impl ExternalData {
+    /// 외부 자료만 채운 신호 입력. 지표는 신호 탐지에서 전략 설정으로 계산한다.
+    pub fn signal_inputs(&self) -> SignalInputs<'_> {
+        SignalInputs {
+            kimchi_premium: self.kimchi_premium.as_ref(),
+            investor_flow: self.investor_flow.as_ref(),
+            ..Default::default()
+        }
+    }
+}
 
 pub fn analyze(candles: &[Candle], params: &AnalysisParams) -> AnalysisResponse {
+    analyze_with(candles, params, ExternalData::default())
+}
+
+/// 외부 자료를 함께 넣어 분석한다. 외부 자료의 신호도 합의 신호에 반영된다.
+pub fn analyze_with(
+    candles: &[Candle],
+    params: &AnalysisParams,
+    external: ExternalData,
+) -> AnalysisResponse {
+    // Extended-hours bars stay on the chart but are left out of every indicator and signal
+    let extended = |c: &Candle| matches!(c.session, Some(CandleSession::Pre | CandleSession::Post));
+    if params.regular_hours_only && candles.iter().any(extended) {
+        let regular: Vec<Candle> = candles.iter().filter(|c| !extended(c)).cloned().collect();
+        let mut response = analyze_with(&regular, params, external);
+        response.candles = candles.to_vec();
+        return response;
+    }
+
     let bb = bollinger::calculate(candles, params.bb_period, params.bb_multiplier);
     let rsi_data = rsi::calculate(candles, params.rsi_period);
-    let vwap_result = Some(vwap::calculate(candles));
+    let vwap_reset = vwap::resolve_reset(params.vwap_reset, &params.interval);
+    let vwap_result = Some(vwap::calculate_with_reset(
+        candles,
+        vwap_reset,
+        &params.market,
+    ));
     let atr_result = Some(atr::calculate(candles, 14));
     let ichimoku_result = Some(ichimoku::calculate(candles, 9, 26, 52, 26));
-    let supertrend_result = Some(supertrend::calculate(candles, 10, 3.0));
-    let parabolic_sar_result = Some(parabolic_sar::calculate(candles, 0.02, 0.2));
-
-    let mut signals = Vec::new();
+    // Overlays share the strategy settings so the chart shows what the signals are based on
+    let st_config = &params.signal_strategies.supertrend_adx_config;
+    let sar_config = &params.signal_strategies.parabolic_sar_config;
+    let supertrend_result = Some(supertrend::calculate(
+        candles,
+        st_config.period,
+        st_config.multiplier,
+    ));
+    let parabolic_sar_result = Some(parabolic_sar::calculate(
+        candles,
+        sar_config.step,
+        sar_config.max_step,
+    ));
 
     // SMA — only for requested periods
     let sma_results: Vec<_> = params
@@ -122,7 +205,7 @@ pub fn analyze(candles: &[Candle], params: &AnalysisParams) -> AnalysisResponse
 
     // CVD
     let cvd_result = if params.show_cvd {
-        Some(cvd::calculate(candles))
+        Some(cvd::calculate(candles, params.cvd_estimate))
     } else {
         None
     };
@@ -143,7 +226,15 @@ pub fn analyze(candles: &[Candle], params: &AnalysisParams) -> AnalysisResponse
     let anchored_vwap_result = params
         .anchored_vwap
         .as_ref()
-        .map(|ap| anchored_vwap::calculate(candles, ap.anchor_time));
+        .and_then(|ap| ap.anchor_time)
+        .map(|anchor_time| anchored_vwap::calculate(candles, anchor_time));
+    let anchored_vwaps = params
+        .anchored_vwap
+        .as_ref()
+        .map(|ap| {
+            anchored_vwap::calculate_anchors(candles, &ap.anchors, ap.swing_length, &params.market)
+        })
+        .unwrap_or_default();
 
     // Auto Fibonacci
     let auto_fib_result = params
@@ -153,25 +244,149 @@ pub fn analyze(candles: &[Candle], params: &AnalysisParams) -> AnalysisResponse
 
     // ─── Quant Signal Strategies ───
 
+    let inputs = SignalInputs {
+        bollinger: Some(&bb),
+        rsi: Some(&rsi_data),
+        vwap: vwap_result.as_ref(),
+        supertrend: supertrend_result.as_ref(),
+        parabolic_sar: parabolic_sar_result.as_ref(),
+        macd: macd_result.as_ref(),
+        stochastic: stoch_result.as_ref(),
+        obv: obv_result.as_ref(),
+        cmf: cmf_result.as_ref(),
+        keltner: keltner_result.as_ref(),
+        adx: adx_result.as_ref(),
+        kimchi_premium: external.kimchi_premium.as_ref(),
+        investor_flow: external.investor_flow.as_ref(),
+    };
+    let mut signals = detect_signals(candles, params, &inputs);
+    let consensus_result = apply_consensus(candles, params, &mut signals);
+
+    AnalysisResponse {
+        candles: candles.to_vec(),
+        bollinger_bands: bb,
+        rsi: rsi_data,
+        signals,
+        sma: sma_results,
+        ema: ema_results,
+        hma: hma_results,
+        macd: macd_result,
+        stochastic: stoch_result,
+        obv: obv_result,
+        vwap: vwap_result,
+        atr: atr_result,
+        ichimoku: ichimoku_result,
+        supertrend: supertrend_result,
+        parabolic_sar: parabolic_sar_result,
+        donchian: donchian_result,
+        keltner: keltner_result,
+        mfi: mfi_result,
+        cmf: cmf_result,
+        choppiness: choppiness_result,
+        williams_r: willr_result,
+        adx: adx_result,
+        cvd: cvd_result,
+        stc: stc_result,
+        smc: smc_result,
+        anchored_vwap: anchored_vwap_result,
+        anchored_vwaps,
+        auto_fib: auto_fib_result,
+        consensus: consensus_result,
+        kimchi_premium: external.kimchi_premium,
+        funding_rate: None,
+        open_interest: None,
+        long_short_ratio: None,
+        investor_flow: external.investor_flow,
+        symbol: params.symbol.clone(),
+        interval: params.interval.clone(),
+        data_source: String::new(),
+        source_interval: params.interval.clone(),
+        corporate_actions: Vec::new(),
+        warnings: Vec::new(),
+    }
+}
+
+/// `signal_strategies.consensus`가 지정되면 합의 신호를 `signals`에 합치고 봉별 점수를 반환한다.
+pub fn apply_consensus(
+    candles: &[Candle],
+    params: &AnalysisParams,
+    signals: &mut Vec<SignalPoint>,
+) -> Option<ConsensusResult> {
+    let consensus_params = params.signal_strategies.consensus.as_ref()?;
+    let (result, composite) = consensus::calculate(candles, signals, consensus_params);
+    signals.extend(composite);
+    signals.sort_by_key(|s| s.time);
+    Some(result)
+}
+
+/// 활성화된 퀀트 신호 전략을 실행해 시간순으로 정렬된 신호를 반환한다.
+pub fn detect_signals(
+    candles: &[Candle],
+    params: &AnalysisParams,
+    inputs: &SignalInputs<'_>,
+) -> Vec<SignalPoint> {
     let ss = &params.signal_strategies;
+    let mut signals = Vec::new();
 
     // Compute fallback indicators only when needed by strategies but not already computed
+    let rsi_fallback;
+    let rsi_data = match inputs.rsi {
+        Some(r) => r,
+        None => {
+            rsi_fallback = if ss.stoch_rsi_combined
+                || ss.ibs_mean_reversion
+                || ss.rsi_divergence
+                || inputs.kimchi_premium.is_some()
+                || inputs.investor_flow.is_some()
+            {
+                rsi::calculate(candles, params.rsi_period)
+            } else {
+                Vec::new()
+            };
+            &rsi_fallback
+        }
+    };
+    let macd_fallback = match inputs.macd {
+        Some(_) => None,
+        None => params.macd.as_ref().and_then(|mp| {
+            (ss.ttm_squeeze || ss.macd_hist_reversal)
+                .then(|| macd::calculate(candles, mp.fast_period, mp.slow_period, mp.signal_period))
+        }),
+    };
+    let macd_result = inputs.macd.or(macd_fallback.as_ref());
+    let st_fallback;
     let adx_fallback;
+    let stoch_fallback;
     let cmf_fallback;
     let obv_fallback;
+    let bb_fallback;
     let kelt_fallback;
+    let vwap_fallback;
+    let sar_fallback;
 
     // 1. Supertrend + ADX
     if ss.supertrend_adx {
-        let st = supertrend_result.as_ref().unwrap();
-        let adx_ref = match adx_result.as_ref() {
-            Some(r) => r,
-            None => {
-                adx_fallback = adx::calculate(candles, 14);
+        let config = &ss.supertrend_adx_config;
+        let st = match inputs.supertrend {
+            Some(r) if r.period == config.period && r.multiplier == config.multiplier => r,
+            _ => {
+                st_fallback = supertrend::calculate(candles, config.period, config.multiplier);
+                &st_fallback
+            }
+        };
+        let adx_ref = match inputs.adx {
+            Some(r) if r.period == config.adx_period => r,
+            _ => {
+                adx_fallback = adx::calculate(candles, config.adx_period);
                 &adx_fallback
             }
         };
-        signals.extend(signal::detect_supertrend_adx(st, adx_ref, candles));
+        signals.extend(signal::detect_supertrend_adx(
+            st,
+            adx_ref,
+            candles,
+            &ss.supertrend_adx_config,
+        ));
     }
 
     // 2. EMA Crossover (needs dedicated fast/slow EMA)
@@ -183,113 +398,268 @@ pub fn analyze(candles: &[Candle], params: &AnalysisParams) -> AnalysisResponse
 
     // 3. Stochastic + RSI combined
     if ss.stoch_rsi_combined {
-        if let Some(ref stoch) = stoch_result {
-            signals.extend(signal::detect_stoch_rsi_combined(stoch, &rsi_data, candles));
+        let stoch = match inputs.stochastic {
+            Some(r) => Some(r),
+            None => {
+                stoch_fallback = params
+                    .stochastic
+                    .as_ref()
+                    .map(|sp| stochastic::calculate(candles, sp.k_period, sp.d_period, sp.smooth));
+                stoch_fallback.as_ref()
+            }
+        };
+        if let Some(stoch) = stoch {
+            signals.extend(signal::detect_stoch_rsi_combined(
+                stoch,
+                rsi_data,
+                candles,
+                &ss.stoch_rsi_config,
+            ));
         }
     }
 
     // 4. CMF + OBV
     if ss.cmf_obv {
-        let cmf_ref = match cmf_result.as_ref() {
-            Some(r) => r,
-            None => {
-                cmf_fallback = cmf::calculate(candles, 20);
+        let cmf_period = ss.cmf_obv_config.cmf_period;
+        let cmf_ref = match inputs.cmf {
+            Some(r) if r.period == cmf_period => r,
+            _ => {
+                cmf_fallback = cmf::calculate(candles, cmf_period);
                 &cmf_fallback
             }
         };
-        let obv_ref = match obv_result.as_ref() {
+        let obv_ref = match inputs.obv {
             Some(r) => r,
             None => {
                 obv_fallback = obv::calculate(candles);
                 &obv_fallback
             }
         };
-        signals.extend(signal::detect_cmf_obv(cmf_ref, obv_ref, candles));
+        signals.extend(signal::detect_cmf_obv(
+            cmf_ref,
+            obv_ref,
+            candles,
+            &ss.cmf_obv_config,
+        ));
     }
 
     // 5. TTM Squeeze (needs BB, Keltner, MACD)
     if ss.ttm_squeeze {
-        if let Some(ref macd_r) = macd_result {
-            let kelt_ref = match keltner_result.as_ref() {
+        if let Some(macd_r) = macd_result {
+            let bb = match inputs.bollinger {
                 Some(r) => r,
                 None => {
-                    kelt_fallback = keltner::calculate(candles, 20, 10, 2.0);
+                    bb_fallback =
+                        bollinger::calculate(candles, params.bb_period, params.bb_multiplier);
+                    &bb_fallback
+                }
+            };
+            let config = &ss.ttm_squeeze_config;
+            let kelt_ref = match inputs.keltner {
+                Some(r)
+                    if r.ema_period == config.ema_period
+                        && r.atr_period == config.atr_period
+                        && r.atr_multiplier == config.atr_multiplier =>
+                {
+                    r
+                }
+                _ => {
+                    kelt_fallback = keltner::calculate(
+                        candles,
+                        config.ema_period,
+                        config.atr_period,
+                        config.atr_multiplier,
+                    );
                     &kelt_fallback
                 }
             };
-            signals.extend(signal::detect_ttm_squeeze(&bb, kelt_ref, macd_r, candles));
+            signals.extend(signal::detect_ttm_squeeze(bb, kelt_ref, macd_r, candles));
         }
     }
 
     // 6. VWAP Breakout
     if ss.vwap_breakout {
-        if let Some(ref vwap_r) = vwap_result {
-            signals.extend(signal::detect_vwap_breakout(vwap_r, candles));
-        }
+        // A strategy-specific reset overrides the chart VWAP
+        let reset = vwap::resolve_reset(
+            ss.vwap_breakout_config.reset.unwrap_or(params.vwap_reset),
+            &params.interval,
+        );
+        let vwap_r = match inputs.vwap {
+            Some(r) if r.reset == reset => r,
+            _ => {
+                vwap_fallback = vwap::calculate_with_reset(candles, reset, &params.market);
+                &vwap_fallback
+            }
+        };
+        signals.extend(signal::detect_vwap_breakout(
+            vwap_r,
+            candles,
+            &ss.vwap_breakout_config,
+        ));
     }
 
     // 7. Parabolic SAR reversal
     if ss.parabolic_sar {
-        if let Some(ref sar) = parabolic_sar_result {
-            signals.extend(signal::detect_parabolic_sar_reversal(sar, candles));
-        }
+        let config = &ss.parabolic_sar_config;
+        let sar = match inputs.parabolic_sar {
+            Some(r) if r.step == config.step && r.max_step == config.max_step => r,
+            _ => {
+                sar_fallback = parabolic_sar::calculate(candles, config.step, config.max_step);
+                &sar_fallback
+            }
+        };
+        signals.extend(signal::detect_parabolic_sar_reversal(sar, candles));
     }
 
     // 8. MACD Histogram reversal
     if ss.macd_hist_reversal {
-        if let Some(ref macd_r) = macd_result {
+        if let Some(macd_r) = macd_result {
             signals.extend(signal::detect_macd_hist_reversal(macd_r, candles));
         }
     }
 
     // 9. IBS Mean Reversion
     if ss.ibs_mean_reversion {
-        signals.extend(signal::detect_ibs_mean_reversion(&rsi_data, candles));
+        signals.extend(signal::detect_ibs_mean_reversion(
+            rsi_data,
+            candles,
+            &ss.ibs_mean_reversion_config,
+        ));
     }
 
     // 10. RSI Divergence
     if ss.rsi_divergence {
         signals.extend(signal::detect_rsi_divergence(
-            &rsi_data,
+            rsi_data,
             candles,
             ss.divergence_swing_length,
         ));
     }
 
+    // 11. Kimchi premium extremes
+    if let Some(premium) = inputs.kimchi_premium {
+        signals.extend(signal::detect_kimchi_premium_extremes(
+            &premium.data,
+            candles,
+            rsi_data,
+            premium.band,
+        ));
+    }
+
+    // 12. Foreign + institutional agreement
+    if let Some(flow) = inputs.investor_flow {
+        signals.extend(signal::detect_investor_flow_agreement(
+            &flow.data,
+            candles,
+            rsi_data,
+            flow.streak,
+        ));
+    }
+
     // Sort all signals by time
     signals.sort_by_key(|s| s.time);
+    signals
+}
 
-    AnalysisResponse {
-        candles: candles.to_vec(),
-        bollinger_bands: bb,
-        rsi: rsi_data,
-        signals,
-        sma: sma_results,
-        ema: ema_results,
-        hma: hma_results,
-        macd: macd_result,
-        stochastic: stoch_result,
-        obv: obv_result,
-        vwap: vwap_result,
-        atr: atr_result,
-        ichimoku: ichimoku_result,
-        supertrend: supertrend_result,
-        parabolic_sar: parabolic_sar_result,
-        donchian: donchian_result,
-        keltner: keltner_result,
-        mfi: mfi_result,
-        cmf: cmf_result,
-        choppiness: choppiness_result,
-        williams_r: willr_result,
-        adx: adx_result,
-        cvd: cvd_result,
-        stc: stc_result,
-        smc: smc_result,
-        anchored_vwap: anchored_vwap_result,
-        auto_fib: auto_fib_result,
-        symbol: params.symbol.clone(),
-        interval: params.interval.clone(),
-        data_source: String::new(),
-        source_interval: params.interval.clone(),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        ConsensusParams, InvestorFlowPoint, KimchiPremiumPoint, MarketType, SignalType,
+    };
+    use crate::test_support::candle;
+
+    const DAY: i64 = 86_400;
+
+    #[test]
+    fn external_signals_feed_the_consensus() {
+        let candles: Vec<Candle> = (0..10).map(|d| candle(d * DAY, 100.0)).collect();
+        let z_scores = [None, None, None, Some(0.0), Some(-3.0)];
+        let premium = KimchiPremiumResult {
+            krw_symbol: "KRW-BTC".to_string(),
+            usd_symbol: "BTCUSDT".to_string(),
+            z_period: 3,
+            band: 2.0,
+            data: z_scores
+                .iter()
+                .enumerate()
+                .map(|(d, &z_score)| KimchiPremiumPoint {
+                    time: d as i64 * DAY,
+                    krw_price: 100.0,
+                    usd_price: 1.0,
+                    usd_krw: 100.0,
+                    premium: 0.0,
+                    z_score,
+                })
+                .collect(),
+        };
+        let mut params =
+            AnalysisParams::new("KRW-BTC".to_string(), "1d".to_string(), MarketType::Crypto);
+        params.signal_strategies.consensus = Some(ConsensusParams {
+            weights: [("kimchi_premium".to_string(), 2.0)].into(),
+            ..Default::default()
+        });
+
+        let response = analyze_with(
+            &candles,
+            &params,
+            ExternalData {
+                kimchi_premium: Some(premium),
+                ..Default::default()
+            },
+        );
+
+        let signals: Vec<(i64, SignalType)> = response
+            .signals
+            .iter()
+            .map(|s| (s.time, s.signal_type.clone()))
+            .collect();
+        assert_eq!(
+            signals,
+            [
+                (4 * DAY, SignalType::KimchiPremiumBuy),
+                (4 * DAY, SignalType::ConsensusBuy),
+            ]
+        );
+        assert!(response.kimchi_premium.is_some());
+    }
+
+    #[test]
+    fn external_inputs_alone_produce_their_signals() {
+        let candles: Vec<Candle> = (0..4).map(|d| candle(d * DAY, 100.0)).collect();
+        let flow = InvestorFlowResult {
+            symbol: "005930".to_string(),
+            streak: 2,
+            data: candles
+                .iter()
+                .map(|c| InvestorFlowPoint {
+                    time: c.time,
+                    individual: -2.0,
+                    foreign: 1.0,
+                    institution: 1.0,
+                    individual_amount: 0.0,
+                    foreign_amount: 0.0,
+                    institution_amount: 0.0,
+                    individual_cumulative: 0.0,
+                    foreign_cumulative: 0.0,
+                    institution_cumulative: 0.0,
+                })
+                .collect(),
+        };
+        let external = ExternalData {
+            investor_flow: Some(flow),
+            ..Default::default()
+        };
+        let params =
+            AnalysisParams::new("005930".to_string(), "1d".to_string(), MarketType::KrStock);
+
+        // Walk-forward passes no precomputed indicators, only the external data
+        let signals = detect_signals(&candles, &params, &external.signal_inputs());
+
+        let fired: Vec<(i64, SignalType)> = signals
+            .iter()
+            .map(|s| (s.time, s.signal_type.clone()))
+            .collect();
+        assert_eq!(fired, [(DAY, SignalType::InvestorFlowBuy)]);
     }
 }
@@ -1,3 +1,4 @@
+use super::incremental::IncrementalIndicator;
 use crate::models::{Candle, ObvPoint, ObvResult};
 
 /// Calculate On-Balance Volume (OBV).
@@ -34,3 +35,35 @@ pub fn calculate(candles: &[Candle]) -> ObvResult {
 
     ObvResult { data }
 }
+
+/// Streaming OBV. Matches `calculate` point for point, except that a lone first candle
+/// already yields its starting 0 (the batch version needs at least two candles).
+#[derive(Debug, Clone, Default)]
+pub struct ObvState {
+    prev_close: Option<f64>,
+    obv: f64,
+}
+
+impl ObvState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IncrementalIndicator for ObvState {
+    type Output = ObvPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<ObvPoint> {
+        if let Some(prev_close) = self.prev_close.replace(candle.close) {
+            if candle.close > prev_close {
+                self.obv += candle.volume;
+            } else if candle.close < prev_close {
+                self.obv -= candle.volume;
+            }
+        }
+        Some(ObvPoint {
+            time: candle.time,
+            value: self.obv,
+        })
+    }
+}
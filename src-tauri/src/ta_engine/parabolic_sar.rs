@@ -1,3 +1,6 @@
+use std::collections::VecDeque;
+
+use super::incremental::{self, IncrementalIndicator};
 use crate::models::{Candle, ParabolicSarPoint, ParabolicSarResult};
 
 pub fn calculate(candles: &[Candle], step: f64, max_step: f64) -> ParabolicSarResult {
@@ -87,3 +90,89 @@ pub fn calculate(candles: &[Candle], step: f64, max_step: f64) -> ParabolicSarRe
         data,
     }
 }
+
+/// Streaming Parabolic SAR.
+///
+/// The batch series seeds its first point from the trend between candles 0 and 1, so the
+/// streaming state cannot emit for candle 0; from candle 1 onward the points match `calculate`.
+#[derive(Debug, Clone)]
+pub struct ParabolicSarState {
+    step: f64,
+    max_step: f64,
+    /// Previous two candles, most recent last
+    prev: VecDeque<Candle>,
+    /// (uptrend, sar, extreme point, acceleration factor)
+    trend: Option<(bool, f64, f64, f64)>,
+}
+
+impl ParabolicSarState {
+    pub fn new(step: f64, max_step: f64) -> Self {
+        Self {
+            step,
+            max_step,
+            prev: VecDeque::with_capacity(3),
+            trend: None,
+        }
+    }
+}
+
+impl IncrementalIndicator for ParabolicSarState {
+    type Output = ParabolicSarPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<ParabolicSarPoint> {
+        let Some(last) = self.prev.back() else {
+            self.prev.push_back(candle.clone());
+            return None;
+        };
+
+        let (mut uptrend, mut sar, mut ep, mut af) = self.trend.unwrap_or_else(|| {
+            let uptrend = candle.close >= last.close;
+            if uptrend {
+                (uptrend, last.low, last.high.max(candle.high), self.step)
+            } else {
+                (uptrend, last.high, last.low.min(candle.low), self.step)
+            }
+        });
+        let before_last = (self.prev.len() == 2).then(|| &self.prev[0]);
+
+        sar = sar + af * (ep - sar);
+        if uptrend {
+            sar = sar.min(last.low);
+            if let Some(c) = before_last {
+                sar = sar.min(c.low);
+            }
+
+            if candle.low < sar {
+                uptrend = false;
+                sar = ep;
+                ep = candle.low;
+                af = self.step;
+            } else if candle.high > ep {
+                ep = candle.high;
+                af = (af + self.step).min(self.max_step);
+            }
+        } else {
+            sar = sar.max(last.high);
+            if let Some(c) = before_last {
+                sar = sar.max(c.high);
+            }
+
+            if candle.high > sar {
+                uptrend = true;
+                sar = ep;
+                ep = candle.high;
+                af = self.step;
+            } else if candle.low < ep {
+                ep = candle.low;
+                af = (af + self.step).min(self.max_step);
+            }
+        }
+
+        self.trend = Some((uptrend, sar, ep, af));
+        incremental::push_window(&mut self.prev, candle.clone(), 2);
+        Some(ParabolicSarPoint {
+            time: candle.time,
+            value: sar,
+        })
+    }
+}
@@ -1,3 +1,4 @@
+use super::incremental::{IncrementalIndicator, WilderAverage};
 use crate::models::{Candle, RsiPoint};
 
 pub fn calculate(candles: &[Candle], period: usize) -> Vec<RsiPoint> {
@@ -62,3 +63,48 @@ pub fn calculate(candles: &[Candle], period: usize) -> Vec<RsiPoint> {
 
     result
 }
+
+/// Streaming RSI. Emits the same points as `calculate`, starting at candle `period`.
+#[derive(Debug, Clone)]
+pub struct RsiState {
+    prev_close: Option<f64>,
+    avg_gain: WilderAverage,
+    avg_loss: WilderAverage,
+}
+
+impl RsiState {
+    pub fn new(period: usize) -> Self {
+        Self {
+            prev_close: None,
+            avg_gain: WilderAverage::new(period),
+            avg_loss: WilderAverage::new(period),
+        }
+    }
+}
+
+impl IncrementalIndicator for RsiState {
+    type Output = RsiPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<RsiPoint> {
+        let prev_close = self.prev_close.replace(candle.close)?;
+        let change = candle.close - prev_close;
+        let avg_gain = self
+            .avg_gain
+            .update(if change > 0.0 { change } else { 0.0 });
+        let avg_loss = self
+            .avg_loss
+            .update(if change < 0.0 { -change } else { 0.0 });
+        let (avg_gain, avg_loss) = (avg_gain?, avg_loss?);
+
+        let value = if avg_loss == 0.0 {
+            100.0
+        } else {
+            let rs = avg_gain / avg_loss;
+            100.0 - (100.0 / (1.0 + rs))
+        };
+        Some(RsiPoint {
+            time: candle.time,
+            value,
+        })
+    }
+}
@@ -0,0 +1,525 @@
+use chrono::{Datelike, Days, NaiveDate, TimeZone, Utc, Weekday};
+
+use crate::models::{Candle, CandleSession, MarketType, TradingSession};
+
+const HOUR: i64 = 3_600;
+const MINUTE: i64 = 60;
+
+/// `(month, day)`
+type MonthDay = (u32, u32);
+
+/// Lunar-calendar KRX holidays as (year, Seollal, Buddha's Birthday, Chuseok).
+/// Years outside the table only get the solar holidays.
+const KRX_LUNAR_HOLIDAYS: [(i32, MonthDay, MonthDay, MonthDay); 11] = [
+    (2020, (1, 25), (4, 30), (10, 1)),
+    (2021, (2, 12), (5, 19), (9, 21)),
+    (2022, (2, 1), (5, 8), (9, 10)),
+    (2023, (1, 22), (5, 27), (9, 29)),
+    (2024, (2, 10), (5, 15), (9, 17)),
+    (2025, (1, 29), (5, 5), (10, 6)),
+    (2026, (2, 17), (5, 24), (9, 25)),
+    (2027, (2, 7), (5, 13), (9, 15)),
+    (2028, (1, 27), (5, 2), (10, 3)),
+    (2029, (2, 13), (5, 20), (9, 22)),
+    (2030, (2, 3), (5, 9), (9, 12)),
+];
+
+/// Exchange-local UTC offset in seconds at `time`.
+/// KRX is fixed at KST (UTC+9); NYSE and FX follow New York time including DST; crypto uses UTC.
+pub fn utc_offset(time: i64, market: &MarketType) -> i64 {
+    match market {
+        MarketType::KrStock => 9 * HOUR,
+        MarketType::UsStock | MarketType::Forex => new_york_offset(time),
+        MarketType::Crypto => 0,
+    }
+}
+
+/// Trading date a candle at `time` belongs to.
+/// FX rolls over at 17:00 New York, so Sunday-evening trading counts toward Monday.
+pub fn session_date(time: i64, market: &MarketType) -> NaiveDate {
+    let rollover = if matches!(market, MarketType::Forex) {
+        7 * HOUR
+    } else {
+        0
+    };
+    let local = time + utc_offset(time, market) + rollover;
+    Utc.timestamp_opt(local, 0)
+        .single()
+        .map(|dt| dt.date_naive())
+        .unwrap_or_default()
+}
+
+/// Nominal regular-hours open of `date` in UTC seconds, used to timestamp daily bars.
+pub fn market_open(date: NaiveDate, market: &MarketType) -> i64 {
+    regular_hours(date, market).0
+}
+
+/// Instant intraday resampling aligns its buckets to: the regular-hours open, or the close
+/// for post-market bars so that no bucket straddles the closing bell.
+pub fn bucket_anchor(time: i64, market: &MarketType) -> i64 {
+    let (open, close) = session_hours(session_date(time, market), market);
+    if candle_session(time, market) == Some(CandleSession::Post) {
+        close
+    } else {
+        open
+    }
+}
+
+/// Pre-market, regular or post-market phase of a bar opening at `time`.
+/// Only stock markets trade extended hours; crypto and FX return None.
+pub fn candle_session(time: i64, market: &MarketType) -> Option<CandleSession> {
+    if !matches!(market, MarketType::UsStock | MarketType::KrStock) {
+        return None;
+    }
+    let (open, close) = session_hours(session_date(time, market), market);
+    Some(if time < open {
+        CandleSession::Pre
+    } else if time >= close {
+        CandleSession::Post
+    } else {
+        CandleSession::Regular
+    })
+}
+
+/// Tags intraday stock bars with their session; daily and longer bars stay untagged.
+pub fn tag_sessions(candles: &mut [Candle], interval: &str, market: &MarketType) {
+    if !(interval.ends_with('m') || interval.ends_with('h')) {
+        return;
+    }
+    for candle in candles {
+        candle.session = candle_session(candle.time, market);
+    }
+}
+
+/// Whether the exchange holds a regular session on `date`.
+pub fn is_trading_day(date: NaiveDate, market: &MarketType) -> bool {
+    let weekend = is_weekend(date);
+    match market {
+        MarketType::Crypto => true,
+        MarketType::Forex => !weekend && !matches!((date.month(), date.day()), (1, 1) | (12, 25)),
+        MarketType::UsStock => !weekend && !is_nyse_holiday(date),
+        MarketType::KrStock => !weekend && !krx_holidays(date.year()).contains(&date),
+    }
+}
+
+/// Regular session on `date`, or None when the market is closed.
+/// Irregular closures (KRX election days, NYSE days of mourning) are not modelled.
+pub fn trading_session(date: NaiveDate, market: &MarketType) -> Option<TradingSession> {
+    if !is_trading_day(date, market) {
+        return None;
+    }
+    let (open, close) = session_hours(date, market);
+    Some(TradingSession {
+        date: date.format("%Y-%m-%d").to_string(),
+        open,
+        close,
+        half_day: matches!(market, MarketType::UsStock) && is_nyse_half_day(date),
+    })
+}
+
+/// Regular open/close in UTC seconds with NYSE early closes applied.
+fn session_hours(date: NaiveDate, market: &MarketType) -> (i64, i64) {
+    let (open, close) = regular_hours(date, market);
+    if matches!(market, MarketType::UsStock) && is_nyse_half_day(date) {
+        (open, local_to_utc(date, 13 * HOUR, market))
+    } else {
+        (open, close)
+    }
+}
+
+/// Nominal open/close in UTC seconds, ignoring holidays and early closes.
+/// KRX 09:00–15:30 KST, NYSE 09:30–16:00 ET, FX 17:00 ET (previous day) to 17:00 ET, crypto UTC day.
+fn regular_hours(date: NaiveDate, market: &MarketType) -> (i64, i64) {
+    let (open, close) = match market {
+        MarketType::KrStock => (9 * HOUR, 15 * HOUR + 30 * MINUTE),
+        MarketType::UsStock => (9 * HOUR + 30 * MINUTE, 16 * HOUR),
+        MarketType::Forex => (-7 * HOUR, 17 * HOUR),
+        MarketType::Crypto => (0, 24 * HOUR),
+    };
+    (
+        local_to_utc(date, open, market),
+        local_to_utc(date, close, market),
+    )
+}
+
+/// `seconds` after local midnight of `date`, converted to UTC.
+fn local_to_utc(date: NaiveDate, seconds: i64, market: &MarketType) -> i64 {
+    let local = date
+        .and_hms_opt(0, 0, 0)
+        .unwrap_or_default()
+        .and_utc()
+        .timestamp()
+        + seconds;
+    // The offset at the guessed instant settles DST switches, which fall outside trading hours
+    let guess = local - utc_offset(local, market);
+    local - utc_offset(guess, market)
+}
+
+/// US Eastern offset: EDT from the second Sunday of March 02:00 EST
+/// until the first Sunday of November 02:00 EDT, EST otherwise.
+fn new_york_offset(time: i64) -> i64 {
+    let Some(utc) = Utc.timestamp_opt(time, 0).single() else {
+        return -5 * HOUR;
+    };
+    let year = utc.year();
+    let (Some(dst_start), Some(dst_end)) = (
+        NaiveDate::from_weekday_of_month_opt(year, 3, Weekday::Sun, 2),
+        NaiveDate::from_weekday_of_month_opt(year, 11, Weekday::Sun, 1),
+    ) else {
+        return -5 * HOUR;
+    };
+
+    let start = dst_start
+        .and_hms_opt(7, 0, 0)
+        .map(|dt| dt.and_utc().timestamp());
+    let end = dst_end
+        .and_hms_opt(6, 0, 0)
+        .map(|dt| dt.and_utc().timestamp());
+    match (start, end) {
+        (Some(start), Some(end)) if time >= start && time < end => -4 * HOUR,
+        _ => -5 * HOUR,
+    }
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// NYSE full-day holidays with weekend observance.
+/// New Year's Day falling on a Saturday is not observed on the preceding Friday.
+fn is_nyse_holiday(date: NaiveDate) -> bool {
+    let year = date.year();
+    let nth = |month, weekday, n| NaiveDate::from_weekday_of_month_opt(year, month, weekday, n);
+    let memorial_day = nth(5, Weekday::Mon, 5).or_else(|| nth(5, Weekday::Mon, 4));
+    let good_friday = easter_sunday(year).and_then(|easter| easter.checked_sub_days(Days::new(2)));
+
+    observed_on(date, 1, 1, false)
+        || (year >= 2022 && observed_on(date, 6, 19, true))
+        || observed_on(date, 7, 4, true)
+        || observed_on(date, 12, 25, true)
+        || [
+            nth(1, Weekday::Mon, 3),
+            nth(2, Weekday::Mon, 3),
+            good_friday,
+            memorial_day,
+            nth(9, Weekday::Mon, 1),
+            nth(11, Weekday::Thu, 4),
+        ]
+        .contains(&Some(date))
+}
+
+/// NYSE 13:00 early closes: July 3rd, the day after Thanksgiving and Christmas Eve.
+fn is_nyse_half_day(date: NaiveDate) -> bool {
+    if is_weekend(date) || is_nyse_holiday(date) {
+        return false;
+    }
+    let day_after_thanksgiving =
+        NaiveDate::from_weekday_of_month_opt(date.year(), 11, Weekday::Thu, 4)
+            .and_then(|thanksgiving| thanksgiving.succ_opt());
+    matches!((date.month(), date.day()), (7, 3) | (12, 24)) || day_after_thanksgiving == Some(date)
+}
+
+/// Whether `date` is the observed day of the fixed holiday `month`/`day`:
+/// Sunday holidays move to Monday, Saturday holidays to Friday when `saturday_to_friday`.
+fn observed_on(date: NaiveDate, month: u32, day: u32, saturday_to_friday: bool) -> bool {
+    let Some(holiday) = NaiveDate::from_ymd_opt(date.year(), month, day) else {
+        return false;
+    };
+    match holiday.weekday() {
+        Weekday::Sat => saturday_to_friday && holiday.pred_opt() == Some(date),
+        Weekday::Sun => holiday.succ_opt() == Some(date),
+        _ => holiday == date,
+    }
+}
+
+/// Gregorian Easter Sunday (anonymous algorithm).
+fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+/// KRX closed days for `year` other than weekends, including substitute holidays
+/// and the year-end closing day (the last weekday of December).
+fn krx_holidays(year: i32) -> Vec<NaiveDate> {
+    let date = |(month, day): MonthDay| NaiveDate::from_ymd_opt(year, month, day);
+    let span = |(month, day): MonthDay| -> Vec<NaiveDate> {
+        date((month, day))
+            .map(|center| {
+                [center.pred_opt(), Some(center), center.succ_opt()]
+                    .into_iter()
+                    .flatten()
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let lunar = KRX_LUNAR_HOLIDAYS
+        .iter()
+        .find(|(lunar_year, ..)| *lunar_year == year);
+    let seollal = lunar.map(|l| span(l.1)).unwrap_or_default();
+    let buddha: Vec<NaiveDate> = lunar.and_then(|l| date(l.2)).into_iter().collect();
+    let chuseok = lunar.map(|l| span(l.3)).unwrap_or_default();
+    let children = date((5, 5));
+    let year_end =
+        std::iter::successors(date((12, 31)), |day| day.pred_opt()).find(|day| !is_weekend(*day));
+
+    let mut holidays: Vec<NaiveDate> = [(1, 1), (3, 1), (5, 1), (5, 5), (6, 6), (8, 15)]
+        .into_iter()
+        .chain([(10, 3), (10, 9), (12, 25)])
+        .filter_map(date)
+        .chain(year_end)
+        .chain(seollal.iter().copied())
+        .chain(buddha.iter().copied())
+        .chain(chuseok.iter().copied())
+        .collect();
+
+    // Substitute holidays: (days, also substitute for Saturday)
+    let mut substituted: Vec<(Vec<NaiveDate>, bool)> = vec![
+        (seollal, false),
+        (chuseok, false),
+        (children.into_iter().collect(), true),
+    ];
+    if year >= 2021 {
+        for national in [(3, 1), (8, 15), (10, 3), (10, 9)] {
+            substituted.push((date(national).into_iter().collect(), true));
+        }
+    }
+    if year >= 2023 {
+        substituted.push((buddha, true));
+        substituted.push((date((12, 25)).into_iter().collect(), true));
+    }
+
+    // A day shared by two holidays yields a single substitute
+    let base = holidays.clone();
+    let mut compensated: Vec<NaiveDate> = Vec::new();
+    for (days, saturday) in substituted {
+        let Some(&last) = days.last() else {
+            continue;
+        };
+        let triggers: Vec<NaiveDate> = days
+            .iter()
+            .copied()
+            .filter(|day| {
+                day.weekday() == Weekday::Sun
+                    || (saturday && day.weekday() == Weekday::Sat)
+                    || base.iter().filter(|other| *other == day).count() > 1
+            })
+            .collect();
+        if triggers.iter().all(|day| compensated.contains(day)) {
+            continue;
+        }
+        compensated.extend(triggers);
+        let substitute = last
+            .iter_days()
+            .skip(1)
+            .find(|day| !is_weekend(*day) && !holidays.contains(day));
+        holidays.extend(substitute);
+    }
+
+    holidays
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn weekday_holidays(year: i32) -> Vec<NaiveDate> {
+        let mut holidays: Vec<NaiveDate> = krx_holidays(year)
+            .into_iter()
+            .filter(|date| !is_weekend(*date))
+            .collect();
+        holidays.sort();
+        holidays.dedup();
+        holidays
+    }
+
+    #[test]
+    fn easter_sunday_matches_known_dates() {
+        assert_eq!(easter_sunday(2019), Some(day(2019, 4, 21)));
+        assert_eq!(easter_sunday(2024), Some(day(2024, 3, 31)));
+        assert_eq!(easter_sunday(2025), Some(day(2025, 4, 20)));
+        assert_eq!(easter_sunday(2038), Some(day(2038, 4, 25)));
+    }
+
+    #[test]
+    fn krx_2024_closes_with_substitute_days() {
+        // Seollal ends on a Sunday (-> Feb 12) and Children's Day falls on a Sunday (-> May 6).
+        // The April 10 election and October 1 Armed Forces Day were one-off closures.
+        let expected = [
+            (1, 1),
+            (2, 9),
+            (2, 12),
+            (3, 1),
+            (5, 1),
+            (5, 6),
+            (5, 15),
+            (6, 6),
+            (8, 15),
+            (9, 16),
+            (9, 17),
+            (9, 18),
+            (10, 3),
+            (10, 9),
+            (12, 25),
+            (12, 31),
+        ]
+        .map(|(month, d)| day(2024, month, d));
+        assert_eq!(weekday_holidays(2024), expected);
+    }
+
+    #[test]
+    fn krx_2025_closes_with_substitute_days() {
+        // Independence Day falls on a Saturday (-> Mar 3), Children's Day and Buddha's Birthday
+        // share May 5 (one substitute, May 6) and Chuseok starts on a Sunday (-> Oct 8).
+        let expected = [
+            (1, 1),
+            (1, 28),
+            (1, 29),
+            (1, 30),
+            (3, 3),
+            (5, 1),
+            (5, 5),
+            (5, 6),
+            (6, 6),
+            (8, 15),
+            (10, 3),
+            (10, 6),
+            (10, 7),
+            (10, 8),
+            (10, 9),
+            (12, 25),
+            (12, 31),
+        ]
+        .map(|(month, d)| day(2025, month, d));
+        assert_eq!(weekday_holidays(2025), expected);
+    }
+
+    #[test]
+    fn krx_year_end_closing_day_skips_the_weekend() {
+        // December 31, 2022 was a Saturday, so the market closed on Friday the 30th
+        assert!(krx_holidays(2022).contains(&day(2022, 12, 30)));
+        assert!(!is_trading_day(day(2022, 12, 30), &MarketType::KrStock));
+        assert!(is_trading_day(day(2022, 12, 29), &MarketType::KrStock));
+    }
+
+    #[test]
+    fn nyse_2024_and_2025_holidays() {
+        let holidays = [
+            day(2024, 1, 1),
+            day(2024, 1, 15),
+            day(2024, 2, 19),
+            day(2024, 3, 29),
+            day(2024, 5, 27),
+            day(2024, 6, 19),
+            day(2024, 7, 4),
+            day(2024, 9, 2),
+            day(2024, 11, 28),
+            day(2024, 12, 25),
+            day(2025, 1, 1),
+            day(2025, 1, 20),
+            day(2025, 2, 17),
+            day(2025, 4, 18),
+            day(2025, 5, 26),
+            day(2025, 6, 19),
+            day(2025, 7, 4),
+            day(2025, 9, 1),
+            day(2025, 11, 27),
+            day(2025, 12, 25),
+        ];
+        for date in holidays {
+            assert!(is_nyse_holiday(date), "{date} should be a holiday");
+        }
+
+        let closed = |year| {
+            let mut date = day(year, 1, 1);
+            let mut count = 0;
+            while date.year() == year {
+                count += usize::from(is_nyse_holiday(date) && !is_weekend(date));
+                date = date.succ_opt().unwrap();
+            }
+            count
+        };
+        assert_eq!(closed(2024), 10);
+        assert_eq!(closed(2025), 10);
+    }
+
+    #[test]
+    fn nyse_weekend_holidays_are_observed_on_adjacent_weekdays() {
+        // Sunday -> Monday
+        assert!(is_nyse_holiday(day(2021, 7, 5)));
+        assert!(is_nyse_holiday(day(2022, 6, 20)));
+        // Saturday -> Friday, except New Year's Day
+        assert!(is_nyse_holiday(day(2026, 7, 3)));
+        assert!(is_nyse_holiday(day(2027, 12, 24)));
+        assert!(!is_nyse_holiday(day(2021, 12, 31)));
+        // Juneteenth is only a market holiday from 2022
+        assert!(!is_nyse_holiday(day(2021, 6, 18)));
+    }
+
+    #[test]
+    fn nyse_half_days() {
+        let half_days = [
+            day(2024, 7, 3),
+            day(2024, 11, 29),
+            day(2024, 12, 24),
+            day(2025, 7, 3),
+            day(2025, 11, 28),
+            day(2025, 12, 24),
+        ];
+        for date in half_days {
+            assert!(is_nyse_half_day(date), "{date} should close early");
+        }
+        // Closed or weekend days are not early closes
+        assert!(!is_nyse_half_day(day(2026, 7, 3)));
+        assert!(!is_nyse_half_day(day(2022, 12, 24)));
+        assert!(!is_nyse_half_day(day(2024, 7, 5)));
+
+        let session = trading_session(day(2024, 11, 29), &MarketType::UsStock).unwrap();
+        assert!(session.half_day);
+        // 13:00 EST
+        assert_eq!(session.close, session.open + 3 * HOUR + 30 * MINUTE);
+    }
+
+    #[test]
+    fn sessions_follow_new_york_daylight_saving() {
+        // 2024-03-08 is EST, 2024-03-11 is EDT
+        let winter = trading_session(day(2024, 3, 8), &MarketType::UsStock).unwrap();
+        let summer = trading_session(day(2024, 3, 11), &MarketType::UsStock).unwrap();
+        assert_eq!(winter.open % (24 * HOUR), 14 * HOUR + 30 * MINUTE);
+        assert_eq!(summer.open % (24 * HOUR), 13 * HOUR + 30 * MINUTE);
+        assert!(trading_session(day(2024, 3, 29), &MarketType::UsStock).is_none());
+    }
+
+    #[test]
+    fn bucket_anchor_uses_the_close_for_post_market_bars() {
+        let market = MarketType::UsStock;
+        let (open, close) = session_hours(day(2024, 11, 29), &market);
+        assert_eq!(bucket_anchor(open + 10 * MINUTE, &market), open);
+        assert_eq!(bucket_anchor(close + 10 * MINUTE, &market), close);
+        assert_eq!(
+            candle_session(close + 10 * MINUTE, &market),
+            Some(CandleSession::Post)
+        );
+        assert_eq!(
+            candle_session(open - 10 * MINUTE, &market),
+            Some(CandleSession::Pre)
+        );
+    }
+}
@@ -1,20 +1,22 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::models::{
-    AdxResult, BollingerBandsPoint, Candle, CmfResult, KeltnerResult, MacdResult,
-    MovingAverageResult, ObvResult, ParabolicSarResult, RsiPoint, SignalPoint,
-    SignalType, StochasticResult, SupertrendResult, VwapResult,
+    AdxResult, BollingerBandsPoint, Candle, CmfObvConfig, CmfResult, IbsMeanReversionConfig,
+    InvestorFlowPoint, KeltnerResult, KimchiPremiumPoint, MacdResult, MovingAverageResult,
+    ObvResult, ParabolicSarResult, RsiPoint, SignalPoint, SignalType, StochRsiConfig,
+    StochasticResult, SupertrendAdxConfig, SupertrendResult, VwapBreakoutConfig, VwapResult,
 };
 
 // ─────────────────────────────────────────────────────
-// Quant Signal Strategies (10)
+// Quant Signal Strategies (12)
 // ─────────────────────────────────────────────────────
 
-/// 1. Supertrend direction reversal + ADX > 25 trend confirmation.
+/// 1. Supertrend direction reversal + ADX trend confirmation (default ADX ≥ 25).
 pub fn detect_supertrend_adx(
     st: &SupertrendResult,
     adx: &AdxResult,
     candles: &[Candle],
+    config: &SupertrendAdxConfig,
 ) -> Vec<SignalPoint> {
     let mut signals = Vec::new();
     let adx_map: HashMap<i64, f64> = adx.data.iter().map(|a| (a.time, a.adx)).collect();
@@ -27,7 +29,7 @@ pub fn detect_supertrend_adx(
             continue;
         }
         let adx_val = adx_map.get(&curr.time).copied().unwrap_or(0.0);
-        if adx_val < 25.0 {
+        if adx_val < config.adx_threshold {
             continue;
         }
         let price = candle_map.get(&curr.time).map(|c| c.close).unwrap_or(0.0);
@@ -101,6 +103,7 @@ pub fn detect_stoch_rsi_combined(
     stoch: &StochasticResult,
     rsi: &[RsiPoint],
     candles: &[Candle],
+    config: &StochRsiConfig,
 ) -> Vec<SignalPoint> {
     let mut signals = Vec::new();
     let rsi_map: HashMap<i64, f64> = rsi.iter().map(|r| (r.time, r.value)).collect();
@@ -113,7 +116,7 @@ pub fn detect_stoch_rsi_combined(
         };
         let price = candle_map.get(&pt.time).map(|c| c.close).unwrap_or(0.0);
 
-        if pt.k < 20.0 && rsi_val < 30.0 {
+        if pt.k < config.stoch_oversold && rsi_val < config.rsi_oversold {
             signals.push(SignalPoint {
                 time: pt.time,
                 signal_type: SignalType::StochRsiBuy,
@@ -122,7 +125,7 @@ pub fn detect_stoch_rsi_combined(
                 source: "stoch_rsi".to_string(),
             });
         }
-        if pt.k > 80.0 && rsi_val > 70.0 {
+        if pt.k > config.stoch_overbought && rsi_val > config.rsi_overbought {
             signals.push(SignalPoint {
                 time: pt.time,
                 signal_type: SignalType::StochRsiSell,
@@ -140,6 +143,7 @@ pub fn detect_cmf_obv(
     cmf: &CmfResult,
     obv: &ObvResult,
     candles: &[Candle],
+    config: &CmfObvConfig,
 ) -> Vec<SignalPoint> {
     let mut signals = Vec::new();
     let obv_map: HashMap<i64, f64> = obv.data.iter().map(|o| (o.time, o.value)).collect();
@@ -151,7 +155,7 @@ pub fn detect_cmf_obv(
         .map(|(i, (t, _))| (*t, i))
         .collect();
 
-    let lookback = 10usize;
+    let lookback = config.obv_lookback.max(1);
 
     for i in 1..cmf.data.len() {
         let prev_cmf = cmf.data[i - 1].value;
@@ -255,22 +259,33 @@ pub fn detect_ttm_squeeze(
 }
 
 /// 6. VWAP breakout with volume surge confirmation.
-pub fn detect_vwap_breakout(vwap: &VwapResult, candles: &[Candle]) -> Vec<SignalPoint> {
+pub fn detect_vwap_breakout(
+    vwap: &VwapResult,
+    candles: &[Candle],
+    config: &VwapBreakoutConfig,
+) -> Vec<SignalPoint> {
     let mut signals = Vec::new();
-    if candles.len() < 21 {
+    let window = config.volume_window.max(1);
+    if candles.len() < window + 1 {
         return signals;
     }
     let vwap_map: HashMap<i64, f64> = vwap.data.iter().map(|v| (v.time, v.value)).collect();
+    // A session's first bar has nothing to break out of: VWAP just restarted at its own price
+    let session_starts: HashSet<i64> = vwap.session_starts.iter().copied().collect();
 
-    // Calculate 20-period rolling average volume
-    for i in 20..candles.len() {
-        let avg_vol: f64 = candles[i - 20..i].iter().map(|c| c.volume).sum::<f64>() / 20.0;
+    // Calculate rolling average volume over the configured window
+    for i in window..candles.len() {
+        let avg_vol: f64 =
+            candles[i - window..i].iter().map(|c| c.volume).sum::<f64>() / window as f64;
         let candle = &candles[i];
+        if session_starts.contains(&candle.time) {
+            continue;
+        }
         let vwap_val = match vwap_map.get(&candle.time) {
             Some(v) => *v,
             None => continue,
         };
-        let vol_surge = candle.volume > avg_vol * 1.5;
+        let vol_surge = candle.volume > avg_vol * config.volume_multiplier;
         if !vol_surge {
             continue;
         }
@@ -389,7 +404,11 @@ pub fn detect_macd_hist_reversal(macd: &MacdResult, candles: &[Candle]) -> Vec<S
 }
 
 /// 9. IBS (Internal Bar Strength) mean reversion + RSI confirmation.
-pub fn detect_ibs_mean_reversion(rsi: &[RsiPoint], candles: &[Candle]) -> Vec<SignalPoint> {
+pub fn detect_ibs_mean_reversion(
+    rsi: &[RsiPoint],
+    candles: &[Candle],
+    config: &IbsMeanReversionConfig,
+) -> Vec<SignalPoint> {
     let mut signals = Vec::new();
     let rsi_map: HashMap<i64, f64> = rsi.iter().map(|r| (r.time, r.value)).collect();
 
@@ -401,7 +420,7 @@ pub fn detect_ibs_mean_reversion(rsi: &[RsiPoint], candles: &[Candle]) -> Vec<Si
         let ibs = (candle.close - candle.low) / range;
         let rsi_val = rsi_map.get(&candle.time).copied().unwrap_or(50.0);
 
-        if ibs < 0.2 && rsi_val < 35.0 {
+        if ibs < config.ibs_oversold && rsi_val < config.rsi_oversold {
             signals.push(SignalPoint {
                 time: candle.time,
                 signal_type: SignalType::IbsMeanRevBuy,
@@ -410,7 +429,7 @@ pub fn detect_ibs_mean_reversion(rsi: &[RsiPoint], candles: &[Candle]) -> Vec<Si
                 source: "ibs_mean_rev".to_string(),
             });
         }
-        if ibs > 0.8 && rsi_val > 65.0 {
+        if ibs > config.ibs_overbought && rsi_val > config.rsi_overbought {
             signals.push(SignalPoint {
                 time: candle.time,
                 signal_type: SignalType::IbsMeanRevSell,
@@ -492,3 +511,227 @@ pub fn detect_rsi_divergence(
 
     signals
 }
+
+/// 11. Kimchi premium extremes: z-score crossing into the ±band (above = sell, below = buy).
+pub fn detect_kimchi_premium_extremes(
+    premium: &[KimchiPremiumPoint],
+    candles: &[Candle],
+    rsi: &[RsiPoint],
+    band: f64,
+) -> Vec<SignalPoint> {
+    let mut signals = Vec::new();
+    let close_map: HashMap<i64, f64> = candles.iter().map(|c| (c.time, c.close)).collect();
+    let rsi_map: HashMap<i64, f64> = rsi.iter().map(|r| (r.time, r.value)).collect();
+    let mut prev_z: Option<f64> = None;
+
+    for point in premium {
+        let Some(z) = point.z_score else {
+            prev_z = None;
+            continue;
+        };
+        let signal_type = match prev_z {
+            Some(prev) if prev <= band && z > band => Some(SignalType::KimchiPremiumSell),
+            Some(prev) if prev >= -band && z < -band => Some(SignalType::KimchiPremiumBuy),
+            _ => None,
+        };
+        prev_z = Some(z);
+
+        if let (Some(signal_type), Some(&price)) = (signal_type, close_map.get(&point.time)) {
+            signals.push(SignalPoint {
+                time: point.time,
+                signal_type,
+                price,
+                rsi: rsi_map.get(&point.time).copied().unwrap_or(50.0),
+                source: "kimchi_premium".to_string(),
+            });
+        }
+    }
+    signals
+}
+
+/// 12. Foreign + institutional agreement: both net buying (selling) for `streak` consecutive candles.
+pub fn detect_investor_flow_agreement(
+    flow: &[InvestorFlowPoint],
+    candles: &[Candle],
+    rsi: &[RsiPoint],
+    streak: usize,
+) -> Vec<SignalPoint> {
+    let mut signals = Vec::new();
+    let candle_map: HashMap<i64, (usize, f64)> = candles
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (c.time, (i, c.close)))
+        .collect();
+    let rsi_map: HashMap<i64, f64> = rsi.iter().map(|r| (r.time, r.value)).collect();
+    let streak = streak.max(1);
+    let (mut buy_run, mut sell_run) = (0usize, 0usize);
+    let mut prev_index: Option<usize> = None;
+
+    for point in flow {
+        let candle = candle_map.get(&point.time).copied();
+        let index = candle.map(|(i, _)| i);
+        // A candle missing from the flow data breaks the run
+        if index.is_none() || prev_index.map(|p| p + 1) != index {
+            buy_run = 0;
+            sell_run = 0;
+        }
+        prev_index = index;
+
+        if point.foreign > 0.0 && point.institution > 0.0 {
+            buy_run += 1;
+            sell_run = 0;
+        } else if point.foreign < 0.0 && point.institution < 0.0 {
+            sell_run += 1;
+            buy_run = 0;
+        } else {
+            buy_run = 0;
+            sell_run = 0;
+        }
+
+        let signal_type = if buy_run == streak {
+            SignalType::InvestorFlowBuy
+        } else if sell_run == streak {
+            SignalType::InvestorFlowSell
+        } else {
+            continue;
+        };
+        if let Some((_, price)) = candle {
+            signals.push(SignalPoint {
+                time: point.time,
+                signal_type,
+                price,
+                rsi: rsi_map.get(&point.time).copied().unwrap_or(50.0),
+                source: "investor_flow".to_string(),
+            });
+        }
+    }
+    signals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::candle;
+
+    fn flow(time: i64, foreign: f64, institution: f64) -> InvestorFlowPoint {
+        InvestorFlowPoint {
+            time,
+            individual: -(foreign + institution),
+            foreign,
+            institution,
+            individual_amount: 0.0,
+            foreign_amount: 0.0,
+            institution_amount: 0.0,
+            individual_cumulative: 0.0,
+            foreign_cumulative: 0.0,
+            institution_cumulative: 0.0,
+        }
+    }
+
+    #[test]
+    fn investor_flow_agreement_fires_once_per_streak() {
+        let candles: Vec<Candle> = (0..7).map(|i| candle(i, 100.0 + i as f64)).collect();
+        let rsi = vec![RsiPoint {
+            time: 1,
+            value: 40.0,
+        }];
+        let points = [
+            flow(0, 1.0, 1.0),
+            flow(1, 1.0, 1.0),
+            flow(2, 1.0, 1.0),
+            // Disagreement resets the run
+            flow(3, 1.0, -1.0),
+            flow(4, -1.0, -1.0),
+            flow(5, -1.0, -1.0),
+            flow(6, -1.0, -1.0),
+        ];
+
+        let signals = detect_investor_flow_agreement(&points, &candles, &rsi, 2);
+        let fired: Vec<(i64, SignalType, f64, f64)> = signals
+            .into_iter()
+            .map(|s| (s.time, s.signal_type, s.price, s.rsi))
+            .collect();
+        assert_eq!(
+            fired,
+            [
+                (1, SignalType::InvestorFlowBuy, 101.0, 40.0),
+                (5, SignalType::InvestorFlowSell, 105.0, 50.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn investor_flow_agreement_restarts_after_a_missing_bar() {
+        let candles: Vec<Candle> = (0..6).map(|i| candle(i, 100.0)).collect();
+        // Bar 2 has no flow data (e.g. not yet posted), so bars 1 and 3 are not consecutive
+        let points = [
+            flow(0, 1.0, 1.0),
+            flow(1, 1.0, 1.0),
+            flow(3, 1.0, 1.0),
+            flow(4, 1.0, 1.0),
+            flow(5, 1.0, 1.0),
+        ];
+
+        let signals = detect_investor_flow_agreement(&points, &candles, &[], 2);
+        let times: Vec<i64> = signals.iter().map(|s| s.time).collect();
+        assert_eq!(times, [1, 4]);
+    }
+
+    fn premium(time: i64, z_score: Option<f64>) -> KimchiPremiumPoint {
+        KimchiPremiumPoint {
+            time,
+            krw_price: 0.0,
+            usd_price: 0.0,
+            usd_krw: 0.0,
+            premium: 0.0,
+            z_score,
+        }
+    }
+
+    #[test]
+    fn kimchi_premium_extremes_fire_when_crossing_into_the_band() {
+        let candles: Vec<Candle> = (0..8).map(|i| candle(i, 100.0 + i as f64)).collect();
+        let points = [
+            premium(0, Some(1.0)),
+            // Touching the band is not a crossing
+            premium(1, Some(2.0)),
+            premium(2, Some(2.5)),
+            // Staying above does not repeat
+            premium(3, Some(3.0)),
+            premium(4, Some(0.0)),
+            premium(5, Some(-2.1)),
+            premium(6, Some(-1.0)),
+            premium(7, Some(-2.5)),
+        ];
+
+        let signals = detect_kimchi_premium_extremes(&points, &candles, &[], 2.0);
+        let fired: Vec<(i64, SignalType, f64)> = signals
+            .into_iter()
+            .map(|s| (s.time, s.signal_type, s.price))
+            .collect();
+        assert_eq!(
+            fired,
+            [
+                (2, SignalType::KimchiPremiumSell, 102.0),
+                (5, SignalType::KimchiPremiumBuy, 105.0),
+                (7, SignalType::KimchiPremiumBuy, 107.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn kimchi_premium_extremes_need_a_previous_z_score() {
+        let candles: Vec<Candle> = (0..4).map(|i| candle(i, 100.0)).collect();
+        // Starting inside the band, or right after a gap in the z-score, is not a crossing
+        let points = [
+            premium(0, Some(3.0)),
+            premium(1, None),
+            premium(2, Some(3.0)),
+            premium(3, Some(-3.0)),
+        ];
+
+        let signals = detect_kimchi_premium_extremes(&points, &candles, &[], 2.0);
+        let times: Vec<i64> = signals.iter().map(|s| s.time).collect();
+        assert_eq!(times, [3]);
+    }
+}
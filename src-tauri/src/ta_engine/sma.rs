@@ -1,3 +1,4 @@
+use super::incremental::{IncrementalIndicator, SmaValue};
 use crate::models::{Candle, MaPoint, MovingAverageResult};
 
 /// Calculate SMA from raw candle close prices.
@@ -33,3 +34,28 @@ pub fn calculate_from_values(values: &[f64], period: usize) -> Vec<f64> {
 
     result
 }
+
+/// Streaming SMA over close prices. Emits the same points as `calculate`.
+#[derive(Debug, Clone)]
+pub struct SmaState {
+    sma: SmaValue,
+}
+
+impl SmaState {
+    pub fn new(period: usize) -> Self {
+        Self {
+            sma: SmaValue::new(period),
+        }
+    }
+}
+
+impl IncrementalIndicator for SmaState {
+    type Output = MaPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<MaPoint> {
+        self.sma.update(candle.close).map(|value| MaPoint {
+            time: candle.time,
+            value,
+        })
+    }
+}
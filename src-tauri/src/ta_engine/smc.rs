@@ -12,32 +12,7 @@ pub fn calculate(candles: &[Candle], swing_length: usize) -> SmcResult {
     }
 
     // Step 1: Detect swing highs and swing lows
-    let mut swing_highs: Vec<(usize, f64)> = Vec::new(); // (candle_index, price)
-    let mut swing_lows: Vec<(usize, f64)> = Vec::new();
-
-    for i in swing_length..(candles.len() - swing_length) {
-        let mut is_high = true;
-        let mut is_low = true;
-
-        for j in 1..=swing_length {
-            if candles[i].high < candles[i - j].high || candles[i].high < candles[i + j].high {
-                is_high = false;
-            }
-            if candles[i].low > candles[i - j].low || candles[i].low > candles[i + j].low {
-                is_low = false;
-            }
-            if !is_high && !is_low {
-                break;
-            }
-        }
-
-        if is_high {
-            swing_highs.push((i, candles[i].high));
-        }
-        if is_low {
-            swing_lows.push((i, candles[i].low));
-        }
-    }
+    let (swing_highs, swing_lows) = swing_points(candles, swing_length);
 
     // Step 2: Detect BOS and CHoCH events
     let mut events: Vec<SmcEvent> = Vec::new();
@@ -116,3 +91,41 @@ pub fn calculate(candles: &[Candle], swing_length: usize) -> SmcResult {
 
     SmcResult { data: events }
 }
+
+/// A detected swing as `(candle_index, price)`.
+pub type Swing = (usize, f64);
+
+/// Swing highs and lows, in chronological order.
+pub fn swing_points(candles: &[Candle], swing_length: usize) -> (Vec<Swing>, Vec<Swing>) {
+    let mut swing_highs: Vec<Swing> = Vec::new();
+    let mut swing_lows: Vec<Swing> = Vec::new();
+    if swing_length == 0 || candles.len() < swing_length * 2 + 1 {
+        return (swing_highs, swing_lows);
+    }
+
+    for i in swing_length..(candles.len() - swing_length) {
+        let mut is_high = true;
+        let mut is_low = true;
+
+        for j in 1..=swing_length {
+            if candles[i].high < candles[i - j].high || candles[i].high < candles[i + j].high {
+                is_high = false;
+            }
+            if candles[i].low > candles[i - j].low || candles[i].low > candles[i + j].low {
+                is_low = false;
+            }
+            if !is_high && !is_low {
+                break;
+            }
+        }
+
+        if is_high {
+            swing_highs.push((i, candles[i].high));
+        }
+        if is_low {
+            swing_lows.push((i, candles[i].low));
+        }
+    }
+
+    (swing_highs, swing_lows)
+}
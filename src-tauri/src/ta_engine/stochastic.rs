@@ -1,3 +1,6 @@
+use std::collections::VecDeque;
+
+use super::incremental::{self, IncrementalIndicator, SmaValue};
 use super::sma;
 use crate::models::{Candle, StochasticPoint, StochasticResult};
 
@@ -67,3 +70,57 @@ pub fn calculate(
 
     StochasticResult { data }
 }
+
+/// Streaming Stochastic Oscillator. Emits the same points as `calculate`.
+#[derive(Debug, Clone)]
+pub struct StochasticState {
+    k_period: usize,
+    window: VecDeque<Candle>,
+    k: SmaValue,
+    d: SmaValue,
+}
+
+impl StochasticState {
+    pub fn new(k_period: usize, d_period: usize, smooth: usize) -> Self {
+        Self {
+            k_period,
+            window: VecDeque::with_capacity(k_period + 1),
+            k: SmaValue::new(smooth),
+            d: SmaValue::new(d_period),
+        }
+    }
+}
+
+impl IncrementalIndicator for StochasticState {
+    type Output = StochasticPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<StochasticPoint> {
+        if !incremental::push_window(&mut self.window, candle.clone(), self.k_period) {
+            return None;
+        }
+
+        let hh = self
+            .window
+            .iter()
+            .map(|c| c.high)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let ll = self
+            .window
+            .iter()
+            .map(|c| c.low)
+            .fold(f64::INFINITY, f64::min);
+        let raw_k = if (hh - ll).abs() < f64::EPSILON {
+            50.0
+        } else {
+            100.0 * (candle.close - ll) / (hh - ll)
+        };
+
+        let k = self.k.update(raw_k)?;
+        let d = self.d.update(k)?;
+        Some(StochasticPoint {
+            time: candle.time,
+            k,
+            d,
+        })
+    }
+}
@@ -1,3 +1,4 @@
+use super::incremental::{self, IncrementalIndicator, WilderAverage};
 use crate::models::{Candle, SupertrendPoint, SupertrendResult};
 
 fn atr_values(candles: &[Candle], period: usize) -> Vec<Option<f64>> {
@@ -112,3 +113,81 @@ pub fn calculate(candles: &[Candle], period: usize, multiplier: f64) -> Supertre
         data,
     }
 }
+
+/// Streaming Supertrend. Emits the same points as `calculate`.
+#[derive(Debug, Clone)]
+pub struct SupertrendState {
+    multiplier: f64,
+    prev_close: Option<f64>,
+    atr: WilderAverage,
+    /// (final upper, final lower, supertrend) of the previous candle
+    prev: Option<(f64, f64, f64)>,
+}
+
+impl SupertrendState {
+    pub fn new(period: usize, multiplier: f64) -> Self {
+        Self {
+            multiplier,
+            prev_close: None,
+            atr: WilderAverage::new(period),
+            prev: None,
+        }
+    }
+}
+
+impl IncrementalIndicator for SupertrendState {
+    type Output = SupertrendPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<SupertrendPoint> {
+        let prev_close = self.prev_close.replace(candle.close);
+        let atr = self
+            .atr
+            .update(incremental::true_range(candle, prev_close))?;
+        let hl2 = (candle.high + candle.low) / 2.0;
+        let basic_upper = hl2 + self.multiplier * atr;
+        let basic_lower = hl2 - self.multiplier * atr;
+
+        let (prev_final_upper, prev_final_lower, prev_super, prev_close) =
+            match (self.prev, prev_close) {
+                (Some((upper, lower, value)), Some(close)) => (upper, lower, value, close),
+                _ => {
+                    self.prev = Some((basic_upper, basic_lower, basic_upper));
+                    return Some(SupertrendPoint {
+                        time: candle.time,
+                        value: basic_upper,
+                        direction: -1,
+                    });
+                }
+            };
+
+        let final_upper = if basic_upper < prev_final_upper || prev_close > prev_final_upper {
+            basic_upper
+        } else {
+            prev_final_upper
+        };
+        let final_lower = if basic_lower > prev_final_lower || prev_close < prev_final_lower {
+            basic_lower
+        } else {
+            prev_final_lower
+        };
+
+        let (value, direction) = if prev_super == prev_final_upper {
+            if candle.close <= final_upper {
+                (final_upper, -1)
+            } else {
+                (final_lower, 1)
+            }
+        } else if candle.close >= final_lower {
+            (final_lower, 1)
+        } else {
+            (final_upper, -1)
+        };
+
+        self.prev = Some((final_upper, final_lower, value));
+        Some(SupertrendPoint {
+            time: candle.time,
+            value,
+            direction,
+        })
+    }
+}
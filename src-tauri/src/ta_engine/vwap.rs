@@ -1,26 +1,197 @@
-use crate::models::{Candle, VwapPoint, VwapResult};
+use chrono::Datelike;
 
+use super::incremental::IncrementalIndicator;
+use super::session;
+use crate::models::{Candle, MarketType, VwapBandPoint, VwapPoint, VwapReset, VwapResult};
+
+/// Cumulative VWAP over the whole slice (never resets).
 pub fn calculate(candles: &[Candle]) -> VwapResult {
-    let mut cumulative_pv = 0.0_f64;
-    let mut cumulative_volume = 0.0_f64;
+    calculate_with_reset(candles, VwapReset::Cumulative, &MarketType::Crypto)
+}
+
+/// VWAP that restarts at every daily/weekly/monthly session boundary of `market`,
+/// with ±1/2/3σ volume-weighted standard-deviation bands.
+/// `Auto` is treated as cumulative; resolve it first with `resolve_reset`.
+pub fn calculate_with_reset(
+    candles: &[Candle],
+    reset: VwapReset,
+    market: &MarketType,
+) -> VwapResult {
+    let mut state = VwapState::with_reset(reset, market.clone());
     let mut data = Vec::with_capacity(candles.len());
+    let mut bands = Vec::with_capacity(candles.len());
+    let mut session_starts = Vec::new();
 
     for candle in candles {
+        let (new_session, point, band) = state.accumulate(candle);
+        if new_session {
+            session_starts.push(candle.time);
+        }
+        data.push(point);
+        bands.push(band);
+    }
+
+    VwapResult {
+        reset: state.reset,
+        data,
+        bands,
+        session_starts,
+    }
+}
+
+/// Resolves `Auto`: daily sessions for minute/hour charts, cumulative for daily and above.
+pub fn resolve_reset(reset: VwapReset, interval: &str) -> VwapReset {
+    match reset {
+        VwapReset::Auto if interval.ends_with('m') || interval.ends_with('h') => VwapReset::Daily,
+        VwapReset::Auto => VwapReset::Cumulative,
+        other => other,
+    }
+}
+
+/// Identifies the session a candle belongs to; a change in key restarts accumulation.
+fn session_key(time: i64, reset: VwapReset, market: &MarketType) -> i64 {
+    if matches!(reset, VwapReset::Auto | VwapReset::Cumulative) {
+        return 0;
+    }
+    let date = session::session_date(time, market);
+    match reset {
+        VwapReset::Weekly => {
+            let week = date.iso_week();
+            week.year() as i64 * 100 + week.week() as i64
+        }
+        VwapReset::Monthly => date.year() as i64 * 12 + date.month0() as i64,
+        _ => date.num_days_from_ce() as i64,
+    }
+}
+
+/// Streaming session VWAP. Emits the same points and bands as `calculate_with_reset`.
+#[derive(Debug, Clone, Default)]
+pub struct VwapState {
+    reset: VwapReset,
+    market: MarketType,
+    session: Option<i64>,
+    cumulative_pv: f64,
+    cumulative_pv2: f64,
+    cumulative_volume: f64,
+}
+
+impl VwapState {
+    pub fn with_reset(reset: VwapReset, market: MarketType) -> Self {
+        Self {
+            reset,
+            market,
+            ..Self::default()
+        }
+    }
+
+    /// Adds a candle and reports whether it opened a new session.
+    fn accumulate(&mut self, candle: &Candle) -> (bool, VwapPoint, VwapBandPoint) {
+        let key = session_key(candle.time, self.reset, &self.market);
+        let new_session = self.session != Some(key);
+        if new_session {
+            self.session = Some(key);
+            self.cumulative_pv = 0.0;
+            self.cumulative_pv2 = 0.0;
+            self.cumulative_volume = 0.0;
+        }
+
         let typical_price = (candle.high + candle.low + candle.close) / 3.0;
-        cumulative_pv += typical_price * candle.volume;
-        cumulative_volume += candle.volume.max(0.0);
+        self.cumulative_pv += typical_price * candle.volume;
+        self.cumulative_pv2 += typical_price * typical_price * candle.volume;
+        self.cumulative_volume += candle.volume.max(0.0);
 
-        let value = if cumulative_volume > f64::EPSILON {
-            cumulative_pv / cumulative_volume
+        let (value, std_dev) = if self.cumulative_volume > f64::EPSILON {
+            let value = self.cumulative_pv / self.cumulative_volume;
+            let variance = self.cumulative_pv2 / self.cumulative_volume - value * value;
+            (value, variance.max(0.0).sqrt())
         } else {
-            typical_price
+            (typical_price, 0.0)
         };
 
-        data.push(VwapPoint {
+        let point = VwapPoint {
             time: candle.time,
             value,
-        });
+        };
+        let band = VwapBandPoint {
+            time: candle.time,
+            std_dev,
+            upper1: value + std_dev,
+            lower1: value - std_dev,
+            upper2: value + 2.0 * std_dev,
+            lower2: value - 2.0 * std_dev,
+            upper3: value + 3.0 * std_dev,
+            lower3: value - 3.0 * std_dev,
+        };
+        (new_session, point, band)
+    }
+}
+
+impl IncrementalIndicator for VwapState {
+    type Output = (VwapPoint, VwapBandPoint);
+
+    fn update(&mut self, candle: &Candle) -> Option<(VwapPoint, VwapBandPoint)> {
+        let (_, point, band) = self.accumulate(candle);
+        Some((point, band))
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::candle;
+
+    const HOUR: i64 = 3_600;
+    /// 2024-03-05 20:00 UTC, two hours before the 17:00 New York FX rollover
+    const T0: i64 = 1_709_668_800;
 
-    VwapResult { data }
+    #[test]
+    fn daily_reset_restarts_at_the_session_boundary() {
+        let candles = [
+            candle(T0, 100.0),
+            candle(T0 + HOUR, 110.0),
+            // 17:00 New York opens the next FX session
+            candle(T0 + 2 * HOUR, 200.0),
+            candle(T0 + 3 * HOUR, 210.0),
+        ];
+
+        let result = calculate_with_reset(&candles, VwapReset::Daily, &MarketType::Forex);
+
+        assert_eq!(result.session_starts, vec![T0, T0 + 2 * HOUR]);
+        let values: Vec<f64> = result.data.iter().map(|p| p.value).collect();
+        assert_eq!(values, vec![100.0, 105.0, 200.0, 205.0]);
+        let std_devs: Vec<f64> = result.bands.iter().map(|b| b.std_dev).collect();
+        assert_eq!(std_devs, vec![0.0, 5.0, 0.0, 5.0]);
+        assert_eq!(
+            (result.bands[3].upper2, result.bands[3].lower1),
+            (215.0, 200.0)
+        );
+
+        // Crypto sessions follow the UTC date, so the same bars stay in one session
+        let crypto = calculate_with_reset(&candles, VwapReset::Daily, &MarketType::Crypto);
+        assert_eq!(crypto.session_starts, vec![T0]);
+        assert_eq!(crypto.data[3].value, 155.0);
+    }
+
+    #[test]
+    fn streaming_state_matches_the_batch_reset() {
+        let candles: Vec<Candle> = (0..6)
+            .map(|i| candle(T0 + i * HOUR, 100.0 + (i * i) as f64))
+            .collect();
+        let batch = calculate_with_reset(&candles, VwapReset::Daily, &MarketType::Forex);
+
+        let mut state = VwapState::with_reset(VwapReset::Daily, MarketType::Forex);
+        for (i, candle) in candles.iter().enumerate() {
+            let (point, band) = state.update(candle).unwrap();
+            assert_eq!(point.value, batch.data[i].value);
+            assert_eq!(band.std_dev, batch.bands[i].std_dev);
+        }
+    }
+
+    #[test]
+    fn auto_reset_depends_on_the_interval() {
+        assert_eq!(resolve_reset(VwapReset::Auto, "15m"), VwapReset::Daily);
+        assert_eq!(resolve_reset(VwapReset::Auto, "4h"), VwapReset::Daily);
+        assert_eq!(resolve_reset(VwapReset::Auto, "1d"), VwapReset::Cumulative);
+        assert_eq!(resolve_reset(VwapReset::Weekly, "15m"), VwapReset::Weekly);
+    }
 }
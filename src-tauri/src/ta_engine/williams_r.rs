@@ -1,4 +1,7 @@
+use std::collections::VecDeque;
+
 use super::helpers;
+use super::incremental::{self, IncrementalIndicator};
 use crate::models::{Candle, WillrPoint, WillrResult};
 
 /// Williams %R: -100 × (HH - Close) / (HH - LL) over period
@@ -35,3 +38,50 @@ pub fn calculate(candles: &[Candle], period: usize) -> WillrResult {
 
     WillrResult { period, data }
 }
+
+/// Streaming Williams %R. Emits the same points as `calculate`.
+#[derive(Debug, Clone)]
+pub struct WillrState {
+    period: usize,
+    window: VecDeque<Candle>,
+}
+
+impl WillrState {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            window: VecDeque::with_capacity(period + 1),
+        }
+    }
+}
+
+impl IncrementalIndicator for WillrState {
+    type Output = WillrPoint;
+
+    fn update(&mut self, candle: &Candle) -> Option<WillrPoint> {
+        if !incremental::push_window(&mut self.window, candle.clone(), self.period) {
+            return None;
+        }
+
+        let high = self
+            .window
+            .iter()
+            .map(|c| c.high)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let low = self
+            .window
+            .iter()
+            .map(|c| c.low)
+            .fold(f64::INFINITY, f64::min);
+        let range = high - low;
+        let value = if range.abs() < f64::EPSILON {
+            -50.0
+        } else {
+            -100.0 * (high - candle.close) / range
+        };
+        Some(WillrPoint {
+            time: candle.time,
+            value,
+        })
+    }
+}
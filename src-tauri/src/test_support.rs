@@ -0,0 +1,22 @@
+//! 여러 모듈의 테스트가 함께 쓰는 픽스처.
+
+use crate::models::Candle;
+
+/// 시가·고가·저가가 모두 종가와 같고 거래량이 1인 봉.
+pub fn candle(time: i64, close: f64) -> Candle {
+    candle_from(time, close, close)
+}
+
+/// 시가와 종가로 만든 봉. 고가·저가는 둘 중 큰 값과 작은 값이다.
+pub fn candle_from(time: i64, open: f64, close: f64) -> Candle {
+    Candle {
+        time,
+        open,
+        high: open.max(close),
+        low: open.min(close),
+        close,
+        volume: 1.0,
+        session: None,
+        flow: None,
+    }
+}
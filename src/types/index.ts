@@ -5,6 +5,17 @@ export interface Candle {
   low: number;
   close: number;
   volume: number;
+  session?: CandleSession; // intraday stock bars only
+  flow?: TradeFlow; // Binance only
+}
+
+export type CandleSession = "pre" | "regular" | "post";
+
+// Trades aggregated within a bar; taker buy volume splits volume into buys and sells
+export interface TradeFlow {
+  quoteVolume: number;
+  tradeCount: number;
+  takerBuyVolume: number;
 }
 
 export interface BollingerBandsPoint {
@@ -63,13 +74,47 @@ export interface ObvResult {
   data: ObvPoint[];
 }
 
+// VWAP accumulation resets at each market's trading-day boundary; "auto" is daily on intraday bars
+export type VwapReset = "auto" | "cumulative" | "daily" | "weekly" | "monthly";
+
 export interface VwapPoint {
   time: number;
   value: number;
 }
 
+// Volume-weighted standard deviation bands around the session VWAP
+export interface VwapBandPoint {
+  time: number;
+  stdDev: number;
+  upper1: number;
+  lower1: number;
+  upper2: number;
+  lower2: number;
+  upper3: number;
+  lower3: number;
+}
+
 export interface VwapResult {
+  reset: VwapReset; // the resolved reset ("auto" is never returned)
   data: VwapPoint[];
+  bands: VwapBandPoint[];
+  sessionStarts: number[]; // bar times where accumulation restarted, first bar included
+}
+
+// How an anchored VWAP picks its starting bar
+export type AnchorMode =
+  | "time"
+  | "swingHigh" // last SMC swing high
+  | "swingLow" // last SMC swing low
+  | "earnings" // latest earnings date (US stocks)
+  | "yearToDate" // first bar of the last bar's year
+  | "highestVolume"; // highest-volume bar in the loaded range
+
+// One anchored VWAP series; the VWAP fields are flattened in
+export interface AnchoredVwapSeries extends VwapResult {
+  label: string;
+  mode: AnchorMode;
+  anchorTime: number; // bar where accumulation starts
 }
 
 export interface AtrPoint {
@@ -139,7 +184,13 @@ export type SignalType =
   | "ibsMeanRevBuy"
   | "ibsMeanRevSell"
   | "rsiDivergenceBuy"
-  | "rsiDivergenceSell";
+  | "rsiDivergenceSell"
+  | "consensusBuy"
+  | "consensusSell"
+  | "kimchiPremiumBuy"
+  | "kimchiPremiumSell"
+  | "investorFlowBuy"
+  | "investorFlowSell";
 
 export interface SignalPoint {
   time: number;
@@ -242,8 +293,12 @@ export interface CvdPoint {
 
 export interface CvdResult {
   data: CvdPoint[];
+  estimatedBars: number; // bars without taker buy volume, estimated via cvdEstimate
 }
 
+// How CVD delta is estimated for bars without taker buy volume
+export type CvdEstimate = "barDirection" | "closeLocation";
+
 // STC (Schaff Trend Cycle)
 export interface StcPoint {
   time: number;
@@ -285,6 +340,92 @@ export interface AutoFibResult {
   levels: AutoFibLevel[];
 }
 
+// Weighted agreement of strategy signals
+export interface ConsensusPoint {
+  time: number;
+  score: number; // buyScore - sellScore
+  buyScore: number;
+  sellScore: number;
+}
+
+export interface ConsensusResult {
+  data: ConsensusPoint[];
+}
+
+// Kimchi premium (KRW market vs USDT market converted with USD/KRW)
+export interface KimchiPremiumPoint {
+  time: number;
+  krwPrice: number;
+  usdPrice: number;
+  usdKrw: number;
+  premium: number; // percent
+  zScore: number | null;
+}
+
+export interface KimchiPremiumResult {
+  krwSymbol: string;
+  usdSymbol: string;
+  zPeriod: number;
+  band: number;
+  data: KimchiPremiumPoint[];
+}
+
+// Binance USDⓈ-M perpetual futures data
+export interface FundingRatePoint {
+  time: number;
+  rate: number; // per 8h funding (0.0001 = 0.01%)
+}
+
+export interface FundingRateResult {
+  symbol: string;
+  data: FundingRatePoint[];
+}
+
+export interface OpenInterestPoint {
+  time: number;
+  openInterest: number;
+  openInterestValue: number; // USDT
+}
+
+export interface OpenInterestResult {
+  symbol: string;
+  period: string;
+  data: OpenInterestPoint[];
+}
+
+export interface LongShortRatioPoint {
+  time: number;
+  ratio: number;
+  longShare: number; // 0-1
+  shortShare: number;
+}
+
+export interface LongShortRatioResult {
+  symbol: string;
+  period: string;
+  data: LongShortRatioPoint[];
+}
+
+// Korean investor flow (net buying by investor type, KIS)
+export interface InvestorFlowPoint {
+  time: number;
+  individual: number; // net shares bought within the bar
+  foreign: number;
+  institution: number;
+  individualAmount: number; // KRW
+  foreignAmount: number;
+  institutionAmount: number;
+  individualCumulative: number; // net shares since the first bar
+  foreignCumulative: number;
+  institutionCumulative: number;
+}
+
+export interface InvestorFlowResult {
+  symbol: string;
+  streak: number;
+  data: InvestorFlowPoint[];
+}
+
 export interface AnalysisResponse {
   candles: Candle[];
   bollingerBands: BollingerBandsPoint[];
@@ -312,15 +453,38 @@ export interface AnalysisResponse {
   stc: StcResult | null;
   smc: SmcResult | null;
   anchoredVwap: VwapResult | null;
+  anchoredVwaps: AnchoredVwapSeries[];
   autoFib: AutoFibResult | null;
+  consensus: ConsensusResult | null;
+  kimchiPremium: KimchiPremiumResult | null;
+  fundingRate: FundingRateResult | null;
+  openInterest: OpenInterestResult | null;
+  longShortRatio: LongShortRatioResult | null;
+  investorFlow: InvestorFlowResult | null;
   symbol: string;
   interval: string;
-  dataSource: "binance" | "yahoo" | "kis";
+  dataSource: DataSourceId;
   sourceInterval: string;
+  corporateActions: CorporateAction[]; // splits/dividends within the range (stocks)
+  warnings: string[]; // extra data that was skipped or approximated; the analysis itself succeeded
 }
 
 export type MarketType = "crypto" | "usStock" | "krStock" | "forex";
 
+// Id of the registered market data provider that served the candles
+export type DataSourceId = "binance" | "upbit" | "binance_futures" | "kis" | "yahoo";
+
+// Stock price adjustment; stored series are split-adjusted and recomputed on request
+export type PriceAdjustment = "none" | "splits" | "total_return";
+
+export type CorporateActionKind = "split" | "dividend";
+
+export interface CorporateAction {
+  time: number; // ex-date
+  kind: CorporateActionKind;
+  value: number; // split ratio (4 for 4:1) or split-adjusted dividend per share
+}
+
 export interface FundamentalsParams {
   symbol: string;
   market: MarketType;
@@ -401,8 +565,17 @@ export interface SmcParams {
   swingLength: number;
 }
 
+export interface VwapAnchor {
+  mode: AnchorMode;
+  time?: number | null; // required for "time"; looked up when empty for "earnings"
+  label?: string | null; // defaults to the mode's name
+  bands?: boolean; // include ±1/2/3σ bands
+}
+
 export interface AnchoredVwapParams {
-  anchorTime: number;
+  anchorTime?: number | null; // single anchor, returned as anchoredVwap
+  anchors?: VwapAnchor[]; // returned as anchoredVwaps
+  swingLength?: number; // SMC swing length for swing anchors
 }
 
 export interface AutoFibParams {
@@ -410,6 +583,21 @@ export interface AutoFibParams {
   swingLength: number;
 }
 
+export interface KimchiPremiumParams {
+  zPeriod: number;
+  band: number;
+}
+
+export interface InvestorFlowParams {
+  streak: number;
+}
+
+export interface ConsensusParams {
+  weights: Record<string, number>; // by signal source; 1.0 when omitted, 0 excludes
+  window: number; // bars a signal keeps counting
+  threshold: number; // net score that emits a consensus signal
+}
+
 export interface SignalStrategyParams {
   supertrendAdx: boolean;
   emaCrossover: boolean;
@@ -424,6 +612,7 @@ export interface SignalStrategyParams {
   emaFastPeriod: number;
   emaSlowPeriod: number;
   divergenceSwingLength: number;
+  consensus?: ConsensusParams | null;
 }
 
 export interface AnalysisParams {
@@ -433,6 +622,9 @@ export interface AnalysisParams {
   bbMultiplier: number;
   rsiPeriod: number;
   market: MarketType;
+  startTime?: number | null; // backfill and analyze stored history from this time (seconds)
+  adjustment?: PriceAdjustment; // stocks only; defaults to "splits"
+  regularHoursOnly?: boolean; // indicators and signals skip pre/post bars, which are still returned
   smaPeriods: number[];
   emaPeriods: number[];
   hmaPeriods?: number[];
@@ -440,6 +632,10 @@ export interface AnalysisParams {
   stochastic: StochasticParams | null;
   showObv: boolean;
   showCvd?: boolean;
+  cvdEstimate?: CvdEstimate;
+  showFundingRate?: boolean;
+  showOpenInterest?: boolean;
+  showLongShortRatio?: boolean;
   donchian?: DonchianParams | null;
   keltner?: KeltnerParams | null;
   mfi?: MfiParams | null;
@@ -449,8 +645,11 @@ export interface AnalysisParams {
   adx?: AdxParams | null;
   stc?: StcParams | null;
   smc?: SmcParams | null;
+  vwapReset?: VwapReset;
   anchoredVwap?: AnchoredVwapParams | null;
   autoFib?: AutoFibParams | null;
+  kimchiPremium?: KimchiPremiumParams | null;
+  investorFlow?: InvestorFlowParams | null;
   signalStrategies: SignalStrategyParams;
 }
 
@@ -460,11 +659,13 @@ export interface MultiSymbolCandlesParams {
   symbols: string[];
   interval: string;
   limit: number;
+  adjustment?: PriceAdjustment;
 }
 
 export interface MultiSymbolCandlesResponse {
   data: Record<string, Candle[]>;
   errors: Record<string, string>;
+  warnings: Record<string, string[]>;
 }
 
 export interface PremarketSnapshotParams {
@@ -516,6 +717,6 @@ export interface WatchlistSnapshot {
   high: number;
   low: number;
   sparkline: number[];
-  dataSource: "binance" | "yahoo" | "kis";
+  dataSource: DataSourceId;
   sourceInterval: string;
 }